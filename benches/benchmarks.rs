@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use tui_bricks::bench::{generate, write_items, Database, Item, Mode};
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+fn get_path(lots: usize) -> PathBuf {
+    std::env::temp_dir().join(format!("tui_bricks_bench_{}.yml", lots))
+}
+
+/// Writes a synthetic database of each size and returns where they are.
+fn setup() -> Vec<(usize, PathBuf)> {
+    SIZES
+        .iter()
+        .map(|&lots| {
+            let path = get_path(lots);
+            write_items(&path, &generate(lots, 1)).unwrap();
+            (lots, path)
+        })
+        .collect()
+}
+
+fn get_first_item(db: &Database) -> Item {
+    db.get_item_by_id(generate(1, 1)[0].get_id())
+        .unwrap()
+        .clone()
+}
+
+fn bench_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load");
+    group.sample_size(10);
+    for (lots, path) in setup() {
+        group.bench_with_input(BenchmarkId::from_parameter(lots), &path, |b, path| {
+            b.iter(|| Database::new(path.clone()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_save(c: &mut Criterion) {
+    let mut group = c.benchmark_group("save");
+    group.sample_size(10);
+    for (lots, path) in setup() {
+        let mut db = Database::new(path.clone()).unwrap();
+        let mut item = get_first_item(&db);
+        let mut i = 0;
+        group.bench_function(BenchmarkId::new("one item", lots), |b| {
+            b.iter(|| {
+                let old = item.clone();
+                i += 1;
+                item.set_name(&format!("Renamed {}", i));
+                db.update_item(&old, &item).unwrap();
+            })
+        });
+
+        let items = generate(lots, 1);
+        group.bench_function(BenchmarkId::new("everything", lots), |b| {
+            b.iter(|| write_items(&path, &items).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search");
+    for (lots, path) in setup() {
+        let db = Database::new(path).unwrap();
+        let item = get_first_item(&db);
+        let location = item.get_locations()[0].get_location().clone();
+
+        group.bench_function(BenchmarkId::new("id", lots), |b| {
+            b.iter(|| db.get_item_by_id(item.get_id()).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("name", lots), |b| {
+            b.iter(|| db.get_item_by_name(item.get_name()).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("location", lots), |b| {
+            b.iter(|| db.get_items_at_location(&location).count())
+        });
+    }
+    group.finish();
+}
+
+fn bench_render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    for (lots, path) in setup() {
+        let db = Database::new(path).unwrap();
+        let item = get_first_item(&db);
+        let mut w = Vec::new();
+
+        group.bench_function(BenchmarkId::new("item", lots), |b| {
+            let mode = Mode::DisplayItem {
+                item: item.clone(),
+                msg: None,
+            };
+            b.iter(|| {
+                w.clear();
+                mode.emit_mode(&mut w).unwrap();
+            })
+        });
+        group.bench_function(BenchmarkId::new("statistics", lots), |b| {
+            b.iter(|| {
+                w.clear();
+                let mode = Mode::ViewStatistics {
+                    stats: db.get_stats(true, None),
+                };
+                mode.emit_mode(&mut w).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_load, bench_save, bench_search, bench_render);
+criterion_main!(benches);
@@ -0,0 +1,251 @@
+use std::io::{BufRead, Write};
+
+use term_lib::{Error, Result};
+
+use crate::locale::tr;
+use crate::ui::{Confirm, InputSource, Lines};
+
+/// Writes the output as plain lines for screen readers: escape sequences for colors and moving the
+/// cursor are left out, lines drawn only from box-drawing characters or dashes are dropped, and
+/// each clearing of the screen becomes an empty line between one screen and the next.
+pub struct Linear<W: Write> {
+    inner: W,
+    line: Vec<u8>,
+}
+
+impl<W: Write> Linear<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            line: Vec::new(),
+        }
+    }
+
+    fn write_line(&mut self, end: &[u8]) -> std::io::Result<()> {
+        let line = String::from_utf8_lossy(&self.line).to_string();
+        self.line.clear();
+        let (text, cleared) = strip_escapes(&line);
+        if cleared {
+            self.inner.write_all(b"\n")?;
+        }
+        let is_drawing = text.chars().count() >= 3
+            && text
+                .chars()
+                .all(|c| c == '-' || c == '=' || ('\u{2500}'..='\u{257f}').contains(&c));
+        if is_drawing {
+            return Ok(());
+        }
+        self.inner.write_all(text.trim_end().as_bytes())?;
+        self.inner.write_all(end)
+    }
+}
+
+/// Leaves the escape sequences out of the text, and tells whether one of them clears the screen.
+fn strip_escapes(text: &str) -> (String, bool) {
+    let mut res = String::new();
+    let mut cleared = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // Control sequences end with a letter or similar, like `[2J` to clear the screen.
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            cleared |= c == 'J' && params.contains('2');
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                // Operating system commands, like setting the title, end with a bell.
+                Some(']') => {
+                    for c in chars.by_ref() {
+                        if c == '\x07' {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\r' => {}
+            c => res.push(c),
+        }
+    }
+    (res, cleared)
+}
+
+impl<W: Write> Write for Linear<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &b in buf {
+            if b == b'\n' {
+                self.write_line(b"\n")?;
+            } else {
+                self.line.push(b);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    /// Lines that are not ended yet are written as well, like prompts waiting for an answer.
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.line.is_empty() {
+            self.write_line(b"")?;
+        }
+        self.inner.flush()
+    }
+}
+
+/// Reads one answer per line like `Lines`, but writes every prompt as a labeled line first and
+/// lists the options with their numbers, so that a screen reader can follow what is asked.
+pub struct Plain<R: BufRead, O: Write> {
+    lines: Lines<R>,
+    out: O,
+}
+
+impl<R: BufRead, O: Write> Plain<R, O> {
+    pub fn new(reader: R, out: O) -> Self {
+        Self {
+            lines: Lines::new(reader),
+            out,
+        }
+    }
+
+    fn ask(&mut self, msg: &str) -> Result<()> {
+        writeln!(self.out)?;
+        for line in msg.lines() {
+            writeln!(self.out, "{}", line)?;
+        }
+        write!(self.out, "> ")?;
+        self.out.flush()?;
+        Ok(())
+    }
+
+    /// Asks again until the answer could be read, which an empty line cancels.
+    fn ask_until<T>(
+        &mut self,
+        msg: &str,
+        mut read: impl FnMut(&mut Lines<R>, &mut O) -> Result<T>,
+    ) -> Result<T> {
+        loop {
+            self.ask(msg)?;
+            match read(&mut self.lines, &mut self.out) {
+                Err(Error::ParsingError(answer)) => {
+                    writeln!(self.out, "{}", tr!("{} is not an answer to this", answer))?;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+impl<W: Write, R: BufRead, O: Write> InputSource<W> for Plain<R, O> {
+    fn wait_for_cmdchar(&mut self) -> Result<char> {
+        self.ask(tr!("Command key, or an empty line to cancel:"))?;
+        InputSource::<O>::wait_for_cmdchar(&mut self.lines)
+    }
+
+    fn input_string(&mut self, _: &mut W, msg: &str) -> Result<String> {
+        self.ask_until(msg, |lines, out| lines.input_string(out, ""))
+    }
+
+    fn input_u32(&mut self, _: &mut W, msg: &str) -> Result<u32> {
+        self.ask_until(msg, |lines, out| lines.input_u32(out, ""))
+    }
+
+    fn edit_string(&mut self, _: &mut W, msg: &str, old: &str) -> Result<String> {
+        let msg = format!("{}\n{}", msg, tr!("Currently: {}", old));
+        self.ask_until(&msg, |lines, out| lines.edit_string(out, "", ""))
+    }
+
+    fn edit_u32(&mut self, _: &mut W, msg: &str, old: u32) -> Result<u32> {
+        let msg = format!("{}\n{}", msg, tr!("Currently: {}", old));
+        self.ask_until(&msg, |lines, out| lines.edit_u32(out, "", 0))
+    }
+
+    fn confirmation(&mut self, _: &mut W, msg: &str) -> Result<bool> {
+        let msg = format!("{}\n{}", msg, tr!("Answer y for yes or n for no:"));
+        self.ask_until(&msg, |lines, out| lines.confirmation(out, ""))
+    }
+
+    fn confirm(&mut self, _: &mut W, msg: &str, kind: Confirm) -> Result<bool> {
+        let hint = match kind {
+            Confirm::Default(true) => tr!("Answer y for yes or n for no, or nothing for yes:"),
+            Confirm::Default(false) => tr!("Answer y for yes or n for no, or nothing for no:"),
+            Confirm::Destructive => tr!("Answer y for yes or n for no:"),
+            Confirm::Typed(text) => &tr!("Type \"{}\" to confirm:", text),
+        };
+        let msg = format!("{}\n{}", msg, hint);
+        self.ask_until(&msg, |lines, out| lines.confirm(out, "", kind))
+    }
+
+    /// Asks for text to search for, and lets the line found be chosen if there are several.
+    fn fzf_search(&mut self, opts: &str) -> Result<String> {
+        let searched =
+            self.ask_until(tr!("Search for:"), |lines, out| lines.input_string(out, ""))?;
+        let searched = searched.trim().to_lowercase();
+        if searched.is_empty() {
+            return Ok(String::new());
+        }
+        let found: Vec<String> = opts
+            .lines()
+            .filter(|line| line.to_lowercase().contains(&searched))
+            .map(str::to_string)
+            .collect();
+        match found.as_slice() {
+            [] => Ok(String::new()),
+            [line] => Ok(line.clone()),
+            _ => {
+                let i = InputSource::<Vec<u8>>::select(self, &mut Vec::new(), None, &found)?;
+                Ok(found[i].clone())
+            }
+        }
+    }
+
+    fn select(&mut self, _: &mut W, header: Option<&str>, options: &[String]) -> Result<usize> {
+        let mut msg = header.unwrap_or_default().to_string();
+        for (i, option) in options.iter().enumerate() {
+            msg.push_str(&format!("\n{}: {}", i + 1, option));
+        }
+        msg.push_str(&format!("\n{}", tr!("Enter the number of a choice:")));
+        self.ask_until(msg.trim_start(), |lines, out| {
+            lines.select(out, None, options)
+        })
+    }
+
+    fn select_cmd(&mut self, _: &mut W, header: &str, cmds: &[(char, String)]) -> Result<usize> {
+        let mut msg = header.to_string();
+        for (c, info) in cmds {
+            msg.push_str(&format!("\n{}: {}", c, info));
+        }
+        msg.push_str(&format!("\n{}", tr!("Enter the key of a choice:")));
+        self.ask_until(&msg, |lines, out| lines.select_cmd(out, "", cmds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear() {
+        let mut w = Linear::new(Vec::new());
+        write!(w, "\x1b[2J\x1b[1;1HPart ID: 3001\r\n\x1b[7mName\x1b[0m\n").unwrap();
+        write!(w, "\u{2500}\u{2500}\u{2500}\u{2500}\n------\nEnter:").unwrap();
+        w.flush().unwrap();
+        assert_eq!(
+            String::from_utf8(w.inner).unwrap(),
+            "\nPart ID: 3001\nName\nEnter:"
+        );
+
+        let mut out = Vec::new();
+        let mut input = Plain::new("7\n2\n".as_bytes(), &mut out);
+        let options = ["red".to_string(), "blue".to_string()];
+        let i = InputSource::<Vec<u8>>::select(&mut input, &mut Vec::new(), None, &options);
+        assert_eq!(i.unwrap(), 1);
+        let asked = String::from_utf8(out).unwrap();
+        assert!(asked.starts_with("\n1: red\n2: blue\nEnter the number of a choice:\n> "));
+        assert!(asked.contains("7 is not an answer to this"));
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use term_lib::command::Command;
+use term_lib::Result;
+
+use crate::data::{ColorGroup, Item};
+use crate::io;
+use crate::locale::tr;
+use crate::timestamp::Timestamp;
+use crate::types::PartId;
+
+/// Why the number of pieces in a lot changed.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    BuiltIntoMoc,
+    Sold,
+    Lost,
+    Found,
+    Bought,
+    /// Assemblies were put together from their components or taken apart.
+    Assembly,
+    PartedOut,
+    Unspecified,
+}
+
+impl Command for Reason {
+    fn get_char(&self) -> char {
+        use Reason::*;
+        match self {
+            BuiltIntoMoc => 'm',
+            Sold => 's',
+            Lost => 'l',
+            Found => 'f',
+            Bought => 'b',
+            Assembly => 'a',
+            PartedOut => 'p',
+            Unspecified => 'n',
+        }
+    }
+
+    fn get_info(&self) -> &str {
+        use Reason::*;
+        match self {
+            BuiltIntoMoc => tr!("built into a (m)OC"),
+            Sold => tr!("(s)old"),
+            Lost => tr!("(l)ost"),
+            Found => tr!("(f)ound"),
+            Bought => tr!("(b)ought"),
+            Assembly => tr!("(a)ssembled or taken apart"),
+            PartedOut => tr!("(p)arted out of a set"),
+            Unspecified => tr!("(n)o reason"),
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Reason::*;
+        let name = match self {
+            BuiltIntoMoc => tr!("built into MOCs"),
+            Sold => tr!("sold"),
+            Lost => tr!("lost"),
+            Found => tr!("found"),
+            Bought => tr!("bought"),
+            Assembly => tr!("assembled or taken apart"),
+            PartedOut => tr!("parted out of sets"),
+            Unspecified => tr!("no reason"),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A change to the number of pieces in a lot, kept in a log next to the database file.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Adjustment {
+    /// Seconds since the unix epoch.
+    time: u64,
+    id: PartId,
+    color_group: ColorGroup,
+    change: i64,
+    reason: Reason,
+    /// Who made the change, when a user is set in the config or with `--user`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+}
+
+impl Adjustment {
+    pub fn get_id(&self) -> PartId {
+        self.id
+    }
+
+    pub fn get_reason(&self) -> Reason {
+        self.reason
+    }
+
+    pub fn get_user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    pub fn get_change(&self) -> i64 {
+        self.change
+    }
+
+    pub fn get_time(&self) -> Timestamp {
+        Timestamp::from_secs(self.time)
+    }
+}
+
+impl fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {:+} ({})",
+            fmt_time(self.time),
+            self.color_group,
+            self.change,
+            self.reason
+        )?;
+        if let Some(user) = &self.user {
+            write!(f, ", {}", user)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn get_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("adjustments")
+}
+
+/// Returns the adjustments from the lots whose counted pieces differ between the old and the new
+/// item, where lots without a known quantity count as having none.
+pub fn get_adjustments(
+    old_item: &Item,
+    new_item: &Item,
+    reasons: &BTreeMap<ColorGroup, Reason>,
+) -> Vec<Adjustment> {
+    let count = |item: &Item, color_group: &ColorGroup| -> i64 {
+        item.get_locations()
+            .iter()
+            .filter(|lot| lot.get_color_group() == color_group)
+            .filter_map(|lot| lot.get_quantity())
+            .map(|quantity| quantity.get() as i64)
+            .sum()
+    };
+    let time = now();
+
+    let mut color_groups = old_item.get_color_set();
+    color_groups.append(&mut new_item.get_color_set());
+    color_groups
+        .into_iter()
+        .filter_map(|color_group| {
+            let change = count(new_item, color_group) - count(old_item, color_group);
+            (change != 0).then(|| Adjustment {
+                time,
+                id: new_item.get_id(),
+                color_group: color_group.clone(),
+                change,
+                reason: reasons
+                    .get(color_group)
+                    .copied()
+                    .unwrap_or(Reason::Unspecified),
+                user: None,
+            })
+        })
+        .collect()
+}
+
+/// Appends the adjustments to the log, recorded as made by the user if there is one.
+pub fn append(path: &Path, adjustments: &[Adjustment], user: Option<&str>) -> Result<()> {
+    let mut appended = String::new();
+    for adjustment in adjustments {
+        let adjustment = Adjustment {
+            user: user.map(str::to_string),
+            ..adjustment.clone()
+        };
+        appended.push_str("---\n");
+        appended.push_str(&serde_yaml::to_string(&adjustment)?);
+    }
+    io::append_private(path, &appended)?;
+    Ok(())
+}
+
+/// Reads the log, which is empty if there is none.
+pub fn read(path: &Path) -> Result<Vec<Adjustment>> {
+    let contents = match io::read_private(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut adjustments = Vec::new();
+    if contents.trim().is_empty() {
+        return Ok(adjustments);
+    }
+    for document in serde_yaml::Deserializer::from_str(&contents) {
+        adjustments.push(Adjustment::deserialize(document)?);
+    }
+    Ok(adjustments)
+}
+
+/// The users who made the adjustments.
+pub fn get_users(adjustments: &[Adjustment]) -> BTreeSet<&str> {
+    adjustments
+        .iter()
+        .filter_map(Adjustment::get_user)
+        .collect()
+}
+
+/// Returns the current time in seconds since the unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Returns the year and month of the time in UTC.
+pub fn get_month(time: u64) -> (i64, u32) {
+    let (year, month, _) = get_date(time);
+    (year, month)
+}
+
+/// Formats the time in local time, by default like `2024-02-29 12:00 UTC`.
+pub fn fmt_time(time: u64) -> String {
+    Timestamp::from_secs(time).to_string()
+}
+
+/// Returns the year, month and day of the time in UTC.
+pub fn get_date(time: u64) -> (i64, u32, u32) {
+    // Converts days since the epoch to a civil date, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (time / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Sums up the changes by month and reason, one line per month.
+pub fn report(adjustments: &[Adjustment]) -> Vec<String> {
+    let mut months: BTreeMap<(i64, u32), BTreeMap<Reason, i64>> = BTreeMap::new();
+    for adjustment in adjustments {
+        *months
+            .entry(get_month(adjustment.time))
+            .or_default()
+            .entry(adjustment.reason)
+            .or_default() += adjustment.change;
+    }
+
+    months
+        .iter()
+        .map(|((year, month), reasons)| {
+            format!("{}-{:02}: {}", year, month, describe_reasons(reasons))
+        })
+        .collect()
+}
+
+fn describe_reasons(reasons: &BTreeMap<Reason, i64>) -> String {
+    let changes: Vec<String> = reasons
+        .iter()
+        .map(|(reason, change)| format!("{} {:+}", reason, change))
+        .collect();
+    changes.join(", ")
+}
+
+/// Sums up all the changes by reason.
+pub fn total(adjustments: &[Adjustment]) -> String {
+    let mut reasons: BTreeMap<Reason, i64> = BTreeMap::new();
+    for adjustment in adjustments {
+        *reasons.entry(adjustment.reason).or_default() += adjustment.change;
+    }
+    tr!("Total: {}", describe_reasons(&reasons))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adjustment(time: u64, change: i64, reason: Reason) -> Adjustment {
+        Adjustment {
+            time,
+            id: PartId::new(3001).unwrap(),
+            color_group: ColorGroup::Basic,
+            change,
+            reason,
+            user: None,
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        assert_eq!(get_month(0), (1970, 1));
+        assert_eq!(fmt_time(1709208000), "2024-02-29 12:00 UTC");
+        // 2024-02-29 12:00 and 2024-03-01 00:00 UTC.
+        let adjustments = vec![
+            adjustment(1709208000, -400, Reason::Sold),
+            adjustment(1709208000, 3, Reason::Found),
+            adjustment(1709208000, -12, Reason::Sold),
+            adjustment(1709251200, -2, Reason::Lost),
+        ];
+        assert_eq!(
+            report(&adjustments),
+            vec!["2024-02: sold -412, found +3", "2024-03: lost -2"]
+        );
+        assert_eq!(total(&adjustments), "Total: sold -412, lost -2, found +3");
+
+        let path = PathBuf::from("test_adjustments.adjustments");
+        append(&path, &adjustments[..2], Some("Alice")).unwrap();
+        append(&path, &adjustments[2..], None).unwrap();
+        let read_back = read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_back[0].get_user(), Some("Alice"));
+        assert_eq!(read_back[3].get_user(), None);
+        assert_eq!(get_users(&read_back), BTreeSet::from(["Alice"]));
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Item, Lot};
+use crate::locale::{self, tr};
+use crate::timestamp::Timestamp;
+
+const DAY: u64 = 86400;
+
+/// Lots not changed for this long are listed as candidates for selling or sorting anew.
+pub const STALE_AGE: u64 = 2 * 365 * DAY;
+
+/// The upper bounds of the age brackets in days.
+const BRACKETS: [u64; 4] = [30, 182, 365, 730];
+
+/// When the lot was last touched, by being added, changed or bought for.
+pub fn get_last_touched(lot: &Lot) -> Option<Timestamp> {
+    [lot.get_added(), lot.get_modified(), lot.get_purchased()]
+        .into_iter()
+        .flatten()
+        .max()
+}
+
+/// The number of lots by the time since they were last touched.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone, Copy)]
+pub struct Ages {
+    /// Lots in each of the brackets, followed by the lots older than two years.
+    brackets: [usize; BRACKETS.len() + 1],
+    /// Lots without any times, which were added before times were recorded.
+    unknown: usize,
+}
+
+impl Ages {
+    pub fn add(&mut self, lot: &Lot, now: Timestamp) {
+        let Some(touched) = get_last_touched(lot) else {
+            self.unknown += 1;
+            return;
+        };
+        let days = now.get_secs().saturating_sub(touched.get_secs()) / DAY;
+        let bracket = BRACKETS
+            .iter()
+            .position(|max_days| days < *max_days)
+            .unwrap_or(BRACKETS.len());
+        self.brackets[bracket] += 1;
+    }
+}
+
+impl fmt::Display for Ages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", tr!("Lots by time since they were last changed:"))?;
+        let names = [
+            tr!("under a month"),
+            tr!("1 to 6 months"),
+            tr!("6 to 12 months"),
+            tr!("1 to 2 years"),
+            tr!("over 2 years"),
+            tr!("not known"),
+        ];
+        let counts = self.brackets.iter().chain([&self.unknown]);
+        for (name, count) in names.into_iter().zip(counts) {
+            write!(f, "\n  {}: {}", name, locale::fmt_count(*count))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the lots not touched for at least the given number of seconds, the oldest first.
+pub fn get_stale_lots(items: &[Item], now: Timestamp, min_age: u64) -> Vec<(&Item, &Lot)> {
+    let mut lots: Vec<(&Item, &Lot, Timestamp)> = items
+        .iter()
+        .flat_map(|item| item.get_locations().iter().map(move |lot| (item, lot)))
+        .filter_map(|(item, lot)| Some((item, lot, get_last_touched(lot)?)))
+        .filter(|(_, _, touched)| now.get_secs().saturating_sub(touched.get_secs()) >= min_age)
+        .collect();
+    lots.sort_by_key(|(_, _, touched)| *touched);
+    lots.into_iter().map(|(item, lot, _)| (item, lot)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::PartId;
+
+    #[test]
+    fn test_ages() {
+        let now = Timestamp::parse("2024-06-01").unwrap();
+        let item = |id, added: Option<&str>| {
+            let mut item = Item::new(PartId::new(id).unwrap(), String::new());
+            item.add_color_group(ColorGroup::Basic, "A1".to_string());
+            item.set_purchased(&ColorGroup::Basic, added.and_then(Timestamp::parse));
+            item
+        };
+        let items = vec![
+            item(3001, Some("2024-05-20")),
+            item(3002, Some("2021-01-01")),
+            item(3003, Some("2020-01-01")),
+            item(3004, None),
+        ];
+
+        let mut ages = Ages::default();
+        for item in items.iter() {
+            ages.add(&item.get_locations()[0], now);
+        }
+        assert_eq!(ages.brackets, [1, 0, 0, 0, 2]);
+        assert_eq!(ages.unknown, 1);
+
+        let stale = get_stale_lots(&items, now, STALE_AGE);
+        let ids: Vec<String> = stale
+            .iter()
+            .map(|(item, _)| item.get_id().to_string())
+            .collect();
+        assert_eq!(ids, vec!["3003", "3002"]);
+    }
+}
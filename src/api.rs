@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::site::Site;
+use crate::types::PartId;
+
+const REBRICKABLE_URL: &str = "https://rebrickable.com/api/v3/lego";
+
+/// The wait before the first retry, doubled for every retry after it.
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The API key and limits for the API of a site.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ApiConfig {
+    /// Better kept in the secrets file, see `secret`, which takes precedence.
+    #[serde(default)]
+    key: Option<String>,
+    /// Requests are spread out to send at most this many a minute.
+    #[serde(default = "default_requests_per_minute")]
+    requests_per_minute: u32,
+    /// How many times a request is retried when the site is busy or cannot be reached.
+    #[serde(default = "default_max_retries")]
+    max_retries: u32,
+}
+
+fn default_requests_per_minute() -> u32 {
+    60
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            key: None,
+            requests_per_minute: default_requests_per_minute(),
+            max_retries: default_max_retries(),
+        }
+    }
+}
+
+impl ApiConfig {
+    pub fn has_key(&self) -> bool {
+        self.key.is_some()
+    }
+
+    pub fn set_key(&mut self, key: Option<String>) {
+        self.key = key;
+    }
+}
+
+/// Where an API key for the site can be made, for the sites whose API is used.
+pub fn get_key_page(site: Site) -> Option<&'static str> {
+    match site {
+        Site::Rebrickable => Some("https://rebrickable.com/api/"),
+        Site::Brickset => Some("https://brickset.com/tools/webservices/requestkey"),
+        Site::BrickLink => Some("https://www.bricklink.com/v2/api/register_consumer.page"),
+    }
+}
+
+/// Spaces out requests evenly to stay within a number of requests a minute.
+struct RateLimiter {
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_minute: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            last: None,
+        }
+    }
+
+    /// How long to wait at the given time before the next request may be sent.
+    fn get_wait(&self, now: Instant) -> Duration {
+        self.last.map_or(Duration::ZERO, |last| {
+            (last + self.interval).saturating_duration_since(now)
+        })
+    }
+
+    fn wait(&mut self) {
+        thread::sleep(self.get_wait(Instant::now()));
+        self.last = Some(Instant::now());
+    }
+}
+
+/// The wait before the given retry, starting from 0.
+fn get_backoff(retry: u32) -> Duration {
+    BASE_BACKOFF
+        .saturating_mul(2u32.saturating_pow(retry))
+        .min(MAX_BACKOFF)
+}
+
+/// Whether the request may succeed when it is sent again later.
+fn is_transient(status: u16) -> bool {
+    status == 429 || status >= 500
+}
+
+/// A part as given by Rebrickable.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct PartInfo {
+    pub part_num: String,
+    pub name: String,
+    #[serde(default)]
+    pub part_cat_id: Option<u32>,
+    #[serde(default)]
+    pub year_from: Option<i32>,
+    #[serde(default)]
+    pub year_to: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct PartColor {
+    color_name: String,
+}
+
+/// A page of results of a list, of which only the first is read.
+#[derive(Deserialize)]
+struct Page<T> {
+    results: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct PartCategory {
+    name: String,
+}
+
+pub fn parse<'a, T: Deserialize<'a>>(response: &'a str) -> Result<T> {
+    serde_json::from_str(response).map_err(|e| Error::ApiError { msg: e.to_string() })
+}
+
+/// A client for the API of Rebrickable or Brickset, which keeps to the rate limit of the config,
+/// retries with exponential backoff, and sends each request for a page only once per session.
+pub struct Client {
+    site: Site,
+    key: String,
+    max_retries: u32,
+    limiter: RateLimiter,
+    /// The responses by URL, where none means the site has no such page.
+    responses: HashMap<String, Option<String>>,
+}
+
+impl Client {
+    pub fn rebrickable(config: ApiConfig) -> Result<Self> {
+        Self::new(Site::Rebrickable, config)
+    }
+
+    pub fn brickset(config: ApiConfig) -> Result<Self> {
+        Self::new(Site::Brickset, config)
+    }
+
+    fn new(site: Site, config: ApiConfig) -> Result<Self> {
+        let key = config.key.ok_or_else(|| Error::ApiError {
+            msg: format!(
+                "there is no API key for {}, set one up with `tuibricks api-key {}`",
+                site.get_name(),
+                site.get_name()
+            ),
+        })?;
+        Ok(Self {
+            site,
+            key,
+            max_retries: config.max_retries,
+            limiter: RateLimiter::new(config.requests_per_minute),
+            responses: HashMap::new(),
+        })
+    }
+
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+
+    fn get(&mut self, url: &str) -> Result<Option<String>> {
+        if let Some(response) = self.responses.get(url) {
+            return Ok(response.clone());
+        }
+        let response = self.send(url, &[])?;
+        self.responses.insert(url.to_string(), response.clone());
+        Ok(response)
+    }
+
+    /// Posts the form to the URL, which is none if the site has no such page. Unlike pages, forms
+    /// are sent every time, and they keep what they contain, like keys, out of the logged URL.
+    pub fn post(&mut self, url: &str, form: &[(&str, &str)]) -> Result<Option<String>> {
+        self.send(url, form)
+    }
+
+    /// Sends a GET request, or a POST request with the form if it is not empty.
+    fn send(&mut self, url: &str, form: &[(&str, &str)]) -> Result<Option<String>> {
+        let mut retry = 0;
+        loop {
+            self.limiter.wait();
+            let mut request = if form.is_empty() {
+                ureq::get(url)
+            } else {
+                ureq::post(url)
+            };
+            request = request.set("Accept", "application/json");
+            if self.site == Site::Rebrickable {
+                request = request.set("Authorization", &format!("key {}", self.key));
+            }
+            let result = if form.is_empty() {
+                request.call()
+            } else {
+                request.send_form(form)
+            };
+            let backoff = match result {
+                Ok(response) => return Ok(Some(response.into_string()?)),
+                Err(ureq::Error::Status(404, _)) => return Ok(None),
+                Err(ureq::Error::Status(401 | 403, _)) => {
+                    return Err(Error::ApiError {
+                        msg: "the API key was not accepted".to_string(),
+                    })
+                }
+                Err(ureq::Error::Status(status, response))
+                    if is_transient(status) && retry < self.max_retries =>
+                {
+                    // Sites that are rate limiting may say how long to wait.
+                    response
+                        .header("Retry-After")
+                        .and_then(|secs| secs.parse().ok())
+                        .map_or(get_backoff(retry), Duration::from_secs)
+                }
+                Err(ureq::Error::Transport(_)) if retry < self.max_retries => get_backoff(retry),
+                Err(e) => return Err(Error::ApiError { msg: e.to_string() }),
+            };
+            log::info!("retrying {} in {:?}", url, backoff);
+            thread::sleep(backoff);
+            retry += 1;
+        }
+    }
+
+    /// Sends a small request to find out whether the key is accepted.
+    pub fn check_key(&mut self) -> Result<()> {
+        self.get(&format!("{}/colors/?page_size=1", REBRICKABLE_URL))?;
+        Ok(())
+    }
+
+    /// Looks up the part, which is none if Rebrickable does not know it. Groups are not parts.
+    pub fn get_part(&mut self, id: PartId) -> Result<Option<PartInfo>> {
+        let Some(number) = id.to_bricklink() else {
+            return Ok(None);
+        };
+        let Some(response) = self.get(&format!("{}/parts/{}/", REBRICKABLE_URL, number))? else {
+            return Ok(None);
+        };
+        Ok(Some(parse(&response)?))
+    }
+
+    /// The names of the colors the part was produced in, which is none if Rebrickable does not
+    /// know the part.
+    pub fn get_part_colors(&mut self, id: PartId) -> Result<Option<Vec<String>>> {
+        let Some(number) = id.to_bricklink() else {
+            return Ok(None);
+        };
+        let url = format!(
+            "{}/parts/{}/colors/?page_size=1000",
+            REBRICKABLE_URL, number
+        );
+        let Some(response) = self.get(&url)? else {
+            return Ok(None);
+        };
+        let page: Page<PartColor> = parse(&response)?;
+        Ok(Some(
+            page.results
+                .into_iter()
+                .map(|color| color.color_name)
+                .collect(),
+        ))
+    }
+
+    /// The name of the part category, which is looked up only once for all parts in it.
+    pub fn get_category(&mut self, id: u32) -> Result<Option<String>> {
+        let url = format!("{}/part_categories/{}/", REBRICKABLE_URL, id);
+        let Some(response) = self.get(&url)? else {
+            return Ok(None);
+        };
+        let category: PartCategory = parse(&response)?;
+        Ok(Some(category.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let mut limiter = RateLimiter::new(30);
+        let now = Instant::now();
+        assert_eq!(limiter.get_wait(now), Duration::ZERO);
+        limiter.last = Some(now);
+        assert_eq!(limiter.get_wait(now), Duration::from_secs(2));
+        assert_eq!(
+            limiter.get_wait(now + Duration::from_secs(3)),
+            Duration::ZERO
+        );
+
+        assert_eq!(get_backoff(0), Duration::from_secs(1));
+        assert_eq!(get_backoff(3), Duration::from_secs(8));
+        assert_eq!(get_backoff(40), MAX_BACKOFF);
+        assert!(is_transient(429) && is_transient(503) && !is_transient(404));
+    }
+}
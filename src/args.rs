@@ -0,0 +1,553 @@
+use std::path::PathBuf;
+
+use crate::date::Date;
+use crate::error::{Error, Result};
+use crate::filter::Filter;
+use crate::link::Link;
+use crate::mode::StartMode;
+use crate::site::Site;
+use crate::types::PartId;
+
+/// Commands that are run without starting the interactive program.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliCmd {
+    BundleExport {
+        path: PathBuf,
+    },
+    BundleImport {
+        path: PathBuf,
+        force: bool,
+    },
+    /// Reports problems in the database, and repairs those that can be repaired without asking.
+    Check {
+        repair: bool,
+    },
+    /// Writes printable labels for the locations as an HTML page.
+    Labels {
+        path: PathBuf,
+        qr_codes: bool,
+        force: bool,
+    },
+    /// Writes the inventory as a searchable HTML page that can be viewed without the program.
+    Viewer {
+        path: PathBuf,
+        images: bool,
+        force: bool,
+    },
+    /// Prints a line for each lot of the parts whose ID or name contains the text.
+    Search {
+        text: String,
+    },
+    /// Prints the link to the location and its QR code.
+    QrCode {
+        location: String,
+    },
+    /// Adds the parts received in an order to the database, resuming an interrupted import unless
+    /// forced to start over.
+    OrderImport {
+        path: PathBuf,
+        force: bool,
+    },
+    /// Writes the counted lots as a BrickStore file.
+    BrickStoreExport {
+        path: PathBuf,
+        force: bool,
+    },
+    /// Adds the lots of a BrickStore file to the database, storing the new ones in the location.
+    BrickStoreImport {
+        path: PathBuf,
+        location: String,
+    },
+    /// Adds the sets owned on Brickset to the config, and marks the sets of the config as owned on
+    /// Brickset if pushing.
+    BricksetSync {
+        push: bool,
+    },
+    /// Pushes the lots listed for sale to the store on BrickLink, previewing the changes if it is
+    /// a dry run.
+    StorePush,
+    /// Reports the sets missing their paper instructions, with links to the PDFs.
+    Instructions,
+    /// Adds the set with the inventory in the file to the config, replacing a set with the same
+    /// number.
+    SetImport {
+        number: String,
+        path: PathBuf,
+    },
+    /// Reports the changes going from the old to the new database, which is the current one if it
+    /// is not given.
+    Diff {
+        old: PathBuf,
+        new: Option<PathBuf>,
+        json: bool,
+    },
+    SnapshotCreate {
+        name: String,
+    },
+    SnapshotList,
+    /// Replaces the database with the snapshot.
+    SnapshotRollback {
+        name: String,
+    },
+    /// Lists the transactions made to the database, newest first.
+    History,
+    /// Undoes the last transaction, if the parts in it have not been changed since.
+    Undo,
+    /// Prints the weight of the lots of the parts matching the filter, and the shipping estimate.
+    Weight {
+        filter: String,
+    },
+    /// Asks for the API key of the site, checks it with the site and saves it in the secrets file.
+    ApiKey {
+        site: Site,
+    },
+    /// Looks up the part on Rebrickable.
+    Lookup {
+        id: PartId,
+    },
+    /// Looks up names and categories for the parts missing them.
+    Enrich,
+    /// Serves the database to other machines over HTTP.
+    Serve,
+    /// Pulls changes from the server and pushes changes made here.
+    Sync,
+    /// Writes the summary of the month, the current one if not given, as a text or HTML file, and
+    /// pipes it to the command in the config if sending it.
+    Summary {
+        path: PathBuf,
+        month: Option<Date>,
+        send: bool,
+        force: bool,
+    },
+    /// Writes a synthetic database with the given number of lots.
+    Generate {
+        lots: usize,
+        path: PathBuf,
+        force: bool,
+    },
+}
+
+impl CliCmd {
+    /// The words the command is given with, as named in errors.
+    pub fn get_name(&self) -> &'static str {
+        use CliCmd::*;
+        match self {
+            BundleExport { .. } => "bundle export",
+            BundleImport { .. } => "bundle import",
+            Check { .. } => "check",
+            Labels { .. } => "labels",
+            Viewer { .. } => "viewer",
+            Search { .. } => "search",
+            QrCode { .. } => "qr",
+            OrderImport { .. } => "order import",
+            BrickStoreExport { .. } => "brickstore export",
+            BrickStoreImport { .. } => "brickstore import",
+            BricksetSync { .. } => "brickset sync",
+            StorePush => "store push",
+            Instructions => "instructions",
+            SetImport { .. } => "set import",
+            Diff { .. } => "diff",
+            SnapshotCreate { .. } => "snapshot create",
+            SnapshotList => "snapshot list",
+            SnapshotRollback { .. } => "snapshot rollback",
+            History => "history",
+            Undo => "undo",
+            Weight { .. } => "weight",
+            ApiKey { .. } => "api-key",
+            Lookup { .. } => "lookup",
+            Enrich => "enrich",
+            Serve => "serve",
+            Sync => "sync",
+            Summary { .. } => "summary",
+            Generate { .. } => "generate",
+        }
+    }
+
+    /// Whether the command can change the database, its logs, the config, the secrets or the store
+    /// on BrickLink, so that it must be refused in read-only sessions, like `Cmd::is_mutating`.
+    pub fn is_mutating(&self) -> bool {
+        use CliCmd::*;
+        match self {
+            BundleImport { .. }
+            | OrderImport { .. }
+            | BrickStoreImport { .. }
+            | BricksetSync { .. }
+            | StorePush
+            | SetImport { .. }
+            | SnapshotRollback { .. }
+            | Undo
+            | ApiKey { .. }
+            | Enrich
+            | Serve
+            | Sync => true,
+            Check { repair } => *repair,
+            BundleExport { .. }
+            | Labels { .. }
+            | Viewer { .. }
+            | Search { .. }
+            | QrCode { .. }
+            | BrickStoreExport { .. }
+            | Instructions
+            | Diff { .. }
+            | SnapshotCreate { .. }
+            | SnapshotList
+            | History
+            | Weight { .. }
+            | Lookup { .. }
+            | Summary { .. }
+            | Generate { .. } => false,
+        }
+    }
+}
+
+/// Options given on the command line when launching the program.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    pub read_only: bool,
+    pub dry_run: bool,
+    pub watch: bool,
+    /// Writes plain lines for screen readers, see `accessible`.
+    pub accessible: bool,
+    /// Serves the API of the session, see `rest`.
+    pub api: bool,
+    pub cmd: Option<CliCmd>,
+    /// Where to start the interactive program, given as a link like `tuibricks://location/A3` or
+    /// with `--item` or `--filter`.
+    pub open: Option<Link>,
+    /// Who is making changes in place of the user in the config.
+    pub user: Option<String>,
+    /// The database used in place of the one in the config, for this run only.
+    pub db: Option<PathBuf>,
+    pub mode: Option<StartMode>,
+}
+
+impl Args {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self> {
+        let mut res = Args::default();
+        let mut force = false;
+        let mut repair = false;
+        let mut qr_codes = false;
+        let mut images = false;
+        let mut json = false;
+        let mut push = false;
+        let mut send = false;
+        let mut month = None;
+        let mut positional = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--item" => {
+                    let id = args.next().unwrap_or_default();
+                    let id = id.parse().ok().and_then(PartId::new);
+                    res.open = Some(Link::Item(id.ok_or(Error::UnknownArgument { arg })?));
+                }
+                "--filter" => {
+                    let filter = args.next().ok_or(Error::UnknownArgument { arg })?;
+                    Filter::parse(&filter)?;
+                    res.open = Some(Link::Filter(filter));
+                }
+                "--db" => res.db = Some(args.next().ok_or(Error::UnknownArgument { arg })?.into()),
+                "--mode" => {
+                    let name = args.next().unwrap_or_default();
+                    res.mode = Some(StartMode::parse(&name).ok_or(Error::UnknownArgument { arg })?);
+                }
+                "--user" => res.user = Some(args.next().ok_or(Error::UnknownArgument { arg })?),
+                "--read-only" => res.read_only = true,
+                "--dry-run" => res.dry_run = true,
+                "--watch" => res.watch = true,
+                "--accessible" => res.accessible = true,
+                "--api" => res.api = true,
+                "--force" => force = true,
+                "--repair" => repair = true,
+                "--qr" => qr_codes = true,
+                "--images" => images = true,
+                "--json" => json = true,
+                "--push" => push = true,
+                "--send" => send = true,
+                "--month" => {
+                    let given = args.next().unwrap_or_default();
+                    let first = Date::parse(&format!("{}-01", given.trim()), Date::today());
+                    month = Some(first.ok_or(Error::UnknownArgument { arg })?);
+                }
+                _ if arg.starts_with("--") => return Err(Error::UnknownArgument { arg }),
+                _ if Link::is_link(&arg) => {
+                    res.open = Some(Link::parse(&arg).ok_or(Error::UnknownArgument { arg })?)
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let positional: Vec<&str> = positional.iter().map(String::as_str).collect();
+        res.cmd = match positional.as_slice() {
+            [] => None,
+            ["bundle", "export", path] => Some(CliCmd::BundleExport { path: path.into() }),
+            ["bundle", "import", path] => Some(CliCmd::BundleImport {
+                path: path.into(),
+                force,
+            }),
+            ["check"] => Some(CliCmd::Check { repair }),
+            ["labels", path] => Some(CliCmd::Labels {
+                path: path.into(),
+                qr_codes,
+                force,
+            }),
+            ["viewer", path] => Some(CliCmd::Viewer {
+                path: path.into(),
+                images,
+                force,
+            }),
+            ["search", text] => Some(CliCmd::Search {
+                text: text.to_string(),
+            }),
+            ["qr", location] => Some(CliCmd::QrCode {
+                location: location.to_string(),
+            }),
+            ["order", "import", path] => Some(CliCmd::OrderImport {
+                path: path.into(),
+                force,
+            }),
+            ["brickstore", "export", path] => Some(CliCmd::BrickStoreExport {
+                path: path.into(),
+                force,
+            }),
+            ["brickstore", "import", path, location] => Some(CliCmd::BrickStoreImport {
+                path: path.into(),
+                location: location.to_string(),
+            }),
+            ["instructions"] => Some(CliCmd::Instructions),
+            ["brickset", "sync"] => Some(CliCmd::BricksetSync { push }),
+            ["store", "push"] => Some(CliCmd::StorePush),
+            ["set", "import", number, path] => Some(CliCmd::SetImport {
+                number: number.to_string(),
+                path: path.into(),
+            }),
+            ["diff", old] => Some(CliCmd::Diff {
+                old: old.into(),
+                new: None,
+                json,
+            }),
+            ["diff", old, new] => Some(CliCmd::Diff {
+                old: old.into(),
+                new: Some(new.into()),
+                json,
+            }),
+            ["snapshot", "create", name] => Some(CliCmd::SnapshotCreate {
+                name: name.to_string(),
+            }),
+            ["snapshot", "list"] => Some(CliCmd::SnapshotList),
+            ["snapshot", "rollback", name] => Some(CliCmd::SnapshotRollback {
+                name: name.to_string(),
+            }),
+            ["history"] => Some(CliCmd::History),
+            ["undo"] => Some(CliCmd::Undo),
+            ["weight", filter] => {
+                Filter::parse(filter)?;
+                Some(CliCmd::Weight {
+                    filter: filter.to_string(),
+                })
+            }
+            ["enrich"] => Some(CliCmd::Enrich),
+            ["api-key", "rebrickable"] => Some(CliCmd::ApiKey {
+                site: Site::Rebrickable,
+            }),
+            ["api-key", "brickset"] => Some(CliCmd::ApiKey {
+                site: Site::Brickset,
+            }),
+            ["api-key", "bricklink"] => Some(CliCmd::ApiKey {
+                site: Site::BrickLink,
+            }),
+            ["serve"] => Some(CliCmd::Serve),
+            ["sync"] => Some(CliCmd::Sync),
+            ["lookup", id] => Some(CliCmd::Lookup {
+                id: id.parse().ok().and_then(PartId::new).ok_or_else(|| {
+                    Error::UnknownArgument {
+                        arg: id.to_string(),
+                    }
+                })?,
+            }),
+            ["summary", path] => Some(CliCmd::Summary {
+                path: path.into(),
+                month,
+                send,
+                force,
+            }),
+            ["generate", lots, path] => Some(CliCmd::Generate {
+                lots: lots.parse().map_err(|_| Error::UnknownArgument {
+                    arg: lots.to_string(),
+                })?,
+                path: path.into(),
+                force,
+            }),
+            _ => {
+                return Err(Error::UnknownArgument {
+                    arg: positional.join(" "),
+                })
+            }
+        };
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args> {
+        Args::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]).unwrap(), Args::default());
+        assert!(parse(&["--read-only"]).unwrap().read_only);
+        assert!(parse(&["--api"]).unwrap().api);
+        assert_eq!(
+            parse(&["--user", "Alice"]).unwrap().user.as_deref(),
+            Some("Alice")
+        );
+        assert!(parse(&["--user"]).is_err());
+        let args = parse(&["--db", "other.yml", "--mode", "search"]).unwrap();
+        assert_eq!(args.db, Some(PathBuf::from("other.yml")));
+        assert_eq!(args.mode, Some(StartMode::Search));
+        assert!(parse(&["--mode", "wat"]).is_err());
+        assert!(parse(&["--db"]).is_err());
+        assert_eq!(
+            parse(&["bundle", "import", "b.yml", "--force"])
+                .unwrap()
+                .cmd,
+            Some(CliCmd::BundleImport {
+                path: "b.yml".into(),
+                force: true
+            })
+        );
+        assert!(parse(&["--wat"]).is_err());
+        assert!(parse(&["bundle"]).is_err());
+        assert_eq!(
+            parse(&["check", "--repair"]).unwrap().cmd,
+            Some(CliCmd::Check { repair: true })
+        );
+        assert_eq!(
+            parse(&["labels", "labels.html", "--qr"]).unwrap().cmd,
+            Some(CliCmd::Labels {
+                path: "labels.html".into(),
+                qr_codes: true,
+                force: false
+            })
+        );
+        assert_eq!(
+            parse(&["viewer", "parts.html", "--images"]).unwrap().cmd,
+            Some(CliCmd::Viewer {
+                path: "parts.html".into(),
+                images: true,
+                force: false
+            })
+        );
+        assert_eq!(
+            parse(&["order", "import", "1234.xml"]).unwrap().cmd,
+            Some(CliCmd::OrderImport {
+                path: "1234.xml".into(),
+                force: false
+            })
+        );
+        assert_eq!(
+            parse(&["brickstore", "export", "parts.bsx", "--force"])
+                .unwrap()
+                .cmd,
+            Some(CliCmd::BrickStoreExport {
+                path: "parts.bsx".into(),
+                force: true
+            })
+        );
+        assert_eq!(
+            parse(&["brickstore", "import", "parts.bsx", "Unsorted"])
+                .unwrap()
+                .cmd,
+            Some(CliCmd::BrickStoreImport {
+                path: "parts.bsx".into(),
+                location: "Unsorted".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&["tuibricks://location/A3"]).unwrap().open,
+            Some(Link::Location("A3".to_string()))
+        );
+        assert!(parse(&["tuibricks://nowhere/A3"]).is_err());
+        assert_eq!(
+            parse(&["--item", "3001"]).unwrap().open,
+            Some(Link::Item(PartId::new(3001).unwrap()))
+        );
+        assert!(parse(&["--item"]).is_err());
+        assert_eq!(
+            parse(&["--filter", "color=red"]).unwrap().open,
+            Some(Link::Filter("color=red".to_string()))
+        );
+        assert!(parse(&["--filter", "colour=red"]).is_err());
+        assert_eq!(
+            parse(&["diff", "backup.yml", "--json"]).unwrap().cmd,
+            Some(CliCmd::Diff {
+                old: "backup.yml".into(),
+                new: None,
+                json: true
+            })
+        );
+        assert_eq!(
+            parse(&["snapshot", "rollback", "before-big-import"])
+                .unwrap()
+                .cmd,
+            Some(CliCmd::SnapshotRollback {
+                name: "before-big-import".to_string()
+            })
+        );
+        assert_eq!(parse(&["undo"]).unwrap().cmd, Some(CliCmd::Undo));
+        assert!(parse(&["generate", "many", "db.yml"]).is_err());
+        assert_eq!(
+            parse(&["lookup", "3001"]).unwrap().cmd,
+            Some(CliCmd::Lookup {
+                id: PartId::new(3001).unwrap()
+            })
+        );
+        assert!(parse(&["lookup", "brick"]).is_err());
+        assert_eq!(parse(&["sync"]).unwrap().cmd, Some(CliCmd::Sync));
+        assert_eq!(
+            parse(&["api-key", "rebrickable"]).unwrap().cmd,
+            Some(CliCmd::ApiKey {
+                site: Site::Rebrickable
+            })
+        );
+        assert_eq!(
+            parse(&["api-key", "bricklink"]).unwrap().cmd,
+            Some(CliCmd::ApiKey {
+                site: Site::BrickLink
+            })
+        );
+        assert_eq!(
+            parse(&["store", "push"]).unwrap().cmd,
+            Some(CliCmd::StorePush)
+        );
+        assert_eq!(
+            parse(&["instructions"]).unwrap().cmd,
+            Some(CliCmd::Instructions)
+        );
+        assert_eq!(
+            parse(&["brickset", "sync", "--push"]).unwrap().cmd,
+            Some(CliCmd::BricksetSync { push: true })
+        );
+        assert_eq!(
+            parse(&["search", "3001"]).unwrap().cmd,
+            Some(CliCmd::Search {
+                text: "3001".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&["summary", "may.html", "--month", "2024-05", "--send"])
+                .unwrap()
+                .cmd,
+            Some(CliCmd::Summary {
+                path: "may.html".into(),
+                month: Date::new(2024, 5, 1),
+                send: true,
+                force: false
+            })
+        );
+        assert!(parse(&["summary", "may.txt", "--month", "May"]).is_err());
+    }
+}
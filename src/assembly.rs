@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use crate::data::{ColorGroup, Item};
+use crate::error::{Error, Result};
+use crate::types::{PartId, Quantity};
+
+/// Parses components written like `3482 x 1, 3483 x 2`, where a part without a count is needed
+/// once. Empty input means the part is not an assembly.
+pub fn parse(s: &str) -> Option<BTreeMap<PartId, u32>> {
+    let mut components = BTreeMap::new();
+    for component in s.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        let (id, count) = match component.split_once('x') {
+            Some((id, count)) => (id.trim(), count.trim().parse().ok()?),
+            None => (component, 1),
+        };
+        let id = id.parse().ok().and_then(PartId::new)?;
+        if count == 0 {
+            return None;
+        }
+        *components.entry(id).or_insert(0) += count;
+    }
+    Some(components)
+}
+
+pub fn fmt(components: &BTreeMap<PartId, u32>) -> String {
+    let components: Vec<String> = components
+        .iter()
+        .map(|(id, count)| format!("{} x {}", id, count))
+        .collect();
+    components.join(", ")
+}
+
+/// The index of the lot of the item that pieces in the color group are taken from or put in, which
+/// is the first lot in the same color group, or the only lot of the item.
+fn find_lot(item: &Item, color_group: &ColorGroup) -> Option<usize> {
+    let lots = item.get_locations();
+    lots.iter()
+        .position(|lot| lot.get_color_group() == color_group)
+        .or_else(|| (lots.len() == 1).then_some(0))
+}
+
+/// Adds the number of pieces, which may be negative, to the lot of the item.
+fn add_pieces(item: &mut Item, color_group: &ColorGroup, change: i64) -> Result<()> {
+    let id = item.get_id();
+    let i = find_lot(item, color_group).ok_or(Error::NoLotInColorGroup { part_id: id })?;
+    let quantity = match item.get_locations()[i].get_quantity() {
+        // Pieces can be put in uncounted lots, but not taken from them.
+        None if change >= 0 => None,
+        quantity => {
+            let owned = quantity.map_or(0, |q| q.get() as i64);
+            if owned + change < 0 {
+                return Err(Error::NotEnoughPieces {
+                    part_id: id,
+                    needed: -change as u32,
+                });
+            }
+            Some(Quantity::new((owned + change) as u32))
+        }
+    };
+    item.set_lot_quantity(i, quantity);
+    Ok(())
+}
+
+/// Returns the assembly and its components after taking apart the number of assemblies in the
+/// color group, or putting them together from their components if `assemble`. Nothing is changed
+/// unless all the pieces needed are there.
+pub fn get_changes<'a>(
+    assembly: &Item,
+    color_group: &ColorGroup,
+    quantity: u32,
+    assemble: bool,
+    find: impl Fn(PartId) -> Result<&'a Item>,
+) -> Result<Vec<Item>> {
+    let sign = if assemble { 1 } else { -1 };
+    let mut updated = assembly.clone();
+    add_pieces(&mut updated, color_group, sign * quantity as i64)?;
+    let mut changes = vec![updated];
+    for (id, count) in assembly.get_components() {
+        let mut component = find(*id)?.clone();
+        let change = -sign * (quantity * count) as i64;
+        add_pieces(&mut component, color_group, change)?;
+        changes.push(component);
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_changes() {
+        let id = |id| PartId::new(id).unwrap();
+        let components = parse("3482, 3483 x 2").unwrap();
+        assert_eq!(fmt(&components), "3482 x 1, 3483 x 2");
+        assert_eq!(parse(""), Some(BTreeMap::new()));
+        assert!(parse("3482 x 0").is_none() && parse("wheel").is_none());
+
+        let item = |number, quantity: Option<u32>| {
+            let mut item = Item::new(id(number), number.to_string());
+            item.add_color_group(ColorGroup::Grey, "A1".to_string());
+            item.set_quantity(&ColorGroup::Grey, quantity.map(Quantity::new));
+            item
+        };
+        let mut assembly = item(32000, Some(3));
+        assembly.set_components(components);
+        let parts = [item(3482, Some(1)), item(3483, None)];
+        let find = |part_id| {
+            parts
+                .iter()
+                .find(|item| item.get_id() == part_id)
+                .ok_or(Error::PartNotFoundId { part_id })
+        };
+        let count = |item: &Item| item.get_quantity().get();
+
+        let changes = get_changes(&assembly, &ColorGroup::Grey, 2, false, find).unwrap();
+        assert_eq!(changes.iter().map(count).collect::<Vec<_>>(), vec![1, 3, 0]);
+        assert_eq!(changes[2].get_locations()[0].get_quantity(), None);
+
+        // The uncounted tires can not be taken to put assemblies together.
+        assert!(matches!(
+            get_changes(&assembly, &ColorGroup::Grey, 1, true, find),
+            Err(Error::NotEnoughPieces { .. })
+        ));
+        assert!(get_changes(&assembly, &ColorGroup::Grey, 4, false, find).is_err());
+
+        // Pieces are only taken from the first lot of the color group.
+        let mut lots = assembly.get_locations().to_vec();
+        lots.push(lots[0].clone());
+        assembly.set_locations(lots);
+        let changes = get_changes(&assembly, &ColorGroup::Grey, 2, false, find).unwrap();
+        let lots = changes[0].get_locations();
+        assert_eq!(lots[0].get_quantity(), Some(Quantity::new(1)));
+        assert_eq!(lots[1].get_quantity(), Some(Quantity::new(3)));
+    }
+}
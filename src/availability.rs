@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use crate::api::{ApiConfig, Client};
+use crate::error::Error;
+use crate::locale::tr;
+use crate::types::PartId;
+
+/// At most this many of the colors a part comes in are listed in a warning.
+const MAX_LISTED: usize = 8;
+
+/// Checks whether parts were produced in the colors they are given, by the colors Rebrickable
+/// lists for them. Each part is looked up once a session, and the checks stop once Rebrickable
+/// cannot be reached, so that entering parts is not held up.
+pub struct Checker {
+    client: Option<Client>,
+    /// The colors of the parts looked up, where none means Rebrickable does not know the part.
+    colors: HashMap<PartId, Option<Vec<String>>>,
+}
+
+impl Checker {
+    /// Checks nothing if there is no API key for Rebrickable.
+    pub fn new(config: ApiConfig) -> Self {
+        Self {
+            client: Client::rebrickable(config).ok(),
+            colors: HashMap::new(),
+        }
+    }
+
+    /// The colors the part comes in if it is known not to come in the color.
+    pub fn get_other_colors(&mut self, id: PartId, color: &str) -> Option<&[String]> {
+        if !self.colors.contains_key(&id) {
+            let client = self.client.as_mut()?;
+            match client.get_part_colors(id) {
+                Ok(colors) => {
+                    self.colors.insert(id, colors);
+                }
+                Err(e) => {
+                    log::warn!("not checking part colors any more: {}", e);
+                    if !matches!(e, Error::ApiError { .. }) {
+                        self.client = None;
+                    }
+                    return None;
+                }
+            }
+        }
+        let colors = self.colors.get(&id)?.as_deref()?;
+        (!is_produced(colors, color)).then_some(colors)
+    }
+}
+
+/// Color names are compared leaving out case, spaces and dashes, and taking grey for gray.
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+        .replace("grey", "gray")
+}
+
+fn is_produced(colors: &[String], color: &str) -> bool {
+    let color = normalize(color);
+    colors.iter().any(|produced| normalize(produced) == color)
+}
+
+/// Warns that the part was never produced in the color, listing colors it does come in.
+pub fn describe(id: PartId, color: &str, colors: &[String]) -> String {
+    let mut listed: Vec<&str> = colors.iter().take(MAX_LISTED).map(String::as_str).collect();
+    if colors.len() > MAX_LISTED {
+        listed.push("...");
+    }
+    format!(
+        "{}\n{}",
+        tr!(
+            "Part {} was never produced in {}, according to Rebrickable.",
+            id,
+            color
+        ),
+        tr!("It comes in: {}", listed.join(", "))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_produced() {
+        let colors = vec![
+            "Olive Green".to_string(),
+            "Light Bluish Gray".to_string(),
+            "Trans-Clear".to_string(),
+        ];
+        assert!(is_produced(&colors, "olive green"));
+        assert!(is_produced(&colors, "Light Bluish Grey"));
+        assert!(is_produced(&colors, "Trans Clear"));
+        assert!(!is_produced(&colors, "Sand Green"));
+        assert_eq!(
+            describe(PartId::new(3001).unwrap(), "Sand Green", &colors),
+            "Part 3001 was never produced in Sand Green, according to Rebrickable.\n\
+            It comes in: Olive Green, Light Bluish Gray, Trans-Clear"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+//! Entry points for the benchmarks in benches/, which can only use the public API of the crate.
+
+pub use crate::data::{write_items, Database, Item, RawDatabase};
+pub use crate::generate::generate;
+pub use crate::mode::Mode;
@@ -0,0 +1,249 @@
+use serde::Deserialize;
+
+use crate::api::{self, Client};
+use crate::error::{Error, Result};
+use crate::set::Set;
+use crate::types::Years;
+
+const BRICKSET_URL: &str = "https://brickset.com/api/v3.asmx";
+
+/// The most sets Brickset gives in one page.
+const PAGE_SIZE: usize = 500;
+
+/// Every answer of Brickset says whether the request succeeded, and why not if it did not.
+#[derive(Deserialize)]
+struct Status {
+    status: String,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Login {
+    hash: String,
+}
+
+/// A set as given by Brickset.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BricksetSet {
+    #[serde(rename = "setID")]
+    pub set_id: u32,
+    pub number: String,
+    pub number_variant: u32,
+    pub name: String,
+    #[serde(default)]
+    pub year: Option<u16>,
+}
+
+#[derive(Deserialize)]
+struct Sets {
+    #[serde(default)]
+    matches: usize,
+    #[serde(default)]
+    sets: Vec<BricksetSet>,
+}
+
+impl BricksetSet {
+    /// The number with the variant, as set numbers are written in the config, e.g. "10265-1".
+    pub fn get_number(&self) -> String {
+        format!("{}-{}", self.number, self.number_variant)
+    }
+}
+
+/// Set numbers without a variant stand for the first variant.
+fn with_variant(number: &str) -> String {
+    let number = number.trim();
+    if number.contains('-') {
+        number.to_string()
+    } else {
+        format!("{}-1", number)
+    }
+}
+
+/// Calls the method of the API with the key of the client and the fields.
+fn call(client: &mut Client, method: &str, fields: &[(&str, &str)]) -> Result<String> {
+    let key = client.get_key().to_string();
+    let mut form = vec![("apiKey", key.as_str())];
+    form.extend_from_slice(fields);
+    let url = format!("{}/{}", BRICKSET_URL, method);
+    let response = client.post(&url, &form)?.ok_or_else(|| Error::ApiError {
+        msg: format!("Brickset has no method {}", method),
+    })?;
+    let status: Status = api::parse(&response)?;
+    if status.status != "success" {
+        return Err(Error::ApiError {
+            msg: status.message,
+        });
+    }
+    Ok(response)
+}
+
+/// Sends a small request to find out whether the key is accepted.
+pub fn check_key(client: &mut Client) -> Result<()> {
+    call(client, "checkKey", &[])?;
+    Ok(())
+}
+
+/// Logs in as the user, returning the hash that stands for the user in later requests.
+pub fn login(client: &mut Client, username: &str, password: &str) -> Result<String> {
+    let response = call(
+        client,
+        "login",
+        &[("username", username), ("password", password)],
+    )?;
+    let login: Login = api::parse(&response)?;
+    Ok(login.hash)
+}
+
+fn get_sets(client: &mut Client, user_hash: &str, params: serde_json::Value) -> Result<Sets> {
+    let params = params.to_string();
+    let response = call(
+        client,
+        "getSets",
+        &[("userHash", user_hash), ("params", &params)],
+    )?;
+    api::parse(&response)
+}
+
+/// The sets the user owns on Brickset, read page by page.
+pub fn get_owned_sets(client: &mut Client, user_hash: &str) -> Result<Vec<BricksetSet>> {
+    let mut owned = Vec::new();
+    for page in 1.. {
+        let params = serde_json::json!({
+            "owned": 1,
+            "pageSize": PAGE_SIZE,
+            "pageNumber": page,
+        });
+        let mut sets = get_sets(client, user_hash, params)?;
+        let done = sets.sets.len() < PAGE_SIZE || owned.len() + sets.sets.len() >= sets.matches;
+        owned.append(&mut sets.sets);
+        if done {
+            break;
+        }
+    }
+    Ok(owned)
+}
+
+/// Looks up the set by its number, which is none if Brickset does not know it.
+pub fn find_set(client: &mut Client, user_hash: &str, number: &str) -> Result<Option<BricksetSet>> {
+    let params = serde_json::json!({ "setNumber": with_variant(number) });
+    Ok(get_sets(client, user_hash, params)?.sets.into_iter().next())
+}
+
+/// Marks the set as owned by the user on Brickset.
+pub fn set_owned(client: &mut Client, user_hash: &str, set_id: u32) -> Result<()> {
+    let set_id = set_id.to_string();
+    let params = serde_json::json!({ "own": 1 }).to_string();
+    call(
+        client,
+        "setCollection",
+        &[
+            ("userHash", user_hash),
+            ("setID", &set_id),
+            ("params", &params),
+        ],
+    )?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct InstructionsList {
+    #[serde(default)]
+    instructions: Vec<InstructionsPdf>,
+}
+
+#[derive(Deserialize)]
+struct InstructionsPdf {
+    #[serde(rename = "URL")]
+    url: String,
+}
+
+/// The links to the PDFs of the instructions of the set, of which large sets have several.
+pub fn get_instructions(client: &mut Client, number: &str) -> Result<Vec<String>> {
+    let number = with_variant(number);
+    let response = call(client, "getInstructions2", &[("setNumber", &number)])?;
+    let list: InstructionsList = api::parse(&response)?;
+    Ok(list.instructions.into_iter().map(|pdf| pdf.url).collect())
+}
+
+fn is_same_set(set: &Set, brickset_set: &BricksetSet) -> bool {
+    with_variant(set.get_number()) == brickset_set.get_number()
+}
+
+/// The sets owned on Brickset that are not in the config, as sets without an inventory.
+pub fn get_new_sets(owned: &[BricksetSet], sets: &[Set]) -> Vec<Set> {
+    owned
+        .iter()
+        .filter(|owned| !sets.iter().any(|set| is_same_set(set, owned)))
+        .map(|owned| {
+            let mut set = Set::new(owned.get_number(), owned.name.clone());
+            set.set_produced(owned.year.map(|year| Years::new(year, year)));
+            set
+        })
+        .collect()
+}
+
+/// The sets in the config without years of release that Brickset knows, with the years filled in.
+pub fn get_dated_sets(owned: &[BricksetSet], sets: &[Set]) -> Vec<Set> {
+    sets.iter()
+        .filter(|set| set.get_produced().is_none())
+        .filter_map(|set| {
+            let year = owned
+                .iter()
+                .find(|owned| is_same_set(set, owned))
+                .and_then(|owned| owned.year)?;
+            let mut set = set.clone();
+            set.set_produced(Some(Years::new(year, year)));
+            Some(set)
+        })
+        .collect()
+}
+
+/// The sets in the config that are not owned on Brickset.
+pub fn get_unowned<'a>(owned: &[BricksetSet], sets: &'a [Set]) -> Vec<&'a Set> {
+    sets.iter()
+        .filter(|set| !owned.iter().any(|owned| is_same_set(set, owned)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owned_sets() {
+        let response = r#"{"status":"success","matches":2,"sets":[
+            {"setID":2642,"number":"6020","numberVariant":1,"name":"Magic Shop","year":1993},
+            {"setID":31582,"number":"10265","numberVariant":1,"name":"Ford Mustang","year":2019}
+        ]}"#;
+        let sets: Sets = api::parse(response).unwrap();
+        assert_eq!(sets.matches, 2);
+        assert_eq!(sets.sets[1].get_number(), "10265-1");
+
+        let config_sets = vec![
+            Set::new("10265".to_string(), "Ford Mustang".to_string()),
+            Set::new("42083-1".to_string(), "Bugatti Chiron".to_string()),
+        ];
+        let new_sets = get_new_sets(&sets.sets, &config_sets);
+        let mut magic_shop = Set::new("6020-1".to_string(), "Magic Shop".to_string());
+        magic_shop.set_produced(Some(Years::new(1993, 1993)));
+        assert_eq!(new_sets, vec![magic_shop]);
+        let dated = get_dated_sets(&sets.sets, &config_sets);
+        assert_eq!(dated.len(), 1);
+        assert_eq!(dated[0].get_produced(), Some(Years::new(2019, 2019)));
+        assert!(get_dated_sets(&sets.sets, &dated).is_empty());
+        let unowned = get_unowned(&sets.sets, &config_sets);
+        assert_eq!(unowned, vec![&config_sets[1]]);
+
+        let response = r#"{"status":"success","matches":1,"instructions":[
+            {"URL":"https://example.com/4106520.pdf","description":"BI 3017/48"}
+        ]}"#;
+        let list: InstructionsList = api::parse(response).unwrap();
+        assert_eq!(list.instructions[0].url, "https://example.com/4106520.pdf");
+
+        let error = r#"{"status":"error","message":"Invalid API key"}"#;
+        let status: Status = api::parse(error).unwrap();
+        assert_eq!(status.message, "Invalid API key");
+    }
+}
@@ -0,0 +1,507 @@
+use std::fs;
+use std::path::Path;
+
+use strum::IntoEnumIterator;
+
+use crate::color;
+use crate::currency::{self, Amount, Price};
+use crate::data::{ColorGroup, Condition, Item, Lot};
+use crate::error::{Error, Result};
+use crate::locale::tr;
+use crate::order::get_tag;
+use crate::types::{ColorId, PartId, Quantity};
+
+const fn color(id: u32, name: &'static str) -> (ColorId, &'static str) {
+    (ColorId::new(id).unwrap(), name)
+}
+
+/// The BrickLink IDs of common colors, by which BrickStore knows the color of a lot.
+const COLORS: &[(ColorId, &str)] = &[
+    color(1, "White"),
+    color(2, "Tan"),
+    color(3, "Yellow"),
+    color(4, "Orange"),
+    color(5, "Red"),
+    color(6, "Green"),
+    color(7, "Blue"),
+    color(8, "Brown"),
+    color(9, "Light Gray"),
+    color(10, "Dark Gray"),
+    color(11, "Black"),
+    color(12, "Trans-Clear"),
+    color(13, "Trans-Black"),
+    color(14, "Trans-Dark Blue"),
+    color(15, "Trans-Light Blue"),
+    color(17, "Trans-Red"),
+    color(19, "Trans-Yellow"),
+    color(20, "Trans-Green"),
+    color(23, "Pink"),
+    color(24, "Purple"),
+    color(28, "Nougat"),
+    color(34, "Lime"),
+    color(36, "Bright Green"),
+    color(42, "Medium Blue"),
+    color(47, "Dark Pink"),
+    color(59, "Dark Red"),
+    color(63, "Dark Blue"),
+    color(68, "Dark Orange"),
+    color(69, "Dark Tan"),
+    color(80, "Dark Green"),
+    color(85, "Dark Bluish Gray"),
+    color(86, "Light Bluish Gray"),
+    color(88, "Reddish Brown"),
+    color(89, "Dark Purple"),
+    color(98, "Trans-Orange"),
+    color(103, "Bright Light Yellow"),
+    color(104, "Bright Pink"),
+    color(110, "Bright Light Orange"),
+    color(120, "Dark Brown"),
+    color(153, "Dark Azure"),
+    color(156, "Medium Azure"),
+];
+
+/// The color of lots that hold all colors, like those of stickers.
+const NOT_APPLICABLE: (ColorId, &str) = color(0, "(Not Applicable)");
+
+/// A lot of a BrickStore or BrickStock inventory file (.bsx).
+#[derive(Debug, PartialEq, Clone)]
+pub struct BsxLot {
+    id: PartId,
+    name: String,
+    color_group: ColorGroup,
+    quantity: u32,
+    /// The price per piece the lot is sold for.
+    price: Option<Price>,
+    condition: Option<Condition>,
+    remarks: Option<String>,
+    /// The location of lots exported from here, kept in the marker of the lot, which BrickStore
+    /// does not upload to BrickLink.
+    location: Option<String>,
+}
+
+/// Replaces the escaped characters of XML text with the characters themselves.
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Returns the value of the attribute in the first element with the tag, e.g. the currency of
+/// `<Inventory Currency="EUR">`.
+fn get_attribute<'a>(contents: &'a str, tag: &str, attribute: &str) -> Option<&'a str> {
+    let start = contents.find(&format!("<{} ", tag))?;
+    let element = &contents[start..start + contents[start..].find('>')?];
+    let value_start = element.find(&format!("{}=\"", attribute))? + attribute.len() + 2;
+    let value_end = value_start + element[value_start..].find('"')?;
+    Some(&element[value_start..value_end])
+}
+
+/// The color group of a BrickStore color, given by name or by ID if the file has no name. The
+/// color groups that are not colors are written by their name with no color.
+fn to_color_group(name: Option<&str>, id: Option<&str>) -> ColorGroup {
+    let name = name.filter(|name| !name.is_empty()).or_else(|| {
+        let id = ColorId::parse(id?)?;
+        COLORS
+            .iter()
+            .chain([&NOT_APPLICABLE])
+            .find(|(color_id, _)| *color_id == id)
+            .map(|(_, name)| *name)
+    });
+    match name {
+        None => ColorGroup::All,
+        Some(name) if name == NOT_APPLICABLE.1 => ColorGroup::All,
+        Some(name) => ColorGroup::iter()
+            .find(|group| format!("{:?}", group) == name)
+            .unwrap_or_else(|| ColorGroup::Other(color::resolve(name).to_string())),
+    }
+}
+
+/// The BrickStore color of a color group, by its ID if it is known and its name.
+pub fn from_color_group(color_group: &ColorGroup) -> (Option<ColorId>, String) {
+    match color_group {
+        ColorGroup::All => (Some(NOT_APPLICABLE.0), NOT_APPLICABLE.1.to_string()),
+        ColorGroup::Other(name) => {
+            let name = color::resolve(name);
+            let id = COLORS
+                .iter()
+                .find(|(_, color)| color.eq_ignore_ascii_case(name))
+                .map(|(id, _)| *id);
+            (id, name.to_string())
+        }
+        group => (None, format!("{:?}", group)),
+    }
+}
+
+fn to_condition(code: Option<&str>) -> Option<Condition> {
+    match code {
+        Some("N") => Some(Condition::New),
+        Some("U") => Some(Condition::Used),
+        _ => None,
+    }
+}
+
+fn from_condition(condition: Condition) -> &'static str {
+    match condition {
+        Condition::New => "N",
+        Condition::Used => "U",
+    }
+}
+
+impl BsxLot {
+    /// The lot as it is exported, with its price in the home currency. Lots that are not counted
+    /// are exported with their estimated pieces, and can not be exported if there is no estimate.
+    fn from_lot(item: &Item, lot: &Lot) -> Option<Self> {
+        let quantity = lot
+            .get_quantity()
+            .or_else(|| item.get_estimated_quantity(lot))?;
+        Some(Self {
+            id: item.get_id(),
+            name: item.get_name().clone(),
+            color_group: lot.get_color_group().clone(),
+            quantity: quantity.get(),
+            price: lot
+                .get_asking_price()
+                .and_then(|price| price.to_home_currency()),
+            condition: lot.get_condition(),
+            remarks: lot.get_notes().map(str::to_string),
+            location: Some(lot.get_location().clone()),
+        })
+    }
+
+    /// The lot as an item of a BrickStore file, which groups can not be as they have no BrickLink
+    /// number.
+    fn to_xml(&self) -> Option<String> {
+        let (color_id, color_name) = from_color_group(&self.color_group);
+        let mut fields = vec![
+            ("ItemID", self.id.to_bricklink()?),
+            ("ItemTypeID", "P".to_string()),
+        ];
+        if let Some(color_id) = color_id {
+            fields.push(("ColorID", color_id.to_string()));
+        }
+        fields.push(("ItemName", self.name.clone()));
+        fields.push(("ColorName", color_name));
+        fields.push(("Qty", self.quantity.to_string()));
+        if let Some(price) = &self.price {
+            fields.push(("Price", format!("{:.3}", f64::from(price.get_amount()))));
+        }
+        if let Some(condition) = self.condition {
+            fields.push(("Condition", from_condition(condition).to_string()));
+        }
+        if let Some(remarks) = &self.remarks {
+            fields.push(("Remarks", remarks.clone()));
+        }
+        if let Some(location) = &self.location {
+            fields.push(("MarkerText", location.clone()));
+        }
+
+        let mut xml = "  <Item>\n".to_string();
+        for (tag, value) in fields {
+            xml.push_str(&format!("   <{}>{}</{}>\n", tag, escape(&value), tag));
+        }
+        xml.push_str("  </Item>\n");
+        Some(xml)
+    }
+}
+
+/// Parses the lots of a BrickStore file. Only parts can be imported, so other items like sets and
+/// minifigures are skipped, as are parts with IDs that are not numbers. Returns the lots and a
+/// message for each skipped item.
+fn parse(contents: &str) -> Result<(Vec<BsxLot>, Vec<String>)> {
+    let currency = get_attribute(contents, "Inventory", "Currency")
+        .unwrap_or_else(|| currency::get_home_currency())
+        .to_string();
+
+    let mut lots = Vec::new();
+    let mut skipped = Vec::new();
+    for element in contents.split("<Item>").skip(1) {
+        let item_id = get_tag(element, "ItemID").unwrap_or_default();
+        let name = unescape(get_tag(element, "ItemName").unwrap_or_default());
+        if get_tag(element, "ItemTypeID").is_some_and(|item_type| item_type != "P") {
+            skipped.push(tr!("{} {} is not a part", item_id, name));
+            continue;
+        }
+        let Some(id) = item_id.parse().ok().and_then(PartId::new) else {
+            skipped.push(tr!(
+                "Part {} {} has an ID that is not a number",
+                item_id,
+                name
+            ));
+            continue;
+        };
+        let quantity = get_tag(element, "Qty").unwrap_or_default();
+        let quantity = Quantity::parse(quantity)
+            .map(|quantity| quantity.get())
+            .ok_or_else(|| Error::InvalidBrickStore {
+                msg: format!("{} is not a valid number of pieces", quantity),
+            })?;
+        let color_name = get_tag(element, "ColorName").map(unescape);
+        let text = |tag| {
+            get_tag(element, tag)
+                .filter(|text| !text.is_empty())
+                .map(unescape)
+        };
+        lots.push(BsxLot {
+            id,
+            name,
+            color_group: to_color_group(color_name.as_deref(), get_tag(element, "ColorID")),
+            quantity,
+            price: get_tag(element, "Price")
+                .and_then(Amount::parse)
+                .filter(|amount| *amount != Amount::from_cents(0))
+                .map(|amount| Price::new(amount, &currency)),
+            condition: to_condition(get_tag(element, "Condition")),
+            remarks: text("Remarks"),
+            location: text("MarkerText"),
+        });
+    }
+    Ok((lots, skipped))
+}
+
+/// Reads the lots of a BrickStore file, and a message for each item that can not be imported.
+pub fn read(path: &Path) -> Result<(Vec<BsxLot>, Vec<String>)> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/// Puts the pieces of the BrickStore lots into lots of their colors, adding the parts that are not
+/// in the database yet. The remarks of a lot become its notes, and the price its asking price.
+///
+/// Lots exported from here replace the lot they were exported from, so that changes made in
+/// BrickStore can be brought back. Other lots are added to the lot in the given location, where
+/// the condition is only kept if the lots agree on it. Returns how many lots were imported.
+pub fn import(bsx_lots: &[BsxLot], items: &mut Vec<Item>, location: &str) -> usize {
+    for bsx_lot in bsx_lots.iter() {
+        let index = items.iter().position(|item| {
+            item.get_id() == bsx_lot.id || item.get_alternative_ids().contains(&bsx_lot.id)
+        });
+        let item = match index {
+            Some(index) => &mut items[index],
+            None => {
+                items.push(Item::new(bsx_lot.id, bsx_lot.name.clone()));
+                items.last_mut().unwrap()
+            }
+        };
+
+        let replace = bsx_lot.location.is_some();
+        let location = bsx_lot.location.as_deref().unwrap_or(location);
+        let mut lots = item.get_locations().to_vec();
+        let existing = lots.iter().position(|lot| {
+            *lot.get_color_group() == bsx_lot.color_group && lot.get_location() == location
+        });
+        let lot = match existing {
+            Some(index) if replace => {
+                let lot = &mut lots[index];
+                lot.set_quantity(Some(Quantity::new(bsx_lot.quantity)));
+                lot.set_condition(bsx_lot.condition);
+                lot.set_notes(bsx_lot.remarks.clone());
+                lot.set_asking_price(bsx_lot.price.clone());
+                lot
+            }
+            Some(index) => {
+                let lot = &mut lots[index];
+                let quantity = lot.get_quantity().map(|q| q.get()).unwrap_or(0);
+                lot.set_quantity(Some(Quantity::new(quantity + bsx_lot.quantity)));
+                if lot.get_condition() != bsx_lot.condition {
+                    lot.set_condition(None);
+                }
+                lot
+            }
+            None => {
+                let mut lot = Lot::new(bsx_lot.color_group.clone(), location.to_string());
+                lot.set_quantity(Some(Quantity::new(bsx_lot.quantity)));
+                lot.set_condition(bsx_lot.condition);
+                if replace {
+                    lot.set_notes(bsx_lot.remarks.clone());
+                    lot.set_asking_price(bsx_lot.price.clone());
+                }
+                lots.push(lot);
+                lots.last_mut().unwrap()
+            }
+        };
+        if replace {
+            item.set_locations(lots);
+            continue;
+        }
+        if let Some(remarks) = &bsx_lot.remarks {
+            let notes = match lot.get_notes() {
+                Some(notes) if notes.contains(remarks.as_str()) => notes.to_string(),
+                Some(notes) => format!("{}; {}", notes, remarks),
+                None => remarks.clone(),
+            };
+            lot.set_notes(Some(notes));
+        }
+        if lot.get_asking_price().is_none() {
+            lot.set_asking_price(bsx_lot.price.clone());
+        }
+        item.set_locations(lots);
+    }
+    bsx_lots.len()
+}
+
+/// Writes the lots of the items as a BrickStore file, with prices in the home currency. Returns
+/// the file and a message for each lot that could not be exported or that BrickStore will not
+/// know the color of.
+pub fn export(items: &[Item]) -> (String, Vec<String>) {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <!DOCTYPE BrickStoreXML>\n\
+        <BrickStoreXML>\n \
+        <Inventory Currency=\"{}\">\n",
+        escape(currency::get_home_currency())
+    );
+    let mut skipped = Vec::new();
+    for item in items.iter() {
+        if item.is_group() {
+            skipped.push(tr!(
+                "Group {} is not a part on BrickLink, it is left out",
+                item.get_id()
+            ));
+            continue;
+        }
+        for lot in item.get_locations() {
+            let Some(bsx_lot) = BsxLot::from_lot(item, lot) else {
+                skipped.push(tr!(
+                    "The pieces of part {} in {} are not counted, it is left out",
+                    item.get_id(),
+                    lot
+                ));
+                continue;
+            };
+            if from_color_group(lot.get_color_group()).0.is_none() {
+                skipped.push(tr!(
+                    "BrickStore will not know color {} of part {}",
+                    lot.get_color_group(),
+                    item.get_id()
+                ));
+            }
+            xml.extend(bsx_lot.to_xml());
+        }
+    }
+    xml.push_str(" </Inventory>\n</BrickStoreXML>\n");
+    (xml, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_import() {
+        let bsx = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE BrickStoreXML>\n\
+            <BrickStoreXML><Inventory Currency=\"EUR\">\
+            <Item><ItemID>3001</ItemID><ItemTypeID>P</ItemTypeID><ColorID>11</ColorID>\
+            <ItemName>Brick 2 x 4</ItemName><ColorName>Black</ColorName><Qty>10</Qty>\
+            <Price>0.100</Price><Condition>U</Condition><Remarks>Box 3 &amp; 4</Remarks></Item>\
+            <Item><ItemID>3001</ItemID><ItemTypeID>P</ItemTypeID><ColorID>11</ColorID>\
+            <ItemName>Brick 2 x 4</ItemName><ColorName>Black</ColorName><Qty>5</Qty>\
+            <Condition>N</Condition><Remarks/></Item>\
+            <Item><ItemID>3003</ItemID><ItemTypeID>P</ItemTypeID><ColorID>5</ColorID>\
+            <ItemName>Brick 2 x 2</ItemName><ColorName>Red</ColorName><Qty>2</Qty>\
+            <Condition>N</Condition></Item>\
+            <Item><ItemID>6020</ItemID><ItemTypeID>S</ItemTypeID><ItemName>Magic Shop</ItemName>\
+            <Qty>1</Qty></Item>\
+            <Item><ItemID>3626cpb12</ItemID><ItemTypeID>P</ItemTypeID><ItemName>Head</ItemName>\
+            <Qty>1</Qty></Item>\
+            </Inventory></BrickStoreXML>";
+        let (bsx_lots, skipped) = parse(bsx).unwrap();
+        assert_eq!(bsx_lots.len(), 3);
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(bsx_lots[0].remarks.as_deref(), Some("Box 3 & 4"));
+        assert_eq!(
+            bsx_lots[0].price,
+            Some(Price::new(Amount::from_cents(10), "EUR"))
+        );
+        assert_eq!(bsx_lots[1].remarks, None);
+
+        let mut item = Item::new(PartId::new(3003).unwrap(), "Brick 2 x 2".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        let mut items = vec![item];
+        assert_eq!(import(&bsx_lots, &mut items, "Unsorted"), 3);
+        assert_eq!(items.len(), 2);
+
+        let lots = items[1].get_locations();
+        assert_eq!(items[1].get_name(), "Brick 2 x 4");
+        assert_eq!(lots.len(), 1);
+        assert_eq!(
+            lots[0].get_color_group(),
+            &ColorGroup::Other("Black".to_string())
+        );
+        assert_eq!(lots[0].get_quantity(), Some(Quantity::new(15)));
+        assert_eq!(lots[0].get_condition(), None);
+        assert_eq!(lots[0].get_notes(), Some("Box 3 & 4"));
+
+        let lots = items[0].get_locations();
+        assert_eq!(lots.len(), 2);
+        assert_eq!(lots[1].get_location(), "Unsorted");
+        assert_eq!(lots[1].get_condition(), Some(Condition::New));
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4 & more".to_string());
+        item.add_pieces(&ColorGroup::Other("Black".to_string()), "A1", 10);
+        item.add_pieces(&ColorGroup::Basic, "B2", 3);
+        item.add_color_group(ColorGroup::All, "C3".to_string());
+        let black = ColorGroup::Other("Black".to_string());
+        let mut lots = item.get_locations().to_vec();
+        lots[0].set_condition(Some(Condition::Used));
+        lots[0].set_notes(Some("from the <big> box".to_string()));
+        lots[0].set_asking_price(Some(Price::new(
+            Amount::from_cents(5),
+            currency::get_home_currency(),
+        )));
+        item.set_locations(lots);
+        let mut group = Item::new(PartId::group(3001).unwrap(), "Bricks".to_string());
+        group.add_pieces(&ColorGroup::Basic, "D1", 1);
+        let items = vec![item, group];
+
+        let (xml, skipped) = export(&items);
+        assert_eq!(skipped.len(), 3);
+        assert_eq!(xml.matches("<ItemID>3001</ItemID>").count(), 2);
+        assert!(xml.contains("<ColorID>11</ColorID>"));
+        assert_eq!(from_color_group(&black).0, ColorId::new(11));
+        assert_eq!(to_color_group(None, Some("11")), black);
+        assert_eq!(ColorId::parse("70000"), None);
+        assert!(xml.contains("<Remarks>from the &lt;big&gt; box</Remarks>"));
+        let (bsx_lots, _) = parse(&xml).unwrap();
+        assert_eq!(bsx_lots.len(), 2);
+        assert_eq!(bsx_lots[1].color_group, ColorGroup::Basic);
+
+        // Changed in BrickStore and imported again, which replaces the lots.
+        let changed = xml.replace("<Qty>10</Qty>", "<Qty>12</Qty>");
+        let (bsx_lots, _) = parse(&changed).unwrap();
+        let mut imported = items.clone();
+        import(&bsx_lots, &mut imported, "Unsorted");
+        let lot = |items: &[Item], i: usize| items[0].get_locations()[i].clone();
+        assert_eq!(imported[0].get_locations().len(), 3);
+        assert_eq!(lot(&imported, 0).get_quantity(), Some(Quantity::new(12)));
+        assert_eq!(lot(&imported, 0).get_color_group(), &black);
+        assert_eq!(lot(&imported, 0).get_notes(), lot(&items, 0).get_notes());
+        assert_eq!(lot(&imported, 1), lot(&items, 1));
+
+        // Moved in BrickStore to a location without a lot, which makes a new lot there.
+        let moved = xml.replace("<MarkerText>A1</MarkerText>", "<MarkerText>D4</MarkerText>");
+        let (bsx_lots, _) = parse(&moved).unwrap();
+        let mut imported = items.clone();
+        import(&bsx_lots, &mut imported, "Unsorted");
+        let new_lot = lot(&imported, 3);
+        assert_eq!(new_lot.get_location(), "D4");
+        assert_eq!(new_lot.get_quantity(), Some(Quantity::new(10)));
+        assert_eq!(new_lot.get_condition(), Some(Condition::Used));
+        assert_eq!(new_lot.get_notes(), lot(&items, 0).get_notes());
+        assert_eq!(
+            new_lot.get_asking_price(),
+            lot(&items, 0).get_asking_price()
+        );
+    }
+}
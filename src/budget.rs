@@ -0,0 +1,188 @@
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::adjustment;
+use crate::currency::{self, Amount, Price};
+use crate::date::{Date, Range};
+use crate::error::Result;
+use crate::io;
+use crate::locale::{tr, tr_n};
+use crate::order::Order;
+use crate::timestamp::Timestamp;
+
+/// Money spent on parts, kept in a log next to the database file. Orders are logged with their
+/// total when they are taken in, other purchases are entered by hand.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Purchase {
+    /// Seconds since the unix epoch.
+    time: u64,
+    description: String,
+    price: Price,
+    /// The number of the order the purchase was logged for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    order: Option<String>,
+}
+
+impl Purchase {
+    pub fn new(description: String, price: Price, time: Timestamp) -> Self {
+        Self {
+            time: time.get_secs(),
+            description,
+            price,
+            order: None,
+        }
+    }
+
+    /// The purchase of the order, which is none if none of its lines have a price.
+    pub fn of_order(order: &Order) -> Option<Self> {
+        Some(Self {
+            time: order.get_time().get_secs(),
+            description: tr!("Order {}", order.get_number()),
+            price: order.get_total()?,
+            order: Some(order.get_number().to_string()),
+        })
+    }
+
+    pub fn get_time(&self) -> Timestamp {
+        Timestamp::from_secs(self.time)
+    }
+}
+
+impl fmt::Display for Purchase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr!(
+                "{}: {} for {}",
+                adjustment::fmt_time(self.time),
+                self.description,
+                self.price
+            )
+        )
+    }
+}
+
+pub fn get_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("purchases")
+}
+
+pub fn append(path: &Path, purchases: &[Purchase]) -> Result<()> {
+    let mut appended = String::new();
+    for purchase in purchases {
+        appended.push_str("---\n");
+        appended.push_str(&serde_yaml::to_string(purchase).map_err(term_lib::Error::from)?);
+    }
+    io::append_private(path, &appended)?;
+    Ok(())
+}
+
+/// Reads the log, which is empty if there is none.
+pub fn read(path: &Path) -> Result<Vec<Purchase>> {
+    let contents = match io::read_private(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut purchases = Vec::new();
+    if contents.trim().is_empty() {
+        return Ok(purchases);
+    }
+    for document in serde_yaml::Deserializer::from_str(&contents) {
+        purchases.push(Purchase::deserialize(document).map_err(term_lib::Error::from)?);
+    }
+    Ok(purchases)
+}
+
+/// The money spent on the days in the home currency, with the number of purchases left out as
+/// they have no exchange rate to it.
+pub fn get_spent(purchases: &[Purchase], range: Range) -> (Amount, usize) {
+    let mut spent = Amount::ZERO;
+    let mut num_unconverted = 0;
+    for purchase in purchases
+        .iter()
+        .filter(|purchase| range.contains(purchase.get_time()))
+    {
+        match purchase.price.to_home_currency() {
+            Some(price) => spent += price.get_amount(),
+            None => num_unconverted += 1,
+        }
+    }
+    (spent, num_unconverted)
+}
+
+/// Describes how much of the monthly budget is left in the month of today, and whether more than
+/// the budget has been spent.
+pub fn describe(budget: &Price, purchases: &[Purchase], today: Date) -> (String, bool) {
+    let home = currency::get_home_currency();
+    let Some(budget) = budget.to_home_currency() else {
+        return (
+            tr!("The budget of {} has no exchange rate to {}", budget, home),
+            false,
+        );
+    };
+    let (spent, num_unconverted) = get_spent(purchases, Range::this_month(today));
+    let left = budget.get_amount() - spent;
+    let mut line = if left.is_negative() {
+        tr!(
+            "Spent {} of {} this month, {} over the budget",
+            Price::new(spent, home),
+            budget,
+            Price::new(Amount::ZERO - left, home)
+        )
+    } else {
+        tr!(
+            "Spent {} of {} this month, {} left",
+            Price::new(spent, home),
+            budget,
+            Price::new(left, home)
+        )
+    };
+    if num_unconverted != 0 {
+        line.push(' ');
+        line.push_str(&tr_n!(
+            "({} purchase without an exchange rate is not included)",
+            "({} purchases without an exchange rate are not included)",
+            num_unconverted
+        ));
+    }
+    (line, left.is_negative())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let path = PathBuf::from("test_budget.purchases");
+        let today = Date::new(2024, 5, 20).unwrap();
+        let bought = |day, price| {
+            let time = Date::new(2024, 5, day).unwrap().to_timestamp();
+            Purchase::new("Bricks".to_string(), Price::parse(price).unwrap(), time)
+        };
+        let purchases = vec![bought(2, "30 EUR"), bought(10, "15.50 EUR")];
+        append(&path, &purchases).unwrap();
+        let read_back = read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(read_back, purchases);
+
+        let budget = Price::parse("50 EUR").unwrap();
+        let (line, exceeded) = describe(&budget, &purchases, today);
+        assert_eq!(
+            line,
+            "Spent 45.50 EUR of 50.00 EUR this month, 4.50 EUR left"
+        );
+        assert!(!exceeded);
+
+        let purchases = vec![bought(2, "30 EUR"), bought(10, "25 EUR")];
+        let (line, exceeded) = describe(&budget, &purchases, today);
+        assert!(line.ends_with("5.00 EUR over the budget"));
+        assert!(exceeded);
+        let (_, exceeded) = describe(&budget, &purchases, today.add_months(1));
+        assert!(!exceeded);
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::adjustment;
+use crate::budget;
+use crate::config::Config;
+use crate::data::{self, RawDatabase};
+use crate::error::{Error, Result};
+use crate::io;
+use crate::order;
+use crate::remote;
+use crate::sale;
+use crate::snapshot::{self, Snapshot};
+use crate::transaction;
+
+/// Version 2 added the logs and snapshots kept next to the database.
+const BUNDLE_VERSION: u32 = 2;
+
+/// Everything needed to move an installation to another machine, stored as a single file.
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    version: u32,
+    config: Config,
+    database: RawDatabase,
+    /// The contents of the logs kept next to the database, by the names in `get_log_paths`.
+    #[serde(default)]
+    logs: BTreeMap<String, String>,
+    #[serde(default)]
+    snapshots: Vec<(Snapshot, RawDatabase)>,
+}
+
+/// The files kept next to the database that belong to it, by the names they are bundled as.
+fn get_log_paths(db_path: &Path) -> [(&'static str, PathBuf); 6] {
+    [
+        ("sales", sale::get_path(db_path)),
+        ("adjustments", adjustment::get_path(db_path)),
+        ("orders", order::get_path(db_path)),
+        ("purchases", budget::get_path(db_path)),
+        ("transactions", transaction::get_path(db_path)),
+        ("sync-base", remote::get_base_path(db_path)),
+    ]
+}
+
+pub fn export(config: &Config, path: &Path) -> Result<()> {
+    let db_path = config.get_db_path();
+    let database = data::read_items(&db_path)?;
+    let mut logs = BTreeMap::new();
+    for (name, log_path) in get_log_paths(&db_path) {
+        match io::read_private(&log_path) {
+            Ok(contents) => {
+                logs.insert(name.to_string(), contents);
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let mut snapshots = Vec::new();
+    for snapshot in snapshot::list(&db_path)? {
+        let items = snapshot::read(&db_path, snapshot.get_name())?;
+        snapshots.push((snapshot, items));
+    }
+
+    let bundle = Bundle {
+        version: BUNDLE_VERSION,
+        config: config.clone(),
+        database,
+        logs,
+        snapshots,
+    };
+    io::write_contents_to_yaml(path, &bundle)?;
+    Ok(())
+}
+
+/// Describes the changes importing the bundle would make to the database.
+pub fn describe_import(config: &Config, path: &Path, force: bool) -> Result<String> {
+    let bundle = read_bundle(path)?;
+    let db_path = config.get_db_path();
+    if io::get_modified(&db_path).is_none() {
+        return Ok(data::describe_changes(&[], &bundle.database));
+    }
+    if !force {
+        return Err(Error::FileAlreadyExists {
+            path: db_path.to_string_lossy().to_string(),
+        });
+    }
+    let current = data::read_items(&db_path)?;
+    Ok(data::describe_changes(&current, &bundle.database))
+}
+
+fn read_bundle(path: &Path) -> Result<Bundle> {
+    let bundle: Bundle = io::read_contents_from_yaml(path)?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err(Error::UnsupportedVersion {
+            version: bundle.version,
+        });
+    }
+    if bundle.version < BUNDLE_VERSION {
+        log::warn!(
+            "the bundle was written by an older version of tui_bricks, it has no sales, orders, purchases, history or snapshots"
+        );
+    }
+    Ok(bundle)
+}
+
+/// Imports the bundle, overwriting the config, database and its logs, and adding its snapshots.
+/// The database is placed at the path of the current config, since the path in the bundle is
+/// specific to the machine it came from. The logs are left as they are for bundles of older
+/// versions, which do not have them.
+pub fn import(current_config: Config, config_path: &Path, path: &Path, force: bool) -> Result<()> {
+    let mut bundle = read_bundle(path)?;
+
+    let db_path = current_config.get_db_path();
+    if !force && io::get_modified(&db_path).is_some() {
+        return Err(Error::FileAlreadyExists {
+            path: db_path.to_string_lossy().to_string(),
+        });
+    }
+
+    let mut config = bundle.config;
+    config.set_db_path(&db_path);
+    io::write_contents_to_yaml(config_path, &config)?;
+    data::write_items(&db_path, &bundle.database)?;
+    if bundle.version < BUNDLE_VERSION {
+        return Ok(());
+    }
+    for (name, log_path) in get_log_paths(&db_path) {
+        match bundle.logs.remove(name) {
+            Some(contents) => io::write_private(&log_path, &contents)?,
+            // The log of the database that was replaced does not belong to the new one.
+            None => match fs::remove_file(&log_path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            },
+        }
+    }
+    for (snapshot, items) in bundle.snapshots.iter() {
+        snapshot::restore(&db_path, snapshot, items)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::Purchase;
+    use crate::currency::Price;
+    use crate::data::{ColorGroup, Item};
+    use crate::sale::Sale;
+    use crate::timestamp::Timestamp;
+    use crate::types::PartId;
+
+    #[test]
+    fn test_round_trip() {
+        let (db_path, other_db_path) = (
+            PathBuf::from("test_bundle.yml"),
+            PathBuf::from("test_bundle_other.yml"),
+        );
+        let (bundle_path, config_path) = (
+            PathBuf::from("test_bundle.bundle"),
+            PathBuf::from("test_bundle_config.yml"),
+        );
+        let id = PartId::new(3001).unwrap();
+        let mut item = Item::new(id, "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        let sale = Sale::new(
+            id,
+            ColorGroup::Basic,
+            3,
+            "Alice".to_string(),
+            Price::parse("1.50 EUR").unwrap(),
+            Timestamp::now(),
+        );
+        let purchase = Purchase::new(
+            "Bricks".to_string(),
+            Price::parse("12 EUR").unwrap(),
+            Timestamp::now(),
+        );
+
+        let result = (|| -> Result<()> {
+            data::write_items(&db_path, &RawDatabase::new(vec![item.clone()]))?;
+            sale::append(&sale::get_path(&db_path), std::slice::from_ref(&sale))?;
+            budget::append(&budget::get_path(&db_path), std::slice::from_ref(&purchase))?;
+            snapshot::create(&db_path, "moved")?;
+            let mut config = Config::default();
+            config.set_db_path(&db_path);
+            export(&config, &bundle_path)?;
+
+            let mut other_config = Config::default();
+            other_config.set_db_path(&other_db_path);
+            import(other_config, &config_path, &bundle_path, false)?;
+            assert_eq!(data::read_items(&other_db_path)?.to_vec(), vec![item]);
+            assert_eq!(sale::read(&sale::get_path(&other_db_path))?, vec![sale]);
+            let purchases = budget::read(&budget::get_path(&other_db_path))?;
+            assert_eq!(purchases, vec![purchase]);
+            assert_eq!(snapshot::read(&other_db_path, "moved")?.len(), 1);
+            Ok(())
+        })();
+
+        for path in [&db_path, &other_db_path] {
+            let _ = fs::remove_file(path);
+            let _ = fs::remove_file(sale::get_path(path));
+            let _ = fs::remove_file(budget::get_path(path));
+            let _ = fs::remove_dir_all(path.with_extension("snapshots"));
+        }
+        let _ = fs::remove_file(&bundle_path);
+        let _ = fs::remove_file(&config_path);
+        result.unwrap();
+    }
+}
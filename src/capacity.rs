@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::Item;
+use crate::locale::{tr, tr_n};
+
+/// How many pieces fit in a location, given as a number of pieces or as a size class like
+/// `small` whose number of pieces is set in the config.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum Capacity {
+    Pieces(u32),
+    Class(String),
+}
+
+/// Returns the capacity of the location in pieces. Locations are matched by name, or by a prefix
+/// ending in `*` like `A*` for all drawers in cabinet A, where the longest match is used.
+pub fn get_capacity(
+    capacities: &BTreeMap<String, Capacity>,
+    classes: &BTreeMap<String, u32>,
+    location: &str,
+) -> Option<u32> {
+    let capacity = capacities.get(location).or_else(|| {
+        capacities
+            .iter()
+            .filter_map(|(pattern, capacity)| {
+                let prefix = pattern.strip_suffix('*')?;
+                location
+                    .starts_with(prefix)
+                    .then_some((prefix.len(), capacity))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, capacity)| capacity)
+    })?;
+    match capacity {
+        Capacity::Pieces(pieces) => Some(*pieces),
+        Capacity::Class(class) => classes.get(class).copied(),
+    }
+}
+
+/// How full a location is.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Fill {
+    /// The counted pieces, and those estimated by weight.
+    pub pieces: u32,
+    /// Lots at the location whose number of pieces is not known.
+    pub uncounted: usize,
+    pub capacity: Option<u32>,
+}
+
+impl Fill {
+    pub fn new<'a>(
+        items: impl Iterator<Item = &'a Item>,
+        location: &str,
+        capacity: Option<u32>,
+    ) -> Self {
+        let mut fill = Self {
+            pieces: 0,
+            uncounted: 0,
+            capacity,
+        };
+        for item in items {
+            for lot in item
+                .get_locations()
+                .iter()
+                .filter(|lot| lot.get_location() == location)
+            {
+                match lot
+                    .get_quantity()
+                    .or_else(|| item.get_estimated_quantity(lot))
+                {
+                    Some(quantity) => fill.pieces += quantity.get(),
+                    None => fill.uncounted += 1,
+                }
+            }
+        }
+        fill
+    }
+
+    pub fn is_overfull(&self) -> bool {
+        self.capacity.is_some_and(|capacity| self.pieces > capacity)
+    }
+}
+
+impl fmt::Display for Fill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fill = match self.capacity {
+            Some(capacity) => tr!(
+                "{} of {} pieces ({}%)",
+                self.pieces,
+                capacity,
+                (self.pieces as u64 * 100) / capacity.max(1) as u64
+            ),
+            None => tr_n!(
+                "{} piece, no capacity is set",
+                "{} pieces, no capacity is set",
+                self.pieces as usize
+            ),
+        };
+        write!(f, "{}", fill)?;
+        if self.uncounted != 0 {
+            let uncounted = tr_n!(
+                "and {} uncounted lot",
+                "and {} uncounted lots",
+                self.uncounted
+            );
+            write!(f, " {}", uncounted)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::{PartId, Quantity};
+
+    #[test]
+    fn test_fill() {
+        let capacities = BTreeMap::from([
+            ("A*".to_string(), Capacity::Class("small".to_string())),
+            ("A1*".to_string(), Capacity::Pieces(500)),
+            ("B1".to_string(), Capacity::Pieces(20)),
+        ]);
+        let classes = BTreeMap::from([("small".to_string(), 100)]);
+        let capacity = |location| get_capacity(&capacities, &classes, location);
+        assert_eq!(capacity("A3"), Some(100));
+        assert_eq!(capacity("A12"), Some(500));
+        assert_eq!(capacity("B1"), Some(20));
+        assert_eq!(capacity("B12"), None);
+
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "B1".to_string());
+        item.add_color_group(ColorGroup::Grey, "B1".to_string());
+        item.set_quantity(&ColorGroup::Basic, Some(Quantity::new(25)));
+        let fill = Fill::new([&item].into_iter(), "B1", capacity("B1"));
+        assert_eq!((fill.pieces, fill.uncounted), (25, 1));
+        assert!(fill.is_overfull());
+    }
+}
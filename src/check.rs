@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::config::Config;
+use crate::data::{ColorGroup, Item};
+use crate::locale::tr;
+use crate::rule;
+use crate::types::PartId;
+
+/// An inconsistency in the database, or between the database and the config.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Problem {
+    EmptyName {
+        id: PartId,
+    },
+    EmptyLocation {
+        id: PartId,
+        color_group: ColorGroup,
+    },
+    DuplicateLots {
+        id: PartId,
+        color_group: ColorGroup,
+        location: String,
+        count: usize,
+    },
+    OwnAltId {
+        id: PartId,
+    },
+    /// The ID belongs to the first item, either as its main or as an alternative ID, and is also
+    /// an alternative ID of the second item, so that searching for it is ambiguous.
+    SharedAltId {
+        id: PartId,
+        first: PartId,
+        second: PartId,
+    },
+    /// The item is stored with all colors together, but also has lots of single color groups.
+    MixedAll {
+        id: PartId,
+    },
+    /// A set or goal in the config refers to a part that is not in the database.
+    UnknownPart {
+        reference: String,
+        id: PartId,
+    },
+    /// Lots of the item have no ID yet, as they were not saved since IDs were introduced.
+    LotsWithoutId {
+        id: PartId,
+    },
+    /// The item breaks a rule from the config.
+    BrokenRule {
+        id: PartId,
+        rule: String,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Problem::*;
+        let msg = match self {
+            EmptyName { id } => tr!("Part {} has no name.", id),
+            EmptyLocation { id, color_group } => {
+                tr!("The lot of {} of part {} has no location.", color_group, id)
+            }
+            DuplicateLots {
+                id,
+                color_group,
+                location,
+                count,
+            } => tr!(
+                "Part {} has {} lots of {} at {}.",
+                id,
+                count,
+                color_group,
+                location
+            ),
+            OwnAltId { id } => tr!("Part {} is listed as its own alternative ID.", id),
+            SharedAltId { id, first, second } => tr!(
+                "ID {} belongs to part {} but is also an alternative ID of part {}.",
+                id,
+                first,
+                second
+            ),
+            MixedAll { id } => tr!(
+                "Part {} is stored with all colors together but also by color group.",
+                id
+            ),
+            UnknownPart { reference, id } => tr!(
+                "{} in the config refers to part {}, which is not in the database.",
+                reference,
+                id
+            ),
+            LotsWithoutId { id } => tr!("Lots of part {} have no ID.", id),
+            BrokenRule { id, rule } => tr!("Part {} breaks the rule: {}", id, rule),
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+impl Problem {
+    /// The item changed by the repair, or None if the problem can not be repaired.
+    pub fn get_item_to_repair(&self) -> Option<PartId> {
+        use Problem::*;
+        match self {
+            EmptyName { id }
+            | EmptyLocation { id, .. }
+            | DuplicateLots { id, .. }
+            | OwnAltId { id }
+            | LotsWithoutId { id } => Some(*id),
+            SharedAltId { second, .. } => Some(*second),
+            MixedAll { .. } | UnknownPart { .. } | BrokenRule { .. } => None,
+        }
+    }
+
+    /// Describes the repair, which for items without a name is to ask for one.
+    pub fn get_repair_info(&self) -> Option<&'static str> {
+        use Problem::*;
+        match self {
+            EmptyName { .. } => Some(tr!("Give the part a name?")),
+            EmptyLocation { .. } => Some(tr!("Remove the lot?")),
+            DuplicateLots { .. } => Some(tr!("Merge the lots and add up their quantities?")),
+            OwnAltId { .. } | SharedAltId { .. } => Some(tr!("Remove the alternative ID?")),
+            LotsWithoutId { .. } => Some(tr!("Give the lots IDs?")),
+            MixedAll { .. } | UnknownPart { .. } | BrokenRule { .. } => None,
+        }
+    }
+
+    /// Repairs the problem in the item returned by `get_item_to_repair`. Returns false if the
+    /// repair needs a name from the user.
+    pub fn repair(&self, item: &mut Item) -> bool {
+        use Problem::*;
+        match self {
+            EmptyName { .. } => return false,
+            EmptyLocation { color_group, .. } => {
+                let lots = item
+                    .get_locations()
+                    .iter()
+                    .filter(|lot| {
+                        lot.get_color_group() != color_group
+                            || !lot.get_location().trim().is_empty()
+                    })
+                    .cloned()
+                    .collect();
+                item.set_locations(lots);
+            }
+            DuplicateLots { .. } => item.consolidate_lots(),
+            OwnAltId { id } | SharedAltId { id, .. } => item.remove_alt_id(*id),
+            LotsWithoutId { .. } => item.assign_lot_ids(),
+            MixedAll { .. } | UnknownPart { .. } | BrokenRule { .. } => {}
+        }
+        true
+    }
+}
+
+/// Finds the problems in the items and in the references to them from the config.
+pub fn check(items: &[Item], config: &Config) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut ids: HashMap<PartId, PartId> = HashMap::new();
+
+    for item in items.iter() {
+        let id = item.get_id();
+        ids.insert(id, id);
+
+        if item.get_name().trim().is_empty() {
+            problems.push(Problem::EmptyName { id });
+        }
+        for lot in item.get_locations() {
+            if lot.get_location().trim().is_empty() {
+                problems.push(Problem::EmptyLocation {
+                    id,
+                    color_group: lot.get_color_group().clone(),
+                });
+            }
+        }
+        for (lot, count) in item.get_duplicate_lots() {
+            problems.push(Problem::DuplicateLots {
+                id,
+                color_group: lot.get_color_group().clone(),
+                location: lot.get_location().clone(),
+                count,
+            });
+        }
+        if item.get_alternative_ids().contains(&id) {
+            problems.push(Problem::OwnAltId { id });
+        }
+        let color_set = item.get_color_set();
+        if color_set.contains(&ColorGroup::All) && color_set.len() > 1 {
+            problems.push(Problem::MixedAll { id });
+        }
+        if item
+            .get_locations()
+            .iter()
+            .any(|lot| lot.get_id().is_none())
+        {
+            problems.push(Problem::LotsWithoutId { id });
+        }
+        for rule in rule::get_broken(config.get_rules(), item) {
+            problems.push(Problem::BrokenRule {
+                id,
+                rule: rule.to_string(),
+            });
+        }
+    }
+
+    for item in items.iter() {
+        let second = item.get_id();
+        for id in item.get_alternative_ids() {
+            if *id == second {
+                continue;
+            }
+            match ids.get(id) {
+                Some(first) if *first != second => problems.push(Problem::SharedAltId {
+                    id: *id,
+                    first: *first,
+                    second,
+                }),
+                Some(_) => {}
+                None => {
+                    ids.insert(*id, second);
+                }
+            }
+        }
+    }
+
+    for set in config.get_sets() {
+        for id in set.get_parts().keys() {
+            if !ids.contains_key(id) {
+                problems.push(Problem::UnknownPart {
+                    reference: tr!("Set {}", set.get_number()),
+                    id: *id,
+                });
+            }
+        }
+    }
+    for goal in config.get_goals() {
+        for id in goal.get_part_ids() {
+            if !ids.contains_key(&id) {
+                problems.push(Problem::UnknownPart {
+                    reference: tr!("Goal {}", goal.get_name()),
+                    id,
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_and_repair() {
+        let (a, b) = (PartId::new(3001).unwrap(), PartId::new(3002).unwrap());
+        let mut first = Item::new(a, "Brick 2 x 4".to_string());
+        first.add_color_group(ColorGroup::Basic, " ".to_string());
+        let mut second = Item::new(b, "Brick 2 x 3".to_string());
+        second.add_alt_id(a);
+        second.add_color_group(ColorGroup::Earth, "B1".to_string());
+
+        let problems = check(&[first.clone(), second.clone()], &Config::default());
+        assert_eq!(
+            problems,
+            vec![
+                Problem::EmptyLocation {
+                    id: a,
+                    color_group: ColorGroup::Basic
+                },
+                Problem::LotsWithoutId { id: a },
+                Problem::LotsWithoutId { id: b },
+                Problem::SharedAltId {
+                    id: a,
+                    first: a,
+                    second: b
+                },
+            ]
+        );
+
+        assert!(problems[0].repair(&mut first));
+        assert!(problems[2].repair(&mut second));
+        assert!(problems[3].repair(&mut second));
+        assert!(check(&[first, second], &Config::default()).is_empty());
+    }
+}
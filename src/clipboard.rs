@@ -0,0 +1,132 @@
+use crate::data::Item;
+
+/// Whether the program was built with support for the system clipboard.
+pub const SUPPORTED: bool = cfg!(feature = "clipboard");
+
+#[cfg(feature = "clipboard")]
+mod system {
+    use std::cell::RefCell;
+
+    use crate::error::{Error, Result};
+
+    thread_local! {
+        // On some platforms the copied text is only available while the clipboard is open, so it
+        // is kept open for the rest of the session.
+        static CLIPBOARD: RefCell<Option<arboard::Clipboard>> = const { RefCell::new(None) };
+    }
+
+    fn with_clipboard<T>(
+        f: impl FnOnce(&mut arboard::Clipboard) -> std::result::Result<T, arboard::Error>,
+    ) -> Result<T> {
+        CLIPBOARD
+            .with_borrow_mut(|clipboard| match clipboard {
+                Some(clipboard) => f(clipboard),
+                None => f(clipboard.insert(arboard::Clipboard::new()?)),
+            })
+            .map_err(|e| Error::ClipboardUnavailable { msg: e.to_string() })
+    }
+
+    pub fn copy(text: &str) -> Result<()> {
+        with_clipboard(|clipboard| clipboard.set_text(text))
+    }
+
+    pub fn paste() -> Result<String> {
+        with_clipboard(|clipboard| clipboard.get_text())
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+mod system {
+    use crate::error::{Error, Result};
+
+    fn not_supported() -> Error {
+        Error::ClipboardUnavailable {
+            msg: "built without the clipboard feature".to_string(),
+        }
+    }
+
+    pub fn copy(_: &str) -> Result<()> {
+        Err(not_supported())
+    }
+
+    pub fn paste() -> Result<String> {
+        Err(not_supported())
+    }
+}
+
+pub use system::{copy, paste};
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Lists the items as CSV with their part ID, name, locations separated by spaces, and number of
+/// pieces. The number is prefixed with `~` when it includes pieces estimated by weight.
+pub fn to_csv(items: &[&Item]) -> String {
+    let mut csv = String::from("Part,Name,Locations,Pieces\n");
+    for item in items {
+        let locations: Vec<&str> = item
+            .get_locations()
+            .iter()
+            .map(|lot| lot.get_location().as_str())
+            .collect();
+        let estimated: u32 = item
+            .get_locations()
+            .iter()
+            .filter_map(|lot| item.get_estimated_quantity(lot))
+            .map(|q| q.get())
+            .sum();
+        let counted = item
+            .get_locations()
+            .iter()
+            .any(|lot| lot.get_quantity().is_some());
+        let pieces = if estimated != 0 {
+            format!("~{}", item.get_quantity().get() + estimated)
+        } else if counted {
+            item.get_quantity().get().to_string()
+        } else {
+            String::new()
+        };
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            item.get_id(),
+            escape_csv(item.get_name()),
+            escape_csv(&locations.join(" ")),
+            pieces
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::{PartId, Quantity};
+
+    #[test]
+    fn test_to_csv() {
+        let mut item = Item::new(
+            PartId::new(3001).unwrap(),
+            "Brick 2 x 4, \"classic\"".to_string(),
+        );
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Earth, "A2".to_string());
+        assert_eq!(
+            to_csv(&[&item]),
+            "Part,Name,Locations,Pieces\n3001,\"Brick 2 x 4, \"\"classic\"\"\",A1 A2,\n"
+        );
+
+        item.set_quantity(&ColorGroup::Basic, Some(Quantity::new(10)));
+        item.set_weight(&ColorGroup::Earth, Some(23.0));
+        item.set_piece_weight(Some(2.3));
+        assert_eq!(
+            to_csv(&[&item]),
+            "Part,Name,Locations,Pieces\n3001,\"Brick 2 x 4, \"\"classic\"\"\",A1 A2,~20\n"
+        );
+    }
+}
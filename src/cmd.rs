@@ -1,13 +1,22 @@
 use term_lib::command::{CmdList, Command};
 
+use crate::key::{self, Keys};
+use crate::locale::tr;
+use crate::plugin;
+use crate::section::{self, Section};
+
 /// Appearence order is as the order is written in code.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum Cmd {
     AddItem,
     DeleteItem,
     AddGroup,
+    SortBin,
+    Rebalance,
 
-    Bricklink,
+    OpenPartPage,
+    CopyPartID,
+    CopyPartList,
 
     Quit,
     Edit,
@@ -16,7 +25,32 @@ pub enum Cmd {
     QuitEdit,
     EditName,
     EditPartID,
+    EditPrice,
     MoveColorGroup,
+    EditQuantity,
+    ListForSale,
+    RecordSale,
+    EditPieceWeight,
+    EditBaseMold,
+    EditComponents,
+    EditDimensions,
+    EditFields,
+    Assemble,
+    Disassemble,
+    WeighLot,
+    SplitLot,
+    MergeLots,
+    EditPurchased,
+    /// Adds the count typed before the key, or one, to the pieces of a lot.
+    AddPieces,
+    /// Removes the count typed before the key, or one, from the pieces of a lot.
+    RemovePieces,
+    /// Shows or hides the section of the item display.
+    ToggleSection(Section),
+    /// Shows the similar part with the index in the list shown with the item.
+    ShowSimilar(usize),
+    /// Runs the plugin with the index in the config.
+    Plugin(usize),
 
     MCmd(MultiCmd),
 
@@ -29,9 +63,36 @@ pub enum Cmd {
     SearchPartID,
     SearchName,
     SearchLocation,
+    SearchPastedPartID,
+    SearchFilter,
 
     ViewStats,
+    ToggleListedLots,
+    ToggleWantedParts,
+    ViewStaleLots,
+    ChooseStatsRange,
     QuitStats,
+
+    ViewSets,
+    SortSets,
+    ToggleSpareParts,
+    QuitSets,
+    PartOutValue,
+    PartOutSet,
+    ResaleReadiness,
+
+    ConsolidateLots,
+    CheckDatabase,
+    ViewAdjustments,
+    ViewRevenue,
+    LogPurchase,
+    /// Reports the commands used and the time spent in modes, from the usage log.
+    ViewUsage,
+    ChooseColumns,
+
+    StartRecording,
+    StopRecording,
+    PlayMacro,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
@@ -48,8 +109,11 @@ impl Command for Cmd {
             AddItem => 'a',
             DeleteItem => 'd',
             AddGroup => 'g',
+            SortBin => 'b',
+            Rebalance => 'l',
 
-            Bricklink => 'b',
+            OpenPartPage => 'b',
+            CopyPartID | CopyPartList => 'y',
 
             Quit => 'q',
             Edit => 'e',
@@ -58,7 +122,30 @@ impl Command for Cmd {
             QuitEdit => 'q',
             EditName => 'n',
             EditPartID => 'i',
+            EditPrice => 'p',
             MoveColorGroup => 'm',
+            EditQuantity => 'c',
+            ListForSale => 'l',
+            RecordSale => 'o',
+            EditPieceWeight => 'g',
+            EditBaseMold => 't',
+            EditComponents => 'u',
+            EditDimensions => 'z',
+            EditFields => 'f',
+            Assemble => 'j',
+            Disassemble => 'x',
+            WeighLot => 'w',
+            SplitLot => 'v',
+            MergeLots => 'e',
+            EditPurchased => 'k',
+            AddPieces => '+',
+            RemovePieces => '-',
+            ToggleSection(section) => section.get_char(),
+            // The numbers of the sections come first.
+            ShowSimilar(i) => {
+                char::from_digit((*i + 1 + section::NUM_SECTIONS) as u32, 10).unwrap_or('?')
+            }
+            Plugin(i) => plugin::get(*i).map_or('?', |plugin| plugin.get_key()),
 
             MCmd(m_cmd) => m_cmd.get_char(),
 
@@ -71,46 +158,129 @@ impl Command for Cmd {
             SearchPartID => 'i',
             SearchName => 'n',
             SearchLocation => 'l',
+            SearchPastedPartID => 'p',
+            SearchFilter => 'f',
 
             ViewStats => 'v',
+            ToggleListedLots => 't',
+            ToggleWantedParts => 'w',
+            ViewStaleLots => 's',
+            ChooseStatsRange => 'r',
             QuitStats => 'q',
+
+            ViewSets => 'o',
+            SortSets => 's',
+            ToggleSpareParts => 't',
+            QuitSets => 'q',
+            PartOutValue => 'p',
+            PartOutSet => 'w',
+            ResaleReadiness => 'r',
+
+            ConsolidateLots => 'c',
+            CheckDatabase => 'k',
+            ViewAdjustments => 'h',
+            ViewRevenue => 'r',
+            LogPurchase => 'm',
+            ViewUsage => 'u',
+            ChooseColumns => 't',
+
+            StartRecording | StopRecording => 'Q',
+            PlayMacro => '@',
         }
     }
 
     fn get_info(&self) -> &str {
         use Cmd::*;
         match &self {
-            AddItem => "(a)dd a new item to the database",
-            DeleteItem => "(d)elete the current item",
-            AddGroup => "add a new (g)roup to the database",
-
-            Bricklink => "open in (b)ricklink",
-
-            Quit => "(q)uit the program",
-            Edit => "(e)dit the current item",
-
-            SaveEdit => "(s)ave the current changes and quit editing",
-            QuitEdit => "(q)uit editing this part without saving changes currently made",
-            EditName => "edit the (n)ame of this part",
-            EditPartID => "edit the (i)d of this item if a wrong id was written",
-            MoveColorGroup => "(m)ove a location for this item",
+            AddItem => tr!("(a)dd a new item to the database"),
+            DeleteItem => tr!("(d)elete the current item"),
+            AddGroup => tr!("add a new (g)roup to the database"),
+            SortBin => tr!("sort the parts of an intake (b)in into their locations"),
+            Rebalance => tr!("move lots out of overfull (l)ocations"),
+
+            OpenPartPage => tr!("open the part page in the (b)rowser"),
+            CopyPartID => tr!("cop(y) the part ID to the clipboard"),
+            CopyPartList => tr!("cop(y) a filtered list of parts to the clipboard as CSV"),
+
+            Quit => tr!("(q)uit the program"),
+            Edit => tr!("(e)dit the current item"),
+
+            SaveEdit => tr!("(s)ave the current changes and quit editing"),
+            QuitEdit => tr!("(q)uit editing this part without saving changes currently made"),
+            EditName => tr!("edit the (n)ame of this part"),
+            EditPartID => tr!("edit the (i)d of this item if a wrong id was written"),
+            EditPrice => tr!("edit the (p)rice of this item"),
+            MoveColorGroup => tr!("(m)ove a location for this item"),
+            EditQuantity => tr!("edit the (c)ount of pieces in a color group"),
+            ListForSale => tr!("(l)ist a color group for sale"),
+            RecordSale => tr!("rec(o)rd a sale of pieces in a color group"),
+            EditPieceWeight => tr!("edit the weight in (g)rams of one piece"),
+            EditBaseMold => tr!("edit the base mold of a prin(t)ed or patterned part"),
+            EditComponents => tr!("edit the parts this assembly is b(u)ilt from"),
+            EditDimensions => tr!("edit the si(z)e of the part in studs and its height"),
+            EditFields => tr!("edit the custom (f)ields of this item"),
+            Assemble => tr!("(j)oin components into assemblies"),
+            Disassemble => tr!("take assemblies apart into their components (x)"),
+            WeighLot => tr!("enter the (w)eight of the pieces in a color group"),
+            SplitLot => tr!("di(v)ide a lot, moving some of its pieces to another location"),
+            MergeLots => tr!("m(e)rge two lots of the same color group"),
+            EditPurchased => tr!("mar(k) when the pieces of a lot were bought"),
+            AddPieces => tr!("add pieces to a lot (+), type a count first like 5+ for more"),
+            RemovePieces => {
+                tr!("remove pieces from a lot (-), type a count first like 12- for more")
+            }
+            ToggleSection(_) => tr!("show or hide the section with this number"),
+            ShowSimilar(_) => tr!("show the similar part with this number"),
+            Plugin(i) => plugin::get(*i).map_or("", |plugin| plugin.get_label()),
 
             MCmd(m_cmd) => m_cmd.get_info(),
 
-            AddColorGroup => "add a new (c)olor group and its location for this item",
-            AddAltId => "add a new alternative (i)d for this item",
-
-            RemoveColorGroup => "remove a (c)olor group and its location for this item",
-            RemoveAltId => "remove an alternative (i)d for this item",
-
-            SearchPartID => "search by part (i)d",
-            SearchName => "search by (n)ame",
-            SearchLocation => "search by (l)ocation",
-
-            ViewStats => "(v)iew statistics",
-            QuitStats => "(q)uit viewing statistics",
+            AddColorGroup => tr!("add a new (c)olor group and its location for this item"),
+            AddAltId => tr!("add a new alternative (i)d for this item"),
+
+            RemoveColorGroup => tr!("remove a (c)olor group and its location for this item"),
+            RemoveAltId => tr!("remove an alternative (i)d for this item"),
+
+            SearchPartID => tr!("search by part (i)d"),
+            SearchName => tr!("search by (n)ame"),
+            SearchLocation => tr!("search by (l)ocation"),
+            SearchPastedPartID => tr!("search by the part ID in the clipboard (p)"),
+            SearchFilter => tr!("search by (f)ilter"),
+
+            ViewStats => tr!("(v)iew statistics"),
+            ToggleListedLots => tr!("(t)oggle whether lots listed for sale are counted"),
+            ToggleWantedParts => {
+                tr!("toggle whether pieces still (w)anted for the goals are counted")
+            }
+            ViewStaleLots => tr!("list the lots not changed for over two years, (s)tale lots"),
+            ChooseStatsRange => tr!("count only the lots added in a date (r)ange"),
+            QuitStats => tr!("(q)uit viewing statistics"),
+
+            ViewSets => tr!("view completion of (o)wned sets"),
+            SortSets => tr!("(s)ort the sets by completion or by set number"),
+            ToggleSpareParts => tr!("(t)oggle whether spare parts are needed for completion"),
+            QuitSets => tr!("(q)uit viewing sets"),
+            PartOutValue => tr!("compute the (p)art-out value of a set"),
+            PartOutSet => tr!("part out a set, (w)orking through its inventory"),
+            ResaleReadiness => tr!("check whether a set is (r)eady to be sold"),
+
+            ConsolidateLots => tr!("(c)onsolidate duplicate lots"),
+            CheckDatabase => tr!("chec(k) the database for problems"),
+            ViewAdjustments => tr!("view the (h)istory of quantity changes"),
+            ViewRevenue => tr!("view the monthly (r)evenue from sales"),
+            LogPurchase => tr!("log (m)oney spent on a purchase of parts"),
+            ViewUsage => tr!("view how much commands and modes are (u)sed"),
+            ChooseColumns => tr!("choose the columns of the (t)able of parts matching a filter"),
+
+            StartRecording => tr!("start recording a macro (Q)"),
+            StopRecording => tr!("stop recording the macro (Q)"),
+            PlayMacro => tr!("play a macro (@)"),
         }
     }
+
+    fn display_as_cmd(&self) -> String {
+        format!("{}: {}", self.get_keys(), self.get_info())
+    }
 }
 
 impl Command for MultiCmd {
@@ -126,14 +296,61 @@ impl Command for MultiCmd {
     fn get_info(&self) -> &'static str {
         use MultiCmd::*;
         match &self {
-            SearchItem => "(s)earch for an item",
-            AddToItem => "(a)dd something to an item",
-            RemoveFromItem => "(r)emove something from an item",
+            SearchItem => tr!("(s)earch for an item"),
+            AddToItem => tr!("(a)dd something to an item"),
+            RemoveFromItem => tr!("(r)emove something from an item"),
+        }
+    }
+}
+
+impl Cmd {
+    /// The name of the command used for binding it to other keys in the config, e.g. `SaveEdit`.
+    pub fn get_name(&self) -> String {
+        match self {
+            Cmd::MCmd(m_cmd) => format!("{:?}", m_cmd),
+            cmd => format!("{:?}", cmd),
+        }
+    }
+
+    /// The keys that give the command, which are its char unless bound to other keys.
+    pub fn get_keys(&self) -> Keys {
+        key::get_binding(&self.get_name()).unwrap_or_else(|| Keys::single(self.get_char()))
+    }
+
+    pub fn uses_clipboard(&self) -> bool {
+        use Cmd::*;
+        matches!(self, CopyPartID | CopyPartList | SearchPastedPartID)
+    }
+
+    /// Whether the command can change the database, so that it must be disabled in read-only
+    /// sessions.
+    pub fn is_mutating(&self) -> bool {
+        use Cmd::*;
+        match &self {
+            OpenPartPage | CopyPartID | CopyPartList | Quit | SearchPartID | SearchName
+            | SearchLocation | SearchPastedPartID | SearchFilter | ViewStats | ToggleListedLots
+            | ToggleWantedParts | ViewStaleLots | ChooseStatsRange | QuitStats | ViewSets
+            | SortSets | ToggleSpareParts | QuitSets | PartOutValue | ResaleReadiness
+            | CheckDatabase | ViewAdjustments | ViewRevenue | ViewUsage | ChooseColumns
+            | StartRecording | StopRecording | PlayMacro | ToggleSection(_) | ShowSimilar(_) => {
+                false
+            }
+            MCmd(m_cmd) => m_cmd.is_mutating(),
+            Plugin(i) => plugin::get(*i).is_none_or(|plugin| plugin.changes_items()),
+            _ => true,
         }
     }
 }
 
 impl MultiCmd {
+    pub fn is_mutating(&self) -> bool {
+        use MultiCmd::*;
+        match &self {
+            SearchItem => false,
+            AddToItem | RemoveFromItem => true,
+        }
+    }
+
     pub fn get_possible_cmds(&self) -> CmdList<Cmd> {
         use MultiCmd::*;
         match &self {
@@ -141,6 +358,8 @@ impl MultiCmd {
                 Cmd::SearchPartID,
                 Cmd::SearchName,
                 Cmd::SearchLocation,
+                Cmd::SearchPastedPartID,
+                Cmd::SearchFilter,
             ]),
             AddToItem => CmdList::new(vec![Cmd::AddColorGroup, Cmd::AddAltId]),
             RemoveFromItem => CmdList::new(vec![Cmd::RemoveColorGroup, Cmd::RemoveAltId]),
@@ -150,9 +369,9 @@ impl MultiCmd {
     pub fn get_header(&self) -> &str {
         use MultiCmd::*;
         match &self {
-            SearchItem => "What do you want to search by?",
-            AddToItem => "What would you like to add to this item?",
-            RemoveFromItem => "What would you like to remove from this item?",
+            SearchItem => tr!("What do you want to search by?"),
+            AddToItem => tr!("What would you like to add to this item?"),
+            RemoveFromItem => tr!("What would you like to remove from this item?"),
         }
     }
 }
@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+static COLOR_ALIASES: OnceLock<BTreeMap<String, String>> = OnceLock::new();
+
+/// Sets the aliases of color names used for the rest of the session. Can only be set once.
+pub fn init(aliases: BTreeMap<String, String>) {
+    let _ = COLOR_ALIASES.set(aliases);
+}
+
+fn resolve_with<'a>(aliases: &'a BTreeMap<String, String>, name: &'a str) -> &'a str {
+    let name = name.trim();
+    aliases
+        .iter()
+        .find(|(alias, _)| alias.trim().eq_ignore_ascii_case(name))
+        .map_or(name, |(_, color)| color.as_str())
+}
+
+/// Returns the color name that the alias stands for, where aliases are matched ignoring case.
+/// Names that are not aliases are returned as they are.
+pub fn resolve(name: &str) -> &str {
+    match COLOR_ALIASES.get() {
+        Some(aliases) => resolve_with(aliases, name),
+        None => name.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let aliases = BTreeMap::from([
+            ("dkbley".to_string(), "Dark Bluish Gray".to_string()),
+            ("Trans-Clear".to_string(), "Transparent".to_string()),
+        ]);
+        assert_eq!(resolve_with(&aliases, " DKBley"), "Dark Bluish Gray");
+        assert_eq!(resolve_with(&aliases, "trans-clear"), "Transparent");
+        assert_eq!(resolve_with(&aliases, "Pink "), "Pink");
+    }
+}
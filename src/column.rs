@@ -0,0 +1,193 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::currency::{self, Amount, Price};
+use crate::data::Item;
+use crate::locale::{self, tr};
+use crate::width;
+
+/// A column of the list of parts matching a filter.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Id,
+    Name,
+    Color,
+    /// The counted pieces of all lots.
+    Quantity,
+    Location,
+    /// The counted pieces times the price, in the home currency.
+    Value,
+}
+
+impl Column {
+    pub fn get_char(&self) -> char {
+        use Column::*;
+        match self {
+            Id => 'i',
+            Name => 'n',
+            Color => 'c',
+            Quantity => 'q',
+            Location => 'l',
+            Value => 'v',
+        }
+    }
+
+    fn get_cell(&self, item: &Item) -> String {
+        let lots = item.get_locations();
+        let counted: Option<u32> = lots
+            .iter()
+            .filter_map(|lot| lot.get_quantity())
+            .map(|quantity| quantity.get())
+            .reduce(|a, b| a + b);
+        match self {
+            Column::Id => item.get_id().to_string(),
+            Column::Name => item.get_name().to_string(),
+            Column::Color => {
+                let groups: BTreeSet<String> = lots
+                    .iter()
+                    .map(|lot| lot.get_color_group().to_string())
+                    .collect();
+                groups.into_iter().collect::<Vec<_>>().join(", ")
+            }
+            Column::Quantity => counted.map(|n| n.to_string()).unwrap_or_default(),
+            Column::Location => {
+                let locations: Vec<&str> =
+                    lots.iter().map(|lot| lot.get_location().as_str()).collect();
+                locations.join(", ")
+            }
+            Column::Value => item
+                .get_price()
+                .and_then(|price| price.to_home_currency())
+                .zip(counted)
+                .map(|(price, n)| Price::new(price.get_amount().times(n), price.get_currency()))
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Column::Id => tr!("ID"),
+            Column::Name => tr!("Name"),
+            Column::Color => tr!("Color"),
+            Column::Quantity => tr!("Pieces"),
+            Column::Location => tr!("Location"),
+            Column::Value => tr!("Value"),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The columns shown when none are chosen, as the list looked before columns could be chosen.
+pub fn default_columns() -> Vec<Column> {
+    vec![Column::Id, Column::Name]
+}
+
+/// Lays out the items as rows of the columns, with each column as wide as its widest cell in
+/// terminal columns. Returns the header row naming the columns, the rows and the terminal column
+/// where each column starts in them.
+pub fn to_rows(items: &[&Item], columns: &[Column]) -> (String, Vec<String>, Vec<usize>) {
+    let header: Vec<String> = columns.iter().map(Column::to_string).collect();
+    let cells: Vec<Vec<String>> = items
+        .iter()
+        .map(|item| columns.iter().map(|column| column.get_cell(item)).collect())
+        .collect();
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| {
+            cells
+                .iter()
+                .chain([&header])
+                .map(|row| width::get_width(&row[i]))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let layout = |row: &[String]| {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            if i + 1 < row.len() {
+                line.push_str(&width::pad(cell, widths[i] + 2));
+            } else {
+                line.push_str(cell);
+            }
+        }
+        line.trim_end().to_string()
+    };
+    let starts = widths
+        .iter()
+        .scan(0, |start, width| {
+            let column_start = *start;
+            *start += width + 2;
+            Some(column_start)
+        })
+        .collect();
+    (
+        layout(&header),
+        cells.iter().map(|row| layout(row)).collect(),
+        starts,
+    )
+}
+
+/// Sums up the items for the footer of the list.
+pub fn get_totals(items: &[&Item]) -> String {
+    let mut pieces = 0;
+    let mut value = Amount::ZERO;
+    for item in items {
+        let counted: u32 = item
+            .get_locations()
+            .iter()
+            .filter_map(|lot| lot.get_quantity())
+            .map(|quantity| quantity.get())
+            .sum();
+        pieces += counted as usize;
+        if let Some(price) = item.get_price().and_then(|price| price.to_home_currency()) {
+            value += price.get_amount().times(counted);
+        }
+    }
+    tr!(
+        "Total: {} parts, {} counted pieces, worth {}",
+        locale::fmt_count(items.len()),
+        locale::fmt_count(pieces),
+        Price::new(value, currency::get_home_currency())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::{PartId, Quantity};
+
+    #[test]
+    fn test_to_rows() {
+        let mut brick = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        brick.add_color_group(ColorGroup::Basic, "A1".to_string());
+        brick.add_color_group(ColorGroup::Grey, "B12".to_string());
+        brick.set_quantity(&ColorGroup::Basic, Some(Quantity::new(12)));
+        let tile = Item::new(PartId::new(3068).unwrap(), "Tile".to_string());
+
+        let columns = [Column::Id, Column::Name, Column::Quantity, Column::Location];
+        let (header, rows, starts) = to_rows(&[&brick, &tile], &columns);
+        assert_eq!(starts, vec![0, 6, 19, 27]);
+        assert_eq!(header, "ID    Name         Pieces  Location");
+        assert_eq!(rows[0], "3001  Brick 2 x 4  12      A1, B12");
+        assert_eq!(rows[1], "3068  Tile");
+        assert_eq!(to_rows(&[&tile], &[Column::Id]).1, vec!["3068"]);
+
+        // Wide characters take two columns, so fewer spaces are needed to line them up.
+        let wide = Item::new(PartId::new(3069).unwrap(), "積み木 Tile".to_string());
+        let (_, rows, starts) = to_rows(&[&brick, &wide], &[Column::Name, Column::Id]);
+        assert_eq!(starts, vec![0, 13]);
+        assert_eq!(rows, vec!["Brick 2 x 4  3001", "積み木 Tile  3069"]);
+        assert_eq!(
+            get_totals(&[&brick, &tile]),
+            "Total: 2 parts, 12 counted pieces, worth 0.00 EUR"
+        );
+    }
+}
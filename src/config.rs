@@ -1,26 +1,460 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::api::ApiConfig;
+use crate::capacity::{self, Capacity};
+use crate::column::{self, Column};
+use crate::currency::{CurrencyConfig, Price};
+use crate::field::Field;
+use crate::goal::Goal;
+use crate::hook::Hook;
+#[cfg(not(debug_assertions))]
 use crate::io;
+use crate::key::Feedback;
+use crate::locale::{Locale, NumberFormat};
+use crate::platform;
+use crate::plugin::Plugin;
+use crate::remote::RemoteConfig;
+use crate::rest::RestConfig;
+use crate::rule::Rule;
+use crate::secret::Secrets;
+use crate::set::Set;
+use crate::shipping::ShippingRate;
+use crate::site::Site;
+use crate::summary::SummaryCommand;
+use crate::theme::Theme;
+use crate::timestamp::TimeConfig;
+use crate::types::PartId;
+use crate::ui::Answer;
+use crate::widget::{self, Widget};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
+    /// Where the database is, where `~` at the start stands for the home directory.
     db_path: String,
+    /// The database given with `--db`, which is used in place of `db_path` and never saved.
+    #[serde(skip)]
+    db_override: Option<PathBuf>,
+    #[serde(default)]
+    locale: Locale,
+    #[serde(default)]
+    number_format: NumberFormat,
+    #[serde(default)]
+    currency: CurrencyConfig,
+    #[serde(default)]
+    read_only: bool,
+    #[serde(default)]
+    dry_run: bool,
+    /// Reload the database while running when it is changed on disk.
+    #[serde(default)]
+    watch: bool,
+    /// Write plain lines that screen readers can follow, and ask for answers one line at a time.
+    #[serde(default)]
+    accessible: bool,
+    /// Encrypt the database and the logs with a passphrase asked for at startup. An encrypted
+    /// database is opened with its passphrase even when this is off.
+    #[serde(default)]
+    encrypted: bool,
+    /// Log the commands used and the time spent in modes to a file next to the config, which is
+    /// only ever read to report on it.
+    #[serde(default)]
+    usage_log: bool,
+    /// Warn when giving a part a color it was never produced in, looked up on Rebrickable if
+    /// there is an API key for it.
+    #[serde(default = "default_check_colors")]
+    check_colors: bool,
+    /// Recorded macros by name.
+    #[serde(default)]
+    macros: BTreeMap<String, Vec<Answer>>,
+    /// The widgets shown on the default screen, in order.
+    #[serde(default = "widget::default_widgets")]
+    widgets: Vec<Widget>,
+    /// Lots with fewer pieces than this are shown in the low stock widget.
+    #[serde(default = "default_low_stock_threshold")]
+    low_stock_threshold: u32,
+    /// Names of saved filters to the filters, see `Filter`.
+    #[serde(default)]
+    saved_filters: BTreeMap<String, String>,
+    /// Collection goals shown in the goals widget.
+    #[serde(default)]
+    goals: Vec<Goal>,
+    /// The owned sets and their inventories.
+    #[serde(default)]
+    sets: Vec<Set>,
+    /// The site whose part pages are opened in the browser.
+    #[serde(default)]
+    part_page_site: Site,
+    /// Other names for colors, like "dkbley" for "Dark Bluish Gray", that are used in place of the
+    /// color name when entering a color, taking in orders and filtering.
+    #[serde(default)]
+    color_aliases: BTreeMap<String, String>,
+    /// Keys for commands by name, e.g. `SaveEdit: C-s` or `DeleteItem: d d`, in place of their
+    /// char.
+    #[serde(default)]
+    key_bindings: BTreeMap<String, String>,
+    /// How long to wait in milliseconds for the next key of a chord, when the keys pressed so far
+    /// also give a command.
+    #[serde(default)]
+    chord_timeout_ms: Option<u64>,
+    /// How pressing keys that give no command is shown.
+    #[serde(default)]
+    invalid_key_feedback: Feedback,
+    /// The shipping rates by weight used to estimate the cost of shipping parts.
+    #[serde(default)]
+    shipping_rates: Vec<ShippingRate>,
+    /// The API keys and rate limits for the APIs of sites.
+    #[serde(default)]
+    api: BTreeMap<Site, ApiConfig>,
+    /// The API keys from the secrets file, which are never saved in the config.
+    #[serde(skip)]
+    secret_api_keys: BTreeMap<Site, String>,
+    /// Groups of parts that can be used in place of each other, shown as similar parts.
+    #[serde(default)]
+    substitutes: Vec<Vec<PartId>>,
+    /// How many pieces fit in locations, by location or by prefix like `A*`, see `Capacity`.
+    #[serde(default)]
+    location_capacities: BTreeMap<String, Capacity>,
+    /// The number of pieces of size classes like `small` used as location capacities.
+    #[serde(default)]
+    capacity_classes: BTreeMap<String, u32>,
+    /// Who is making changes, recorded with each change to a quantity when the database is
+    /// shared.
+    #[serde(default)]
+    user: Option<String>,
+    /// The server to sync the database with, or to serve it as.
+    #[serde(default)]
+    remote: RemoteConfig,
+    /// Where the API of a session started with `--api` listens.
+    #[serde(default)]
+    rest_api: RestConfig,
+    /// External programs run as commands, see `Plugin`.
+    #[serde(default)]
+    plugins: Vec<Plugin>,
+    /// Commands run on changes to the database, see `Hook`.
+    #[serde(default)]
+    hooks: Vec<Hook>,
+    /// How much may be spent on parts each month, shown by the budget widget, e.g.
+    /// `{amount: 50, currency: EUR}`.
+    #[serde(default)]
+    monthly_budget: Option<Price>,
+    /// The command the monthly summary is piped to when sending it, see `SummaryCommand`.
+    #[serde(default)]
+    summary_command: Option<SummaryCommand>,
+    /// Fields of items for what the database has no place for, see `Field`.
+    #[serde(default)]
+    custom_fields: Vec<Field>,
+    /// Rules items must keep to, checked when saving them and in the check of the database, see
+    /// `Rule`.
+    #[serde(default)]
+    rules: Vec<Rule>,
+    /// The columns of the list of parts matching a filter.
+    #[serde(default = "column::default_columns")]
+    list_columns: Vec<Column>,
+    /// The columns chosen for the saved filters by name, in place of `list_columns`.
+    #[serde(default)]
+    filter_columns: BTreeMap<String, Vec<Column>>,
+    /// How selection and emphasis are shown, see `Theme`.
+    #[serde(default)]
+    theme: Theme,
+    /// How times are shown, see `TimeConfig`.
+    #[serde(default)]
+    time: TimeConfig,
+}
+
+fn default_low_stock_threshold() -> u32 {
+    5
+}
+
+fn default_check_colors() -> bool {
+    true
+}
+
+#[cfg(not(debug_assertions))]
+fn get_default_db_path() -> String {
+    let mut db_path = io::get_storage_dir();
+    db_path.push("database.yml");
+    db_path.to_string_lossy().to_string()
+}
+
+#[cfg(debug_assertions)]
+fn get_default_db_path() -> String {
+    "test_db.yml".to_string()
 }
 
 impl Default for Config {
     fn default() -> Self {
-        let mut db_path = io::get_storage_dir();
-        db_path.push("database.yml");
         Self {
-            db_path: db_path.to_string_lossy().to_string(),
+            db_path: get_default_db_path(),
+            db_override: None,
+            locale: Locale::default(),
+            number_format: NumberFormat::default(),
+            currency: CurrencyConfig::default(),
+            read_only: false,
+            dry_run: false,
+            watch: false,
+            accessible: false,
+            encrypted: false,
+            usage_log: false,
+            check_colors: default_check_colors(),
+            macros: BTreeMap::new(),
+            widgets: widget::default_widgets(),
+            low_stock_threshold: default_low_stock_threshold(),
+            saved_filters: BTreeMap::new(),
+            goals: Vec::new(),
+            sets: Vec::new(),
+            part_page_site: Site::default(),
+            color_aliases: BTreeMap::new(),
+            key_bindings: BTreeMap::new(),
+            chord_timeout_ms: None,
+            invalid_key_feedback: Feedback::default(),
+            shipping_rates: Vec::new(),
+            api: BTreeMap::new(),
+            secret_api_keys: BTreeMap::new(),
+            substitutes: Vec::new(),
+            location_capacities: BTreeMap::new(),
+            capacity_classes: BTreeMap::new(),
+            user: None,
+            remote: RemoteConfig::default(),
+            rest_api: RestConfig::default(),
+            plugins: Vec::new(),
+            hooks: Vec::new(),
+            monthly_budget: None,
+            summary_command: None,
+            custom_fields: Vec::new(),
+            rules: Vec::new(),
+            list_columns: column::default_columns(),
+            filter_columns: BTreeMap::new(),
+            theme: Theme::default(),
+            time: TimeConfig::default(),
         }
     }
 }
 
 impl Config {
-    pub fn get_db_path(self) -> PathBuf {
-        self.db_path.into()
+    pub fn get_db_path(&self) -> PathBuf {
+        match &self.db_override {
+            Some(path) => path.clone(),
+            None => platform::expand_path(&self.db_path),
+        }
+    }
+
+    /// Uses the database for this run, without changing the config that is saved.
+    pub fn override_db_path(&mut self, db_path: PathBuf) {
+        self.db_override = Some(db_path);
+    }
+
+    pub fn set_db_path(&mut self, db_path: &Path) {
+        self.db_path = db_path.to_string_lossy().to_string();
+    }
+
+    pub fn get_locale(&self) -> Locale {
+        self.locale
+    }
+
+    pub fn get_number_format(&self) -> NumberFormat {
+        self.number_format
+    }
+
+    pub fn get_currency(&self) -> CurrencyConfig {
+        self.currency.clone()
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn get_user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    pub fn set_user(&mut self, user: String) {
+        self.user = Some(user);
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watch
+    }
+
+    pub fn is_accessible(&self) -> bool {
+        self.accessible
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    pub fn is_logging_usage(&self) -> bool {
+        self.usage_log
+    }
+
+    pub fn is_checking_colors(&self) -> bool {
+        self.check_colors
+    }
+
+    pub fn get_macros(&self) -> &BTreeMap<String, Vec<Answer>> {
+        &self.macros
+    }
+
+    pub fn set_macro(&mut self, name: String, answers: Vec<Answer>) {
+        self.macros.insert(name, answers);
+    }
+
+    pub fn get_widgets(&self) -> &[Widget] {
+        &self.widgets
+    }
+
+    pub fn get_low_stock_threshold(&self) -> u32 {
+        self.low_stock_threshold
+    }
+
+    pub fn get_saved_filters(&self) -> &BTreeMap<String, String> {
+        &self.saved_filters
+    }
+
+    /// Returns the columns of the list for the saved filter, or for other filters if `None`.
+    pub fn get_columns(&self, saved_filter: Option<&str>) -> &[Column] {
+        saved_filter
+            .and_then(|name| self.filter_columns.get(name))
+            .unwrap_or(&self.list_columns)
+    }
+
+    pub fn set_columns(&mut self, saved_filter: Option<&str>, columns: Vec<Column>) {
+        match saved_filter {
+            Some(name) => {
+                self.filter_columns.insert(name.to_string(), columns);
+            }
+            None => self.list_columns = columns,
+        }
+    }
+
+    pub fn get_goals(&self) -> &[Goal] {
+        &self.goals
+    }
+
+    pub fn get_sets(&self) -> &[Set] {
+        &self.sets
+    }
+
+    /// Adds the set, replacing the set with the same number if there is one.
+    pub fn add_set(&mut self, set: Set) {
+        match self
+            .sets
+            .iter_mut()
+            .find(|other| other.get_number() == set.get_number())
+        {
+            Some(other) => *other = set,
+            None => self.sets.push(set),
+        }
+    }
+
+    pub fn get_part_page_site(&self) -> Site {
+        self.part_page_site
+    }
+
+    pub fn get_color_aliases(&self) -> BTreeMap<String, String> {
+        self.color_aliases.clone()
+    }
+
+    pub fn get_key_bindings(&self) -> &BTreeMap<String, String> {
+        &self.key_bindings
+    }
+
+    pub fn get_chord_timeout(&self) -> Option<Duration> {
+        self.chord_timeout_ms.map(Duration::from_millis)
+    }
+
+    pub fn get_shipping_rates(&self) -> &[ShippingRate] {
+        &self.shipping_rates
+    }
+
+    pub fn get_substitutes(&self) -> &[Vec<PartId>] {
+        &self.substitutes
+    }
+
+    pub fn get_capacity(&self, location: &str) -> Option<u32> {
+        capacity::get_capacity(&self.location_capacities, &self.capacity_classes, location)
+    }
+
+    pub fn get_remote_config(&self) -> &RemoteConfig {
+        &self.remote
+    }
+
+    pub fn get_hooks(&self) -> &[Hook] {
+        &self.hooks
+    }
+
+    pub fn get_monthly_budget(&self) -> Option<&Price> {
+        self.monthly_budget.as_ref()
+    }
+
+    pub fn get_summary_command(&self) -> Option<&SummaryCommand> {
+        self.summary_command.as_ref()
+    }
+
+    pub fn get_custom_fields(&self) -> &[Field] {
+        &self.custom_fields
+    }
+
+    pub fn get_rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn get_invalid_key_feedback(&self) -> Feedback {
+        self.invalid_key_feedback
+    }
+
+    pub fn get_theme(&self) -> Theme {
+        self.theme
+    }
+
+    pub fn get_time(&self) -> &TimeConfig {
+        &self.time
+    }
+
+    pub fn get_plugins(&self) -> &[Plugin] {
+        &self.plugins
+    }
+
+    pub fn get_rest_config(&self) -> &RestConfig {
+        &self.rest_api
+    }
+
+    pub fn get_api_config(&self, site: Site) -> ApiConfig {
+        let mut config = self.api.get(&site).cloned().unwrap_or_default();
+        if let Some(key) = self.secret_api_keys.get(&site) {
+            config.set_key(Some(key.clone()));
+        }
+        config
+    }
+
+    pub fn set_secrets(&mut self, secrets: &Secrets) {
+        self.secret_api_keys = secrets.get_api_keys().clone();
+    }
+
+    /// The sites whose API key is written in the config itself.
+    pub fn get_sites_with_plain_keys(&self) -> Vec<Site> {
+        self.api
+            .iter()
+            .filter(|(_, api)| api.has_key())
+            .map(|(site, _)| *site)
+            .collect()
+    }
+
+    /// Removes the API key of the site from the config, returning whether there was one.
+    pub fn remove_api_key(&mut self, site: Site) -> bool {
+        match self.api.get_mut(&site) {
+            Some(api) if api.has_key() => {
+                api.set_key(None);
+                true
+            }
+            _ => false,
+        }
     }
 }
@@ -0,0 +1,209 @@
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+use crate::error::{Error, Result};
+use crate::locale::tr;
+use crate::secret;
+
+/// Starts every encrypted file, so that it can be told apart from a plain one. It is followed by
+/// the salt the key is derived with, the nonce, and the encrypted contents with their tag.
+const MAGIC: &[u8] = b"tuibricks-encrypted-v1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Given in place of typing the passphrase, e.g. for scripts.
+const PASSPHRASE_VAR: &str = "TUIBRICKS_PASSPHRASE";
+
+static VAULT: OnceLock<Vault> = OnceLock::new();
+
+/// The passphrase of the session and the key derived from it, with which files are written.
+struct Vault {
+    passphrase: String,
+    salt: [u8; SALT_LEN],
+    key: Key,
+}
+
+impl Vault {
+    fn new(passphrase: String, salt: [u8; SALT_LEN]) -> std::io::Result<Self> {
+        let key = derive_key(&passphrase, &salt)?;
+        Ok(Self {
+            passphrase,
+            salt,
+            key,
+        })
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> std::io::Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&self.salt);
+        header.extend_from_slice(&nonce);
+        let payload = Payload {
+            msg: plaintext,
+            aad: &header,
+        };
+        let ciphertext = XChaCha20Poly1305::new(&self.key)
+            .encrypt(&nonce, payload)
+            .map_err(|_| invalid("the contents could not be encrypted"))?;
+        header.extend(ciphertext);
+        Ok(header)
+    }
+
+    /// Decrypts a file written with this passphrase, also when it was written with another salt.
+    fn decrypt(&self, contents: &[u8]) -> std::io::Result<Vec<u8>> {
+        let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+        if !is_encrypted(contents) || contents.len() < header_len {
+            return Err(invalid("the encrypted file is cut off"));
+        }
+        let (header, ciphertext) = contents.split_at(header_len);
+        let salt = &header[MAGIC.len()..MAGIC.len() + SALT_LEN];
+        let key = if salt == self.salt {
+            self.key
+        } else {
+            derive_key(&self.passphrase, salt)?
+        };
+        let payload = Payload {
+            msg: ciphertext,
+            aad: header,
+        };
+        XChaCha20Poly1305::new(&key)
+            .decrypt(
+                XNonce::from_slice(&header[MAGIC.len() + SALT_LEN..]),
+                payload,
+            )
+            .map_err(|_| invalid("the passphrase is wrong or the encrypted file is damaged"))
+    }
+}
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> std::io::Result<Key> {
+    let mut key = Key::default();
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| invalid(&e.to_string()))?;
+    Ok(key)
+}
+
+pub fn is_encrypted(contents: &[u8]) -> bool {
+    contents.starts_with(MAGIC)
+}
+
+/// Whether files with valuable data are written encrypted in this session.
+pub fn is_enabled() -> bool {
+    VAULT.get().is_some()
+}
+
+/// Encrypts the contents with the passphrase of the session, or leaves them as they are if there
+/// is none.
+pub fn encrypt(plaintext: &[u8]) -> std::io::Result<Vec<u8>> {
+    match VAULT.get() {
+        Some(vault) => vault.encrypt(plaintext),
+        None => Ok(plaintext.to_vec()),
+    }
+}
+
+/// Decrypts the contents if they are encrypted, which needs the passphrase of the session.
+pub fn decrypt(contents: Vec<u8>) -> std::io::Result<Vec<u8>> {
+    if !is_encrypted(&contents) {
+        return Ok(contents);
+    }
+    match VAULT.get() {
+        Some(vault) => vault.decrypt(&contents),
+        None => Err(invalid(
+            "the file is encrypted, but no passphrase was given",
+        )),
+    }
+}
+
+/// Asks for the passphrase if the database is encrypted or is to be encrypted, and uses it for
+/// the rest of the session. A new passphrase is asked for twice, and the passphrase of an existing
+/// database is checked before going on. Can only be done once.
+pub fn unlock(db_path: &Path, wanted: bool) -> Result<()> {
+    let contents = match std::fs::read(db_path) {
+        Ok(contents) => Some(contents),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let existing = contents
+        .as_deref()
+        .filter(|contents| is_encrypted(contents));
+    if existing.is_none() && !wanted {
+        return Ok(());
+    }
+
+    let locked = |msg: &str| Error::Locked {
+        msg: msg.to_string(),
+    };
+    let vault = match existing {
+        Some(contents) => {
+            let passphrase = read_passphrase(tr!("Passphrase of the database:"))?;
+            let salt = contents[MAGIC.len()..]
+                .get(..SALT_LEN)
+                .and_then(|salt| salt.try_into().ok())
+                .ok_or_else(|| locked("the encrypted file is cut off"))?;
+            let vault = Vault::new(passphrase, salt)?;
+            vault
+                .decrypt(contents)
+                .map_err(|e| locked(&e.to_string()))?;
+            vault
+        }
+        None => {
+            let passphrase = read_passphrase(tr!("New passphrase to encrypt the database with:"))?;
+            if passphrase.is_empty() {
+                return Err(locked("the passphrase is empty"));
+            }
+            if std::env::var_os(PASSPHRASE_VAR).is_none()
+                && read_passphrase(tr!("Repeat the passphrase:"))? != passphrase
+            {
+                return Err(locked("the passphrases are not the same"));
+            }
+            let mut salt = [0; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            Vault::new(passphrase, salt)?
+        }
+    };
+    let _ = VAULT.set(vault);
+    Ok(())
+}
+
+/// Reads the passphrase from the environment, or asks for it without showing what is typed.
+fn read_passphrase(prompt: &str) -> Result<String> {
+    match std::env::var_os(PASSPHRASE_VAR) {
+        Some(passphrase) => Ok(passphrase.to_string_lossy().to_string()),
+        None => secret::ask(prompt),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt() {
+        let vault = Vault::new("hunter2".to_string(), [7; SALT_LEN]).unwrap();
+        let plaintext = b"items:\n- id: 3001\n";
+        let encrypted = vault.encrypt(plaintext).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.windows(4).any(|w| w == b"3001"));
+        assert_eq!(vault.decrypt(&encrypted).unwrap(), plaintext);
+        assert_ne!(vault.encrypt(plaintext).unwrap(), encrypted);
+
+        let other_salt = Vault::new("hunter2".to_string(), [8; SALT_LEN]).unwrap();
+        assert_eq!(other_salt.decrypt(&encrypted).unwrap(), plaintext);
+        let wrong = Vault::new("hunter3".to_string(), [7; SALT_LEN]).unwrap();
+        assert!(wrong.decrypt(&encrypted).is_err());
+
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(vault.decrypt(&tampered).is_err());
+        assert!(vault.decrypt(&encrypted[..MAGIC.len() + 3]).is_err());
+        assert!(!is_encrypted(plaintext));
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::locale;
+
+static CURRENCY_CONFIG: OnceLock<CurrencyConfig> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CurrencyConfig {
+    home_currency: String,
+    /// How many units of the home currency one unit of the given currency is worth.
+    #[serde(default)]
+    exchange_rates: BTreeMap<String, f64>,
+}
+
+impl Default for CurrencyConfig {
+    fn default() -> Self {
+        Self {
+            home_currency: "EUR".to_string(),
+            exchange_rates: BTreeMap::new(),
+        }
+    }
+}
+
+impl CurrencyConfig {
+    fn get_rate(&self, currency: &str) -> Option<f64> {
+        if currency == self.home_currency {
+            Some(1.0)
+        } else {
+            self.exchange_rates.get(currency).copied()
+        }
+    }
+}
+
+/// Sets the currency configuration used for the rest of the session. Can only be set once.
+pub fn init(config: CurrencyConfig) {
+    let _ = CURRENCY_CONFIG.set(config);
+}
+
+fn get_config() -> &'static CurrencyConfig {
+    CURRENCY_CONFIG.get_or_init(CurrencyConfig::default)
+}
+
+pub fn get_home_currency() -> &'static str {
+    &get_config().home_currency
+}
+
+/// An amount of money in ten-thousandths of a unit, so that sums are exact. Part prices are often
+/// fractions of a cent, so cents are not precise enough. It is stored as a decimal string with
+/// four decimals, like "12.3400", and amounts stored as numbers by older versions are read too.
+#[derive(
+    Serialize, Deserialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy,
+)]
+#[serde(try_from = "StoredAmount", into = "String")]
+pub struct Amount(i64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredAmount {
+    Decimal(String),
+    Legacy(f64),
+}
+
+impl TryFrom<StoredAmount> for Amount {
+    type Error = String;
+
+    fn try_from(stored: StoredAmount) -> Result<Self, String> {
+        match stored {
+            StoredAmount::Decimal(s) => {
+                Amount::parse(&s).ok_or_else(|| format!("invalid amount: {}", s))
+            }
+            StoredAmount::Legacy(x) => Ok(Amount::from(x)),
+        }
+    }
+}
+
+impl From<Amount> for String {
+    fn from(amount: Amount) -> Self {
+        let sign = if amount.0 < 0 { "-" } else { "" };
+        let units = amount.0.unsigned_abs();
+        let scale = SCALE as u64;
+        format!("{}{}.{:04}", sign, units / scale, units % scale)
+    }
+}
+
+const SCALE: i64 = 10_000;
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    pub fn from_cents(cents: i64) -> Self {
+        Self(cents * (SCALE / 100))
+    }
+
+    /// Parses amounts such as "12", "0.25", "0,0125" or "-3.5", rounding to four decimals.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let (int_part, frac_part) = s.split_once(['.', ',']).unwrap_or((s, ""));
+        let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if int_part.is_empty() && frac_part.is_empty()
+            || !is_digits(int_part)
+            || !is_digits(frac_part)
+        {
+            return None;
+        }
+
+        let int_part: i64 = if int_part.is_empty() {
+            0
+        } else {
+            int_part.parse().ok()?
+        };
+        let mut digits = frac_part.bytes().map(|digit| (digit - b'0') as i64);
+        let mut frac = 0;
+        for _ in 0..4 {
+            frac = frac * 10 + digits.next().unwrap_or(0);
+        }
+        if digits.next().is_some_and(|digit| digit >= 5) {
+            frac += 1;
+        }
+        let units = int_part.checked_mul(SCALE)?.checked_add(frac)?;
+        Some(Self(if negative { -units } else { units }))
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn times(self, n: u32) -> Self {
+        Self(self.0 * n as i64)
+    }
+
+    /// The average of the amounts weighted by the counts, rounded once half away from zero, or
+    /// none if the counts add up to zero.
+    pub fn weighted_average(amounts: &[(Amount, u32)]) -> Option<Self> {
+        let total: i128 = amounts
+            .iter()
+            .map(|(amount, n)| amount.0 as i128 * *n as i128)
+            .sum();
+        let weight: i128 = amounts.iter().map(|(_, n)| *n as i128).sum();
+        if weight == 0 {
+            return None;
+        }
+        Some(Self(
+            ((2 * total + total.signum() * weight) / (2 * weight)) as i64,
+        ))
+    }
+
+    /// Converts the amount with an exchange rate, rounding once.
+    pub fn convert(self, rate: f64) -> Self {
+        Self::from(self.0 as f64 / SCALE as f64 * rate)
+    }
+
+    /// Formats the amount for the locale, rounded half away from zero to the given number of
+    /// decimals, e.g. "1,234.50".
+    pub fn fmt_decimal(self, decimals: usize) -> String {
+        let step = 10i64.pow(4u32.saturating_sub(decimals as u32));
+        let rounded = (self.0 + self.0.signum() * (step / 2)) / step * step;
+        locale::fmt_decimal(rounded as f64 / SCALE as f64, decimals.min(4))
+    }
+}
+
+impl From<f64> for Amount {
+    fn from(x: f64) -> Self {
+        Self((x * SCALE as f64).round() as i64)
+    }
+}
+
+impl From<Amount> for f64 {
+    fn from(amount: Amount) -> Self {
+        amount.0 as f64 / SCALE as f64
+    }
+}
+
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, other: Amount) -> Amount {
+        Amount(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Amount {
+    fn add_assign(&mut self, other: Amount) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Amount {
+    type Output = Amount;
+
+    fn sub(self, other: Amount) -> Amount {
+        Amount(self.0 - other.0)
+    }
+}
+
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Amount {
+        iter.fold(Amount::ZERO, Add::add)
+    }
+}
+
+/// Displays the amount with as few decimals as needed and a point, as it can be parsed again,
+/// e.g. "12" or "0.0125".
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 < 0 {
+            write!(f, "-")?;
+        }
+        let (int_part, frac_part) = (self.0.abs() / SCALE, self.0.abs() % SCALE);
+        write!(f, "{}", int_part)?;
+        if frac_part != 0 {
+            write!(f, ".{}", format!("{:04}", frac_part).trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Price {
+    amount: Amount,
+    currency: String,
+}
+
+impl Price {
+    pub fn new(amount: Amount, currency: &str) -> Self {
+        Self {
+            amount,
+            currency: currency.to_uppercase(),
+        }
+    }
+
+    /// Parses prices such as "0.25", "0,25 usd" or "12 DKK". If no currency is given the home
+    /// currency is used.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.split_whitespace();
+        let amount = Amount::parse(parts.next()?)?;
+        if amount.is_negative() {
+            return None;
+        }
+        let currency = parts.next().unwrap_or(get_home_currency());
+        if parts.next().is_some() || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        Some(Self::new(amount, currency))
+    }
+
+    pub fn get_amount(&self) -> Amount {
+        self.amount
+    }
+
+    pub fn get_currency(&self) -> &str {
+        &self.currency
+    }
+
+    /// Converts the price to the home currency, if an exchange rate is known.
+    pub fn to_home_currency(&self) -> Option<Price> {
+        let rate = get_config().get_rate(&self.currency)?;
+        Some(Price::new(self.amount.convert(rate), get_home_currency()))
+    }
+
+    /// Displays the price in its own currency followed by the home currency if they differ.
+    pub fn display_with_home(&self) -> String {
+        match self.to_home_currency() {
+            Some(home) if home.currency != self.currency => format!("{} ({})", self, home),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount.fmt_decimal(2), self.currency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount() {
+        let parse = |s| Amount::parse(s).map(|amount| amount.to_string());
+        assert_eq!(parse("0,25").as_deref(), Some("0.25"));
+        assert_eq!(parse("12").as_deref(), Some("12"));
+        assert_eq!(parse("-.5").as_deref(), Some("-0.5"));
+        assert_eq!(parse("0.00125").as_deref(), Some("0.0013"));
+        assert_eq!(parse("1e3"), None);
+        assert_eq!(parse("."), None);
+        assert!(Price::parse("-1 EUR").is_none());
+
+        // Ten cents added up a thousand times are exactly a hundred, unlike with floats.
+        let total: Amount = std::iter::repeat_n(Amount::parse("0.1").unwrap(), 1000).sum();
+        assert_eq!(total, Amount::from_cents(10_000));
+        assert_eq!(
+            Amount::from_cents(5).times(3),
+            Amount::parse("0.15").unwrap()
+        );
+        assert_eq!(Amount::parse("0.005").unwrap().fmt_decimal(2), "0.01");
+        assert_eq!(Amount::parse("-0.125").unwrap().fmt_decimal(2), "-0.13");
+        assert_eq!(Amount::from(0.1 + 0.2), Amount::parse("0.3").unwrap());
+
+        let average = |amounts: &[(&str, u32)]| {
+            let amounts: Vec<_> = amounts
+                .iter()
+                .map(|(s, n)| (Amount::parse(s).unwrap(), *n))
+                .collect();
+            Amount::weighted_average(&amounts).map(|amount| amount.to_string())
+        };
+        assert_eq!(
+            average(&[("0.1", 10), ("0.3", 30)]).as_deref(),
+            Some("0.25")
+        );
+        assert_eq!(
+            average(&[("0.0001", 1), ("0", 1)]).as_deref(),
+            Some("0.0001")
+        );
+        assert_eq!(average(&[("1", 0)]), None);
+    }
+
+    #[test]
+    fn test_stored_amount() {
+        let price = Price::parse("12.34 EUR").unwrap();
+        let yaml = serde_yaml::to_string(&price).unwrap();
+        assert!(yaml.contains("12.3400"));
+        assert_eq!(serde_yaml::from_str::<Price>(&yaml).unwrap(), price);
+        let negative: String = Amount::parse("-0.05").unwrap().into();
+        assert_eq!(negative, "-0.0500");
+
+        let legacy: Price = serde_yaml::from_str("amount: 12.34\ncurrency: EUR\n").unwrap();
+        assert_eq!(legacy, price);
+        assert!(serde_yaml::from_str::<Price>("amount: twelve\ncurrency: EUR\n").is_err());
+    }
+}
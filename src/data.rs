@@ -1,15 +1,33 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use serde::{Deserialize, Serialize};
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use strum::{EnumIter, IntoEnumIterator};
 
 use term_lib::command::Command;
-
+use ulid::Ulid;
+
+use crate::age::Ages;
+use crate::assembly;
+use crate::crypt;
+use crate::currency::{self, Amount, Price};
+use crate::date::Range;
+use crate::dimensions::Dimensions;
 use crate::error::{Error, Result};
+use crate::field;
+use crate::hook;
 use crate::io;
+use crate::journal;
+use crate::locale::{self, tr, tr_n};
+use crate::search;
+use crate::sync::{self, Conflict};
+use crate::timestamp::Timestamp;
+use crate::transaction::{self, Pending};
+use crate::types::{PartId, Quantity, Years};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, EnumIter, Ord, PartialOrd)]
 pub enum ColorGroup {
@@ -47,16 +65,16 @@ impl Command for ColorGroup {
     fn get_info(&self) -> &str {
         use ColorGroup::*;
         match &self {
-            All => "All",
-            Basic => "Basic",
-            Earth => "Earth",
-            Grey => "Grey",
-            Road => "Road",
-            Nice => "Nice",
-            Build => "Build",
-            Translucent => "Translucent",
-            Colorful => "Colorful",
-            Misc => "Misc",
+            All => tr!("All"),
+            Basic => tr!("Basic"),
+            Earth => tr!("Earth"),
+            Grey => tr!("Grey"),
+            Road => tr!("Road"),
+            Nice => tr!("Nice"),
+            Build => tr!("Build"),
+            Translucent => tr!("Translucent"),
+            Colorful => tr!("Colorful"),
+            Misc => tr!("Misc"),
             Other(name) => name,
         }
     }
@@ -68,33 +86,476 @@ impl fmt::Display for ColorGroup {
     }
 }
 
+/// Whether the pieces of a lot are new or used, as told apart on BrickLink.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Condition {
+    New,
+    Used,
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::New => write!(f, "{}", tr!("new")),
+            Condition::Used => write!(f, "{}", tr!("used")),
+        }
+    }
+}
+
+/// A location where pieces of one color group of an item are stored.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct Lot {
+    /// Tells the lot apart from other lots of the item also when its place changes, and in other
+    /// databases the lot is copied to. Given when the lot is first saved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<Ulid>,
+    color_group: ColorGroup,
+    location: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quantity: Option<Quantity>,
+    /// The asking price per piece when the lot is listed for sale.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    asking_price: Option<Price>,
+    /// The weight of the pieces in grams, for lots that are weighed rather than counted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    weight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    added: Option<Timestamp>,
+    /// When the pieces, price or weight of the lot last changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<Timestamp>,
+    /// When pieces were last bought for the lot, by taking in an order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    purchased: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    condition: Option<Condition>,
+    /// Free text about the lot, e.g. the remarks of a lot imported from BrickStore.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    /// The ID of the lot this lot was split off from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    split_from: Option<Ulid>,
+    /// The IDs of the lots that were merged into this lot.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    merged_from: Vec<Ulid>,
+}
+
+#[derive(Deserialize)]
+struct LotFields {
+    #[serde(default)]
+    id: Option<Ulid>,
+    color_group: ColorGroup,
+    location: String,
+    #[serde(default)]
+    quantity: Option<Quantity>,
+    #[serde(default)]
+    asking_price: Option<Price>,
+    #[serde(default)]
+    weight: Option<f64>,
+    #[serde(default)]
+    added: Option<Timestamp>,
+    #[serde(default)]
+    modified: Option<Timestamp>,
+    #[serde(default)]
+    purchased: Option<Timestamp>,
+    #[serde(default)]
+    condition: Option<Condition>,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    split_from: Option<Ulid>,
+    #[serde(default)]
+    merged_from: Vec<Ulid>,
+}
+
+/// Lots used to be stored as a (color group, location) pair. An untagged enum cannot be used to
+/// accept both, as it does not support the tagged other color groups.
+struct LotVisitor;
+
+impl<'de> Visitor<'de> for LotVisitor {
+    type Value = Lot;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a lot or a (color group, location) pair")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Lot, A::Error> {
+        let color_group = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let location = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Lot::new(color_group, location))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> std::result::Result<Lot, A::Error> {
+        let fields = LotFields::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        Ok(Lot {
+            id: fields.id,
+            color_group: fields.color_group,
+            location: fields.location,
+            quantity: fields.quantity,
+            asking_price: fields.asking_price,
+            weight: fields.weight,
+            added: fields.added,
+            modified: fields.modified,
+            purchased: fields.purchased,
+            condition: fields.condition,
+            notes: fields.notes,
+            split_from: fields.split_from,
+            merged_from: fields.merged_from,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Lot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Lot, D::Error> {
+        deserializer.deserialize_any(LotVisitor)
+    }
+}
+
+impl Lot {
+    pub fn new(color_group: ColorGroup, location: String) -> Self {
+        Self {
+            id: None,
+            color_group,
+            location,
+            quantity: None,
+            asking_price: None,
+            weight: None,
+            added: None,
+            modified: None,
+            purchased: None,
+            condition: None,
+            notes: None,
+            split_from: None,
+            merged_from: Vec::new(),
+        }
+    }
+
+    pub fn get_id(&self) -> Option<Ulid> {
+        self.id
+    }
+
+    pub fn get_color_group(&self) -> &ColorGroup {
+        &self.color_group
+    }
+
+    pub fn get_location(&self) -> &String {
+        &self.location
+    }
+
+    pub fn get_quantity(&self) -> Option<Quantity> {
+        self.quantity
+    }
+
+    pub fn set_quantity(&mut self, quantity: Option<Quantity>) {
+        self.quantity = quantity;
+    }
+
+    pub fn get_asking_price(&self) -> Option<&Price> {
+        self.asking_price.as_ref()
+    }
+
+    pub fn set_asking_price(&mut self, asking_price: Option<Price>) {
+        self.asking_price = asking_price;
+    }
+
+    pub fn get_weight(&self) -> Option<f64> {
+        self.weight
+    }
+
+    pub fn set_weight(&mut self, weight: Option<f64>) {
+        self.weight = weight;
+    }
+
+    pub fn get_added(&self) -> Option<Timestamp> {
+        self.added
+    }
+
+    pub fn get_modified(&self) -> Option<Timestamp> {
+        self.modified
+    }
+
+    pub fn get_purchased(&self) -> Option<Timestamp> {
+        self.purchased
+    }
+
+    pub fn set_purchased(&mut self, purchased: Option<Timestamp>) {
+        self.purchased = purchased;
+    }
+
+    pub fn get_condition(&self) -> Option<Condition> {
+        self.condition
+    }
+
+    pub fn set_condition(&mut self, condition: Option<Condition>) {
+        self.condition = condition;
+    }
+
+    pub fn get_notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    pub fn set_notes(&mut self, notes: Option<String>) {
+        self.notes = notes;
+    }
+
+    pub fn get_split_from(&self) -> Option<Ulid> {
+        self.split_from
+    }
+
+    pub fn get_merged_from(&self) -> &[Ulid] {
+        &self.merged_from
+    }
+
+    /// Whether the lot has the ID, or the lot with the ID was merged into it.
+    fn is_or_has_merged(&self, id: Ulid) -> bool {
+        self.id == Some(id) || self.merged_from.contains(&id)
+    }
+
+    /// Keeps the times of the lot as it was before a change, and marks the lot as added or
+    /// modified now if it is new or changed.
+    fn stamp(&mut self, old: Option<&Lot>, now: Timestamp) {
+        let Some(old) = old else {
+            self.added.get_or_insert(now);
+            self.id.get_or_insert_with(Ulid::generate);
+            return;
+        };
+        self.id = self.id.or(old.id);
+        self.added = self.added.or(old.added);
+        self.modified = self.modified.or(old.modified);
+        self.purchased = self.purchased.or(old.purchased);
+        if self != old {
+            self.modified = Some(now);
+        }
+        self.id.get_or_insert_with(Ulid::generate);
+    }
+
+    /// Describes when the lot was added, modified and bought, if known.
+    fn describe_times(&self) -> Option<String> {
+        let times = [
+            self.added.map(|time| tr!("added {}", time)),
+            self.modified.map(|time| tr!("modified {}", time)),
+            self.purchased.map(|time| tr!("bought {}", time)),
+        ];
+        let times: Vec<String> = times.into_iter().flatten().collect();
+        (!times.is_empty()).then(|| times.join(", "))
+    }
+
+    pub fn is_same_place(&self, other: &Lot) -> bool {
+        self.color_group == other.color_group && self.location == other.location
+    }
+
+    /// Lots are duplicates if they are at the same place in the same condition.
+    fn is_duplicate_of(&self, other: &Lot) -> bool {
+        self.is_same_place(other) && self.condition == other.condition
+    }
+
+    /// Lots are the same if they have the same ID, or the same place if either has no ID yet.
+    pub fn is_same_lot(&self, other: &Lot) -> bool {
+        match (self.id, other.id) {
+            (Some(id), Some(other_id)) => id == other_id,
+            _ => self.is_same_place(other),
+        }
+    }
+}
+
+impl fmt::Display for Lot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.color_group, self.location)?;
+        if let Some(quantity) = self.quantity {
+            write!(
+                f,
+                " ({})",
+                tr_n!("{} piece", "{} pieces", quantity.get() as usize)
+            )?;
+        }
+        if let Some(weight) = self.weight {
+            write!(f, " ({} g)", locale::fmt_decimal(weight, 1))?;
+        }
+        if let Some(condition) = self.condition {
+            write!(f, " ({})", condition)?;
+        }
+        if let Some(price) = &self.asking_price {
+            write!(f, " {}", tr!("listed for {} each", price))?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct Item {
-    id: u32,
-    alternative_ids: Vec<u32>,
+    id: PartId,
+    alternative_ids: Vec<PartId>,
     name: String,
-    location: Vec<(ColorGroup, String)>,
+    location: Vec<Lot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    price: Option<Price>,
+    /// The weight of one piece in grams, used to estimate the pieces in weighed lots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    piece_weight: Option<f64>,
+    /// The category of the part on Rebrickable, e.g. "Bricks" or "Plates".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    /// The plain part this printed or patterned part is a decoration of, e.g. 3068 for printed
+    /// 2 x 2 tiles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_mold: Option<PartId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dimensions: Option<Dimensions>,
+    /// The parts one piece of this assembly is made of and how many of each, e.g. a wheel and a
+    /// tire.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    components: BTreeMap<PartId, u32>,
+    /// The years the part was produced in, as given by Rebrickable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    produced: Option<Years>,
+    /// The values of the custom fields defined in the config, by the name of the field.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<String, String>,
 }
 
 impl Item {
-    pub fn new(id: u32, name: String) -> Self {
+    /// Keeps the times of the lots as they were before a change, and marks new and changed lots.
+    fn stamp(&mut self, old: Option<&Item>) {
+        let now = Timestamp::now();
+        for lot in self.location.iter_mut() {
+            let old_lot = old.and_then(|old| old.location.iter().find(|l| l.is_same_lot(lot)));
+            lot.stamp(old_lot, now);
+        }
+    }
+
+    /// Moves pieces of the lot at the index into a new lot of the same color group at the location,
+    /// which keeps the condition, price and notes of the lot and is linked to it. Returns the index
+    /// of the new lot, or none if the lot does not have more pieces than are moved or there already
+    /// is a lot of the color group at the location.
+    pub fn split_lot(&mut self, i: usize, pieces: u32, location: String) -> Option<usize> {
+        let lot = self.location.get_mut(i)?;
+        let quantity = lot.quantity?.get();
+        if pieces == 0 || pieces >= quantity {
+            return None;
+        }
+        let mut new_lot = Lot::new(lot.color_group.clone(), location);
+        if self
+            .location
+            .iter()
+            .any(|other| other.is_same_place(&new_lot))
+        {
+            return None;
+        }
+
+        let lot = &mut self.location[i];
+        lot.quantity = Some(Quantity::new(quantity - pieces));
+        let id = *lot.id.get_or_insert_with(Ulid::generate);
+        new_lot.id = Some(Ulid::generate());
+        new_lot.quantity = Some(Quantity::new(pieces));
+        new_lot.asking_price = lot.asking_price.clone();
+        new_lot.purchased = lot.purchased;
+        new_lot.condition = lot.condition;
+        new_lot.notes = lot.notes.clone();
+        new_lot.split_from = Some(id);
+        self.location.insert(i + 1, new_lot);
+        Some(i + 1)
+    }
+
+    /// Merges the lot at `other` into the lot at `keep`, which must have the same color group and
+    /// must not be in another condition, and returns the index of the merged lot. The pieces and
+    /// weights are added up, and the asking price is averaged by the pieces if both lots are priced
+    /// in the same currency. The merged lot keeps its own location, and its own notes if it has
+    /// them, so differing notes are to be reconciled before merging.
+    pub fn merge_lots(&mut self, keep: usize, other: usize) -> Option<usize> {
+        if keep == other
+            || keep >= self.location.len()
+            || other >= self.location.len()
+            || self.location[keep].color_group != self.location[other].color_group
+        {
+            return None;
+        }
+        if let (Some(a), Some(b)) = (
+            self.location[keep].condition,
+            self.location[other].condition,
+        ) {
+            if a != b {
+                return None;
+            }
+        }
+
+        let keep = if other < keep { keep - 1 } else { keep };
+        let mut other = self.location.remove(other);
+        let lot = &mut self.location[keep];
+        lot.asking_price = match (&lot.asking_price, &other.asking_price) {
+            (Some(a), Some(b)) if a.get_currency() == b.get_currency() => {
+                let average = match (lot.quantity, other.quantity) {
+                    (Some(qa), Some(qb)) => Amount::weighted_average(&[
+                        (a.get_amount(), qa.get()),
+                        (b.get_amount(), qb.get()),
+                    ]),
+                    _ => None,
+                };
+                match average {
+                    Some(average) => Some(Price::new(average, a.get_currency())),
+                    None => Some(a.clone()),
+                }
+            }
+            (a, b) => a.clone().or(b.clone()),
+        };
+        lot.quantity = match (lot.quantity, other.quantity) {
+            (Some(a), Some(b)) => Some(Quantity::new(a.get() + b.get())),
+            (a, b) => a.or(b),
+        };
+        lot.weight = match (lot.weight, other.weight) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        lot.added = match (lot.added, other.added) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        lot.purchased = lot.purchased.max(other.purchased);
+        lot.condition = lot.condition.or(other.condition);
+        lot.notes = lot.notes.take().or(other.notes.take());
+        lot.id.get_or_insert_with(Ulid::generate);
+        lot.merged_from
+            .push(other.id.unwrap_or_else(Ulid::generate));
+        lot.merged_from.append(&mut other.merged_from);
+        Some(keep)
+    }
+
+    /// Gives the lots without an ID one, as saving them does.
+    pub fn assign_lot_ids(&mut self) {
+        for lot in self.location.iter_mut() {
+            lot.id.get_or_insert_with(Ulid::generate);
+        }
+    }
+
+    pub fn new(id: PartId, name: String) -> Self {
         Item {
             id,
             alternative_ids: Vec::new(),
             name,
             location: Vec::new(),
+            price: None,
+            piece_weight: None,
+            category: None,
+            base_mold: None,
+            dimensions: None,
+            components: BTreeMap::new(),
+            produced: None,
+            fields: BTreeMap::new(),
         }
     }
 
-    pub fn get_id(&self) -> u32 {
+    pub fn get_id(&self) -> PartId {
         self.id
     }
 
     pub fn is_group(&self) -> bool {
-        self.id >= 1 << 31
+        self.id.is_group()
     }
 
-    pub fn get_alternative_ids(&self) -> &[u32] {
+    pub fn get_alternative_ids(&self) -> &[PartId] {
         &self.alternative_ids
     }
 
@@ -102,11 +563,71 @@ impl Item {
         &self.name
     }
 
-    pub fn get_locations(&self) -> &[(ColorGroup, String)] {
+    pub fn get_category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    pub fn get_base_mold(&self) -> Option<PartId> {
+        self.base_mold
+    }
+
+    pub fn get_dimensions(&self) -> Option<Dimensions> {
+        self.dimensions
+    }
+
+    pub fn get_components(&self) -> &BTreeMap<PartId, u32> {
+        &self.components
+    }
+
+    pub fn get_locations(&self) -> &[Lot] {
         &self.location
     }
 
-    pub fn set_id(&mut self, id: u32) {
+    pub fn get_price(&self) -> Option<&Price> {
+        self.price.as_ref()
+    }
+
+    /// The total number of pieces in lots with a known quantity.
+    pub fn get_quantity(&self) -> Quantity {
+        Quantity::new(
+            self.location
+                .iter()
+                .filter_map(|lot| lot.get_quantity())
+                .map(|q| q.get())
+                .sum(),
+        )
+    }
+
+    pub fn get_produced(&self) -> Option<Years> {
+        self.produced
+    }
+
+    pub fn set_produced(&mut self, produced: Option<Years>) {
+        self.produced = produced;
+    }
+
+    pub fn get_fields(&self) -> &BTreeMap<String, String> {
+        &self.fields
+    }
+
+    /// The value of the custom field with the name, ignoring case.
+    pub fn get_field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(other, _)| other.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of the custom field, or removes it if there is none.
+    pub fn set_field(&mut self, name: &str, value: Option<String>) {
+        self.fields
+            .retain(|other, _| !other.eq_ignore_ascii_case(name));
+        if let Some(value) = value {
+            self.fields.insert(name.to_string(), value);
+        }
+    }
+
+    pub fn set_id(&mut self, id: PartId) {
         self.id = id;
     }
 
@@ -114,14 +635,42 @@ impl Item {
         self.name = name.to_string();
     }
 
+    pub fn set_category(&mut self, category: Option<String>) {
+        self.category = category;
+    }
+
+    pub fn set_base_mold(&mut self, base_mold: Option<PartId>) {
+        self.base_mold = base_mold;
+    }
+
+    pub fn set_dimensions(&mut self, dimensions: Option<Dimensions>) {
+        self.dimensions = dimensions;
+    }
+
+    pub fn set_components(&mut self, components: BTreeMap<PartId, u32>) {
+        self.components = components;
+    }
+
+    pub fn set_price(&mut self, price: Option<Price>) {
+        self.price = price;
+    }
+
+    pub fn set_alternative_ids(&mut self, alternative_ids: Vec<PartId>) {
+        self.alternative_ids = alternative_ids;
+    }
+
+    pub fn set_locations(&mut self, location: Vec<Lot>) {
+        self.location = location;
+    }
+
     pub fn get_color_set(&self) -> BTreeSet<&ColorGroup> {
-        self.location.iter().map(|(c, _)| c).collect()
+        self.location.iter().map(|lot| &lot.color_group).collect()
     }
 
     pub fn get_other_color_set(&self) -> BTreeSet<String> {
         let mut res = BTreeSet::new();
-        for (c, _) in self.location.iter() {
-            if let ColorGroup::Other(s) = c {
+        for lot in self.location.iter() {
+            if let ColorGroup::Other(s) = &lot.color_group {
                 res.insert(s.to_string());
             }
         }
@@ -129,67 +678,395 @@ impl Item {
     }
 
     pub fn add_color_group(&mut self, color_group: ColorGroup, location: String) {
-        self.location.push((color_group, location))
+        self.location.push(Lot::new(color_group, location))
+    }
+
+    /// Puts pieces in the lot of the color group at the location, adding a lot if there is none.
+    /// Lots without a known count stay uncounted.
+    pub fn add_pieces(&mut self, color_group: &ColorGroup, location: &str, quantity: u32) {
+        match self
+            .location
+            .iter_mut()
+            .find(|lot| &lot.color_group == color_group && lot.location == location)
+        {
+            Some(lot) => {
+                lot.quantity = lot.quantity.map(|q| Quantity::new(q.get() + quantity));
+            }
+            None => {
+                let mut lot = Lot::new(color_group.clone(), location.to_string());
+                lot.set_quantity(Some(Quantity::new(quantity)));
+                self.location.push(lot);
+            }
+        }
     }
 
     pub fn remove_color_group(&mut self, color_group: &ColorGroup) {
-        self.location.retain(|(c, _)| c != color_group);
+        self.location.retain(|lot| &lot.color_group != color_group);
+    }
+
+    /// Moves the lot at the index to a new location, keeping everything else about it and leaving
+    /// the other lots of its color group where they are.
+    pub fn move_lot(&mut self, i: usize, location: String) {
+        if let Some(lot) = self.location.get_mut(i) {
+            lot.location = location;
+        }
+    }
+
+    /// Sets the count of every lot of the color group, see `set_lot_quantity` for a single lot.
+    pub fn set_quantity(&mut self, color_group: &ColorGroup, quantity: Option<Quantity>) {
+        for lot in self.location.iter_mut() {
+            if &lot.color_group == color_group {
+                lot.set_quantity(quantity);
+            }
+        }
     }
 
-    pub fn add_alt_id(&mut self, id: u32) {
+    pub fn set_lot_quantity(&mut self, i: usize, quantity: Option<Quantity>) {
+        if let Some(lot) = self.location.get_mut(i) {
+            lot.set_quantity(quantity);
+        }
+    }
+
+    /// Sets when every lot of the color group was bought, see `set_lot_purchased` for a single
+    /// lot.
+    pub fn set_purchased(&mut self, color_group: &ColorGroup, purchased: Option<Timestamp>) {
+        for lot in self.location.iter_mut() {
+            if &lot.color_group == color_group {
+                lot.set_purchased(purchased);
+            }
+        }
+    }
+
+    pub fn set_lot_purchased(&mut self, i: usize, purchased: Option<Timestamp>) {
+        if let Some(lot) = self.location.get_mut(i) {
+            lot.set_purchased(purchased);
+        }
+    }
+
+    /// Returns the lots that share their color group and location with another lot of this item,
+    /// along with how many lots there are of each.
+    pub fn get_duplicate_lots(&self) -> Vec<(&Lot, usize)> {
+        let mut res: Vec<(&Lot, usize)> = Vec::new();
+        for lot in self.location.iter() {
+            match res.iter_mut().find(|(other, _)| other.is_duplicate_of(lot)) {
+                Some((_, count)) => *count += 1,
+                None => res.push((lot, 1)),
+            }
+        }
+        res.retain(|(_, count)| *count > 1);
+        res
+    }
+
+    /// Merges duplicate lots into the first of them with `merge_lots`.
+    pub fn consolidate_lots(&mut self) {
+        let mut i = 0;
+        while i < self.location.len() {
+            let duplicate = (i + 1..self.location.len())
+                .find(|&j| self.location[j].is_duplicate_of(&self.location[i]));
+            match duplicate {
+                Some(j) => {
+                    self.merge_lots(i, j);
+                }
+                None => i += 1,
+            }
+        }
+    }
+
+    pub fn get_piece_weight(&self) -> Option<f64> {
+        self.piece_weight
+    }
+
+    pub fn set_piece_weight(&mut self, piece_weight: Option<f64>) {
+        self.piece_weight = piece_weight;
+    }
+
+    /// Sets the weight of every lot of the color group, see `set_lot_weight` for a single lot.
+    pub fn set_weight(&mut self, color_group: &ColorGroup, weight: Option<f64>) {
+        for lot in self.location.iter_mut() {
+            if &lot.color_group == color_group {
+                lot.set_weight(weight);
+            }
+        }
+    }
+
+    pub fn set_lot_weight(&mut self, i: usize, weight: Option<f64>) {
+        if let Some(lot) = self.location.get_mut(i) {
+            lot.set_weight(weight);
+        }
+    }
+
+    /// The weight in grams of the given number of pieces.
+    pub fn get_weight(&self, quantity: u32) -> Option<f64> {
+        self.piece_weight.map(|weight| weight * quantity as f64)
+    }
+
+    /// The weight of a lot, either as weighed or from its count and the weight per piece.
+    pub fn get_lot_weight(&self, lot: &Lot) -> Option<f64> {
+        lot.weight.or_else(|| self.get_weight(lot.quantity?.get()))
+    }
+
+    /// Estimates the pieces in a lot that is weighed but not counted.
+    pub fn get_estimated_quantity(&self, lot: &Lot) -> Option<Quantity> {
+        if lot.quantity.is_some() {
+            return None;
+        }
+        let piece_weight = self.piece_weight.filter(|w| *w > 0.0)?;
+        Some(Quantity::new((lot.weight? / piece_weight).round() as u32))
+    }
+
+    /// Lists every lot of the color group for the price, see `set_lot_asking_price` for a single
+    /// lot.
+    pub fn set_asking_price(&mut self, color_group: &ColorGroup, asking_price: Option<Price>) {
+        for lot in self.location.iter_mut() {
+            if &lot.color_group == color_group {
+                lot.set_asking_price(asking_price.clone());
+            }
+        }
+    }
+
+    pub fn set_lot_asking_price(&mut self, i: usize, asking_price: Option<Price>) {
+        if let Some(lot) = self.location.get_mut(i) {
+            lot.set_asking_price(asking_price);
+        }
+    }
+
+    pub fn add_alt_id(&mut self, id: PartId) {
         self.alternative_ids.push(id);
     }
 
-    pub fn remove_alt_id(&mut self, id: u32) {
+    pub fn remove_alt_id(&mut self, id: PartId) {
         self.alternative_ids.retain(|old_id| id != *old_id);
     }
 
     pub fn diff(&self, other: &Item) -> String {
         let mut diff = String::new();
         if self.get_id() != other.get_id() {
-            diff.push_str(&format!(
-                "Part ID: {} -> {}\n",
-                self.get_id(),
-                other.get_id()
-            ));
+            diff.push_str(&tr!("Part ID: {} -> {}", self.get_id(), other.get_id()));
+            diff.push('\n');
         }
         if self.get_name() != other.get_name() {
-            diff.push_str(&format!(
-                "Name: {} -> {}\n",
-                self.get_name(),
-                other.get_name()
+            diff.push_str(&tr!("Name: {} -> {}", self.get_name(), other.get_name()));
+            diff.push('\n');
+        }
+        if self.get_price() != other.get_price() {
+            let fmt_price = |price: Option<&Price>| match price {
+                Some(price) => price.to_string(),
+                None => tr!("None").to_string(),
+            };
+            diff.push_str(&tr!(
+                "Price: {} -> {}",
+                fmt_price(self.get_price()),
+                fmt_price(other.get_price())
+            ));
+            diff.push('\n');
+        }
+        if self.category != other.category {
+            let fmt_category = |category: Option<&str>| match category {
+                Some(category) => category.to_string(),
+                None => tr!("None").to_string(),
+            };
+            diff.push_str(&tr!(
+                "Category: {} -> {}",
+                fmt_category(self.get_category()),
+                fmt_category(other.get_category())
+            ));
+            diff.push('\n');
+        }
+        if self.base_mold != other.base_mold {
+            let fmt_mold = |mold: Option<PartId>| match mold {
+                Some(mold) => mold.to_string(),
+                None => tr!("None").to_string(),
+            };
+            diff.push_str(&tr!(
+                "Base mold: {} -> {}",
+                fmt_mold(self.base_mold),
+                fmt_mold(other.base_mold)
+            ));
+            diff.push('\n');
+        }
+        if self.dimensions != other.dimensions {
+            let fmt_dimensions = |dimensions: Option<Dimensions>| match dimensions {
+                Some(dimensions) => dimensions.to_string(),
+                None => tr!("Unknown").to_string(),
+            };
+            diff.push_str(&tr!(
+                "Size: {} -> {}",
+                fmt_dimensions(self.dimensions),
+                fmt_dimensions(other.dimensions)
+            ));
+            diff.push('\n');
+        }
+        if self.components != other.components {
+            let fmt_components = |components: &BTreeMap<PartId, u32>| {
+                if components.is_empty() {
+                    tr!("None").to_string()
+                } else {
+                    assembly::fmt(components)
+                }
+            };
+            diff.push_str(&tr!(
+                "Components: {} -> {}",
+                fmt_components(&self.components),
+                fmt_components(&other.components)
+            ));
+            diff.push('\n');
+        }
+        if self.produced != other.produced {
+            let fmt_years = |years: Option<Years>| match years {
+                Some(years) => years.to_string(),
+                None => tr!("Unknown").to_string(),
+            };
+            diff.push_str(&tr!(
+                "Produced: {} -> {}",
+                fmt_years(self.produced),
+                fmt_years(other.produced)
+            ));
+            diff.push('\n');
+        }
+        let names: BTreeSet<&String> = self.fields.keys().chain(other.fields.keys()).collect();
+        for name in names {
+            let (old, new) = (self.fields.get(name), other.fields.get(name));
+            if old != new {
+                let fmt_field = |value: Option<&String>| match value {
+                    Some(value) => field::fmt_value(name, value),
+                    None => tr!("Unknown").to_string(),
+                };
+                diff.push_str(&format!(
+                    "{}: {} -> {}\n",
+                    name,
+                    fmt_field(old),
+                    fmt_field(new)
+                ));
+            }
+        }
+        if self.piece_weight != other.piece_weight {
+            let fmt_weight = |weight: Option<f64>| match weight {
+                Some(weight) => format!("{} g", locale::fmt_decimal(weight, 2)),
+                None => tr!("Unknown").to_string(),
+            };
+            diff.push_str(&tr!(
+                "Weight per piece: {} -> {}",
+                fmt_weight(self.piece_weight),
+                fmt_weight(other.piece_weight)
             ));
+            diff.push('\n');
         }
         for id in self.get_alternative_ids().iter() {
             if !other.get_alternative_ids().contains(id) {
-                diff.push_str(&format!("Removed alt ID: {}\n", id));
+                diff.push_str(&tr!("Removed alt ID: {}", id));
+                diff.push('\n');
             }
         }
         for id in other.get_alternative_ids().iter() {
             if !self.get_alternative_ids().contains(id) {
-                diff.push_str(&format!("Added alt ID: {}\n", id));
+                diff.push_str(&tr!("Added alt ID: {}", id));
+                diff.push('\n');
             }
         }
-        for loc in self.get_locations().iter() {
-            if !other.get_locations().contains(loc) {
-                diff.push_str(&format!("Removed location: ({}, {})\n", loc.0, loc.1));
+        let same_place =
+            |a: &Lot, b: &Lot| a.color_group == b.color_group && a.location == b.location;
+        for lot in self.get_locations().iter() {
+            match other.get_locations().iter().find(|o| same_place(lot, o)) {
+                None => {
+                    diff.push_str(&tr!(
+                        "Removed location: ({}, {})",
+                        lot.color_group,
+                        lot.location
+                    ));
+                    diff.push('\n');
+                }
+                Some(o) => {
+                    if o.quantity != lot.quantity {
+                        let fmt_quantity = |quantity: Option<Quantity>| match quantity {
+                            Some(quantity) => quantity.to_string(),
+                            None => tr!("Unknown").to_string(),
+                        };
+                        diff.push_str(&tr!(
+                            "Quantity of ({}, {}): {} -> {}",
+                            lot.color_group,
+                            lot.location,
+                            fmt_quantity(lot.quantity),
+                            fmt_quantity(o.quantity)
+                        ));
+                        diff.push('\n');
+                    }
+                    if o.weight != lot.weight {
+                        let fmt_weight = |weight: Option<f64>| match weight {
+                            Some(weight) => format!("{} g", locale::fmt_decimal(weight, 1)),
+                            None => tr!("Unknown").to_string(),
+                        };
+                        diff.push_str(&tr!(
+                            "Weight of ({}, {}): {} -> {}",
+                            lot.color_group,
+                            lot.location,
+                            fmt_weight(lot.weight),
+                            fmt_weight(o.weight)
+                        ));
+                        diff.push('\n');
+                    }
+                    if o.asking_price != lot.asking_price {
+                        let fmt_price = |price: &Option<Price>| match price {
+                            Some(price) => price.to_string(),
+                            None => tr!("Not listed").to_string(),
+                        };
+                        diff.push_str(&tr!(
+                            "Asking price of ({}, {}): {} -> {}",
+                            lot.color_group,
+                            lot.location,
+                            fmt_price(&lot.asking_price),
+                            fmt_price(&o.asking_price)
+                        ));
+                        diff.push('\n');
+                    }
+                    if o.condition != lot.condition {
+                        let fmt_condition = |condition: Option<Condition>| match condition {
+                            Some(condition) => condition.to_string(),
+                            None => tr!("Unknown").to_string(),
+                        };
+                        diff.push_str(&tr!(
+                            "Condition of ({}, {}): {} -> {}",
+                            lot.color_group,
+                            lot.location,
+                            fmt_condition(lot.condition),
+                            fmt_condition(o.condition)
+                        ));
+                        diff.push('\n');
+                    }
+                    if o.notes != lot.notes {
+                        let fmt_notes =
+                            |notes: Option<&str>| notes.unwrap_or(tr!("None")).to_string();
+                        diff.push_str(&tr!(
+                            "Notes of ({}, {}): {} -> {}",
+                            lot.color_group,
+                            lot.location,
+                            fmt_notes(lot.get_notes()),
+                            fmt_notes(o.get_notes())
+                        ));
+                        diff.push('\n');
+                    }
+                }
             }
         }
-        for loc in other.get_locations().iter() {
-            if !self.get_locations().contains(loc) {
-                diff.push_str(&format!("Added location: ({}, {})\n", loc.0, loc.1));
+        for lot in other.get_locations().iter() {
+            if !self.get_locations().iter().any(|o| same_place(lot, o)) {
+                diff.push_str(&tr!(
+                    "Added location: ({}, {})",
+                    lot.color_group,
+                    lot.location
+                ));
+                diff.push('\n');
             }
         }
         diff
     }
 }
 
-impl fmt::Display for Item {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let name = &self.name;
-
+impl Item {
+    /// Describes the part apart from its lots: its IDs, name, price and what is known about the
+    /// part itself.
+    pub fn describe_overview(&self) -> String {
         let altids = if self.alternative_ids.is_empty() {
-            "None".to_string()
+            tr!("None").to_string()
         } else {
             let mut res = String::new();
             for id in self.alternative_ids.iter() {
@@ -198,30 +1075,114 @@ impl fmt::Display for Item {
             res
         };
 
-        let loc_string = if self.location.is_empty() {
-            "There is currently no location for this item".to_string()
-        } else {
-            let mut loc_string = "Location of each color group:\n".to_owned();
-            for (color_group, loc) in self.location.iter() {
-                loc_string.push_str(&format!("{}: {}\n", color_group.to_string(), loc));
-            }
-            loc_string
-        };
+        let mut details = Vec::new();
+        if let Some(price) = &self.price {
+            details.push(tr!("Price: {}", price.display_with_home()));
+        }
+        if let Some(category) = &self.category {
+            details.push(tr!("Category: {}", category));
+        }
+        if let Some(mold) = self.base_mold {
+            details.push(tr!("Base mold: {}", mold));
+        }
+        if !self.components.is_empty() {
+            details.push(tr!("Components: {}", assembly::fmt(&self.components)));
+        }
+        if let Some(dimensions) = self.dimensions {
+            details.push(tr!("Size: {}", dimensions));
+        }
+        if let Some(produced) = self.produced {
+            details.push(tr!("Produced: {}", produced));
+        }
+        for (name, value) in self.fields.iter() {
+            details.push(format!("{}: {}", name, field::fmt_value(name, value)));
+        }
+        if let Some(weight) = self.piece_weight {
+            details.push(tr!(
+                "Weight per piece: {} g",
+                locale::fmt_decimal(weight, 2)
+            ));
+        }
+        let details = details.join("\n\n");
 
-        if self.is_group() {
-            let id = self.id & !(1 << 31);
-            write!(
-                f,
+        let overview = if self.is_group() {
+            tr!(
                 "Group ID: {}\nContained part IDs: {}\n\nName: {}\n\n{}",
-                id, altids, name, loc_string,
+                self.id,
+                altids,
+                self.name,
+                details,
             )
         } else {
-            write!(
-                f,
+            tr!(
                 "Part ID: {}\nAlternative IDs: {}\n\nName: {}\n\n{}",
-                self.id, altids, name, loc_string,
+                self.id,
+                altids,
+                self.name,
+                details,
             )
+        };
+        overview.trim_end().to_string()
+    }
+
+    /// Describes the lots with their pieces and when they were changed.
+    pub fn describe_lots(&self) -> String {
+        if self.location.is_empty() {
+            return tr!("There is currently no location for this item").to_string();
         }
+        let mut loc_string = tr!("Location of each color group:").to_owned();
+        loc_string.push('\n');
+        for lot in self.location.iter() {
+            loc_string.push_str(&lot.to_string());
+            if let Some(estimate) = self.get_estimated_quantity(lot) {
+                loc_string.push(' ');
+                loc_string.push_str(&tr_n!(
+                    "(about {} piece by weight)",
+                    "(about {} pieces by weight)",
+                    estimate.get() as usize
+                ));
+            }
+            if let Some(times) = lot.describe_times() {
+                loc_string.push_str("\n  ");
+                loc_string.push_str(&times);
+            }
+            if let Some(notes) = &lot.notes {
+                loc_string.push_str("\n  ");
+                loc_string.push_str(notes);
+            }
+            if let Some(id) = lot.split_from {
+                let from = self
+                    .location
+                    .iter()
+                    .find(|other| other.is_or_has_merged(id));
+                loc_string.push_str("\n  ");
+                loc_string.push_str(&match from {
+                    Some(from) => tr!("split off from the lot at {}", from.location),
+                    None => tr!("split off from a lot that is gone").to_string(),
+                });
+            }
+            if !lot.merged_from.is_empty() {
+                loc_string.push_str("\n  ");
+                loc_string.push_str(&tr_n!(
+                    "merged from {} other lot",
+                    "merged from {} other lots",
+                    lot.merged_from.len()
+                ));
+            }
+            loc_string.push('\n');
+        }
+        loc_string
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\n\n{}",
+            self.describe_overview(),
+            self.describe_lots()
+        )
     }
 }
 
@@ -230,6 +1191,12 @@ pub struct RawDatabase {
     items: Vec<Item>,
 }
 
+impl RawDatabase {
+    pub fn new(items: Vec<Item>) -> Self {
+        Self { items }
+    }
+}
+
 impl core::ops::Deref for RawDatabase {
     type Target = Vec<Item>;
 
@@ -249,34 +1216,152 @@ pub struct Database {
     raw_data: RawDatabase,
     db_path: PathBuf,
     other_color_groups: BTreeSet<String>,
+    /// The contents of the file on disk as of the last time it was read or written. It is only parsed
+    /// when merging, so that loading does not have to keep a second copy of every item.
+    #[serde(skip)]
+    base: String,
+    /// The contents of the journal belonging to base.
+    #[serde(skip)]
+    base_journal: String,
+    #[serde(skip)]
+    journal_len: usize,
+    /// Items that were changed since the last write.
+    #[serde(skip)]
+    dirty: BTreeSet<PartId>,
+    /// When the file and its journal were last modified.
+    #[serde(skip)]
+    last_modified: (Option<SystemTime>, Option<SystemTime>),
+    /// Conflicts from merging with changes made to the file on disk, that are yet to be resolved.
+    #[serde(skip)]
+    conflicts: Vec<Conflict>,
+    /// When set, changes are kept in memory and reported instead of being written to disk. Holds
+    /// the items as of the last report.
+    #[serde(skip)]
+    dry_run: Option<Vec<Item>>,
+    #[serde(skip)]
+    dry_run_report: String,
+    #[serde(skip)]
+    index: search::Index,
+    /// Changes since the last write, published to the hooks once they are written.
+    #[serde(skip)]
+    events: Vec<hook::Event>,
+    /// While a transaction is open, changes are kept in memory until it is committed.
+    #[serde(skip)]
+    transaction: Option<Pending>,
+}
+
+fn get_other_color_groups(items: &[Item]) -> BTreeSet<String> {
+    let mut other_color_groups = BTreeSet::new();
+    for item in items.iter() {
+        other_color_groups.append(&mut item.get_other_color_set());
+    }
+    other_color_groups
+}
+
+/// Describes the items that were added, removed or changed going from old to new, one per line.
+pub fn describe_changes(old: &[Item], new: &[Item]) -> String {
+    let mut res = String::new();
+    for old_item in old.iter() {
+        match new.iter().find(|item| item.get_id() == old_item.get_id()) {
+            None => res.push_str(&tr!(
+                "Removed part {}: {}",
+                old_item.get_id(),
+                old_item.get_name()
+            )),
+            Some(new_item) if new_item != old_item => {
+                res.push_str(&tr!("Changed part {}:", old_item.get_id()));
+                for line in old_item.diff(new_item).lines() {
+                    res.push_str("\n    ");
+                    res.push_str(line);
+                }
+            }
+            Some(_) => continue,
+        }
+        res.push('\n');
+    }
+    for new_item in new.iter() {
+        if !old.iter().any(|item| item.get_id() == new_item.get_id()) {
+            res.push_str(&tr!(
+                "Added part {}: {}",
+                new_item.get_id(),
+                new_item.get_name()
+            ));
+            res.push('\n');
+        }
+    }
+    res
+}
+
+fn get_modified(db_path: &Path) -> (Option<SystemTime>, Option<SystemTime>) {
+    (
+        io::get_modified(db_path),
+        io::get_modified(journal::get_path(db_path)),
+    )
+}
+
+/// Reads the items in the database file with the changes in its journal applied.
+pub fn read_items(db_path: &Path) -> Result<RawDatabase> {
+    let mut items: RawDatabase = io::read_contents_from_yaml(db_path)?;
+    let journal = journal::read_contents(&journal::get_path(db_path))?;
+    journal::apply(&mut items, journal::parse(&journal)?);
+    Ok(items)
+}
+
+/// Overwrites the database file and removes its journal.
+pub fn write_items(db_path: &Path, items: &RawDatabase) -> Result<()> {
+    io::write_yaml_and_contents(db_path, items)?;
+    journal::remove(&journal::get_path(db_path))?;
+    Ok(())
 }
 
 impl Database {
     pub fn new(db_path: PathBuf) -> Result<Self> {
-        match io::read_contents_from_yaml::<_, RawDatabase>(&db_path) {
-            Ok(raw_data) => Ok({
-                let mut other_color_groups = BTreeSet::new();
-                for item in raw_data.items.iter() {
-                    for c in item.get_color_set() {
-                        if let ColorGroup::Other(name) = c {
-                            other_color_groups.insert(name.to_string());
-                        }
-                    }
-                }
+        match io::read_yaml_and_contents::<_, RawDatabase>(&db_path) {
+            Ok((mut raw_data, base)) => Ok({
+                let base_journal = journal::read_contents(&journal::get_path(&db_path))?;
+                let entries = journal::parse(&base_journal)?;
+                let journal_len = entries.len();
+                journal::apply(&mut raw_data, entries);
+
+                let other_color_groups = get_other_color_groups(&raw_data);
+                let last_modified = get_modified(&db_path);
+                let index = search::Index::new(&raw_data);
                 Self {
                     raw_data,
                     db_path,
                     other_color_groups,
+                    base,
+                    base_journal,
+                    journal_len,
+                    dirty: BTreeSet::new(),
+                    last_modified,
+                    conflicts: Vec::new(),
+                    dry_run: None,
+                    dry_run_report: String::new(),
+                    events: Vec::new(),
+                    index,
+                    transaction: None,
                 }
             }),
             Err(term_lib::Error::IOError(io_error)) if io_error.kind() == ErrorKind::NotFound => {
                 let raw_data = RawDatabase::default();
                 let other_color_groups = BTreeSet::new();
 
-                let db = Self {
+                let mut db = Self {
                     raw_data,
                     db_path,
                     other_color_groups,
+                    base: String::new(),
+                    base_journal: String::new(),
+                    journal_len: 0,
+                    dirty: BTreeSet::new(),
+                    last_modified: (None, None),
+                    conflicts: Vec::new(),
+                    dry_run: None,
+                    dry_run_report: String::new(),
+                    events: Vec::new(),
+                    index: search::Index::default(),
+                    transaction: None,
                 };
                 db.write()?;
                 Ok(db)
@@ -285,17 +1370,196 @@ impl Database {
         }
     }
 
-    pub fn write(&self) -> Result<()> {
-        io::write_contents_to_yaml(&self.db_path, &self.raw_data)?;
+    /// Writes the changed items to disk by appending them to the journal, which is compacted into
+    /// the database file once it grows large. If the file was changed by someone else since it was
+    /// last read or written, those changes are merged in first.
+    pub fn write(&mut self) -> Result<()> {
+        if self.transaction.is_some() {
+            return Ok(());
+        }
+        if let Some(reported) = &mut self.dry_run {
+            self.dry_run_report
+                .push_str(&describe_changes(reported, &self.raw_data));
+            *reported = self.raw_data.to_vec();
+            self.events.clear();
+            return Ok(());
+        }
+        let changed = self.dirty.len();
+
+        let journal_path = journal::get_path(&self.db_path);
+        let mut compact = self.base.is_empty();
+
+        let modified = get_modified(&self.db_path);
+        if modified.0.is_some() && modified != self.last_modified {
+            let remote = read_items(&self.db_path)?;
+            let base = self.parse_base()?;
+            let (merged, mut conflicts) = sync::merge(&base, &self.raw_data, &remote);
+            *self.raw_data = merged;
+            self.other_color_groups = get_other_color_groups(&self.raw_data);
+            self.index = search::Index::new(&self.raw_data);
+            self.conflicts.append(&mut conflicts);
+            // The merge may have changed any item.
+            compact = true;
+        }
+
+        // The journal would give away the changes in plain text, so an encrypted database is
+        // written as a whole every time.
+        if crypt::is_enabled() {
+            compact = true;
+        }
+
+        // Changes go to the journal even when compacting, so that replaying a journal left behind
+        // by an interrupted compaction results in the same items.
+        if !self.base.is_empty() && !crypt::is_enabled() {
+            let entries: Vec<journal::Entry> = self
+                .dirty
+                .iter()
+                .map(
+                    |&id| match self.raw_data.iter().find(|item| item.get_id() == id) {
+                        Some(item) => journal::Entry::Put(item.clone()),
+                        None => journal::Entry::Delete(id),
+                    },
+                )
+                .collect();
+            self.base_journal
+                .push_str(&journal::append(&journal_path, &entries)?);
+            self.journal_len += entries.len();
+        }
+        self.dirty.clear();
+
+        if compact || self.journal_len >= journal::COMPACT_AFTER {
+            self.base = io::write_yaml_and_contents(&self.db_path, &self.raw_data)?;
+            journal::remove(&journal_path)?;
+            self.base_journal.clear();
+            self.journal_len = 0;
+        }
+
+        self.last_modified = get_modified(&self.db_path);
+        if hook::is_listening() {
+            let mut events = std::mem::take(&mut self.events);
+            events.push(hook::Event::DatabaseSaved { changed });
+            hook::publish(events);
+        }
+        Ok(())
+    }
+
+    /// The items as of the last time the file was read or written.
+    fn parse_base(&self) -> Result<RawDatabase> {
+        let mut base: RawDatabase = if self.base.is_empty() {
+            RawDatabase::default()
+        } else {
+            io::parse_yaml(&self.base)?
+        };
+        journal::apply(&mut base, journal::parse(&self.base_journal)?);
+        Ok(base)
+    }
+
+    /// Reads the file again if it was changed on disk since it was last read or written, merging
+    /// in the changes in the same way as when writing. Returns whether it was read.
+    pub fn reload_if_changed(&mut self) -> Result<bool> {
+        let modified = get_modified(&self.db_path);
+        if modified.0.is_none() || modified == self.last_modified || !self.dirty.is_empty() {
+            return Ok(false);
+        }
+
+        let (mut remote, base) = io::read_yaml_and_contents::<_, RawDatabase>(&self.db_path)?;
+        let base_journal = journal::read_contents(&journal::get_path(&self.db_path))?;
+        let entries = journal::parse(&base_journal)?;
+        self.journal_len = entries.len();
+        journal::apply(&mut remote, entries);
+
+        let (merged, mut conflicts) = sync::merge(&self.parse_base()?, &self.raw_data, &remote);
+        *self.raw_data = merged;
+        self.base = base;
+        self.base_journal = base_journal;
+        self.last_modified = modified;
+        self.other_color_groups = get_other_color_groups(&self.raw_data);
+        self.index = search::Index::new(&self.raw_data);
+        self.conflicts.append(&mut conflicts);
+        // Changes made on disk are not changes of this session.
+        if let Some(reported) = &mut self.dry_run {
+            *reported = self.raw_data.to_vec();
+        }
+        Ok(true)
+    }
+
+    pub fn set_dry_run(&mut self) {
+        self.dry_run = Some(self.raw_data.to_vec());
+    }
+
+    /// Returns the changes that would have been written to disk since the last call.
+    pub fn take_dry_run_report(&mut self) -> String {
+        std::mem::take(&mut self.dry_run_report)
+    }
+
+    pub fn take_conflicts(&mut self) -> Vec<Conflict> {
+        std::mem::take(&mut self.conflicts)
+    }
+
+    /// Resolves the conflict by using the version from disk. Call write afterwards.
+    pub fn resolve_with_remote(&mut self, conflict: &Conflict) {
+        sync::apply_remote(&mut self.raw_data, conflict);
+        self.dirty.insert(conflict.id);
+        self.other_color_groups = get_other_color_groups(&self.raw_data);
+        self.index = search::Index::new(&self.raw_data);
+    }
+
+    /// Starts a transaction, so that the changes made until it is committed are written together
+    /// and can be undone together. Changes are only kept in memory until then.
+    pub fn begin(&mut self, name: &str) {
+        self.transaction = Some(Pending::new(name, self.events.len()));
+    }
+
+    /// Writes the changes made in the transaction and records them in the log of transactions.
+    pub fn commit(&mut self, user: Option<&str>) -> Result<()> {
+        let Some(pending) = self.transaction.take() else {
+            return Ok(());
+        };
+        self.write()?;
+        if self.dry_run.is_some() {
+            return Ok(());
+        }
+        if let Some(made) = pending.finish(&self.raw_data) {
+            transaction::append(&transaction::get_path(&self.db_path), &made, user)?;
+        }
         Ok(())
     }
 
-    pub fn add_item(&mut self, item: Item) -> Result<()> {
+    /// Puts the items changed in the transaction back as they were before it started.
+    pub fn abort(&mut self) {
+        let Some(pending) = self.transaction.take() else {
+            return;
+        };
+        for (id, before) in pending.get_before() {
+            let i = self.raw_data.iter().position(|item| item.get_id() == *id);
+            match (i, before) {
+                (Some(i), Some(before)) => self.raw_data[i] = before.clone(),
+                (Some(i), None) => {
+                    self.raw_data.remove(i);
+                }
+                (None, Some(before)) => self.raw_data.push(before.clone()),
+                (None, None) => {}
+            }
+        }
+        self.events.truncate(pending.get_events());
+        self.other_color_groups = get_other_color_groups(&self.raw_data);
+        self.index = search::Index::new(&self.raw_data);
+    }
+
+    /// Keeps the item as it is before it is changed in the open transaction, if there is one.
+    fn remember(&mut self, id: PartId) {
+        if let Some(pending) = &mut self.transaction {
+            pending.remember(id, self.raw_data.iter().find(|item| item.get_id() == id));
+        }
+    }
+
+    pub fn add_item(&mut self, mut item: Item) -> Result<()> {
         if self.contains_id(item.get_id()).is_some() {
             return Err(Error::PartAlreadyExists {
                 part_id: item.get_id(),
             });
         }
+        self.remember(item.get_id());
 
         for c in item.get_color_set() {
             if let ColorGroup::Other(name) = c {
@@ -303,6 +1567,13 @@ impl Database {
             }
         }
 
+        item.stamp(None);
+        self.dirty.insert(item.get_id());
+        self.index.insert(&item);
+        if hook::is_listening() {
+            self.events
+                .push(hook::Event::ItemAdded { item: item.clone() });
+        }
         self.raw_data.push(item);
         self.write()?;
 
@@ -328,7 +1599,17 @@ impl Database {
             .enumerate()
             .find(|&(_, item)| item.get_id() == old_item.get_id())
         {
-            self.raw_data[i] = new_item.clone();
+            self.remember(old_item.get_id());
+            let mut new_item = new_item.clone();
+            new_item.stamp(Some(&self.raw_data[i]));
+            self.index.remove(&self.raw_data[i]);
+            self.index.insert(&new_item);
+            if hook::is_listening() {
+                let mut events = hook::get_change_events(&self.raw_data[i], &new_item);
+                self.events.append(&mut events);
+            }
+            self.dirty.insert(new_item.get_id());
+            self.raw_data[i] = new_item;
             self.write()?;
 
             Ok(())
@@ -340,48 +1621,47 @@ impl Database {
     }
 
     /// TODO maybe do a bin or something similar
-    pub fn remove_item(&mut self, id: u32) -> Result<()> {
+    pub fn remove_item(&mut self, id: PartId) -> Result<()> {
+        self.remember(id);
+        if let Some(item) = self.raw_data.iter().find(|item| item.get_id() == id) {
+            self.index.remove(item);
+        }
+        if hook::is_listening() {
+            self.events.push(hook::Event::ItemRemoved { id });
+        }
         self.raw_data.retain(|item| item.get_id() != id);
+        self.dirty.insert(id);
         self.write()?;
         Ok(())
     }
 
-    pub fn contains_id(&self, part_id: u32) -> Option<u32> {
-        for item in self.raw_data.iter() {
-            if item.get_id() == part_id || item.get_alternative_ids().contains(&part_id) {
-                return Some(item.get_id());
-            }
-        }
-        return None;
+    /// Finds the item with the given main ID.
+    pub fn find(&self, id: PartId) -> Option<&Item> {
+        self.raw_data.iter().find(|item| item.get_id() == id)
     }
 
-    pub fn contains_name(&self, name: &str) -> Option<u32> {
-        for item in self.raw_data.iter() {
-            if item.get_name().to_lowercase() == name.to_lowercase() {
-                return Some(item.get_id());
-            }
-        }
-        return None;
+    pub fn contains_id(&self, part_id: PartId) -> Option<PartId> {
+        self.index.get_id(part_id)
     }
 
-    pub fn get_item_by_id(&self, part_id: u32) -> Result<&Item> {
-        for item in self.raw_data.iter() {
-            if item.get_id() == part_id || item.get_alternative_ids().contains(&part_id) {
-                return Ok(&item);
-            }
-        }
-        Err(Error::PartNotFoundId { part_id })
+    pub fn contains_name(&self, name: &str) -> Option<PartId> {
+        self.index.get_by_name(name)
+    }
+
+    pub fn get_item_by_id(&self, part_id: PartId) -> Result<&Item> {
+        self.index
+            .get_id(part_id)
+            .and_then(|id| self.find(id))
+            .ok_or(Error::PartNotFoundId { part_id })
     }
 
     pub fn get_item_by_name(&self, name: &str) -> Result<&Item> {
-        for item in self.raw_data.iter() {
-            if item.get_name().to_lowercase() == name.to_lowercase() {
-                return Ok(&item);
-            }
-        }
-        Err(Error::PartNotFoundName {
-            name: name.to_string(),
-        })
+        self.index
+            .get_by_name(name)
+            .and_then(|id| self.find(id))
+            .ok_or(Error::PartNotFoundName {
+                name: name.to_string(),
+            })
     }
 
     pub fn get_all_names_string(&self) -> String {
@@ -394,13 +1674,7 @@ impl Database {
     }
 
     pub fn get_all_locations(&self) -> BTreeSet<&String> {
-        let mut locs = BTreeSet::new();
-        for item in self.raw_data.iter() {
-            for (_, loc) in item.get_locations() {
-                locs.insert(loc);
-            }
-        }
-        locs
+        self.index.get_locations().collect()
     }
 
     pub fn get_all_locations_string(&self) -> String {
@@ -418,11 +1692,15 @@ impl Database {
         loc: &str,
     ) -> impl Iterator<Item = LocSearch> + Clone + 'a {
         let loc = loc.to_string();
+        let at_location = self.index.get_at_location(&loc);
         self.raw_data.iter().filter_map(move |item| {
+            if !at_location.is_some_and(|ids| ids.contains(&item.get_id())) {
+                return None;
+            }
             let mut color_groups = Vec::new();
-            for (color_group, o_loc) in item.get_locations() {
-                if o_loc == &loc {
-                    color_groups.push(color_group);
+            for lot in item.get_locations() {
+                if lot.get_location() == &loc {
+                    color_groups.push(lot.get_color_group());
                 }
             }
 
@@ -439,37 +1717,88 @@ impl Database {
     }
 
     pub fn contains_location(&self, loc: &str) -> bool {
-        for item in self.raw_data.iter() {
-            for (_, o_loc) in item.get_locations() {
-                if o_loc == loc {
-                    return true;
-                }
-            }
-        }
-        false
+        self.index.contains_location(loc)
     }
 
     pub fn get_other_color_set(&self) -> &BTreeSet<String> {
         &self.other_color_groups
     }
 
-    pub fn get_next_group_id(&self) -> u32 {
-        let mut id = 1 << 31;
+    pub fn get_next_group_id(&self) -> PartId {
+        let mut number = 0;
         loop {
+            let id = PartId::group(number).expect("all group IDs are in use");
             if self.get_item_by_id(id).is_err() {
                 return id;
             } else {
-                id += 1;
+                number += 1;
             }
         }
     }
 
-    pub fn get_stats(&self) -> DatabaseStats {
-        let num_items = self.raw_data.len();
+    /// Describes the lots that share their color group, condition and location with another lot of
+    /// the same item, one per line.
+    pub fn describe_duplicate_lots(&self) -> Vec<String> {
+        let mut res = Vec::new();
+        for item in self.raw_data.iter() {
+            for (lot, count) in item.get_duplicate_lots() {
+                res.push(tr!(
+                    "Part {} {}: {} lots of {} at {}",
+                    item.get_id(),
+                    item.get_name(),
+                    count,
+                    lot.get_color_group(),
+                    lot.get_location()
+                ));
+            }
+        }
+        res
+    }
+
+    pub fn get_items(&self) -> &[Item] {
+        &self.raw_data
+    }
+
+    /// Lots listed for sale can be left out, so that only the pieces that are free to use are
+    /// counted. Given a range, only the lots added on its days are counted, and only the parts
+    /// with such lots.
+    pub fn get_stats(&self, include_listed: bool, range: Option<Range>) -> DatabaseStats {
+        let in_range = |lot: &Lot| {
+            range.is_none_or(|range| lot.get_added().is_some_and(|added| range.contains(added)))
+        };
+        let items: Vec<&Item> = self
+            .raw_data
+            .iter()
+            .filter(|item| range.is_none() || item.get_locations().iter().any(in_range))
+            .collect();
+        let num_items = items.len();
 
         let mut num_sorts = 0;
-        for item in self.raw_data.iter() {
-            num_sorts += item.get_locations().len();
+        let mut num_pieces = 0;
+        let mut num_estimated = 0;
+        let mut ages = Ages::default();
+        let mut total_value = Amount::ZERO;
+        let mut num_unconverted = 0;
+        let now = Timestamp::now();
+        for item in items.iter() {
+            let mut counted = 0;
+            for lot in item.get_locations().iter().filter(|lot| in_range(lot)) {
+                if include_listed || lot.get_asking_price().is_none() {
+                    num_sorts += 1;
+                    ages.add(lot, now);
+                    counted += lot.get_quantity().map_or(0, |q| q.get());
+                    num_estimated += item
+                        .get_estimated_quantity(lot)
+                        .map_or(0, |q| q.get() as usize);
+                }
+            }
+            num_pieces += counted as usize;
+            // The pieces of the counted lots are valued at the price of one piece of the part.
+            match item.get_price().map(|price| price.to_home_currency()) {
+                Some(Some(price)) => total_value += price.get_amount().times(counted),
+                Some(None) => num_unconverted += 1,
+                None => {}
+            }
         }
 
         let num_color_groups = self.get_other_color_set().len() + ColorGroup::iter().len() - 1;
@@ -477,40 +1806,170 @@ impl Database {
 
         DatabaseStats {
             num_items,
+            num_pieces,
+            num_estimated,
             num_sorts,
             num_color_groups,
             num_locations,
+            total_value,
+            num_unconverted,
+            include_listed,
+            num_wanted: None,
+            wanted_value: Amount::ZERO,
+            range,
+            ages,
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 pub struct DatabaseStats {
     num_items: usize,
+    /// Number of pieces in lots with a known quantity.
+    num_pieces: usize,
+    /// Number of pieces estimated from the weight of lots that are not counted.
+    num_estimated: usize,
     num_sorts: usize,
     num_color_groups: usize,
     num_locations: usize,
+    /// Total value of the counted pieces of all priced items in the home currency.
+    total_value: Amount,
+    /// Number of prices that could not be converted to the home currency.
+    num_unconverted: usize,
+    /// Whether lots listed for sale are counted.
+    include_listed: bool,
+    /// Number of pieces still wanted for the goals, if they are counted.
+    num_wanted: Option<usize>,
+    /// Value of the wanted pieces of the priced items in the home currency.
+    wanted_value: Amount,
+    /// The days the counted lots were added on, if not all lots are counted.
+    #[serde(skip)]
+    range: Option<Range>,
+    ages: Ages,
+}
+
+impl DatabaseStats {
+    pub fn includes_listed(&self) -> bool {
+        self.include_listed
+    }
+
+    pub fn get_range(&self) -> Option<Range> {
+        self.range
+    }
+
+    pub fn includes_wanted(&self) -> bool {
+        self.num_wanted.is_some()
+    }
+
+    /// Counts the pieces still wanted for the goals, which are valued at the price of a piece of
+    /// their part like the owned pieces.
+    pub fn count_wanted(&mut self, db: &Database, wanted: &BTreeMap<PartId, u32>) {
+        let mut num_wanted = 0;
+        self.wanted_value = Amount::ZERO;
+        for (id, pieces) in wanted {
+            num_wanted += *pieces as usize;
+            let price = db.get_item_by_id(*id).ok().and_then(Item::get_price);
+            if let Some(price) = price.and_then(Price::to_home_currency) {
+                self.wanted_value += price.get_amount().times(*pieces);
+            }
+        }
+        self.num_wanted = Some(num_wanted);
+    }
+
+    /// A one line summary of the statistics.
+    pub fn summary(&self) -> String {
+        tr!(
+            "Parts: {}, counted pieces: {}, locations: {}",
+            locale::fmt_count(self.num_items),
+            locale::fmt_count(self.num_pieces),
+            locale::fmt_count(self.num_locations),
+        )
+    }
 }
 
 impl fmt::Display for DatabaseStats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Parts: {}\nSorted categories: {}\nColorgroups: {}\nLocations: {}",
-            self.num_items, self.num_sorts, self.num_color_groups, self.num_locations
-        )
+            "{}",
+            tr!(
+                "Parts: {}\nSorted categories: {}\nColorgroups: {}\nLocations: {}",
+                locale::fmt_count(self.num_items),
+                locale::fmt_count(self.num_sorts),
+                locale::fmt_count(self.num_color_groups),
+                locale::fmt_count(self.num_locations),
+            )
+        )?;
+        write!(
+            f,
+            "\n{}",
+            tr!("Counted pieces: {}", locale::fmt_count(self.num_pieces))
+        )?;
+        if self.num_estimated != 0 {
+            write!(
+                f,
+                "\n{}",
+                tr!(
+                    "Estimated pieces by weight: {}",
+                    locale::fmt_count(self.num_estimated)
+                )
+            )?;
+        }
+        write!(
+            f,
+            "\n{}",
+            tr!(
+                "Total value: {}",
+                Price::new(self.total_value, currency::get_home_currency())
+            )
+        )?;
+        if self.num_unconverted != 0 {
+            write!(
+                f,
+                "\n{}",
+                tr_n!(
+                    "({} price has no exchange rate to the home currency and is not included)",
+                    "({} prices have no exchange rate to the home currency and are not included)",
+                    self.num_unconverted,
+                )
+            )?;
+        }
+        write!(f, "\n{}", self.ages)?;
+        let listed = if self.include_listed {
+            tr!("Lots listed for sale are counted.")
+        } else {
+            tr!("Lots listed for sale are not counted.")
+        };
+        write!(f, "\n{}", listed)?;
+        let wanted = match self.num_wanted {
+            Some(num_wanted) => tr!(
+                "Pieces still wanted for the goals: {}, worth {}",
+                locale::fmt_count(num_wanted),
+                Price::new(self.wanted_value, currency::get_home_currency())
+            ),
+            None => tr!("Pieces still wanted for the goals are not counted.").to_string(),
+        };
+        write!(f, "\n{}", wanted)?;
+        if let Some(range) = self.range {
+            write!(
+                f,
+                "\n{}",
+                tr!("Only the lots added from {} are counted.", range)
+            )?;
+        }
+        Ok(())
     }
 }
 
 pub struct LocSearch<'a> {
-    pub id: u32,
+    pub id: PartId,
     name: &'a str,
     color_groups: Vec<&'a ColorGroup>,
 }
 
 impl fmt::Display for LocSearch<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut res = format!("Name: {}\n   Id: {}\n   Color Groups:", self.name, self.id);
+        let mut res = tr!("Name: {}\n   Id: {}\n   Color Groups:", self.name, self.id);
         for color_group in self.color_groups.iter() {
             res.push_str(&format!("\n       {color_group}"));
         }
@@ -522,22 +1981,44 @@ impl fmt::Display for LocSearch<'_> {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use proptest::prelude::*;
     use std::str::FromStr;
 
     // Used for testing in io module
     pub fn get_test_database() -> RawDatabase {
+        let id = |id| PartId::new(id).unwrap();
+
         let item1 = Item {
-            id: 44,
-            alternative_ids: vec![123, 1324],
+            id: id(44),
+            alternative_ids: vec![id(123), id(1324)],
             name: "Testid".to_string(),
-            location: vec![(ColorGroup::All, String::from_str("B1A3").unwrap())],
+            location: vec![Lot::new(ColorGroup::All, String::from_str("B1A3").unwrap())],
+            price: None,
+            piece_weight: None,
+            category: None,
+            base_mold: None,
+            dimensions: None,
+            produced: None,
+            fields: BTreeMap::new(),
+            components: BTreeMap::new(),
         };
 
+        let mut lot = Lot::new(ColorGroup::All, String::from_str("B1A4").unwrap());
+        lot.set_quantity(Some(Quantity::new(12)));
+
         let item2 = Item {
-            id: 43,
-            alternative_ids: vec![12, 14],
+            id: id(43),
+            alternative_ids: vec![id(12), id(14)],
             name: "blah blah".to_string(),
-            location: vec![(ColorGroup::All, String::from_str("B1A4").unwrap())],
+            location: vec![lot],
+            price: Some(Price::new(Amount::from_cents(25), "EUR")),
+            piece_weight: None,
+            category: None,
+            base_mold: None,
+            dimensions: None,
+            produced: None,
+            fields: BTreeMap::new(),
+            components: BTreeMap::new(),
         };
 
         let test = RawDatabase {
@@ -546,4 +2027,287 @@ pub mod tests {
 
         test
     }
+
+    fn arb_color_group() -> impl Strategy<Value = ColorGroup> {
+        prop_oneof![
+            9 => (0..ColorGroup::iter().count() - 1).prop_map(|i| ColorGroup::iter().nth(i).unwrap()),
+            1 => "\\PC*".prop_map(ColorGroup::Other),
+        ]
+    }
+
+    fn arb_part_id() -> impl Strategy<Value = PartId> {
+        (any::<bool>(), 0..1u32 << 31).prop_map(|(group, n)| {
+            if group {
+                PartId::group(n).unwrap()
+            } else {
+                PartId::new(n).unwrap()
+            }
+        })
+    }
+
+    // Used for testing in io module
+    pub fn arb_item() -> impl Strategy<Value = Item> {
+        (
+            arb_part_id(),
+            prop::collection::vec(arb_part_id(), 0..4),
+            "\\PC*",
+            prop::collection::vec(
+                (arb_color_group(), "\\PC*", prop::option::of(any::<u32>())),
+                0..4,
+            ),
+            prop::option::of((0.0..1e6f64, "[A-Z]{3}")),
+        )
+            .prop_map(|(id, alternative_ids, name, lots, price)| Item {
+                id,
+                alternative_ids,
+                name,
+                location: lots
+                    .into_iter()
+                    .map(|(color_group, location, quantity)| {
+                        let mut lot = Lot::new(color_group, location);
+                        lot.set_quantity(quantity.map(Quantity::new));
+                        lot
+                    })
+                    .collect(),
+                price: price.map(|(amount, currency)| Price::new(Amount::from(amount), &currency)),
+                piece_weight: None,
+                category: None,
+                base_mold: None,
+                dimensions: None,
+                produced: None,
+                fields: BTreeMap::new(),
+                components: BTreeMap::new(),
+            })
+    }
+
+    #[test]
+    fn test_read_legacy_lots() {
+        let yaml = "- id: 3001\n  alternative_ids: []\n  name: Brick 2 x 4\n  location:\n  - - All\n    - A1\n";
+        let items: Vec<Item> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            items[0].get_locations(),
+            &[Lot::new(ColorGroup::All, "A1".to_string())]
+        );
+        assert_eq!(items[0].get_quantity(), Quantity::new(0));
+    }
+
+    #[test]
+    fn test_other_color_group_round_trip() {
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Other("Pink".to_string()), "A1".to_string());
+        let yaml = serde_yaml::to_string(&item).unwrap();
+        assert_eq!(serde_yaml::from_str::<Item>(&yaml).unwrap(), item);
+    }
+
+    #[test]
+    fn test_consolidate_lots() {
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Basic, "A2".to_string());
+        item.location[0].set_quantity(Some(Quantity::new(3)));
+        item.location[1].set_quantity(Some(Quantity::new(4)));
+        assert_eq!(item.get_duplicate_lots().len(), 1);
+
+        item.consolidate_lots();
+        assert_eq!(item.get_locations().len(), 2);
+        assert_eq!(
+            item.get_locations()[0].get_quantity(),
+            Some(Quantity::new(7))
+        );
+        assert!(item.get_duplicate_lots().is_empty());
+
+        // Lots in another condition are kept apart, and the merged lot keeps its details.
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        let mut lots = item.get_locations().to_vec();
+        lots[0].set_notes(Some("Sorted".to_string()));
+        lots[2].set_condition(Some(Condition::Used));
+        lots[3].set_asking_price(Some(Price::new(Amount::from_cents(20), "EUR")));
+        item.set_locations(lots);
+        item.assign_lot_ids();
+        let merged_id = item.get_locations()[3].get_id().unwrap();
+        assert_eq!(item.get_duplicate_lots().len(), 1);
+
+        item.consolidate_lots();
+        let lots = item.get_locations();
+        assert_eq!(lots.len(), 3);
+        assert_eq!(lots[0].get_notes(), Some("Sorted"));
+        assert_eq!(
+            lots[0].get_asking_price(),
+            Some(&Price::new(Amount::from_cents(20), "EUR"))
+        );
+        assert!(lots[0].get_merged_from().contains(&merged_id));
+        assert_eq!(lots[2].get_condition(), Some(Condition::Used));
+    }
+
+    #[test]
+    fn test_split_lot() {
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Basic, "A2".to_string());
+        assert_eq!(item.split_lot(0, 3, "B1".to_string()), None);
+
+        item.location[0].set_quantity(Some(Quantity::new(10)));
+        assert_eq!(item.split_lot(0, 10, "B1".to_string()), None);
+        assert_eq!(item.split_lot(0, 3, "A2".to_string()), None);
+        assert_eq!(item.split_lot(0, 3, "B1".to_string()), Some(1));
+
+        let lots = item.get_locations();
+        assert_eq!(lots.len(), 3);
+        assert_eq!(lots[0].get_quantity(), Some(Quantity::new(7)));
+        assert_eq!(lots[1].get_quantity(), Some(Quantity::new(3)));
+        assert_eq!(lots[1].get_location(), "B1");
+        assert!(lots[0].get_id().is_some());
+        assert_eq!(lots[1].get_split_from(), lots[0].get_id());
+        assert_ne!(lots[1].get_id(), lots[0].get_id());
+
+        // Editing and moving one half leaves the other as it was.
+        let other = item.get_locations()[0].clone();
+        item.set_lot_quantity(1, Some(Quantity::new(2)));
+        item.set_lot_weight(1, Some(5.0));
+        item.set_lot_asking_price(1, Some(Price::new(Amount::from_cents(10), "EUR")));
+        item.set_lot_purchased(1, Some(Timestamp::from_secs(1_700_000_000)));
+        item.move_lot(1, "C1".to_string());
+        let lots = item.get_locations();
+        assert_eq!(lots.len(), 3);
+        assert_eq!(lots[0], other);
+        assert_eq!(lots[1].get_location(), "C1");
+        assert_eq!(lots[1].get_quantity(), Some(Quantity::new(2)));
+        assert_eq!(lots[1].get_weight(), Some(5.0));
+        assert_eq!(lots[2].get_location(), "A2");
+    }
+
+    #[test]
+    fn test_merge_lots() {
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Earth, "A2".to_string());
+        item.add_color_group(ColorGroup::Basic, "B1".to_string());
+        assert_eq!(item.merge_lots(0, 1), None);
+        assert_eq!(item.merge_lots(0, 0), None);
+
+        let mut lots = item.get_locations().to_vec();
+        lots[0].set_quantity(Some(Quantity::new(10)));
+        lots[0].set_asking_price(Some(Price::new(Amount::from_cents(10), "EUR")));
+        lots[2].set_quantity(Some(Quantity::new(30)));
+        lots[2].set_asking_price(Some(Price::new(Amount::from_cents(30), "EUR")));
+        lots[2].set_notes(Some("From the Magic Shop".to_string()));
+        item.set_locations(lots);
+        item.assign_lot_ids();
+        let merged_id = item.get_locations()[0].get_id().unwrap();
+
+        assert_eq!(item.merge_lots(2, 0), Some(1));
+        let lots = item.get_locations();
+        assert_eq!(lots.len(), 2);
+        assert_eq!(lots[1].get_location(), "B1");
+        assert_eq!(lots[1].get_quantity(), Some(Quantity::new(40)));
+        assert_eq!(
+            lots[1].get_asking_price(),
+            Some(&Price::new(Amount::from_cents(25), "EUR"))
+        );
+        assert_eq!(lots[1].get_notes(), Some("From the Magic Shop"));
+        assert_eq!(lots[1].get_merged_from(), &[merged_id]);
+
+        // The notes of the kept lot win, and lots in different conditions are not merged.
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Basic, "B1".to_string());
+        let mut lots = item.get_locations().to_vec();
+        lots[0].set_notes(Some("Sorted".to_string()));
+        lots[1].set_notes(Some("From the Magic Shop".to_string()));
+        lots[0].set_condition(Some(Condition::New));
+        lots[1].set_condition(Some(Condition::Used));
+        item.set_locations(lots.clone());
+        assert_eq!(item.merge_lots(0, 1), None);
+        assert_eq!(item.get_locations().len(), 2);
+
+        lots[1].set_condition(None);
+        item.set_locations(lots);
+        assert_eq!(item.merge_lots(0, 1), Some(0));
+        assert_eq!(item.get_locations()[0].get_notes(), Some("Sorted"));
+        assert_eq!(
+            item.get_locations()[0].get_condition(),
+            Some(Condition::New)
+        );
+    }
+
+    #[test]
+    fn test_estimated_quantity() {
+        let mut item = Item::new(PartId::new(3024).unwrap(), "Plate 1 x 1".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Earth, "A2".to_string());
+        item.set_weight(&ColorGroup::Basic, Some(41.0));
+        item.set_weight(&ColorGroup::Earth, Some(10.0));
+        item.set_quantity(&ColorGroup::Earth, Some(Quantity::new(24)));
+        assert_eq!(item.get_estimated_quantity(&item.get_locations()[0]), None);
+
+        item.set_piece_weight(Some(0.4));
+        let (weighed, counted) = (&item.get_locations()[0], &item.get_locations()[1]);
+        assert_eq!(
+            item.get_estimated_quantity(weighed),
+            Some(Quantity::new(103))
+        );
+        assert_eq!(item.get_estimated_quantity(counted), None);
+    }
+
+    #[test]
+    fn test_stats_value() {
+        let path = PathBuf::from("test_stats_value.yml");
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Earth, "A2".to_string());
+        item.set_quantity(&ColorGroup::Basic, Some(Quantity::new(3)));
+        item.set_quantity(&ColorGroup::Earth, Some(Quantity::new(2)));
+        let home = currency::get_home_currency();
+        item.set_price(Some(Price::new(Amount::from_cents(10), home)));
+        let mut lots = item.get_locations().to_vec();
+        lots[1].set_asking_price(Some(Price::new(Amount::from_cents(20), home)));
+        item.set_locations(lots);
+        write_items(&path, &RawDatabase::new(vec![item])).unwrap();
+        let db = Database::new(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        let db = db.unwrap();
+
+        let stats = db.get_stats(true, None);
+        assert_eq!(stats.num_pieces, 5);
+        assert_eq!(stats.total_value, Amount::from_cents(50));
+        let mut stats = db.get_stats(false, None);
+        assert_eq!(stats.total_value, Amount::from_cents(30));
+        assert!(!stats.includes_wanted());
+
+        let wanted = BTreeMap::from([
+            (PartId::new(3001).unwrap(), 4),
+            (PartId::new(3003).unwrap(), 2),
+        ]);
+        stats.count_wanted(&db, &wanted);
+        assert_eq!(stats.num_wanted, Some(6));
+        assert_eq!(stats.wanted_value, Amount::from_cents(40));
+        assert_eq!(stats.total_value, Amount::from_cents(30));
+    }
+
+    #[test]
+    fn test_reload_if_changed() {
+        let path = PathBuf::from("test_reload.yml");
+        let first = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        write_items(&path, &RawDatabase::new(vec![first.clone()])).unwrap();
+        let mut db = Database::new(path.clone()).unwrap();
+        assert!(!db.reload_if_changed().unwrap());
+
+        let second = Item::new(PartId::new(3002).unwrap(), "Brick 2 x 3".to_string());
+        write_items(&path, &RawDatabase::new(vec![first, second])).unwrap();
+        // The file may otherwise keep its modification time if it is written again right away.
+        let later = SystemTime::now() + std::time::Duration::from_secs(1);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(later))
+            .unwrap();
+
+        let reloaded = db.reload_if_changed();
+        std::fs::remove_file(&path).unwrap();
+        assert!(reloaded.unwrap());
+        assert_eq!(db.get_items().len(), 2);
+        assert!(db.take_conflicts().is_empty());
+    }
 }
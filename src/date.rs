@@ -0,0 +1,407 @@
+use std::fmt;
+
+use term_lib::command::Command;
+use term_lib::display;
+
+use crate::adjustment;
+use crate::error::Result;
+use crate::locale::tr;
+use crate::timestamp::{self, Timestamp};
+use crate::ui::{self, InputSource};
+
+/// A day in local time, as asked for when entering when something happened.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+impl Date {
+    /// The date if it exists, e.g. not for 2023-02-29.
+    pub fn new(year: i64, month: u32, day: u32) -> Option<Self> {
+        let date = Self { year, month, day };
+        ((1..=12).contains(&month) && (1..=date.days_in_month()).contains(&day)).then_some(date)
+    }
+
+    pub fn today() -> Self {
+        Self::of(Timestamp::now())
+    }
+
+    /// The day of the time in local time.
+    pub fn of(time: Timestamp) -> Self {
+        let (year, month, day) = time.get_local_date();
+        Self { year, month, day }
+    }
+
+    pub fn get_month(self) -> (i64, u32) {
+        (self.year, self.month)
+    }
+
+    /// Midnight UTC at the start of the day, as dates are stored.
+    pub fn to_timestamp(self) -> Timestamp {
+        Timestamp::from_secs(self.to_days().max(0) as u64 * 86400)
+    }
+
+    fn to_days(self) -> i64 {
+        timestamp::days_from_civil(self.year, self.month, self.day)
+    }
+
+    /// Days before the unix epoch are not needed, so they are taken as the epoch.
+    fn from_days(days: i64) -> Self {
+        let (year, month, day) = adjustment::get_date(days.max(0) as u64 * 86400);
+        Self { year, month, day }
+    }
+
+    fn days_in_month(self) -> u32 {
+        let (year, month) = match self.month {
+            12 => (self.year + 1, 1),
+            month => (self.year, month + 1),
+        };
+        (timestamp::days_from_civil(year, month, 1)
+            - timestamp::days_from_civil(self.year, self.month, 1)) as u32
+    }
+
+    pub fn add_days(self, days: i64) -> Self {
+        Self::from_days(self.to_days() + days)
+    }
+
+    /// Moves by whole months, keeping the day unless the month is shorter.
+    pub fn add_months(self, months: i64) -> Self {
+        let index = self.year * 12 + self.month as i64 - 1 + months;
+        let mut date = Self {
+            year: index.div_euclid(12),
+            month: index.rem_euclid(12) as u32 + 1,
+            day: 1,
+        };
+        date.day = self.day.min(date.days_in_month());
+        date
+    }
+
+    /// Monday is 0 and Sunday is 6.
+    fn weekday(self) -> usize {
+        // The unix epoch was a Thursday.
+        (self.to_days() + 3).rem_euclid(7) as usize
+    }
+
+    /// Parses dates relative to today, like "today", "yesterday", "-3" or "3 days ago", "2 weeks
+    /// ago" and weekdays like "tue" for the last Tuesday, and dates like "2024-05-01", "05-01" for
+    /// the last 1st of May and "1.5.2024".
+    pub fn parse(s: &str, today: Date) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        if s == "today" || s == tr!("today") {
+            return Some(today);
+        }
+        if s == "yesterday" || s == tr!("yesterday") {
+            return Some(today.add_days(-1));
+        }
+        if let Some(days) = s.strip_prefix('-') {
+            return Some(today.add_days(-days.trim().parse::<i64>().ok()?));
+        }
+        if let Some(ago) = s.strip_suffix("ago") {
+            let (count, unit) = ago.trim().split_once(' ')?;
+            let count: i64 = count.parse().ok()?;
+            return match unit.trim() {
+                "day" | "days" => Some(today.add_days(-count)),
+                "week" | "weeks" => Some(today.add_days(-7 * count)),
+                "month" | "months" => Some(today.add_months(-count)),
+                _ => None,
+            };
+        }
+        if let Some(weekday) = WEEKDAYS
+            .iter()
+            .position(|weekday| s.len() >= 3 && weekday.starts_with(&s[..3.min(s.len())]))
+            .filter(|_| s.chars().all(|c| c.is_alphabetic()))
+        {
+            let days_back = (today.weekday() + 7 - weekday) % 7;
+            return Some(today.add_days(-(days_back as i64)));
+        }
+
+        let numbers = |sep: char| -> Option<Vec<u32>> {
+            s.split(sep).map(|n| n.trim().parse().ok()).collect()
+        };
+        if let Some([day, month, year]) = numbers('.').as_deref() {
+            return Self::new(*year as i64, *month, *day);
+        }
+        match numbers('-').as_deref()? {
+            [year, month, day] => Self::new(*year as i64, *month, *day),
+            [month, day] => {
+                let date = Self::new(today.year, *month, *day)?;
+                if date > today {
+                    Self::new(today.year - 1, *month, *day)
+                } else {
+                    Some(date)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Shows the month of the date as a calendar, with the date in brackets.
+    pub fn render_month(self) -> Vec<String> {
+        let mut lines = vec![format!("{}-{:02}", self.year, self.month)];
+        // The names stand above the days, which are in cells of four.
+        lines.push(format!(" {}", tr!("Mo  Tu  We  Th  Fr  Sa  Su")));
+        let first = Self { day: 1, ..self };
+        let mut line = "    ".repeat(first.weekday());
+        for day in 1..=self.days_in_month() {
+            if day == self.day {
+                line.push_str(&format!("[{:>2}]", day));
+            } else {
+                line.push_str(&format!(" {:>2} ", day));
+            }
+            if (first.weekday() + day as usize).is_multiple_of(7) {
+                lines.push(line.trim_end().to_string());
+                line = String::new();
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.trim_end().to_string());
+        }
+        lines
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The days from one date to another, both included, which reports can be narrowed to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Range {
+    from: Date,
+    to: Date,
+}
+
+impl Range {
+    /// The range between the dates, in whichever order they are given.
+    pub fn new(from: Date, to: Date) -> Self {
+        Self {
+            from: from.min(to),
+            to: from.max(to),
+        }
+    }
+
+    pub fn this_month(today: Date) -> Self {
+        let from = Date { day: 1, ..today };
+        Self::new(from, from.add_months(1).add_days(-1))
+    }
+
+    pub fn last_month(today: Date) -> Self {
+        Self::this_month(today.add_months(-1))
+    }
+
+    pub fn this_year(today: Date) -> Self {
+        Self::new(
+            Date {
+                month: 1,
+                day: 1,
+                ..today
+            },
+            Date {
+                month: 12,
+                day: 31,
+                ..today
+            },
+        )
+    }
+
+    pub fn last_year(today: Date) -> Self {
+        Self::this_year(today.add_months(-12))
+    }
+
+    /// Whether the time falls on one of the days in local time.
+    pub fn contains(self, time: Timestamp) -> bool {
+        (self.from..=self.to).contains(&Date::of(time))
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", tr!("{} to {}", self.from, self.to))
+    }
+}
+
+/// The keys of the date picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    PrevDay,
+    NextDay,
+    PrevWeek,
+    NextWeek,
+    PrevMonth,
+    NextMonth,
+    Today,
+    Type,
+    Choose,
+}
+
+impl Command for Step {
+    fn get_char(&self) -> char {
+        use Step::*;
+        match self {
+            PrevDay => 'h',
+            NextDay => 'l',
+            PrevWeek => 'k',
+            NextWeek => 'j',
+            PrevMonth => 'p',
+            NextMonth => 'n',
+            Today => 't',
+            Type => 'e',
+            Choose => 'c',
+        }
+    }
+
+    fn get_info(&self) -> &str {
+        use Step::*;
+        match self {
+            PrevDay => tr!("a day earlier"),
+            NextDay => tr!("a day later"),
+            PrevWeek => tr!("a week earlier"),
+            NextWeek => tr!("a week later"),
+            PrevMonth => tr!("the previous month"),
+            NextMonth => tr!("the next month"),
+            Today => tr!("today"),
+            Type => tr!("type a date, like yesterday, -3 or 2024-05-01"),
+            Choose => tr!("choose the date"),
+        }
+    }
+}
+
+/// Asks for a date on a calendar that is moved through with the keys, starting at the given
+/// date. Dates can also be typed, see `Date::parse`.
+pub fn input_date<W: std::io::Write>(
+    input: &mut dyn InputSource<W>,
+    w: &mut W,
+    msg: &str,
+    start: Date,
+) -> Result<Date> {
+    use Step::*;
+    let steps = [
+        Choose, PrevDay, NextDay, PrevWeek, NextWeek, PrevMonth, NextMonth, Today, Type,
+    ];
+    let mut date = start;
+    let mut error = None;
+    loop {
+        let mut header = vec![msg.to_string(), String::new()];
+        header.extend(date.render_month());
+        if let Some(error) = error.take() {
+            header.push(String::new());
+            header.push(error);
+        }
+        display::clear(w)?;
+        date = match ui::select_cmd(input, w, &header.join("\n"), steps.into_iter())? {
+            PrevDay => date.add_days(-1),
+            NextDay => date.add_days(1),
+            PrevWeek => date.add_days(-7),
+            NextWeek => date.add_days(7),
+            PrevMonth => date.add_months(-1),
+            NextMonth => date.add_months(1),
+            Today => Date::today(),
+            Type => {
+                display::clear(w)?;
+                let typed = input.edit_string(w, msg, &date.to_string())?;
+                match Date::parse(&typed, Date::today()) {
+                    Some(typed) => typed,
+                    None => {
+                        error = Some(tr!("{} is not a date", typed.trim()));
+                        date
+                    }
+                }
+            }
+            Choose => return Ok(date),
+        };
+    }
+}
+
+/// Asks for the range a report is narrowed to, which is none for all time.
+pub fn input_range<W: std::io::Write>(
+    input: &mut dyn InputSource<W>,
+    w: &mut W,
+    msg: &str,
+) -> Result<Option<Range>> {
+    let today = Date::today();
+    let presets = [
+        Range::this_month(today),
+        Range::last_month(today),
+        Range::this_year(today),
+        Range::last_year(today),
+    ];
+    let mut options = vec![tr!("all time").to_string()];
+    options.extend(
+        [
+            tr!("this month"),
+            tr!("last month"),
+            tr!("this year"),
+            tr!("last year"),
+        ]
+        .iter()
+        .zip(presets.iter())
+        .map(|(name, range)| format!("{} ({})", name, range)),
+    );
+    options.push(tr!("other days").to_string());
+
+    display::clear(w)?;
+    match input.select(w, Some(msg), &options)? {
+        0 => Ok(None),
+        i if i <= presets.len() => Ok(Some(presets[i - 1])),
+        _ => {
+            let from = input_date(input, w, tr!("From which day?"), today.add_months(-1))?;
+            let to = input_date(input, w, tr!("To which day?"), today)?;
+            Ok(Some(Range::new(from, to)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_render() {
+        // A Wednesday.
+        let today = Date::new(2024, 5, 1).unwrap();
+        assert_eq!(Date::parse("Today", today), Some(today));
+        assert_eq!(Date::parse("yesterday", today), Date::new(2024, 4, 30));
+        assert_eq!(Date::parse("-3", today), Date::new(2024, 4, 28));
+        assert_eq!(Date::parse("2 weeks ago", today), Date::new(2024, 4, 17));
+        assert_eq!(Date::parse("1 month ago", today), Date::new(2024, 4, 1));
+        assert_eq!(Date::parse("mon", today), Date::new(2024, 4, 29));
+        assert_eq!(Date::parse("wednesday", today), Some(today));
+        assert_eq!(Date::parse("2024-02-29", today), Date::new(2024, 2, 29));
+        assert_eq!(Date::parse("12-24", today), Date::new(2023, 12, 24));
+        assert_eq!(Date::parse("1.5.2024", today), Some(today));
+        assert_eq!(Date::parse("2023-02-29", today), None);
+        assert_eq!(Date::parse("soon", today), None);
+
+        let date = Date::new(2024, 3, 31).unwrap();
+        assert_eq!(date.add_months(-1), Date::new(2024, 2, 29).unwrap());
+        assert_eq!(date.add_days(1), Date::new(2024, 4, 1).unwrap());
+        assert_eq!(
+            Date::parse("2024-02-29", today).unwrap().to_timestamp(),
+            Timestamp::parse("2024-02-29").unwrap()
+        );
+
+        let range = Range::last_month(today);
+        assert_eq!(range.to_string(), "2024-04-01 to 2024-04-30");
+        assert!(range.contains(Timestamp::parse("2024-04-30").unwrap()));
+        assert!(!range.contains(Timestamp::parse("2024-05-01").unwrap()));
+        assert_eq!(
+            Range::last_year(today),
+            Range::new(
+                Date::new(2023, 12, 31).unwrap(),
+                Date::new(2023, 1, 1).unwrap()
+            )
+        );
+
+        let month = today.render_month();
+        assert_eq!(month[0], "2024-05");
+        assert_eq!(month[1], " Mo  Tu  We  Th  Fr  Sa  Su");
+        assert_eq!(month[2], "        [ 1]  2   3   4   5");
+        assert_eq!(month.len(), 7);
+    }
+}
@@ -0,0 +1,129 @@
+use serde::Serialize;
+
+use crate::data::{Item, Lot};
+use crate::types::PartId;
+
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A lot that was added, removed or changed, where a lot is the same if it has the same ID, or the
+/// same color group and location if it has no ID.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LotDiff<'a> {
+    old: Option<&'a Lot>,
+    new: Option<&'a Lot>,
+}
+
+/// The changes to one item, as written by the diff command with --json.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ItemDiff<'a> {
+    id: PartId,
+    name: &'a str,
+    change: Change,
+    lots: Vec<LotDiff<'a>>,
+    /// The changes described as in the human-readable form, one per line.
+    details: Vec<String>,
+}
+
+fn diff_lots<'a>(old: &'a [Lot], new: &'a [Lot]) -> Vec<LotDiff<'a>> {
+    let mut lots = Vec::new();
+    for old_lot in old.iter() {
+        match new.iter().find(|lot| lot.is_same_lot(old_lot)) {
+            Some(new_lot) if new_lot == old_lot => {}
+            new_lot => lots.push(LotDiff {
+                old: Some(old_lot),
+                new: new_lot,
+            }),
+        }
+    }
+    for new_lot in new.iter() {
+        if !old.iter().any(|lot| lot.is_same_lot(new_lot)) {
+            lots.push(LotDiff {
+                old: None,
+                new: Some(new_lot),
+            });
+        }
+    }
+    lots
+}
+
+/// The items that were added, removed or changed going from old to new, in the same order as
+/// `data::describe_changes`.
+pub fn diff<'a>(old: &'a [Item], new: &'a [Item]) -> Vec<ItemDiff<'a>> {
+    let mut diffs = Vec::new();
+    for old_item in old.iter() {
+        let (change, lots, details) =
+            match new.iter().find(|item| item.get_id() == old_item.get_id()) {
+                None => (
+                    Change::Removed,
+                    diff_lots(old_item.get_locations(), &[]),
+                    vec![],
+                ),
+                Some(new_item) if new_item != old_item => (
+                    Change::Changed,
+                    diff_lots(old_item.get_locations(), new_item.get_locations()),
+                    old_item.diff(new_item).lines().map(String::from).collect(),
+                ),
+                Some(_) => continue,
+            };
+        diffs.push(ItemDiff {
+            id: old_item.get_id(),
+            name: old_item.get_name(),
+            change,
+            lots,
+            details,
+        });
+    }
+    for new_item in new.iter() {
+        if !old.iter().any(|item| item.get_id() == new_item.get_id()) {
+            diffs.push(ItemDiff {
+                id: new_item.get_id(),
+                name: new_item.get_name(),
+                change: Change::Added,
+                lots: diff_lots(&[], new_item.get_locations()),
+                details: vec![],
+            });
+        }
+    }
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::Quantity;
+
+    #[test]
+    fn test_diff() {
+        let mut old = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        old.add_color_group(ColorGroup::Basic, "A1".to_string());
+        old.add_color_group(ColorGroup::Earth, "A2".to_string());
+        let mut new = old.clone();
+        new.set_quantity(&ColorGroup::Basic, Some(Quantity::new(3)));
+        let added = Item::new(PartId::new(3002).unwrap(), "Brick 2 x 3".to_string());
+
+        let (old, new) = (vec![old], vec![new, added]);
+        let diffs = diff(&old, &new);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].change, Change::Changed);
+        assert_eq!(diffs[0].lots.len(), 1);
+        assert_eq!(
+            diffs[0].lots[0].new.unwrap().get_quantity(),
+            Some(Quantity::new(3))
+        );
+        assert_eq!(diffs[1].change, Change::Added);
+
+        let json = serde_json::to_value(&diffs).unwrap();
+        assert_eq!(
+            json[0]["lots"][0]["old"]["quantity"],
+            serde_json::Value::Null
+        );
+        assert_eq!(json[1]["change"], "added");
+    }
+}
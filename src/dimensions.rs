@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The height of a part in plates, a third of the height of a brick.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(transparent)]
+pub struct Height(u32);
+
+impl Height {
+    /// Parses heights in bricks written like `1`, `1/3` or `2/3`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (bricks, thirds) = match s.trim().split_once('/') {
+            Some((n, d)) => (n.trim().parse::<u32>().ok()?, d.trim().parse::<u32>().ok()?),
+            None => (s.trim().parse().ok()?, 1),
+        };
+        match thirds {
+            1 => Some(Self(bricks * 3)),
+            3 => Some(Self(bricks)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_multiple_of(3) {
+            write!(f, "{}", self.0 / 3)
+        } else {
+            write!(f, "{}/3", self.0)
+        }
+    }
+}
+
+/// The size of a part in studs and its height.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Dimensions {
+    width: u32,
+    length: u32,
+    height: Height,
+}
+
+impl Dimensions {
+    /// Parses dimensions written like `2 x 4 x 1/3`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (footprint, height) = s.rsplit_once('x')?;
+        let (width, length) = parse_footprint(footprint)?;
+        Some(Self {
+            width,
+            length,
+            height: Height::parse(height)?,
+        })
+    }
+
+    pub fn get_height(&self) -> Height {
+        self.height
+    }
+
+    /// Whether the parts have the same height and the same footprint but for at most one stud, in
+    /// either direction.
+    pub fn is_similar(&self, other: &Dimensions) -> bool {
+        let diff = |(a, b): (u32, u32), (c, d): (u32, u32)| a.abs_diff(c) + b.abs_diff(d);
+        let (footprint, other_footprint) = ((self.width, self.length), (other.width, other.length));
+        self.height == other.height
+            && diff(footprint, other_footprint).min(diff(footprint, (other.length, other.width)))
+                <= 1
+    }
+
+    /// Whether the part covers the number of studs, in either direction.
+    pub fn has_footprint(&self, width: u32, length: u32) -> bool {
+        (self.width, self.length) == (width, length) || (self.width, self.length) == (length, width)
+    }
+}
+
+impl fmt::Display for Dimensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} x {} x {}", self.width, self.length, self.height)
+    }
+}
+
+/// Parses a number of studs written like `2x4`.
+pub fn parse_footprint(s: &str) -> Option<(u32, u32)> {
+    let (width, length) = s.split_once('x')?;
+    Some((width.trim().parse().ok()?, length.trim().parse().ok()?))
+}
+
+/// A comparison in a filter term like `height<=1/3`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    /// Splits a term like `height<=1/3` into its key, comparison and value.
+    pub fn split(term: &str) -> Option<(&str, Comparison, &str)> {
+        use Comparison::*;
+        let i = term.find(['<', '>', '='])?;
+        let (key, rest) = term.split_at(i);
+        let (comparison, value) = [
+            ("<=", LessOrEqual),
+            (">=", GreaterOrEqual),
+            ("<", Less),
+            (">", Greater),
+            ("=", Equal),
+        ]
+        .into_iter()
+        .find_map(|(op, comparison)| rest.strip_prefix(op).map(|value| (comparison, value)))?;
+        Some((key, comparison, value))
+    }
+
+    pub fn holds<T: Ord>(&self, a: T, b: T) -> bool {
+        use Comparison::*;
+        let ordering = a.cmp(&b);
+        match self {
+            Less => ordering == Ordering::Less,
+            LessOrEqual => ordering != Ordering::Greater,
+            Equal => ordering == Ordering::Equal,
+            GreaterOrEqual => ordering != Ordering::Less,
+            Greater => ordering == Ordering::Greater,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimensions() {
+        let plate = Dimensions::parse("1 x 4 x 1/3").unwrap();
+        assert_eq!(plate.to_string(), "1 x 4 x 1/3");
+        assert!(plate.has_footprint(4, 1) && !plate.has_footprint(2, 2));
+        assert_eq!(Dimensions::parse("2x2x1").unwrap().get_height(), Height(3));
+        assert_eq!(Height::parse("2/3").unwrap().to_string(), "2/3");
+        assert!(Height::parse("1/2").is_none() && Dimensions::parse("2x4").is_none());
+
+        let split = Comparison::split("height<=1/3").unwrap();
+        assert_eq!(split, ("height", Comparison::LessOrEqual, "1/3"));
+        assert_eq!(Comparison::split("dims=2x4").unwrap().1, Comparison::Equal);
+        assert!(Comparison::split("brick").is_none());
+        assert!(split.1.holds(plate.get_height(), Height(1)));
+        assert!(!Comparison::Greater.holds(Height(1), Height(3)));
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::api::Client;
+use crate::data::Item;
+use crate::error::Result;
+use crate::locale::{tr, tr_n};
+use crate::types::{PartId, Years};
+
+/// How many items are looked up between writes of the database, so that an interrupted
+/// enrichment keeps most of what it filled in.
+pub const SAVE_EVERY: usize = 50;
+
+/// Metadata of a part as looked up on a site.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Metadata {
+    pub name: String,
+    pub category: Option<String>,
+    pub produced: Option<Years>,
+}
+
+/// Looks up the name, category and years of production of the part on Rebrickable.
+pub fn lookup(client: &mut Client, id: PartId) -> Result<Option<Metadata>> {
+    let Some(part) = client.get_part(id)? else {
+        return Ok(None);
+    };
+    let category = match part.part_cat_id {
+        Some(id) => client.get_category(id)?,
+        None => None,
+    };
+    let year = |year: Option<i32>| year.and_then(|year| u16::try_from(year).ok());
+    let produced = match (year(part.year_from), year(part.year_to)) {
+        (Some(from), Some(to)) => Some(Years::new(from, to)),
+        (Some(year), None) | (None, Some(year)) => Some(Years::new(year, year)),
+        (None, None) => None,
+    };
+    Ok(Some(Metadata {
+        name: part.name,
+        category,
+        produced,
+    }))
+}
+
+/// Whether the item is missing metadata that can be looked up. Groups are not on any site.
+pub fn needs_metadata(item: &Item) -> bool {
+    !item.is_group()
+        && (item.get_name().trim().is_empty()
+            || item.get_category().is_none()
+            || item.get_produced().is_none())
+}
+
+/// What was filled in by enriching the items.
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub looked_up: usize,
+    pub names: usize,
+    pub categories: usize,
+    pub years: usize,
+    pub not_found: Vec<PartId>,
+    /// Items still without a weight per piece, which the sites do not give.
+    pub without_weight: usize,
+}
+
+impl Summary {
+    /// Fills in what the item is missing from the metadata, keeping what it already has.
+    pub fn fill(&mut self, item: &mut Item, metadata: Metadata) {
+        self.looked_up += 1;
+        if item.get_name().trim().is_empty() && !metadata.name.is_empty() {
+            item.set_name(&metadata.name);
+            self.names += 1;
+        }
+        if item.get_category().is_none() && metadata.category.is_some() {
+            item.set_category(metadata.category);
+            self.categories += 1;
+        }
+        if item.get_produced().is_none() && metadata.produced.is_some() {
+            item.set_produced(metadata.produced);
+            self.years += 1;
+        }
+        if item.get_piece_weight().is_none() {
+            self.without_weight += 1;
+        }
+    }
+
+    pub fn not_found(&mut self, id: PartId) {
+        self.looked_up += 1;
+        self.not_found.push(id);
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}",
+            tr_n!(
+                "Looked up {} part, filled in {} names, {} categories and {} years of production.",
+                "Looked up {} parts, filled in {} names, {} categories and {} years of production.",
+                self.looked_up,
+                self.names,
+                self.categories,
+                self.years
+            )
+        )?;
+        if !self.not_found.is_empty() {
+            let ids: Vec<String> = self.not_found.iter().map(PartId::to_string).collect();
+            writeln!(f, "{}", tr!("Not found: {}", ids.join(", ")))?;
+        }
+        if self.without_weight != 0 {
+            writeln!(
+                f,
+                "{}",
+                tr_n!(
+                    "{} part still has no weight per piece, weigh a lot to fill it in.",
+                    "{} parts still have no weight per piece, weigh a lot to fill them in.",
+                    self.without_weight
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        let id = |id| PartId::new(id).unwrap();
+        let mut named = Item::new(id(3001), "My brick".to_string());
+        let mut unnamed = Item::new(id(3002), String::new());
+        let group = Item::new(PartId::group(1).unwrap(), String::new());
+        assert!(needs_metadata(&named) && needs_metadata(&unnamed));
+        assert!(!needs_metadata(&group));
+
+        let metadata = |name: &str| Metadata {
+            name: name.to_string(),
+            category: Some("Bricks".to_string()),
+            produced: Some(Years::new(1958, 2024)),
+        };
+        let mut summary = Summary::default();
+        summary.fill(&mut named, metadata("Brick 2 x 4"));
+        summary.fill(&mut unnamed, metadata("Brick 2 x 3"));
+        summary.not_found(id(99999));
+
+        assert_eq!(named.get_name(), "My brick");
+        assert_eq!(unnamed.get_name(), "Brick 2 x 3");
+        assert_eq!(named.get_category(), Some("Bricks"));
+        assert_eq!(unnamed.get_produced(), Some(Years::new(1958, 2024)));
+        assert!(!needs_metadata(&named));
+        assert_eq!(
+            summary,
+            Summary {
+                looked_up: 3,
+                names: 1,
+                categories: 2,
+                years: 2,
+                not_found: vec![id(99999)],
+                without_weight: 2,
+            }
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// The version of the JSON written by the diff command and the API. It is raised whenever a field
+/// is renamed or removed, while new fields that may be left out do not change it.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Data as given to other programs, marked with what it is and the version of its format, so that
+/// readers can refuse data they do not understand instead of misreading it.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Envelope<T> {
+    kind: String,
+    version: u32,
+    data: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(kind: &str, data: T) -> Self {
+        Self {
+            kind: kind.to_string(),
+            version: FORMAT_VERSION,
+            data,
+        }
+    }
+
+    /// Returns the data if it is of the kind and of a version that can be read.
+    pub fn open(self, kind: &str) -> Result<T> {
+        if self.version > FORMAT_VERSION {
+            return Err(Error::UnsupportedVersion {
+                version: self.version,
+            });
+        }
+        if self.kind != kind {
+            return Err(Error::UnexpectedKind {
+                expected: kind.to_string(),
+                found: self.kind,
+            });
+        }
+        Ok(self.data)
+    }
+}
+
+/// Writes the data as JSON in an envelope of the kind.
+pub fn to_json(kind: &str, data: &impl Serialize) -> Result<String> {
+    serde_json::to_string_pretty(&Envelope::new(kind, data))
+        .map_err(|e| Error::InvalidJson { msg: e.to_string() })
+}
+
+/// Reads JSON data of the kind from an envelope.
+pub fn from_json<T: DeserializeOwned>(kind: &str, json: &str) -> Result<T> {
+    let envelope: Envelope<T> =
+        serde_json::from_str(json).map_err(|e| Error::InvalidJson { msg: e.to_string() })?;
+    envelope.open(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::currency::{Amount, Price};
+    use crate::data::{ColorGroup, Item};
+    use crate::types::{PartId, Quantity};
+
+    #[test]
+    fn test_round_trip() {
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Other("Trans".to_string()), "A2".to_string());
+        item.set_quantity(&ColorGroup::Basic, Some(Quantity::new(12)));
+        item.set_price(Some(Price::new(Amount::from_cents(10), "EUR")));
+
+        let json = to_json("item", &item).unwrap();
+        assert!(json.contains("\"version\": 1"));
+        assert_eq!(from_json::<Item>("item", &json).unwrap(), item);
+        let yaml = serde_yaml::to_string(&Envelope::new("item", &item)).unwrap();
+        let envelope: Envelope<Item> = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(envelope.open("item").unwrap(), item);
+
+        assert!(matches!(
+            from_json::<Item>("stats", &json),
+            Err(Error::UnexpectedKind { .. })
+        ));
+        let newer = json.replace("\"version\": 1", "\"version\": 2");
+        assert!(matches!(
+            from_json::<Item>("item", &newer),
+            Err(Error::UnsupportedVersion { version: 2 })
+        ));
+    }
+}
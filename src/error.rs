@@ -1,19 +1,70 @@
 use thiserror::Error;
 
+use crate::types::PartId;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("term_lib threw an error")]
     TermError(#[from] term_lib::Error),
 
     #[error("part not found by id: {part_id}")]
-    PartNotFoundId { part_id: u32 },
+    PartNotFoundId { part_id: PartId },
+    #[error("part {part_id} has fewer than {needed} counted pieces in the color group")]
+    NotEnoughPieces { part_id: PartId, needed: u32 },
+    #[error("part {part_id} has no lot in the color group")]
+    NoLotInColorGroup { part_id: PartId },
     #[error("part not found by name: {name}")]
     PartNotFoundName { name: String },
     #[error("part already exists in database: {part_id}")]
-    PartAlreadyExists { part_id: u32 },
+    PartAlreadyExists { part_id: PartId },
 
+    #[error("{cmd} changes the database, which is disabled in read-only mode")]
+    ReadOnly { cmd: String },
     #[error("command {cmd} cannot be executed in mode {mode}")]
     CmdModeMismatch { cmd: String, mode: String },
+
+    #[error("unknown argument: {arg}")]
+    UnknownArgument { arg: String },
+    #[error("file already exists, use --force to overwrite it: {path}")]
+    FileAlreadyExists { path: String },
+    #[error("file was written by a newer version of tui_bricks: version {version}")]
+    UnsupportedVersion { version: u32 },
+    #[error("expected {expected} but found {found}")]
+    UnexpectedKind { expected: String, found: String },
+    #[error("invalid JSON: {msg}")]
+    InvalidJson { msg: String },
+    #[error("invalid order file: {msg}")]
+    InvalidOrder { msg: String },
+    #[error("order {number} is partly taken in, take it in again to resume or use --force to start over")]
+    ImportInProgress { number: String },
+    #[error("invalid BrickStore file: {msg}")]
+    InvalidBrickStore { msg: String },
+    #[error("invalid set inventory: {msg}")]
+    InvalidInventory { msg: String },
+    #[error("invalid key binding, expected keys like g, g g, C-s or M-x: {binding}")]
+    InvalidKeyBinding { binding: String },
+    #[error("request to the API failed: {msg}")]
+    ApiError { msg: String },
+    #[error("syncing with the server failed: {msg}")]
+    SyncError { msg: String },
+    #[error("plugin {label} failed: {msg}")]
+    PluginFailed { label: String, msg: String },
+    #[error("the summary could not be sent: {msg}")]
+    SummaryNotSent { msg: String },
+    #[error("clipboard is not available: {msg}")]
+    ClipboardUnavailable { msg: String },
+    #[error(
+        "invalid filter, expected terms like name=, color=, location=, mold=, dims=2x4, height<=1/3, produced<1990, pieces<100, has=price, custom fields like box=original or by=mold: {filter}"
+    )]
+    InvalidFilter { filter: String },
+    #[error("snapshot names may only contain letters, digits, '-', '_' and '.': {name}")]
+    InvalidSnapshotName { name: String },
+    #[error("snapshot not found: {name}")]
+    SnapshotNotFound { name: String },
+    #[error("{name} cannot be undone, as part {part_id} was changed since")]
+    ChangedSinceTransaction { name: String, part_id: PartId },
+    #[error("the database could not be unlocked: {msg}")]
+    Locked { msg: String },
 }
 
 impl From<std::io::Error> for Error {
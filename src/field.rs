@@ -0,0 +1,152 @@
+use std::cmp::Ordering;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dimensions::Comparison;
+use crate::locale::tr;
+use crate::search;
+
+static FIELDS: OnceLock<Vec<Field>> = OnceLock::new();
+
+/// A field of items defined in the config, for what the database has no place of its own for,
+/// e.g. `{name: Box, type: enum, values: [original, none]}`. Items keep the values of the fields
+/// by name, which stay when the field is removed from the config.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Field {
+    name: String,
+    #[serde(flatten)]
+    kind: Kind,
+}
+
+/// What the values of a field may be.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Kind {
+    Text,
+    Number,
+    Bool,
+    /// One of the values.
+    Enum {
+        values: Vec<String>,
+    },
+}
+
+impl Field {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Checks a value given for the field, returning it as it is stored, which is none if it is
+    /// not valid. Yes and no are stored as `true` and `false`, and enum values as in the config.
+    pub fn parse(&self, value: &str) -> Option<String> {
+        let value = value.trim();
+        match &self.kind {
+            Kind::Text => Some(value.to_string()),
+            Kind::Number => value.parse::<f64>().ok().map(|_| value.to_string()),
+            Kind::Bool => match value.to_lowercase().as_str() {
+                "true" | "yes" | "y" => Some("true".to_string()),
+                "false" | "no" | "n" => Some("false".to_string()),
+                _ => None,
+            },
+            Kind::Enum { values } => values
+                .iter()
+                .find(|v| v.trim().eq_ignore_ascii_case(value))
+                .cloned(),
+        }
+    }
+
+    /// Whether the stored value compares to the wanted value, which is given as `parse` returns
+    /// it. Only numbers can be less or greater, and text matches if it contains the wanted text.
+    pub fn compare(&self, value: &str, comparison: Comparison, wanted: &str) -> bool {
+        match &self.kind {
+            Kind::Number => match (value.trim().parse::<f64>(), wanted.parse::<f64>()) {
+                (Ok(value), Ok(wanted)) => {
+                    comparison.holds(value.total_cmp(&wanted), Ordering::Equal)
+                }
+                _ => false,
+            },
+            Kind::Text => search::normalize(value).contains(&search::normalize(wanted)),
+            Kind::Bool | Kind::Enum { .. } => value.eq_ignore_ascii_case(wanted),
+        }
+    }
+
+    /// The stored value as it is shown, with yes or no for booleans.
+    pub fn fmt_value(&self, value: &str) -> String {
+        match (&self.kind, value) {
+            (Kind::Bool, "true") => tr!("yes").to_string(),
+            (Kind::Bool, "false") => tr!("no").to_string(),
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// Sets the fields defined for the rest of the session. Can only be set once.
+pub fn init(fields: Vec<Field>) {
+    let _ = FIELDS.set(fields);
+}
+
+pub fn get_fields() -> &'static [Field] {
+    FIELDS.get().map_or(&[], Vec::as_slice)
+}
+
+/// Finds the field with the name among the fields, ignoring case.
+pub fn find_in<'a>(fields: &'a [Field], name: &str) -> Option<&'a Field> {
+    let name = name.trim();
+    fields
+        .iter()
+        .find(|field| field.name.trim().eq_ignore_ascii_case(name))
+}
+
+/// Shows the value of an item for the field with the name, also if the field is no longer
+/// defined.
+pub fn fmt_value(name: &str, value: &str) -> String {
+    match find_in(get_fields(), name) {
+        Some(field) => field.fmt_value(value),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields() {
+        let fields: Vec<Field> = serde_yaml::from_str(
+            "- {name: Box, type: enum, values: [original, none]}\n\
+            - {name: Rating, type: number}\n\
+            - {name: Sealed, type: bool}\n\
+            - {name: Origin, type: text}\n",
+        )
+        .unwrap();
+        let box_field = find_in(&fields, "box").unwrap();
+        assert_eq!(
+            box_field.get_kind(),
+            &Kind::Enum {
+                values: vec!["original".to_string(), "none".to_string()]
+            }
+        );
+        assert_eq!(box_field.parse(" Original"), Some("original".to_string()));
+        assert_eq!(box_field.parse("lost"), None);
+
+        let rating = find_in(&fields, "Rating").unwrap();
+        assert_eq!(rating.parse("4.5"), Some("4.5".to_string()));
+        assert_eq!(rating.parse("good"), None);
+        assert!(rating.compare("4.5", Comparison::Greater, "4"));
+        assert!(!rating.compare("4.5", Comparison::LessOrEqual, "4"));
+
+        let sealed = find_in(&fields, "Sealed").unwrap();
+        assert_eq!(sealed.parse("Yes"), Some("true".to_string()));
+        assert_eq!(sealed.fmt_value("false"), "no");
+        assert!(sealed.compare("true", Comparison::Equal, "true"));
+
+        let origin = find_in(&fields, "origin").unwrap();
+        assert!(origin.compare("Grandpa's attic", Comparison::Equal, "attic"));
+        assert!(find_in(&fields, "Color").is_none());
+    }
+}
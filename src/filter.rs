@@ -0,0 +1,252 @@
+use crate::color;
+use crate::data::Item;
+use crate::dimensions::{self, Comparison, Height};
+use crate::error::{Error, Result};
+use crate::field::{self, Field};
+use crate::search;
+use crate::types::PartId;
+
+/// A condition on items, written as terms separated by commas like `color=red, location=A3`.
+/// Terms without a key match the name, and all terms must match. The term `by=mold` rolls the
+/// matching parts up to their base molds, and heights can be compared like `height<=1/3`. Years of
+/// production can be compared too, where `produced<1990` matches parts first produced before 1990,
+/// `produced>2000` parts still produced after 2000 and `produced=1995` parts produced in 1995.
+/// Custom fields are filtered by their name, like `box=original`, and numbers can be compared.
+/// The counted pieces are compared like `pieces<100`, and `has=price` matches parts with a price,
+/// as do `has=location`, `has=category`, `has=dims`, `has=weight` and `has=` with a custom field.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Filter {
+    terms: Vec<Term>,
+    roll_up: bool,
+}
+
+/// The keys of terms compared for equality, which custom fields of the same name cannot be
+/// filtered by.
+const KEYS: &[&str] = &["name", "color", "location", "mold", "dims", "has", "by"];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Term {
+    Name(String),
+    Color(String),
+    Location(String),
+    /// The part with the ID and the printed and patterned parts based on it.
+    Mold(PartId),
+    /// The number of studs the part covers, in either direction.
+    Footprint(u32, u32),
+    Height(Comparison, Height),
+    Produced(Comparison, u16),
+    /// The custom field with the value as it is stored.
+    Field(Field, Comparison, String),
+    Pieces(Comparison, u32),
+    Has(Property),
+}
+
+/// What parts can be required to have with `has=`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Property {
+    Price,
+    /// At least one lot, and a location for each lot.
+    Location,
+    Category,
+    Dimensions,
+    Weight,
+    /// A value for the custom field with the name.
+    Field(String),
+}
+
+impl Property {
+    fn is_had_by(&self, item: &Item) -> bool {
+        let lots = item.get_locations();
+        match self {
+            Property::Price => item.get_price().is_some(),
+            Property::Location => {
+                !lots.is_empty() && lots.iter().all(|lot| !lot.get_location().trim().is_empty())
+            }
+            Property::Category => item.get_category().is_some(),
+            Property::Dimensions => item.get_dimensions().is_some(),
+            Property::Weight => item.get_piece_weight().is_some(),
+            Property::Field(name) => item.get_field(name).is_some(),
+        }
+    }
+}
+
+impl Filter {
+    pub fn parse(filter: &str) -> Result<Self> {
+        Self::parse_with(filter, field::get_fields())
+    }
+
+    /// Parses the filter with the custom fields, whose names are used as keys. The keys of the
+    /// filter itself come first.
+    fn parse_with(filter: &str, fields: &[Field]) -> Result<Self> {
+        let invalid = || Error::InvalidFilter {
+            filter: filter.to_string(),
+        };
+        let mut terms = Vec::new();
+        let mut roll_up = false;
+        for term in filter.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let term = match Comparison::split(term) {
+                None => Term::Name(search::normalize(term)),
+                Some((key, comparison, value)) if key.trim() == "height" => {
+                    Term::Height(comparison, Height::parse(value).ok_or_else(invalid)?)
+                }
+                Some((key, comparison, value)) if key.trim() == "produced" => {
+                    Term::Produced(comparison, value.trim().parse().map_err(|_| invalid())?)
+                }
+                Some((key, comparison, value)) if key.trim() == "pieces" => {
+                    Term::Pieces(comparison, value.trim().parse().map_err(|_| invalid())?)
+                }
+                Some((key, comparison, value)) if !KEYS.contains(&key.trim()) => {
+                    let field = field::find_in(fields, key).ok_or_else(invalid)?;
+                    let is_number = *field.get_kind() == field::Kind::Number;
+                    if comparison != Comparison::Equal && !is_number {
+                        return Err(invalid());
+                    }
+                    let value = field.parse(value).ok_or_else(invalid)?;
+                    Term::Field(field.clone(), comparison, value)
+                }
+                Some((_, comparison, _)) if comparison != Comparison::Equal => {
+                    return Err(invalid())
+                }
+                Some((key, _, raw)) => {
+                    let value = search::normalize(raw.trim());
+                    match key.trim() {
+                        "name" => Term::Name(value),
+                        "color" => Term::Color(search::normalize(color::resolve(&value))),
+                        "location" => Term::Location(value),
+                        "mold" => {
+                            let id = value.parse().ok().and_then(PartId::new);
+                            Term::Mold(id.ok_or_else(invalid)?)
+                        }
+                        "dims" => {
+                            let (width, length) =
+                                dimensions::parse_footprint(&value).ok_or_else(invalid)?;
+                            Term::Footprint(width, length)
+                        }
+                        "has" => Term::Has(match value.as_str() {
+                            "price" => Property::Price,
+                            "location" => Property::Location,
+                            "category" => Property::Category,
+                            "dims" => Property::Dimensions,
+                            "weight" => Property::Weight,
+                            _ => {
+                                let field = field::find_in(fields, raw).ok_or_else(invalid)?;
+                                Property::Field(field.get_name().to_string())
+                            }
+                        }),
+                        "by" if value == "mold" => {
+                            roll_up = true;
+                            continue;
+                        }
+                        _ => return Err(invalid()),
+                    }
+                }
+            };
+            terms.push(term);
+        }
+        Ok(Self { terms, roll_up })
+    }
+
+    /// Whether the matching parts should be counted by base mold.
+    pub fn rolls_up(&self) -> bool {
+        self.roll_up
+    }
+
+    pub fn matches(&self, item: &Item) -> bool {
+        self.terms.iter().all(|term| match term {
+            Term::Name(name) => search::normalize(item.get_name()).contains(name),
+            Term::Color(color) => item
+                .get_locations()
+                .iter()
+                .any(|lot| search::normalize(&lot.get_color_group().to_string()).contains(color)),
+            Term::Location(location) => item
+                .get_locations()
+                .iter()
+                .any(|lot| search::normalize(lot.get_location()) == *location),
+            Term::Mold(id) => item.get_id() == *id || item.get_base_mold() == Some(*id),
+            Term::Footprint(width, length) => item
+                .get_dimensions()
+                .is_some_and(|dims| dims.has_footprint(*width, *length)),
+            Term::Height(comparison, height) => item
+                .get_dimensions()
+                .is_some_and(|dims| comparison.holds(dims.get_height(), *height)),
+            Term::Field(field, comparison, value) => item
+                .get_field(field.get_name())
+                .is_some_and(|stored| field.compare(stored, *comparison, value)),
+            Term::Pieces(comparison, pieces) => {
+                comparison.holds(item.get_quantity().get(), *pieces)
+            }
+            Term::Has(property) => property.is_had_by(item),
+            Term::Produced(comparison, year) => {
+                item.get_produced().is_some_and(|years| match comparison {
+                    Comparison::Less | Comparison::LessOrEqual => {
+                        comparison.holds(years.from, *year)
+                    }
+                    Comparison::Equal => years.contains(*year),
+                    Comparison::GreaterOrEqual | Comparison::Greater => {
+                        comparison.holds(years.to, *year)
+                    }
+                })
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::dimensions::Dimensions;
+    use crate::types::{PartId, Years};
+
+    #[test]
+    fn test_filter() {
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Other("Red".to_string()), "A3".to_string());
+
+        let matches = |filter: &str| Filter::parse(filter).unwrap().matches(&item);
+        assert!(matches(""));
+        assert!(matches("brick"));
+        assert!(matches("color=red, location=a3"));
+        assert!(!matches("color=red, location=A"));
+        assert!(!matches("name=plate"));
+        assert!(Filter::parse("size=2").is_err());
+
+        item.set_base_mold(PartId::new(3005));
+        let matches = |filter: &str| Filter::parse(filter).unwrap().matches(&item);
+        assert!(matches("mold=3005") && matches("mold=3001"));
+        assert!(!matches("mold=3002"));
+        assert!(Filter::parse("tile, by=mold").unwrap().rolls_up());
+        assert!(Filter::parse("by=color").is_err());
+
+        item.set_dimensions(Dimensions::parse("2 x 4 x 1"));
+        let matches = |filter: &str| Filter::parse(filter).unwrap().matches(&item);
+        assert!(matches("dims=4x2, height>=1") && matches("height=1"));
+        assert!(!matches("dims=2x4, height<=1/3"));
+        assert!(Filter::parse("location<3").is_err());
+        assert!(Filter::parse("height<=half").is_err());
+
+        assert!(!matches("produced<1990"));
+        item.set_produced(Some(Years::new(1978, 1992)));
+        let matches = |filter: &str| Filter::parse(filter).unwrap().matches(&item);
+        assert!(matches("produced<1990") && matches("produced=1985"));
+        assert!(matches("produced>=1992, produced<=1978"));
+        assert!(!matches("produced>1992") && !matches("produced<1978"));
+        assert!(Filter::parse("produced<long ago").is_err());
+
+        let fields: Vec<Field> = serde_yaml::from_str(
+            "- {name: Box, type: enum, values: [original, none]}\n\
+            - {name: Rating, type: number}\n\
+            - {name: Name, type: text}\n",
+        )
+        .unwrap();
+        item.set_field("Box", Some("original".to_string()));
+        item.set_field("Rating", Some("4".to_string()));
+        let matches = |filter: &str| Filter::parse_with(filter, &fields).unwrap().matches(&item);
+        assert!(matches("box=Original, rating>=3.5") && matches("name=brick"));
+        assert!(!matches("box=none") && !matches("rating<4"));
+        assert!(Filter::parse_with("box<original", &fields).is_err());
+        assert!(Filter::parse_with("box=lost", &fields).is_err());
+        assert!(matches("has=box, has=location, pieces<1") && !matches("has=price"));
+        assert!(Filter::parse_with("has=wings", &fields).is_err());
+    }
+}
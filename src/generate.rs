@@ -0,0 +1,103 @@
+use crate::currency::{Amount, Price};
+use crate::data::{ColorGroup, Item, RawDatabase};
+use crate::types::{PartId, Quantity};
+
+const SHAPES: [&str; 8] = [
+    "Brick",
+    "Plate",
+    "Tile",
+    "Slope",
+    "Wedge",
+    "Round Brick",
+    "Panel",
+    "Bracket",
+];
+
+const COLOR_GROUPS: [ColorGroup; 9] = [
+    ColorGroup::Basic,
+    ColorGroup::Earth,
+    ColorGroup::Grey,
+    ColorGroup::Road,
+    ColorGroup::Nice,
+    ColorGroup::Build,
+    ColorGroup::Translucent,
+    ColorGroup::Colorful,
+    ColorGroup::Misc,
+];
+
+/// A small xorshift generator, so that the same seed always gives the same database.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Generates a synthetic database with the given number of lots, used for measuring performance.
+pub fn generate(num_lots: usize, seed: u64) -> RawDatabase {
+    let mut rng = Rng(seed.max(1));
+    let mut items = Vec::new();
+    let mut lots = 0;
+
+    while lots < num_lots {
+        let id = PartId::new(1000 + items.len() as u32).expect("too many items to generate");
+        let name = format!(
+            "{} {} x {}",
+            SHAPES[rng.below(SHAPES.len())],
+            1 + rng.below(4),
+            1 + rng.below(16),
+        );
+        let mut item = Item::new(id, name);
+
+        let num_lots_item = (1 + rng.below(3)).min(num_lots - lots);
+        for i in 0..num_lots_item {
+            let color_group =
+                COLOR_GROUPS[(rng.below(COLOR_GROUPS.len()) + i) % COLOR_GROUPS.len()].clone();
+            if item.get_color_set().contains(&color_group) {
+                continue;
+            }
+            let location = format!(
+                "{}{}{}",
+                (b'A' + rng.below(26) as u8) as char,
+                1 + rng.below(20),
+                (b'A' + rng.below(8) as u8) as char,
+            );
+            item.add_color_group(color_group.clone(), location);
+            if rng.below(2) == 0 {
+                item.set_quantity(&color_group, Some(Quantity::new(rng.below(500) as u32)));
+            }
+            lots += 1;
+        }
+        if rng.below(4) == 0 {
+            item.set_price(Some(Price::new(
+                Amount::from_cents(rng.below(100) as i64),
+                "EUR",
+            )));
+        }
+
+        items.push(item);
+    }
+
+    RawDatabase::new(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let db = generate(1000, 7);
+        let lots: usize = db.iter().map(|item| item.get_locations().len()).sum();
+        assert_eq!(lots, 1000);
+        assert_eq!(db, generate(1000, 7));
+    }
+}
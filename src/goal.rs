@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::data::{ColorGroup, Database};
+use crate::locale::tr;
+use crate::types::PartId;
+
+/// The width of the progress bars in characters.
+const BAR_WIDTH: usize = 20;
+
+fn get_owned(db: &Database, id: PartId) -> u32 {
+    db.get_item_by_id(id)
+        .map_or(0, |item| item.get_quantity().get())
+}
+
+/// Returns how many of the needed pieces are owned out of the total number needed.
+pub fn get_parts_progress(db: &Database, parts: &BTreeMap<PartId, u32>) -> (u32, u32) {
+    parts.iter().fold((0, 0), |(done, total), (id, needed)| {
+        (done + get_owned(db, *id).min(*needed), total + needed)
+    })
+}
+
+/// The pieces of each part still missing for the goals that ask for pieces of parts. A part in
+/// several goals is missing as many pieces as the goal needing the most of it asks for.
+pub fn get_wanted(db: &Database, goals: &[Goal]) -> BTreeMap<PartId, u32> {
+    let mut needed: BTreeMap<PartId, u32> = BTreeMap::new();
+    for goal in goals {
+        if let Target::Parts(parts) = &goal.target {
+            for (id, count) in parts {
+                let most = needed.entry(*id).or_insert(0);
+                *most = (*most).max(*count);
+            }
+        }
+    }
+    needed
+        .into_iter()
+        .map(|(id, count)| (id, count.saturating_sub(get_owned(db, id))))
+        .filter(|(_, missing)| *missing != 0)
+        .collect()
+}
+
+/// A collection goal defined in the config, whose progress is computed from the database.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Goal {
+    name: String,
+    #[serde(flatten)]
+    target: Target,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    /// Own the part in every color group.
+    ColorGroups(PartId),
+    /// Own at least the given number of pieces of each part, e.g. the inventory of a set.
+    Parts(BTreeMap<PartId, u32>),
+}
+
+impl Goal {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// The parts the goal refers to.
+    pub fn get_part_ids(&self) -> Vec<PartId> {
+        match &self.target {
+            Target::ColorGroups(id) => vec![*id],
+            Target::Parts(parts) => parts.keys().copied().collect(),
+        }
+    }
+
+    /// Returns how much of the goal is reached out of the total.
+    pub fn get_progress(&self, db: &Database) -> (u32, u32) {
+        match &self.target {
+            Target::ColorGroups(id) => {
+                let groups: Vec<ColorGroup> = ColorGroup::iter()
+                    .filter(|group| !matches!(group, ColorGroup::All | ColorGroup::Other(_)))
+                    .collect();
+                let total = groups.len() as u32;
+                let Ok(item) = db.get_item_by_id(*id) else {
+                    return (0, total);
+                };
+                let owned = item.get_color_set();
+                if owned.contains(&ColorGroup::All) {
+                    return (total, total);
+                }
+                let done = groups.iter().filter(|group| owned.contains(group)).count();
+                (done as u32, total)
+            }
+            Target::Parts(parts) => get_parts_progress(db, parts),
+        }
+    }
+
+    /// Formats the progress of the goal as a bar, e.g. "[##########----------] 50% name".
+    pub fn fmt_progress(&self, db: &Database) -> String {
+        let (done, total) = self.get_progress(db);
+        let ratio = if total == 0 {
+            1.0
+        } else {
+            done as f64 / total as f64
+        };
+        let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+        tr!(
+            "[{}{}] {}% {} ({} of {})",
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            (ratio * 100.0).floor(),
+            self.name,
+            done,
+            total,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{write_items, Item, RawDatabase};
+    use crate::types::Quantity;
+
+    #[test]
+    fn test_progress() {
+        let id = PartId::new(3069).unwrap();
+        let mut item = Item::new(id, "Tile 1 x 2".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Grey, "A2".to_string());
+        item.set_quantity(&ColorGroup::Basic, Some(Quantity::new(3)));
+        let path = std::path::PathBuf::from("test_goal.yml");
+        write_items(&path, &RawDatabase::new(vec![item])).unwrap();
+        let db = Database::new(path.clone()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let goal: Goal = serde_yaml::from_str("name: Tiles\ncolor_groups: 3069").unwrap();
+        assert_eq!(goal.get_progress(&db), (2, 9));
+
+        let goal: Goal = serde_yaml::from_str("name: Set\nparts:\n  3069: 4\n  3001: 2").unwrap();
+        assert_eq!(goal.get_progress(&db), (3, 6));
+
+        let other: Goal = serde_yaml::from_str("name: Other\nparts:\n  3069: 6").unwrap();
+        let tiles: Goal = serde_yaml::from_str("name: Tiles\ncolor_groups: 3069").unwrap();
+        let wanted = get_wanted(&db, &[goal, other, tiles]);
+        assert_eq!(
+            wanted,
+            BTreeMap::from([(id, 3), (PartId::new(3001).unwrap(), 2)])
+        );
+    }
+}
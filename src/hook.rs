@@ -0,0 +1,188 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{ColorGroup, Item};
+use crate::error::Result;
+use crate::types::PartId;
+
+static HOOKS: OnceLock<Vec<Hook>> = OnceLock::new();
+
+/// The kinds of events that hooks can run on.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    ItemAdded,
+    ItemChanged,
+    QuantityChanged,
+    ItemRemoved,
+    DatabaseSaved,
+}
+
+/// A change to the database, given to hooks as JSON with its kind in the `event` field.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum Event {
+    ItemAdded {
+        item: Item,
+    },
+    ItemChanged {
+        old: Item,
+        new: Item,
+    },
+    /// The number of counted pieces in a color group of the part changed.
+    QuantityChanged {
+        id: PartId,
+        color_group: ColorGroup,
+        old: u32,
+        new: u32,
+    },
+    ItemRemoved {
+        id: PartId,
+    },
+    /// The changes were written to disk.
+    DatabaseSaved {
+        changed: usize,
+    },
+}
+
+impl Event {
+    pub fn get_kind(&self) -> EventKind {
+        match self {
+            Event::ItemAdded { .. } => EventKind::ItemAdded,
+            Event::ItemChanged { .. } => EventKind::ItemChanged,
+            Event::QuantityChanged { .. } => EventKind::QuantityChanged,
+            Event::ItemRemoved { .. } => EventKind::ItemRemoved,
+            Event::DatabaseSaved { .. } => EventKind::DatabaseSaved,
+        }
+    }
+}
+
+/// Returns the events for changing the old item into the new one, which are the change itself
+/// and a change of quantity for each color group whose counted pieces changed.
+pub fn get_change_events(old: &Item, new: &Item) -> Vec<Event> {
+    let count = |item: &Item, color_group: &ColorGroup| -> u32 {
+        item.get_locations()
+            .iter()
+            .filter(|lot| lot.get_color_group() == color_group)
+            .filter_map(|lot| lot.get_quantity())
+            .map(|quantity| quantity.get())
+            .sum()
+    };
+    let mut color_groups = old.get_color_set();
+    color_groups.append(&mut new.get_color_set());
+
+    let mut events = vec![Event::ItemChanged {
+        old: old.clone(),
+        new: new.clone(),
+    }];
+    for color_group in color_groups {
+        let (old_count, new_count) = (count(old, color_group), count(new, color_group));
+        if old_count != new_count {
+            events.push(Event::QuantityChanged {
+                id: new.get_id(),
+                color_group: color_group.clone(),
+                old: old_count,
+                new: new_count,
+            });
+        }
+    }
+    events
+}
+
+/// A command run with an event as JSON on stdin whenever such an event happens.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Hook {
+    on: EventKind,
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+/// Sets the hooks used for the rest of the session. Can only be set once.
+pub fn init(hooks: Vec<Hook>) {
+    let _ = HOOKS.set(hooks);
+}
+
+fn get_hooks() -> &'static [Hook] {
+    HOOKS.get().map_or(&[], Vec::as_slice)
+}
+
+/// Whether any hooks are set, so that events need to be collected at all.
+pub fn is_listening() -> bool {
+    !get_hooks().is_empty()
+}
+
+/// Runs the hook with the event and waits for it to finish.
+fn run(hook: &Hook, event: &Event) -> Result<()> {
+    let input = serde_json::to_vec(event).map_err(std::io::Error::other)?;
+    let mut child = Command::new(&hook.command)
+        .args(&hook.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Hooks that do not need the event may close stdin without reading it.
+        let _ = stdin.write_all(&input);
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        log::warn!("hook {} exited with {}", hook.command, status);
+    }
+    Ok(())
+}
+
+/// Runs the hooks of the events in the background, in the order of the events, so that the
+/// session does not wait for notifications or downstream syncs to finish.
+pub fn publish(events: Vec<Event>) {
+    let hooks = get_hooks();
+    if hooks.is_empty() || events.is_empty() {
+        return;
+    }
+    thread::spawn(move || {
+        for event in events.iter() {
+            for hook in hooks.iter().filter(|hook| hook.on == event.get_kind()) {
+                if let Err(e) = run(hook, event) {
+                    log::warn!("hook {} could not be run: {}", hook.command, e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Quantity;
+
+    #[test]
+    fn test_events() {
+        let mut old = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        old.add_color_group(ColorGroup::Basic, "A1".to_string());
+        let mut new = old.clone();
+        new.set_quantity(&ColorGroup::Basic, Some(Quantity::new(12)));
+        let events = get_change_events(&old, &new);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].get_kind(), EventKind::QuantityChanged);
+        assert_eq!(get_change_events(&old, &old).len(), 1);
+
+        let path = "test_hook.json";
+        let hook = Hook {
+            on: EventKind::QuantityChanged,
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), format!("cat > {}", path)],
+        };
+        run(&hook, &events[1]).unwrap();
+        let json = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(
+            json,
+            r#"{"event":"quantity_changed","id":3001,"color_group":"Basic","old":0,"new":12}"#
+        );
+    }
+}
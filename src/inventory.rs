@@ -0,0 +1,37 @@
+//! The inventory logic of the crate without the terminal interface, so that other tools can read,
+//! search and change a database in the same way.
+//!
+//! ```no_run
+//! use std::path::PathBuf;
+//!
+//! use tui_bricks::inventory::{Database, Filter};
+//!
+//! let db = Database::new(PathBuf::from("database.yml"))?;
+//! let filter = Filter::parse("color=red, location=A3")?;
+//! for item in db.get_items().iter().filter(|item| filter.matches(item)) {
+//!     println!("{} {}", item.get_id(), item.get_name());
+//! }
+//! # Ok::<(), tui_bricks::error::Error>(())
+//! ```
+//!
+//! Changes made through `Database` are journaled and merged with changes made on disk the same
+//! way as in a session, while `read_items` and `write_items` work on the whole file at once.
+
+pub use crate::adjustment::{Adjustment, Reason};
+pub use crate::clipboard::to_csv;
+pub use crate::currency::{Amount, Price};
+pub use crate::data::{
+    describe_changes, read_items, write_items, ColorGroup, Database, DatabaseStats, Item,
+    LocSearch, Lot, RawDatabase,
+};
+pub use crate::diff::{diff, Change, ItemDiff, LotDiff};
+pub use crate::envelope::{from_json, to_json, Envelope, FORMAT_VERSION};
+pub use crate::error::{Error, Result};
+pub use crate::filter::Filter;
+pub use crate::label::{get_labels, to_html as labels_to_html};
+pub use crate::order::{read as read_order, take_in, Order, OrderLine};
+pub use crate::search::{normalize, roll_up, MoldCount};
+pub use crate::site::Site;
+pub use crate::sync::{merge, Conflict, Field};
+pub use crate::types::{PartId, Quantity};
+pub use crate::viewer::to_html as viewer_to_html;
@@ -1,10 +1,13 @@
 use std::fs;
+use std::io::{ErrorKind, Write};
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
 use term_lib::Result;
 
+use crate::crypt;
+
 /// linux: /home/alice/.local/share/tui_bricks/
 /// macos: /Users/Alice/Library/Application Support/com.simaflux.tui_bricks/database.yml
 /// windows: C:\Users\Alice\AppData\Roaming\simaflux\tui_bricks\data\database.yml
@@ -49,13 +52,76 @@ pub fn get_config_dir() -> std::path::PathBuf {
     }
 }
 
+/// Returns the time the file was last modified, or None if it does not exist.
+pub fn get_modified<P: AsRef<Path>>(path: P) -> Option<std::time::SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reads the file as text, decrypting it if it is encrypted, see `crypt`.
+pub fn read_private<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
+    let contents = crypt::decrypt(fs::read(path)?)?;
+    String::from_utf8(contents).map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Writes the text, encrypted if the session has a passphrase.
+pub fn write_private<P: AsRef<Path>>(path: P, contents: &str) -> std::io::Result<()> {
+    fs::write(path, crypt::encrypt(contents.as_bytes())?)
+}
+
+/// Appends the text to the file, where an encrypted file is written again as a whole.
+pub fn append_private<P: AsRef<Path>>(path: P, appended: &str) -> std::io::Result<()> {
+    if !crypt::is_enabled() {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        return file.write_all(appended.as_bytes());
+    }
+    let mut contents = match read_private(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    contents.push_str(appended);
+    write_private(path, &contents)
+}
+
 pub fn read_contents_from_yaml<P, T>(path: P) -> Result<T>
 where
     P: AsRef<Path>,
     T: for<'de> Deserialize<'de>,
 {
-    let file = fs::File::open(path)?;
-    let contents = serde_yaml::from_reader(file)?;
+    parse_yaml(&read_private(path)?)
+}
+
+/// Reads the file once and parses it, also returning the contents so they can be parsed again later
+/// without going to disk.
+pub fn read_yaml_and_contents<P, T>(path: P) -> Result<(T, String)>
+where
+    P: AsRef<Path>,
+    T: for<'de> Deserialize<'de>,
+{
+    let contents = read_private(path)?;
+    let parsed = parse_yaml(&contents)?;
+    Ok((parsed, contents))
+}
+
+pub fn parse_yaml<T>(contents: &str) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    Ok(serde_yaml::from_str(contents)?)
+}
+
+/// Writes the contents, encrypted if the session has a passphrase, and returns them as text before
+/// encrypting.
+pub fn write_yaml_and_contents<P, T>(path: P, contents: &T) -> Result<String>
+where
+    P: AsRef<Path>,
+    T: Serialize,
+{
+    let contents = serde_yaml::to_string(contents)?;
+    write_private(path, &contents)?;
     Ok(contents)
 }
 
@@ -72,7 +138,10 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::data::tests::get_test_database;
+    use crate::data::tests::{arb_item, get_test_database};
+    use crate::data::RawDatabase;
+    use crate::journal;
+    use proptest::prelude::*;
 
     #[test]
     fn test_read_write_database() {
@@ -88,4 +157,29 @@ mod tests {
 
         assert_eq!(test, yaml);
     }
+
+    proptest! {
+        #[test]
+        fn prop_parse_never_panics(contents in "\\PC*") {
+            let _ = parse_yaml::<RawDatabase>(&contents);
+            let _ = journal::parse(&contents);
+        }
+
+        #[test]
+        fn prop_parse_truncated(items in prop::collection::vec(arb_item(), 1..8), cut in 0.0..1.0f64) {
+            let contents = serde_yaml::to_string(&RawDatabase::new(items)).unwrap();
+            let mut end = (contents.len() as f64 * cut) as usize;
+            while !contents.is_char_boundary(end) {
+                end -= 1;
+            }
+            let _ = parse_yaml::<RawDatabase>(&contents[..end]);
+        }
+
+        #[test]
+        fn prop_round_trip(items in prop::collection::vec(arb_item(), 0..8)) {
+            let database = RawDatabase::new(items);
+            let contents = serde_yaml::to_string(&database).unwrap();
+            prop_assert_eq!(parse_yaml::<RawDatabase>(&contents).unwrap(), database);
+        }
+    }
 }
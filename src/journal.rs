@@ -0,0 +1,110 @@
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use term_lib::Result;
+
+use crate::data::Item;
+use crate::types::PartId;
+
+/// The journal is compacted into the database file once it holds this many entries.
+pub const COMPACT_AFTER: usize = 200;
+
+/// A change to a single item. Saves append entries for the changed items to the journal next to
+/// the database file instead of rewriting the whole database.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Entry {
+    Put(Item),
+    Delete(PartId),
+}
+
+pub fn get_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("journal")
+}
+
+/// Returns the contents of the journal, which are empty if there is none.
+pub fn read_contents(path: &Path) -> Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn parse(contents: &str) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    // An empty string would be parsed as a single empty document.
+    if contents.trim().is_empty() {
+        return Ok(entries);
+    }
+    for document in serde_yaml::Deserializer::from_str(contents) {
+        entries.push(Entry::deserialize(document)?);
+    }
+    Ok(entries)
+}
+
+/// Applies the entries in order, so the last entry for an item wins.
+pub fn apply(items: &mut Vec<Item>, entries: Vec<Entry>) {
+    for entry in entries {
+        match entry {
+            Entry::Put(item) => match items.iter_mut().find(|i| i.get_id() == item.get_id()) {
+                Some(old) => *old = item,
+                None => items.push(item),
+            },
+            Entry::Delete(id) => items.retain(|item| item.get_id() != id),
+        }
+    }
+}
+
+/// Appends the entries to the journal and returns what was appended.
+pub fn append(path: &Path, entries: &[Entry]) -> Result<String> {
+    let mut appended = String::new();
+    for entry in entries {
+        appended.push_str("---\n");
+        appended.push_str(&serde_yaml::to_string(entry)?);
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(appended.as_bytes())?;
+    Ok(appended)
+}
+
+/// Removes the journal after its entries have been written to the database file.
+pub fn remove(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_apply() {
+        let path = PathBuf::from("test_journal.journal");
+        let id = PartId::new(3001).unwrap();
+
+        let mut item = Item::new(id, "Brick 2 x 4".to_string());
+        append(&path, &[Entry::Put(item.clone())]).unwrap();
+        item.set_name("Brick 2x4");
+        append(&path, &[Entry::Put(item.clone())]).unwrap();
+
+        let contents = read_contents(&path).unwrap();
+        remove(&path).unwrap();
+
+        let mut items = Vec::new();
+        apply(&mut items, parse(&contents).unwrap());
+        assert_eq!(items, vec![item]);
+
+        apply(&mut items, vec![Entry::Delete(id)]);
+        assert!(items.is_empty());
+        assert!(parse("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+static KEY_CONFIG: OnceLock<KeyConfig> = OnceLock::new();
+
+/// How long to wait for the next key of a chord when the keys so far are also a command.
+const DEFAULT_CHORD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// A key press, with the modifiers held down.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Key {
+    c: char,
+    #[serde(default)]
+    ctrl: bool,
+    #[serde(default)]
+    alt: bool,
+}
+
+impl Key {
+    pub fn new(c: char) -> Self {
+        Self {
+            c,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    pub fn with_modifiers(c: char, ctrl: bool, alt: bool) -> Self {
+        Self { c, ctrl, alt }
+    }
+
+    /// The character of a key pressed without modifiers.
+    pub fn get_plain_char(&self) -> Option<char> {
+        (!self.ctrl && !self.alt).then_some(self.c)
+    }
+
+    /// Parses keys written like `g`, `C-d` for Ctrl+d, or `M-x` for Alt+x.
+    fn parse(s: &str) -> Option<Self> {
+        let mut key = Key::new(' ');
+        let mut rest = s.trim();
+        loop {
+            if let Some(r) = rest.strip_prefix("C-") {
+                key.ctrl = true;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("M-") {
+                key.alt = true;
+                rest = r;
+            } else {
+                break;
+            }
+        }
+        let mut chars = rest.chars();
+        key.c = chars.next()?;
+        chars.next().is_none().then_some(key)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "C-")?;
+        }
+        if self.alt {
+            write!(f, "M-")?;
+        }
+        write!(f, "{}", self.c)
+    }
+}
+
+/// The keys pressed one after the other to give a command, e.g. `g g`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Keys(Vec<Key>);
+
+impl Keys {
+    pub fn single(c: char) -> Self {
+        Self(vec![Key::new(c)])
+    }
+
+    /// Parses keys separated by spaces, e.g. `d d` or `C-s`.
+    pub fn parse(keys: &str) -> Option<Self> {
+        let keys: Option<Vec<Key>> = keys.split_whitespace().map(Key::parse).collect();
+        keys.filter(|keys| !keys.is_empty()).map(Self)
+    }
+
+    fn starts_with(&self, typed: &[Key]) -> bool {
+        self.0.starts_with(typed)
+    }
+}
+
+impl fmt::Display for Keys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(Key::to_string).collect();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+/// How pressing keys that give no command in the current mode is shown.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Feedback {
+    /// The keys are ignored without showing it.
+    None,
+    /// The terminal bell is rung.
+    Bell,
+    /// A message is shown at the bottom of the screen until the next key is pressed.
+    #[default]
+    Flash,
+    Both,
+}
+
+impl Feedback {
+    pub fn rings(&self) -> bool {
+        matches!(self, Feedback::Bell | Feedback::Both)
+    }
+
+    pub fn flashes(&self) -> bool {
+        matches!(self, Feedback::Flash | Feedback::Both)
+    }
+}
+
+struct KeyConfig {
+    bindings: BTreeMap<String, Keys>,
+    chord_timeout: Duration,
+}
+
+/// Sets the key bindings, from command names to keys, used for the rest of the session. Can only
+/// be set once.
+pub fn init(bindings: &BTreeMap<String, String>, chord_timeout: Option<Duration>) -> Result<()> {
+    let mut parsed = BTreeMap::new();
+    for (cmd, keys) in bindings.iter() {
+        let keys = Keys::parse(keys).ok_or_else(|| Error::InvalidKeyBinding {
+            binding: format!("{}: {}", cmd, keys),
+        })?;
+        parsed.insert(cmd.clone(), keys);
+    }
+    let _ = KEY_CONFIG.set(KeyConfig {
+        bindings: parsed,
+        chord_timeout: chord_timeout.unwrap_or(DEFAULT_CHORD_TIMEOUT),
+    });
+    Ok(())
+}
+
+/// The keys the command with the given name is bound to in the config, if they were changed.
+pub fn get_binding(cmd: &str) -> Option<Keys> {
+    KEY_CONFIG.get()?.bindings.get(cmd).cloned()
+}
+
+pub fn get_chord_timeout() -> Duration {
+    KEY_CONFIG
+        .get()
+        .map_or(DEFAULT_CHORD_TIMEOUT, |config| config.chord_timeout)
+}
+
+/// Finds the command given by exactly the typed keys, and whether a longer chord starts with them
+/// so that the next key should be waited for.
+pub fn find<'a, C>(cmds: &'a [(C, Keys)], typed: &[Key]) -> (Option<&'a C>, bool) {
+    let exact = cmds
+        .iter()
+        .find(|(_, keys)| keys.0 == typed)
+        .map(|(cmd, _)| cmd);
+    let longer = cmds
+        .iter()
+        .any(|(_, keys)| keys.0.len() > typed.len() && keys.starts_with(typed));
+    (exact, longer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_find() {
+        let keys = Keys::parse("C-M-x  g").unwrap();
+        assert_eq!(keys.to_string(), "C-M-x g");
+        assert!(Keys::parse("gg").is_none());
+        assert!(Keys::parse(" ").is_none());
+
+        let cmds = vec![
+            ("add group", Keys::single('g')),
+            ("go to top", Keys::parse("g g").unwrap()),
+            ("save", Keys::parse("C-s").unwrap()),
+        ];
+        let g = Key::new('g');
+        assert_eq!(find(&cmds, &[g]), (Some(&"add group"), true));
+        assert_eq!(find(&cmds, &[g, g]), (Some(&"go to top"), false));
+        assert_eq!(find(&cmds, &[Key::new('s')]), (None, false));
+        assert_eq!(
+            find(&cmds, &[Key::with_modifiers('s', true, false)]),
+            (Some(&"save"), false)
+        );
+    }
+}
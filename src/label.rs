@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use qrcode::render::svg;
+use qrcode::QrCode;
+
+use crate::data::Item;
+use crate::link::Link;
+use crate::types::PartId;
+
+/// The parts stored at each location, sorted by location and then by part ID.
+pub fn get_labels(items: &[Item]) -> BTreeMap<&str, Vec<(PartId, &str)>> {
+    let mut labels: BTreeMap<&str, Vec<(PartId, &str)>> = BTreeMap::new();
+    for item in items.iter() {
+        for lot in item.get_locations() {
+            let parts = labels.entry(lot.get_location().as_str()).or_default();
+            let part = (item.get_id(), item.get_name().as_str());
+            if !parts.contains(&part) {
+                parts.push(part);
+            }
+        }
+    }
+    for parts in labels.values_mut() {
+        parts.sort();
+    }
+    labels
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A QR code encoding the link to the location, or None if it is too long to encode.
+fn get_qr_code(location: &str) -> Option<String> {
+    let link = Link::Location(location.to_string());
+    let code = QrCode::new(link.to_string().as_bytes()).ok()?;
+    Some(
+        code.render::<svg::Color>()
+            .min_dimensions(96, 96)
+            .quiet_zone(false)
+            .build(),
+    )
+}
+
+const STYLE: &str = "body { font-family: sans-serif; }
+.label { display: inline-block; vertical-align: top; width: 9cm; margin: 2mm; padding: 2mm;
+  border: 1px dashed #888; page-break-inside: avoid; }
+.label h1 { font-size: 16pt; margin: 0 0 2mm 0; }
+.qr { float: right; }
+.part { display: flex; align-items: center; margin-bottom: 1mm; font-size: 9pt; }
+.image { width: 1cm; height: 1cm; margin-right: 2mm; border: 1px solid #ccc; }
+";
+
+/// Renders one label per location as an HTML page, meant to be printed from a browser. Each part
+/// has an empty box in which a picture of it can be glued or drawn.
+pub fn to_html(labels: &BTreeMap<&str, Vec<(PartId, &str)>>, qr_codes: bool) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Labels</title>\n\
+         <style>\n{}</style>\n</head>\n<body>\n",
+        STYLE
+    );
+    for (location, parts) in labels.iter() {
+        html.push_str("<div class=\"label\">\n");
+        if let Some(qr_code) = get_qr_code(location).filter(|_| qr_codes) {
+            html.push_str(&format!("<div class=\"qr\">{}</div>\n", qr_code));
+        }
+        html.push_str(&format!("<h1>{}</h1>\n", escape(location)));
+        for (id, name) in parts.iter() {
+            html.push_str(&format!(
+                "<div class=\"part\"><div class=\"image\"></div><b>{}</b>&nbsp;{}</div>\n",
+                id,
+                escape(name)
+            ));
+        }
+        html.push_str("</div>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+
+    #[test]
+    fn test_labels() {
+        let (a, b) = (PartId::new(3001).unwrap(), PartId::new(3002).unwrap());
+        let mut first = Item::new(b, "Brick 2 x 3".to_string());
+        first.add_color_group(ColorGroup::Basic, "A1".to_string());
+        first.add_color_group(ColorGroup::Earth, "A1".to_string());
+        let mut second = Item::new(a, "Brick <2 x 4>".to_string());
+        second.add_color_group(ColorGroup::Basic, "A1".to_string());
+        second.add_color_group(ColorGroup::Grey, "B2".to_string());
+
+        let items = [first, second];
+        let labels = get_labels(&items);
+        assert_eq!(
+            labels,
+            BTreeMap::from([
+                ("A1", vec![(a, "Brick <2 x 4>"), (b, "Brick 2 x 3")]),
+                ("B2", vec![(a, "Brick <2 x 4>")]),
+            ])
+        );
+        let html = to_html(&labels, true);
+        assert!(html.contains("Brick &lt;2 x 4&gt;"));
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(!to_html(&labels, false).contains("<svg"));
+    }
+}
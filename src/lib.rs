@@ -1,59 +1,1013 @@
 extern crate log;
 
+mod accessible;
+mod adjustment;
+mod age;
+mod api;
+mod args;
+mod assembly;
+mod availability;
+#[doc(hidden)]
+pub mod bench;
+mod brickset;
+mod brickstore;
+mod budget;
+mod bundle;
+mod capacity;
+mod check;
+mod clipboard;
 mod cmd;
+mod color;
+mod column;
+mod config;
+mod crypt;
+mod currency;
 mod data;
+mod date;
+mod diff;
+mod dimensions;
+mod enrich;
+mod envelope;
 pub mod error;
+mod field;
+mod filter;
+mod generate;
+mod goal;
+mod hook;
+pub mod inventory;
 mod io;
+mod journal;
+mod key;
+mod label;
+mod link;
+mod locale;
 mod mode;
+mod oauth;
+mod order;
+mod placement;
+mod platform;
+mod plugin;
+mod rebalance;
+mod remote;
+mod rest;
+mod rule;
+mod sale;
+mod search;
+mod secret;
+mod section;
+mod set;
+mod shipping;
+mod similar;
+mod site;
+mod snapshot;
 mod state;
+mod store;
+mod summary;
+mod sync;
+mod theme;
+mod throughput;
+mod timestamp;
+mod transaction;
+mod types;
+mod ui;
+mod usage;
+mod viewer;
+mod widget;
+mod width;
+
+use std::io::IsTerminal;
+
+use args::CliCmd;
+use config::Config;
+use locale::{tr, tr_n};
 
 #[cfg(not(debug_assertions))]
-mod config;
+fn get_config_path() -> std::path::PathBuf {
+    io::get_config_dir().join("config.yml")
+}
+
+#[cfg(debug_assertions)]
+fn get_config_path() -> std::path::PathBuf {
+    std::path::PathBuf::new().join("test_config.yml")
+}
 
 #[cfg(not(debug_assertions))]
-fn get_user_db_path() -> error::Result<std::path::PathBuf> {
-    use config::Config;
+fn get_usage_path() -> std::path::PathBuf {
+    io::get_config_dir().join("usage.log")
+}
+
+#[cfg(debug_assertions)]
+fn get_usage_path() -> std::path::PathBuf {
+    std::path::PathBuf::new().join("test_usage.log")
+}
+
+/// The secrets are kept next to the config, but in a file of their own, see `secret`.
+#[cfg(not(debug_assertions))]
+fn get_secrets_path() -> std::path::PathBuf {
+    io::get_config_dir().join("secrets.yml")
+}
+
+#[cfg(debug_assertions)]
+fn get_secrets_path() -> std::path::PathBuf {
+    std::path::PathBuf::new().join("test_secrets.yml")
+}
+
+fn get_config() -> error::Result<Config> {
     use figment::{
         providers::{Format, Serialized, Yaml},
         Figment,
     };
 
-    let mut config_path = io::get_config_dir();
-    config_path.push("config.yml");
-
     let config: Config = Figment::from(Serialized::defaults(Config::default()))
-        .merge(Yaml::file(config_path))
+        .merge(Yaml::file(get_config_path()))
         .extract()
         .unwrap();
 
-    Ok(config.get_db_path())
+    Ok(config)
+}
+
+/// Runs the command, refusing those that would make changes in read-only mode. Dry runs of them
+/// are allowed, as they only report the changes.
+fn run_cli_cmd(cmd: CliCmd, config: Config, dry_run: bool, read_only: bool) -> error::Result<()> {
+    if read_only && !dry_run && cmd.is_mutating() {
+        return Err(error::Error::ReadOnly {
+            cmd: cmd.get_name().to_string(),
+        });
+    }
+    use CliCmd::*;
+    match cmd {
+        BundleExport { path } if dry_run => {
+            println!("{}", tr!("Would export bundle to {}", path.display()));
+        }
+        BundleExport { path } => {
+            bundle::export(&config, &path)?;
+            println!("{}", tr!("Exported bundle to {}", path.display()));
+        }
+        BundleImport { path, force } if dry_run => {
+            let changes = bundle::describe_import(&config, &path, force)?;
+            println!(
+                "{}",
+                tr!(
+                    "Importing bundle from {} would make these changes:",
+                    path.display()
+                )
+            );
+            print!("{}", changes);
+        }
+        BundleImport { path, force } => {
+            bundle::import(config, &get_config_path(), &path, force)?;
+            println!("{}", tr!("Imported bundle from {}", path.display()));
+        }
+        Check { repair } => {
+            let db_path = config.get_db_path();
+            let mut items = data::read_items(&db_path)?;
+            let old_items = items.to_vec();
+            let problems = check::check(&items, &config);
+            if problems.is_empty() {
+                println!("{}", tr!("No problems were found in the database."));
+                return Ok(());
+            }
+
+            let mut repaired = 0;
+            for problem in problems.iter() {
+                let item = problem
+                    .get_item_to_repair()
+                    .filter(|_| repair)
+                    .and_then(|id| items.iter_mut().find(|item| item.get_id() == id));
+                if item.is_some_and(|item| problem.repair(item)) {
+                    println!("{}", tr!("Repaired: {}", problem));
+                    repaired += 1;
+                } else {
+                    println!("{}", problem);
+                }
+            }
+            if repaired != 0 && !dry_run {
+                data::write_items(&db_path, &items)?;
+                transaction::record(
+                    &db_path,
+                    tr!("Database repairs"),
+                    config.get_user(),
+                    &old_items,
+                    &items,
+                )?;
+            }
+            println!(
+                "{}",
+                tr_n!(
+                    "{} problem was found and {} repaired.",
+                    "{} problems were found and {} repaired.",
+                    problems.len(),
+                    repaired
+                )
+            );
+        }
+        Labels { path, .. } if dry_run => {
+            println!("{}", tr!("Would write labels to {}", path.display()));
+        }
+        Labels {
+            path,
+            qr_codes,
+            force,
+        } => {
+            if !force && io::get_modified(&path).is_some() {
+                return Err(error::Error::FileAlreadyExists {
+                    path: path.to_string_lossy().to_string(),
+                });
+            }
+            let items = data::read_items(&config.get_db_path())?;
+            let labels = label::get_labels(&items);
+            std::fs::write(&path, label::to_html(&labels, qr_codes))?;
+            println!(
+                "{}",
+                tr_n!(
+                    "Wrote {} label to {}",
+                    "Wrote {} labels to {}",
+                    labels.len(),
+                    path.display()
+                )
+            );
+        }
+        Viewer { path, .. } if dry_run => {
+            println!("{}", tr!("Would write the viewer to {}", path.display()));
+        }
+        Viewer {
+            path,
+            images,
+            force,
+        } => {
+            if !force && io::get_modified(&path).is_some() {
+                return Err(error::Error::FileAlreadyExists {
+                    path: path.to_string_lossy().to_string(),
+                });
+            }
+            let items = data::read_items(&config.get_db_path())?;
+            let html = viewer::to_html(&items, images, config.get_part_page_site());
+            std::fs::write(&path, html)?;
+            println!(
+                "{}",
+                tr_n!(
+                    "Wrote {} part to {}",
+                    "Wrote {} parts to {}",
+                    items.len(),
+                    path.display()
+                )
+            );
+        }
+        Search { text } => {
+            let items = data::read_items(&config.get_db_path())?;
+            for line in search::list_matches(&items, &text) {
+                println!("{}", line);
+            }
+        }
+        QrCode { location } => {
+            let link = link::Link::Location(location);
+            println!("{}", link);
+            if let Some(art) = link.to_qr_art() {
+                println!("{}", art);
+            }
+        }
+        OrderImport { path, force } => {
+            let order = order::read(&path)?;
+            let db_path = config.get_db_path();
+            let checkpoint_path = order::get_checkpoint_path(&db_path);
+            let mut items = data::read_items(&db_path)?;
+            let old_items = items.to_vec();
+            let (adjustments, skipped) = if dry_run {
+                order::take_in(&order, &mut items)
+            } else {
+                let (adjustments, skipped, resumed_from) =
+                    order::take_in_resumable(&order, &mut items, &checkpoint_path, force)?;
+                if resumed_from != 0 {
+                    println!(
+                        "{}",
+                        tr_n!(
+                            "Resumed taking in the order after {} line.",
+                            "Resumed taking in the order after {} lines.",
+                            resumed_from
+                        )
+                    );
+                }
+                (adjustments, skipped)
+            };
+            for msg in skipped.iter() {
+                println!("{}", msg);
+            }
+            if dry_run {
+                println!(
+                    "{}",
+                    tr!(
+                        "Taking in order {} would make these changes:",
+                        order.get_number()
+                    )
+                );
+                print!("{}", data::describe_changes(&old_items, &items));
+                return Ok(());
+            }
+            data::write_items(&db_path, &items)?;
+            transaction::record(
+                &db_path,
+                &tr!("Import of order {}", order.get_number()),
+                config.get_user(),
+                &old_items,
+                &items,
+            )?;
+            let path = adjustment::get_path(&db_path);
+            adjustment::append(&path, &adjustments, config.get_user())?;
+            order::append(&order::get_path(&db_path), &order)?;
+            if let Some(purchase) = budget::Purchase::of_order(&order) {
+                budget::append(&budget::get_path(&db_path), &[purchase])?;
+            }
+            order::finish_import(&checkpoint_path)?;
+            println!(
+                "{}",
+                tr_n!(
+                    "{} lot was changed by taking in order {}.",
+                    "{} lots were changed by taking in order {}.",
+                    adjustments.len(),
+                    order.get_number()
+                )
+            );
+        }
+        BrickStoreExport { path, .. } if dry_run => {
+            println!("{}", tr!("Would export the lots to {}", path.display()));
+        }
+        BrickStoreExport { path, force } => {
+            if !force && io::get_modified(&path).is_some() {
+                return Err(error::Error::FileAlreadyExists {
+                    path: path.to_string_lossy().to_string(),
+                });
+            }
+            let items = data::read_items(&config.get_db_path())?;
+            let (xml, skipped) = brickstore::export(&items);
+            for msg in skipped.iter() {
+                println!("{}", msg);
+            }
+            std::fs::write(&path, xml)?;
+            println!("{}", tr!("Exported the lots to {}", path.display()));
+        }
+        BrickStoreImport { path, location } => {
+            let (bsx_lots, skipped) = brickstore::read(&path)?;
+            for msg in skipped.iter() {
+                println!("{}", msg);
+            }
+            let db_path = config.get_db_path();
+            let mut items = data::read_items(&db_path)?;
+            let old_items = items.to_vec();
+            let imported = brickstore::import(&bsx_lots, &mut items, &location);
+            if dry_run {
+                println!(
+                    "{}",
+                    tr!("Importing {} would make these changes:", path.display())
+                );
+                print!("{}", data::describe_changes(&old_items, &items));
+                return Ok(());
+            }
+            data::write_items(&db_path, &items)?;
+            transaction::record(
+                &db_path,
+                &tr!("Import of {}", path.display()),
+                config.get_user(),
+                &old_items,
+                &items,
+            )?;
+            println!(
+                "{}",
+                tr_n!(
+                    "Imported {} lot from {} into {}.",
+                    "Imported {} lots from {} into {}.",
+                    imported,
+                    path.display(),
+                    location
+                )
+            );
+        }
+        Diff { old, new, json } => {
+            let new = new.unwrap_or_else(|| config.get_db_path());
+            let (old, new) = (data::read_items(&old)?, data::read_items(&new)?);
+            if json {
+                let diffs = diff::diff(&old, &new);
+                println!("{}", envelope::to_json("diff", &diffs)?);
+            } else {
+                let changes = data::describe_changes(&old, &new);
+                if changes.is_empty() {
+                    println!("{}", tr!("There are no changes."));
+                }
+                print!("{}", changes);
+            }
+        }
+        SnapshotCreate { name } if dry_run => {
+            println!("{}", tr!("Would take snapshot {}", name));
+        }
+        SnapshotCreate { name } => {
+            let snapshot = snapshot::create(&config.get_db_path(), &name)?;
+            println!("{}", tr!("Took snapshot {}", snapshot));
+        }
+        SnapshotList => {
+            let snapshots = snapshot::list(&config.get_db_path())?;
+            if snapshots.is_empty() {
+                println!("{}", tr!("No snapshots have been taken yet."));
+            }
+            for snapshot in snapshots {
+                println!("{}", snapshot);
+            }
+        }
+        SnapshotRollback { name } if dry_run => {
+            let db_path = config.get_db_path();
+            let old = data::read_items(&db_path)?;
+            let new = snapshot::read(&db_path, &name)?;
+            println!(
+                "{}",
+                tr!(
+                    "Rolling back to snapshot {} would make these changes:",
+                    name
+                )
+            );
+            print!("{}", data::describe_changes(&old, &new));
+        }
+        SnapshotRollback { name } => {
+            snapshot::rollback(&config.get_db_path(), &name)?;
+            println!(
+                "{}",
+                tr!(
+                    "Rolled back to snapshot {}, the previous database was saved as snapshot {}",
+                    name,
+                    snapshot::BEFORE_ROLLBACK
+                )
+            );
+        }
+        History => {
+            let path = transaction::get_path(&config.get_db_path());
+            println!("{}", transaction::describe(&transaction::read(&path)?));
+        }
+        Undo if dry_run => {
+            let db_path = config.get_db_path();
+            let old = data::read_items(&db_path)?;
+            let mut new = old.to_vec();
+            match transaction::read(&transaction::get_path(&db_path))?.last() {
+                Some(last) => {
+                    transaction::undo(&mut new, last)?;
+                    println!(
+                        "{}",
+                        tr!("Undoing {} would make these changes:", last.get_name())
+                    );
+                    print!("{}", data::describe_changes(&old, &new));
+                }
+                None => println!("{}", tr!("There is nothing to undo.")),
+            }
+        }
+        Undo => {
+            let db_path = config.get_db_path();
+            let mut items = data::read_items(&db_path)?;
+            match transaction::read(&transaction::get_path(&db_path))?.last() {
+                Some(last) => {
+                    transaction::undo(&mut items, last)?;
+                    data::write_items(&db_path, &items)?;
+                    transaction::remove_last(&db_path)?;
+                    println!("{}", tr!("Undid {}", last));
+                }
+                None => println!("{}", tr!("There is nothing to undo.")),
+            }
+        }
+        BricksetSync { push } => {
+            let secrets = secret::read(&get_secrets_path())?;
+            let user_hash = secrets.get_user_hash(site::Site::Brickset).ok_or_else(|| {
+                error::Error::ApiError {
+                    msg: "not logged in to brickset, log in with `tuibricks api-key brickset`"
+                        .to_string(),
+                }
+            })?;
+            let mut client = api::Client::brickset(config.get_api_config(site::Site::Brickset))?;
+            let owned = brickset::get_owned_sets(&mut client, user_hash)?;
+            let new_sets = brickset::get_new_sets(&owned, config.get_sets());
+            let dated_sets = brickset::get_dated_sets(&owned, config.get_sets());
+            let unowned: Vec<String> = match push {
+                true => brickset::get_unowned(&owned, config.get_sets())
+                    .iter()
+                    .map(|set| set.get_number().to_string())
+                    .collect(),
+                false => Vec::new(),
+            };
+            if dry_run {
+                for set in new_sets.iter() {
+                    println!(
+                        "{}",
+                        tr!("Would add set {} {}", set.get_number(), set.get_name())
+                    );
+                }
+                for number in unowned.iter() {
+                    println!("{}", tr!("Would mark set {} as owned on Brickset", number));
+                }
+                return Ok(());
+            }
+
+            let mut config = config;
+            let added = new_sets.len();
+            for set in new_sets {
+                println!(
+                    "{}",
+                    tr!("Added set {} {}", set.get_number(), set.get_name())
+                );
+                config.add_set(set);
+            }
+            let dated = dated_sets.len();
+            for set in dated_sets {
+                config.add_set(set);
+            }
+            if added != 0 || dated != 0 {
+                io::write_contents_to_yaml(get_config_path(), &config)?;
+            }
+            if added != 0 {
+                println!(
+                    "{}",
+                    tr!("Their inventories can be added with `tuibricks set import`.")
+                );
+            }
+            if dated != 0 {
+                println!(
+                    "{}",
+                    tr_n!(
+                        "Filled in the year of release of {} set.",
+                        "Filled in the years of release of {} sets.",
+                        dated
+                    )
+                );
+            }
+            for number in unowned.iter() {
+                match brickset::find_set(&mut client, user_hash, number)? {
+                    Some(set) => {
+                        brickset::set_owned(&mut client, user_hash, set.set_id)?;
+                        println!("{}", tr!("Marked set {} as owned on Brickset", number));
+                    }
+                    None => println!("{}", tr!("Set {} was not found on Brickset", number)),
+                }
+            }
+            println!(
+                "{}",
+                tr_n!(
+                    "{} set owned on Brickset, {} added here.",
+                    "{} sets owned on Brickset, {} added here.",
+                    owned.len(),
+                    added
+                )
+            );
+        }
+        StorePush => {
+            let secrets = secret::read(&get_secrets_path())?;
+            let keys = secrets
+                .get_oauth_keys(site::Site::BrickLink)
+                .ok_or_else(|| error::Error::ApiError {
+                    msg: "there are no keys for the BrickLink store, set them up with `tuibricks api-key bricklink`"
+                        .to_string(),
+                })?;
+            let items = data::read_items(&config.get_db_path())?;
+            let (listings, skipped) = store::get_listings(&items);
+            for line in skipped {
+                println!("{}", line);
+            }
+            let client = store::Store::new(keys.clone());
+            let changes = store::get_changes(&listings, &client.get_lots()?);
+            if changes.is_empty() {
+                println!("{}", tr!("The store is up to date."));
+                return Ok(());
+            }
+            if dry_run {
+                println!("{}", tr!("Pushing would make these changes to the store:"));
+                for change in changes.iter() {
+                    println!("  {}", change);
+                }
+                return Ok(());
+            }
+            for change in changes.iter() {
+                client.apply(change)?;
+                println!("{}", change);
+            }
+            println!(
+                "{}",
+                tr_n!(
+                    "Made {} change to the store.",
+                    "Made {} changes to the store.",
+                    changes.len()
+                )
+            );
+        }
+        Instructions => {
+            let missing = set::get_missing_instructions(config.get_sets());
+            let api_config = config.get_api_config(site::Site::Brickset);
+            let mut pdfs = std::collections::BTreeMap::new();
+            // The PDFs are only looked up when there is a key for Brickset.
+            if api_config.has_key() && !missing.is_empty() {
+                let mut client = api::Client::brickset(api_config)?;
+                for set in missing.iter() {
+                    match brickset::get_instructions(&mut client, set.get_number()) {
+                        Ok(links) => {
+                            pdfs.insert(set.get_number().to_string(), links);
+                        }
+                        Err(e) => log::warn!(
+                            "could not look up the instructions of {}: {}",
+                            set.get_number(),
+                            e
+                        ),
+                    }
+                }
+            }
+            println!("{}", set::describe_missing_instructions(&missing, &pdfs));
+        }
+        SetImport { number, path } => {
+            let set = set::parse_inventory(&std::fs::read_to_string(&path)?, &number)?;
+            let summary = tr!(
+                "set {} with {} parts and {} spare parts",
+                set.get_number(),
+                set.get_parts().len(),
+                set.get_spare_parts().len()
+            );
+            if dry_run {
+                println!("{}", tr!("Would add {} to the config", summary));
+                return Ok(());
+            }
+            let mut config = config;
+            config.add_set(set);
+            io::write_contents_to_yaml(get_config_path(), &config)?;
+            println!("{}", tr!("Added {} to the config", summary));
+        }
+        Weight { filter } => {
+            let filter = filter::Filter::parse(&filter)?;
+            let items = data::read_items(&config.get_db_path())?;
+            let mut weight = shipping::Weight::default();
+            for item in items.iter().filter(|item| filter.matches(item)) {
+                for lot in item.get_locations() {
+                    weight.add(item.get_lot_weight(lot));
+                }
+            }
+            println!(
+                "{}",
+                shipping::describe(&weight, config.get_shipping_rates())
+            );
+        }
+        Lookup { id } => {
+            let mut client =
+                api::Client::rebrickable(config.get_api_config(site::Site::Rebrickable))?;
+            match client.get_part(id)? {
+                Some(part) => {
+                    println!("{} {}", part.part_num, part.name);
+                    if let Some(from) = part.year_from {
+                        let to = part.year_to.unwrap_or(from);
+                        println!("{}", tr!("Produced from {} to {}", from, to));
+                    }
+                }
+                None => println!("{}", tr!("Part {} was not found on Rebrickable.", id)),
+            }
+        }
+        ApiKey {
+            site: site::Site::BrickLink,
+        } => {
+            if let Some(page) = api::get_key_page(site::Site::BrickLink) {
+                println!("{}", tr!("The keys of a store can be made at {}", page));
+            }
+            let keys = oauth::OAuthKeys {
+                consumer_key: secret::ask(tr!("Consumer key:"))?.trim().to_string(),
+                consumer_secret: secret::ask(tr!("Consumer secret:"))?.trim().to_string(),
+                token: secret::ask(tr!("Token value:"))?.trim().to_string(),
+                token_secret: secret::ask(tr!("Token secret:"))?.trim().to_string(),
+            };
+            store::Store::new(keys.clone()).check_keys()?;
+            println!("{}", tr!("The keys were accepted."));
+
+            let path = get_secrets_path();
+            if dry_run {
+                println!("{}", tr!("Would save the keys to {}", path.display()));
+                return Ok(());
+            }
+            let mut secrets = secret::read(&path)?;
+            secrets.set_oauth_keys(site::Site::BrickLink, keys);
+            secret::write(&path, &secrets)?;
+            println!("{}", tr!("Saved the keys to {}", path.display()));
+        }
+        ApiKey { site } => {
+            if let Some(page) = api::get_key_page(site) {
+                println!("{}", tr!("An API key can be made at {}", page));
+            }
+            let key = secret::ask(tr!("API key:"))?.trim().to_string();
+            let mut api_config = config.get_api_config(site);
+            api_config.set_key(Some(key.clone()));
+            let mut user_hash = None;
+            if site == site::Site::Brickset {
+                let mut client = api::Client::brickset(api_config)?;
+                brickset::check_key(&mut client)?;
+                println!("{}", tr!("The key was accepted."));
+                let username = secret::ask_visible(tr!("Brickset username:"))?;
+                let password = secret::ask(tr!("Password:"))?;
+                user_hash = Some(brickset::login(&mut client, &username, &password)?);
+                println!("{}", tr!("Logged in as {}.", username));
+            } else {
+                api::Client::rebrickable(api_config)?.check_key()?;
+                println!("{}", tr!("The key was accepted."));
+            }
+
+            let path = get_secrets_path();
+            if dry_run {
+                println!("{}", tr!("Would save the key to {}", path.display()));
+                return Ok(());
+            }
+            let mut secrets = secret::read(&path)?;
+            secrets.set_api_key(site, key);
+            if let Some(user_hash) = user_hash {
+                secrets.set_user_hash(site, user_hash);
+            }
+            secret::write(&path, &secrets)?;
+            println!("{}", tr!("Saved the key to {}", path.display()));
+
+            let mut config = config;
+            if config.remove_api_key(site) {
+                io::write_contents_to_yaml(get_config_path(), &config)?;
+                println!("{}", tr!("Removed the key written in the config."));
+            }
+        }
+        Enrich => {
+            let db_path = config.get_db_path();
+            let mut items = data::read_items(&db_path)?;
+            let needing: Vec<usize> = (0..items.len())
+                .filter(|i| enrich::needs_metadata(&items[*i]))
+                .collect();
+            let mut client =
+                api::Client::rebrickable(config.get_api_config(site::Site::Rebrickable))?;
+            let mut summary = enrich::Summary::default();
+            for (done, i) in needing.iter().enumerate() {
+                let item = &mut items[*i];
+                println!(
+                    "{}",
+                    tr!(
+                        "Looking up {} ({} of {})",
+                        item.get_id(),
+                        done + 1,
+                        needing.len()
+                    )
+                );
+                let metadata = match enrich::lookup(&mut client, item.get_id()) {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        // Keep what was filled in before the site stopped answering.
+                        if !dry_run && summary.looked_up != 0 {
+                            data::write_items(&db_path, &items)?;
+                        }
+                        print!("{}", summary);
+                        return Err(e);
+                    }
+                };
+                match metadata {
+                    Some(metadata) => summary.fill(item, metadata),
+                    None => summary.not_found(item.get_id()),
+                }
+                if !dry_run && (done + 1) % enrich::SAVE_EVERY == 0 {
+                    data::write_items(&db_path, &items)?;
+                }
+            }
+            if !dry_run && !needing.is_empty() {
+                data::write_items(&db_path, &items)?;
+            }
+            print!("{}", summary);
+        }
+        Serve => {
+            let remote = config.get_remote_config();
+            println!("{}", tr!("Serving the database on {}", remote.get_listen()));
+            remote::serve(&config.get_db_path(), remote)?;
+        }
+        Sync => {
+            let outcome = remote::sync(&config.get_db_path(), config.get_remote_config(), dry_run)?;
+            if dry_run {
+                println!("{}", tr!("Syncing would make these changes:"));
+            }
+            print!("{}", outcome);
+        }
+        Summary { path, .. } if dry_run => {
+            println!("{}", tr!("Would write the summary to {}", path.display()));
+        }
+        Summary {
+            path,
+            month,
+            send,
+            force,
+        } => {
+            if !force && io::get_modified(&path).is_some() {
+                return Err(error::Error::FileAlreadyExists {
+                    path: path.to_string_lossy().to_string(),
+                });
+            }
+            let db_path = config.get_db_path();
+            let summary = summary::Summary::new(
+                month.unwrap_or_else(date::Date::today),
+                &data::read_items(&db_path)?,
+                &adjustment::read(&adjustment::get_path(&db_path))?,
+                &sale::read(&sale::get_path(&db_path))?,
+                &budget::read(&budget::get_path(&db_path))?,
+            );
+            let is_html = path
+                .extension()
+                .is_some_and(|extension| extension == "html" || extension == "htm");
+            let contents = if is_html {
+                summary.to_html()
+            } else {
+                summary.to_text()
+            };
+            std::fs::write(&path, &contents)?;
+            println!("{}", tr!("Wrote the summary to {}", path.display()));
+            if send {
+                let command =
+                    config
+                        .get_summary_command()
+                        .ok_or_else(|| error::Error::SummaryNotSent {
+                            msg: "no summary_command is set in the config".to_string(),
+                        })?;
+                summary::send(command, &contents)?;
+                println!("{}", tr!("Sent the summary."));
+            }
+        }
+        Generate { lots, path, .. } if dry_run => {
+            println!(
+                "{}",
+                tr_n!(
+                    "Would generate a database with {} lot at {}",
+                    "Would generate a database with {} lots at {}",
+                    lots,
+                    path.display()
+                )
+            );
+        }
+        Generate { lots, path, force } => {
+            if !force && io::get_modified(&path).is_some() {
+                return Err(error::Error::FileAlreadyExists {
+                    path: path.to_string_lossy().to_string(),
+                });
+            }
+            data::write_items(&path, &generate::generate(lots, 1))?;
+            println!(
+                "{}",
+                tr_n!(
+                    "Generated a database with {} lot at {}",
+                    "Generated a database with {} lots at {}",
+                    lots,
+                    path.display()
+                )
+            );
+        }
+    }
+    Ok(())
 }
 
 pub fn run() -> error::Result<()> {
-    env_logger::init();
     let mut w = std::io::stdout();
 
-    #[cfg(not(debug_assertions))]
-    let db_path = get_user_db_path()?;
+    let args = args::Args::parse(std::env::args().skip(1))?;
+    let mut config = get_config()?;
+    usage::init(config.is_logging_usage().then(get_usage_path))?;
+    if let Some(user) = args.user {
+        config.set_user(user);
+    }
+    if let Some(db) = args.db {
+        config.override_db_path(db);
+    }
+    config.set_secrets(&secret::read(&get_secrets_path())?);
+    for site in config.get_sites_with_plain_keys() {
+        log::warn!(
+            "the API key for {:?} is written in the config, move it with `tuibricks api-key`",
+            site
+        );
+    }
+    locale::init(config.get_locale(), config.get_number_format());
+    currency::init(config.get_currency());
+    color::init(config.get_color_aliases());
+    plugin::init(config.get_plugins().to_vec());
+    hook::init(config.get_hooks().to_vec());
+    field::init(config.get_custom_fields().to_vec());
+    rule::validate(config.get_rules())?;
+    timestamp::init(config.get_time().clone());
+    theme::init(config.get_theme());
+    key::init(config.get_key_bindings(), config.get_chord_timeout())?;
 
-    #[cfg(debug_assertions)]
-    let db_path = std::path::PathBuf::new().join("test_db.yml");
+    crypt::unlock(&config.get_db_path(), config.is_encrypted())?;
 
-    let mut state = state::State::new(db_path)?;
+    let dry_run = args.dry_run || config.is_dry_run();
+    let read_only = args.read_only || config.is_read_only();
+    if let Some(cmd) = args.cmd {
+        return run_cli_cmd(cmd, config, dry_run, read_only);
+    }
+
+    let start = Start {
+        open: args.open,
+        mode: args.mode,
+    };
+    let options = state::Options {
+        read_only,
+        dry_run,
+        watch: args.watch || config.is_watching(),
+    };
+    if args.api {
+        let read_only = options.read_only || options.dry_run;
+        rest::spawn(
+            config.get_db_path(),
+            config.get_rest_config().clone(),
+            read_only,
+        );
+    }
+
+    // Screen readers are given plain lines, without taking over the terminal.
+    if args.accessible || config.is_accessible() {
+        let mut w = accessible::Linear::new(w);
+        let input = accessible::Plain::new(std::io::stdin().lock(), std::io::stdout());
+        return run_session(&mut w, Box::new(input), config, options, start);
+    }
+
+    // Output that is piped or redirected is given plain lines as well, where the prompts go to
+    // the terminal the answers are typed in.
+    if !w.is_terminal() {
+        let mut w = accessible::Linear::new(w);
+        let input: Box<dyn ui::InputSource<_>> = if std::io::stdin().is_terminal() {
+            Box::new(accessible::Plain::new(
+                std::io::stdin().lock(),
+                std::io::stderr(),
+            ))
+        } else {
+            Box::new(ui::Lines::new(std::io::stdin().lock()))
+        };
+        return run_session(&mut w, input, config, options, start);
+    }
+
+    // Answers can be piped in, e.g. to script a sequence of edits.
+    let input: Box<dyn ui::InputSource<_>> = if std::io::stdin().is_terminal() {
+        Box::new(ui::Terminal)
+    } else {
+        Box::new(ui::Lines::new(std::io::stdin().lock()))
+    };
 
     term_lib::init(&mut w)?;
+    let res = run_session(&mut w, input, config, options, start);
+    term_lib::quit(&mut w)?;
+    res
+}
+
+/// Where the interactive program starts, given on the command line.
+struct Start {
+    open: Option<link::Link>,
+    mode: Option<mode::StartMode>,
+}
+
+/// Runs the interactive program until it is quit.
+fn run_session<W: std::io::Write>(
+    w: &mut W,
+    input: Box<dyn ui::InputSource<W>>,
+    config: config::Config,
+    options: state::Options,
+    start: Start,
+) -> error::Result<()> {
+    let mut state = state::State::new(w, config, get_config_path(), options, input)?;
+    if let Some(mode) = start.mode {
+        match state.start_in(w, mode) {
+            Err(error::Error::TermError(term_lib::Error::Quit)) => return Ok(()),
+            res => res?,
+        }
+    }
+    if let Some(link) = start.open {
+        state.open(w, &link)?;
+    }
 
     loop {
-        match state.wait_for_cmd(&mut w) {
+        match state.wait_for_cmd(w) {
             Ok(()) => {}
-            Err(error::Error::TermError(term_lib::Error::Quit)) => {
-                term_lib::quit(&mut w)?;
-                return Ok(());
-            }
-            Err(e) => {
-                term_lib::quit(&mut w)?;
-                return Err(e);
-            }
+            Err(error::Error::TermError(term_lib::Error::Quit)) => return Ok(()),
+            Err(e) => return Err(e),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Item;
+    use crate::types::PartId;
+
+    #[test]
+    fn test_read_only_cli() {
+        let db_path = std::path::PathBuf::from("test_read_only_cli.yml");
+        let old_items = vec![Item::new(PartId::new(3001).unwrap(), "Brick".to_string())];
+        let items = vec![Item::new(
+            PartId::new(3001).unwrap(),
+            "Brick 2 x 4".to_string(),
+        )];
+        data::write_items(&db_path, &data::RawDatabase::new(items.clone())).unwrap();
+        transaction::record(&db_path, "Rename", None, &old_items, &items).unwrap();
+        let contents = std::fs::read(&db_path).unwrap();
+
+        let mut config = Config::default();
+        config.set_db_path(&db_path);
+        let res = run_cli_cmd(CliCmd::Undo, config.clone(), false, true);
+        let unchanged = std::fs::read(&db_path).unwrap() == contents;
+        let num_transactions = transaction::read(&transaction::get_path(&db_path))
+            .unwrap()
+            .len();
+        let _ = std::fs::remove_file(&db_path);
+        let _ = std::fs::remove_file(transaction::get_path(&db_path));
+        assert!(matches!(res, Err(error::Error::ReadOnly { cmd }) if cmd == "undo"));
+        assert!(unchanged);
+        assert_eq!(num_transactions, 1);
+        assert!(run_cli_cmd(CliCmd::History, config, false, true).is_ok());
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+
+use crate::types::PartId;
+
+const SCHEME: &str = "tuibricks://";
+
+/// A place in the program that can be opened directly, written as a URI like
+/// `tuibricks://location/A3`, `tuibricks://item/3001` or `tuibricks://filter/color=red`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Link {
+    Location(String),
+    Item(PartId),
+    /// The items matching a filter, see `Filter`.
+    Filter(String),
+}
+
+fn encode(s: &str) -> String {
+    let mut encoded = String::new();
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+pub fn decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let mut rest = s.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+impl Link {
+    pub fn is_link(s: &str) -> bool {
+        s.starts_with(SCHEME)
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let (kind, value) = s.strip_prefix(SCHEME)?.split_once('/')?;
+        let value = decode(value.trim_end_matches('/'))?;
+        if value.is_empty() {
+            return None;
+        }
+        match kind {
+            "location" => Some(Link::Location(value)),
+            "item" => value.parse().ok().and_then(PartId::new).map(Link::Item),
+            "filter" => Some(Link::Filter(value)),
+            _ => None,
+        }
+    }
+
+    /// The link as a QR code drawn with Unicode blocks, or None if it is too long to encode.
+    pub fn to_qr_art(&self) -> Option<String> {
+        let code = QrCode::new(self.to_string().as_bytes()).ok()?;
+        Some(code.render::<Dense1x2>().build())
+    }
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Link::Location(location) => write!(f, "{}location/{}", SCHEME, encode(location)),
+            Link::Item(id) => write!(f, "{}item/{}", SCHEME, id),
+            Link::Filter(filter) => write!(f, "{}filter/{}", SCHEME, encode(filter)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_link() {
+        let link = Link::Location("A3".to_string());
+        assert_eq!(link.to_string(), "tuibricks://location/A3");
+        assert_eq!(Link::parse("tuibricks://location/A3/"), Some(link));
+
+        let link = Link::Location("Drawer 2/ü".to_string());
+        assert_eq!(link.to_string(), "tuibricks://location/Drawer%202%2F%C3%BC");
+        assert_eq!(Link::parse(&link.to_string()), Some(link));
+
+        let link = Link::Filter("color=red".to_string());
+        assert_eq!(link.to_string(), "tuibricks://filter/color%3Dred");
+        assert_eq!(Link::parse("tuibricks://filter/color=red"), Some(link));
+        assert_eq!(
+            Link::parse("tuibricks://item/3001"),
+            Some(Link::Item(PartId::new(3001).unwrap()))
+        );
+
+        assert_eq!(Link::parse("tuibricks://location/"), None);
+        assert_eq!(Link::parse("tuibricks://item/brick"), None);
+        assert_eq!(Link::parse("tuibricks://part/3001"), None);
+        assert_eq!(Link::parse("tuibricks://location/%2"), None);
+    }
+}
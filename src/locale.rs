@@ -0,0 +1,1211 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+static NUMBER_FORMAT: OnceLock<NumberFormat> = OnceLock::new();
+
+/// Translates a message into the current locale. The english message is used as the key into the
+/// catalog, and any `{}` in the message is replaced by the given arguments in order.
+macro_rules! tr {
+    ( $msg:literal ) => {
+        crate::locale::translate($msg)
+    };
+    ( $msg:literal, $( $arg:expr ),+ $(,)? ) => {
+        crate::locale::fill(crate::locale::translate($msg), &[$( &$arg as &dyn std::fmt::Display ),+])
+    };
+}
+
+/// Like `tr!` but selects between the singular and plural message based on `n`, which is also
+/// used as the first argument of the message.
+macro_rules! tr_n {
+    ( $singular:literal, $plural:literal, $n:expr $(, $arg:expr )* $(,)? ) => {{
+        let n = $n;
+        crate::locale::fill(
+            crate::locale::translate_n($singular, $plural, n as u64),
+            &[&n as &dyn std::fmt::Display $(, &$arg as &dyn std::fmt::Display )*],
+        )
+    }};
+}
+
+pub(crate) use {tr, tr_n};
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    fn is_plural(&self, n: u64) -> bool {
+        use Locale::*;
+        match self {
+            En | De => n != 1,
+        }
+    }
+
+    fn thousands_separator(&self) -> char {
+        use Locale::*;
+        match self {
+            En => ',',
+            De => '.',
+        }
+    }
+
+    fn decimal_separator(&self) -> char {
+        use Locale::*;
+        match self {
+            En => '.',
+            De => ',',
+        }
+    }
+
+    fn lookup(&self, msg: &'static str) -> &'static str {
+        use Locale::*;
+        match self {
+            En => msg,
+            De => de(msg).unwrap_or(msg),
+        }
+    }
+}
+
+/// Overrides of the separators used when formatting numbers. Unset separators default to the
+/// convention of the locale.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct NumberFormat {
+    #[serde(default)]
+    thousands_separator: Option<char>,
+    #[serde(default)]
+    decimal_separator: Option<char>,
+}
+
+/// Sets the locale and number format used for the rest of the session. Can only be set once.
+pub fn init(locale: Locale, number_format: NumberFormat) {
+    let _ = LOCALE.set(locale);
+    let _ = NUMBER_FORMAT.set(number_format);
+}
+
+pub fn get_locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+pub fn translate(msg: &'static str) -> &'static str {
+    get_locale().lookup(msg)
+}
+
+pub fn translate_n(singular: &'static str, plural: &'static str, n: u64) -> &'static str {
+    let locale = get_locale();
+    if locale.is_plural(n) {
+        locale.lookup(plural)
+    } else {
+        locale.lookup(singular)
+    }
+}
+
+fn get_separators() -> (char, char) {
+    let locale = get_locale();
+    let number_format = NUMBER_FORMAT.get().copied().unwrap_or_default();
+    (
+        number_format
+            .thousands_separator
+            .unwrap_or(locale.thousands_separator()),
+        number_format
+            .decimal_separator
+            .unwrap_or(locale.decimal_separator()),
+    )
+}
+
+/// Formats a count with thousands separators, e.g. 123456 becomes "123,456".
+pub fn fmt_count(n: usize) -> String {
+    let (thousands, _) = get_separators();
+    group_digits(&n.to_string(), thousands)
+}
+
+/// Formats a duration in hours and minutes, e.g. 3660 seconds become "1h 01m".
+pub fn fmt_duration(time: std::time::Duration) -> String {
+    let minutes = time.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// Formats a decimal number with the given number of decimals and thousands separators, e.g.
+/// 1234.5 becomes "1,234.50".
+pub fn fmt_decimal(x: f64, decimals: usize) -> String {
+    let (thousands, decimal) = get_separators();
+    format_decimal_with(x, decimals, thousands, decimal)
+}
+
+fn format_decimal_with(x: f64, decimals: usize, thousands: char, decimal: char) -> String {
+    let formatted = format!("{:.*}", decimals, x.abs());
+    let (int_part, frac_part) = match formatted.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut res = String::new();
+    if x.is_sign_negative() && formatted.chars().any(|c| c != '0' && c != '.') {
+        res.push('-');
+    }
+    res.push_str(&group_digits(int_part, thousands));
+    if let Some(frac_part) = frac_part {
+        res.push(decimal);
+        res.push_str(frac_part);
+    }
+    res
+}
+
+fn group_digits(digits: &str, separator: char) -> String {
+    let mut res = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i != 0 && (digits.len() - i).is_multiple_of(3) {
+            res.push(separator);
+        }
+        res.push(c);
+    }
+    res
+}
+
+/// Replaces each `{}` in the template with the next argument.
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut res = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        res.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            res.push_str(&arg.to_string());
+        }
+        res.push_str(part);
+    }
+    res
+}
+
+fn de(msg: &str) -> Option<&'static str> {
+    let translated = match msg {
+        // cmd
+        "(a)dd a new item to the database" => "neues Teil hinzufügen (a)",
+        "(d)elete the current item" => "aktuelles Teil löschen (d)",
+        "add a new (g)roup to the database" => "neue Gruppe hinzufügen (g)",
+        "open the part page in the (b)rowser" => "Teileseite im Browser öffnen (b)",
+        "cop(y) the part ID to the clipboard" => "Teilenummer in die Zwischenablage kopieren (y)",
+        "cop(y) a filtered list of parts to the clipboard as CSV" => {
+            "gefilterte Teileliste als CSV in die Zwischenablage kopieren (y)"
+        }
+        "(q)uit the program" => "Programm beenden (q)",
+        "(e)dit the current item" => "aktuelles Teil bearbeiten (e)",
+        "(s)ave the current changes and quit editing" => {
+            "Änderungen speichern und Bearbeitung beenden (s)"
+        }
+        "(q)uit editing this part without saving changes currently made" => {
+            "Bearbeitung ohne Speichern beenden (q)"
+        }
+        "edit the (n)ame of this part" => "Namen dieses Teils bearbeiten (n)",
+        "edit the (i)d of this item if a wrong id was written" => "ID dieses Teils korrigieren (i)",
+        "edit the (p)rice of this item" => "Preis dieses Teils bearbeiten (p)",
+        "(m)ove a location for this item" => "Ort dieses Teils verschieben (m)",
+        "edit the weight in (g)rams of one piece" => "Gewicht eines Teils in Gramm bearbeiten (g)",
+        "enter the (w)eight of the pieces in a color group" => {
+            "Gewicht der Teile einer Farbgruppe eingeben (w)"
+        }
+        "edit the (c)ount of pieces in a color group" => {
+            "Anzahl der Teile einer Farbgruppe bearbeiten (c)"
+        }
+        "add a new (c)olor group and its location for this item" => {
+            "neue Farbgruppe mit Ort hinzufügen (c)"
+        }
+        "add a new alternative (i)d for this item" => "neue alternative ID hinzufügen (i)",
+        "remove a (c)olor group and its location for this item" => {
+            "Farbgruppe mit Ort entfernen (c)"
+        }
+        "remove an alternative (i)d for this item" => "alternative ID entfernen (i)",
+        "search by part (i)d" => "nach Teile-ID suchen (i)",
+        "search by (n)ame" => "nach Namen suchen (n)",
+        "search by (l)ocation" => "nach Ort suchen (l)",
+        "search by the part ID in the clipboard (p)" => {
+            "nach der Teilenummer in der Zwischenablage suchen (p)"
+        }
+        "(v)iew statistics" => "Statistik anzeigen (v)",
+        "(t)oggle whether lots listed for sale are counted" => {
+            "umschalten, ob zum Verkauf angebotene Posten mitgezählt werden (t)"
+        }
+        "toggle whether pieces still (w)anted for the goals are counted" => {
+            "umschalten, ob für die Ziele noch gewünschte Teile mitgezählt werden (w)"
+        }
+        "(q)uit viewing statistics" => "Statistik schließen (q)",
+        "(s)earch for an item" => "nach einem Teil suchen (s)",
+        "(a)dd something to an item" => "etwas zu einem Teil hinzufügen (a)",
+        "(r)emove something from an item" => "etwas von einem Teil entfernen (r)",
+        "view completion of (o)wned sets" => "Vollständigkeit eigener Sets anzeigen (o)",
+        "(t)oggle whether spare parts are needed for completion" => {
+            "umschalten, ob Ersatzteile für die Vollständigkeit nötig sind (t)"
+        }
+        "(s)ort the sets by completion or by set number" => {
+            "Sets nach Vollständigkeit oder Setnummer sortieren (s)"
+        }
+        "(q)uit viewing sets" => "Sets schließen (q)",
+        "compute the (p)art-out value of a set" => "Zerlegungswert eines Sets berechnen (p)",
+        "check whether a set is (r)eady to be sold" => {
+            "prüfen, ob ein Set bereit zum Verkauf ist (r)"
+        }
+        "(c)onsolidate duplicate lots" => "doppelte Lose zusammenführen (c)",
+        "chec(k) the database for problems" => "Datenbank auf Probleme prüfen (k)",
+        "view the (h)istory of quantity changes" => "Verlauf der Anzahländerungen anzeigen (h)",
+        "view the monthly (r)evenue from sales" => "monatliche Verkaufserlöse anzeigen (r)",
+        "(l)ist a color group for sale" => "Farbgruppe zum Verkauf anbieten (l)",
+        "rec(o)rd a sale of pieces in a color group" => {
+            "Verkauf von Teilen einer Farbgruppe erfassen (o)"
+        }
+        "What do you want to search by?" => "Wonach möchtest du suchen?",
+        "What would you like to add to this item?" => "Was möchtest du zu diesem Teil hinzufügen?",
+        "What would you like to remove from this item?" => {
+            "Was möchtest du von diesem Teil entfernen?"
+        }
+        "start recording a macro (Q)" => "Makroaufnahme starten (Q)",
+        "stop recording the macro (Q)" => "Makroaufnahme beenden (Q)",
+        "play a macro (@)" => "Makro abspielen (@)",
+
+        // data
+        "All" => "Alle",
+        "Basic" => "Grundfarben",
+        "Earth" => "Erdtöne",
+        "Grey" => "Grau",
+        "Road" => "Straße",
+        "Nice" => "Schön",
+        "Build" => "Bauen",
+        "Translucent" => "Transparent",
+        "Colorful" => "Bunt",
+        "Misc" => "Sonstiges",
+        "None" => "Keine",
+        "There is currently no location for this item" => "Dieses Teil hat noch keinen Ort",
+        "Location of each color group:" => "Ort jeder Farbgruppe:",
+        "Group ID: {}\nContained part IDs: {}\n\nName: {}\n\n{}" => {
+            "Gruppen-ID: {}\nEnthaltene Teile-IDs: {}\n\nName: {}\n\n{}"
+        }
+        "Part ID: {}\nAlternative IDs: {}\n\nName: {}\n\n{}" => {
+            "Teile-ID: {}\nAlternative IDs: {}\n\nName: {}\n\n{}"
+        }
+        "Part ID: {} -> {}" => "Teile-ID: {} -> {}",
+        "Price: {} -> {}" => "Preis: {} -> {}",
+        "Price: {}" => "Preis: {}",
+        "Total value: {}" => "Gesamtwert: {}",
+        "({} price has no exchange rate to the home currency and is not included)" => {
+            "({} Preis hat keinen Wechselkurs zur Heimatwährung und ist nicht enthalten)"
+        }
+        "({} prices have no exchange rate to the home currency and are not included)" => {
+            "({} Preise haben keinen Wechselkurs zur Heimatwährung und sind nicht enthalten)"
+        }
+        "Name: {} -> {}" => "Name: {} -> {}",
+        "Removed alt ID: {}" => "Alternative ID entfernt: {}",
+        "Added alt ID: {}" => "Alternative ID hinzugefügt: {}",
+        "Removed location: ({}, {})" => "Ort entfernt: ({}, {})",
+        "Added location: ({}, {})" => "Ort hinzugefügt: ({}, {})",
+        "Quantity of ({}, {}): {} -> {}" => "Anzahl von ({}, {}): {} -> {}",
+        "Weight of ({}, {}): {} -> {}" => "Gewicht von ({}, {}): {} -> {}",
+        "Weight per piece: {} -> {}" => "Gewicht pro Teil: {} -> {}",
+        "Weight per piece: {} g" => "Gewicht pro Teil: {} g",
+        "(about {} piece by weight)" => "(etwa {} Stück nach Gewicht)",
+        "(about {} pieces by weight)" => "(etwa {} Stück nach Gewicht)",
+        "Unknown" => "Unbekannt",
+        "{} piece" => "{} Stück",
+        "{} pieces" => "{} Stück",
+        "Counted pieces: {}" => "Gezählte Teile: {}",
+        "Estimated pieces by weight: {}" => "Nach Gewicht geschätzte Teile: {}",
+        "Lots listed for sale are counted." => "Zum Verkauf angebotene Posten werden mitgezählt.",
+        "Lots listed for sale are not counted." => "Zum Verkauf angebotene Posten werden nicht mitgezählt.",
+        "Pieces still wanted for the goals: {}, worth {}" => {
+            "Für die Ziele noch gewünschte Teile: {}, im Wert von {}"
+        }
+        "Pieces still wanted for the goals are not counted." => {
+            "Für die Ziele noch gewünschte Teile werden nicht mitgezählt."
+        }
+        "Parts: {}\nSorted categories: {}\nColorgroups: {}\nLocations: {}" => {
+            "Teile: {}\nSortierte Kategorien: {}\nFarbgruppen: {}\nOrte: {}"
+        }
+        "Name: {}\n   Id: {}\n   Color Groups:" => "Name: {}\n   ID: {}\n   Farbgruppen:",
+
+        // mode
+        "Welcome to TUI bricks" => "Willkommen bei TUI bricks",
+        "Viewing item group with group ID {}" => "Gruppe mit Gruppen-ID {}",
+        "Viewing item with part ID {}" => "Teil mit Teile-ID {}",
+        "Now editing item group with group ID {}" => "Bearbeite Gruppe mit Gruppen-ID {}",
+        "Now editing item with part ID {}" => "Bearbeite Teil mit Teile-ID {}",
+        "Default" => "Start",
+        "Display Item" => "Teil anzeigen",
+        "Edit Item" => "Teil bearbeiten",
+        "View Statistics" => "Statistik",
+
+        // state
+        "Loading database..." => "Datenbank wird geladen...",
+        "List of possible commands:" => "Mögliche Befehle:",
+        "READ-ONLY: changes to the database are disabled" => {
+            "NUR LESEN: Änderungen an der Datenbank sind deaktiviert"
+        }
+        "Adding a new item to the database" => "Neues Teil wird hinzugefügt",
+        "Enter the part ID of the new item" => "Teile-ID des neuen Teils eingeben",
+        "Item with part ID {} already exists in database under item with part ID {}" => {
+            "Teil mit Teile-ID {} existiert bereits unter dem Teil mit Teile-ID {}"
+        }
+        "Enter name of new part: {}" => "Namen des neuen Teils eingeben: {}",
+        "Adding a new group to the database" => "Neue Gruppe wird hinzugefügt",
+        "Enter name of new group:" => "Namen der neuen Gruppe eingeben:",
+        "Enter the part ID to search for." => "Gesuchte Teile-ID eingeben.",
+        "Part {} not found in database" => "Teil {} nicht gefunden",
+        "Location {} not found in database" => "Ort {} nicht gefunden",
+        "No parts match {}" => "Keine Teile passen zu {}",
+        "{} part matches {}:" => "{} Teil passt zu {}:",
+        "{} parts match {}:" => "{} Teile passen zu {}:",
+        "{} item located at location:\n{}" => "{} Teil an diesem Ort:\n{}",
+        "{} items located at location:\n{}" => "{} Teile an diesem Ort:\n{}",
+        "Are you sure you want to quit editing and cancel these changes?\n\n{}" => {
+            "Willst du die Bearbeitung wirklich beenden und diese Änderungen verwerfen?\n\n{}"
+        }
+        "Editing name of part: {}" => "Namen bearbeiten von Teil: {}",
+        "Enter new name:" => "Neuen Namen eingeben:",
+        "The item with part ID {} already has the name {}" => {
+            "Das Teil mit Teile-ID {} hat bereits den Namen {}"
+        }
+        "Item name succesfully updated." => "Name erfolgreich geändert.",
+        "Editing ID of part: {}" => "ID bearbeiten von Teil: {}",
+        "Enter new ID:" => "Neue ID eingeben:",
+        "Part ID succesfully updated." => "Teile-ID erfolgreich geändert.",
+        "Editing price of part: {}" => "Preis bearbeiten von Teil: {}",
+        "Enter new price, e.g. \"0.25 EUR\". Leave empty to remove the price:" => {
+            "Neuen Preis eingeben, z. B. \"0,25 EUR\". Leer lassen, um den Preis zu entfernen:"
+        }
+        "{} is not a valid price" => "{} ist kein gültiger Preis",
+        "Enter the weight of one piece in grams. Leave empty if unknown:" => {
+            "Gewicht eines Teils in Gramm eingeben. Leer lassen, wenn unbekannt:"
+        }
+        "Select color group to weigh:" => "Farbgruppe zum Wiegen auswählen:",
+        "Enter the weight of the pieces in grams. Leave empty if they are not weighed:" => {
+            "Gewicht der Teile in Gramm eingeben. Leer lassen, wenn sie nicht gewogen werden:"
+        }
+        "Enter the weight of one piece to estimate the number of pieces." => {
+            "Gib das Gewicht eines Teils ein, um die Anzahl der Teile zu schätzen."
+        }
+        "{} is not a valid weight" => "{} ist kein gültiges Gewicht",
+        "Price succesfully updated." => "Preis erfolgreich geändert.",
+        "Select color group to count:" => "Farbgruppe zum Zählen auswählen:",
+        "Editing count of color group {} of part: {}" => {
+            "Anzahl der Farbgruppe {} von Teil {} wird bearbeitet"
+        }
+        "Enter number of pieces. Leave empty if unknown:" => {
+            "Anzahl der Teile eingeben. Leer lassen, falls unbekannt:"
+        }
+        "{} is not a valid number of pieces" => "{} ist keine gültige Anzahl",
+        "Count succesfully updated." => "Anzahl erfolgreich geändert.",
+        "{} is not a valid part ID" => "{} ist keine gültige Teile-ID",
+        "Select color group to move:" => "Zu verschiebende Farbgruppe wählen:",
+        "Moving color group {} from item with ID: {}" => {
+            "Farbgruppe {} von Teil mit ID {} wird verschoben"
+        }
+        "Enter new location of group {}:" => "Neuen Ort für Gruppe {} eingeben:",
+        "Adding a new color group to item with ID: {}" => {
+            "Neue Farbgruppe für Teil mit ID {} wird hinzugefügt"
+        }
+        "Select a color group for which to add a location" => {
+            "Farbgruppe wählen, für die ein Ort hinzugefügt werden soll"
+        }
+        "Create new color group" => "Neue Farbgruppe anlegen",
+        "Select other color group or create a new one" => {
+            "Andere Farbgruppe wählen oder neue anlegen"
+        }
+        "Enter the name of the new color group:" => "Namen der neuen Farbgruppe eingeben:",
+        "Enter location of group {}:" => "Ort für Gruppe {} eingeben:",
+        "Removing a color group from item with ID: {}" => {
+            "Farbgruppe von Teil mit ID {} wird entfernt"
+        }
+        "Select color group to remove:" => "Zu entfernende Farbgruppe wählen:",
+        "Enter the new alternative part ID to add to this item" => {
+            "Neue alternative Teile-ID für dieses Teil eingeben"
+        }
+        "Which alternative ID do you want to remove?" => {
+            "Welche alternative ID möchtest du entfernen?"
+        }
+        "Are you absolutely sure that you want to delete the item with ID: {}?\n" => {
+            "Willst du das Teil mit ID {} wirklich löschen?\n"
+        }
+        "Item with ID: {} was deleted." => "Teil mit ID {} wurde gelöscht.",
+
+        // cli
+        "Exported bundle to {}" => "Paket exportiert nach {}",
+        "Imported bundle from {}" => "Paket importiert aus {}",
+        "Generated a database with {} lot at {}" => "Datenbank mit {} Posten erstellt in {}",
+        "Generated a database with {} lots at {}" => "Datenbank mit {} Posten erstellt in {}",
+
+        "There are no changes." => "Es gibt keine Änderungen.",
+        "Took snapshot {}" => "Schnappschuss erstellt: {}",
+        "Would take snapshot {}" => "Schnappschuss {} würde erstellt",
+        "No snapshots have been taken yet." => "Es wurden noch keine Schnappschüsse erstellt.",
+        "{}: taken {}, {} parts in {} lots" => "{}: erstellt {}, {} Teile in {} Posten",
+        "Rolling back to snapshot {} would make these changes:" => {
+            "Das Zurücksetzen auf den Schnappschuss {} würde diese Änderungen vornehmen:"
+        }
+        "Rolled back to snapshot {}, the previous database was saved as snapshot {}" => {
+            "Auf Schnappschuss {} zurückgesetzt, die vorherige Datenbank wurde als Schnappschuss {} gespeichert"
+        }
+        "Would export bundle to {}" => "Paket würde exportiert nach {}",
+        "set {} with {} parts and {} spare parts" => "Set {} mit {} Teilen und {} Ersatzteilen",
+        "Would add {} to the config" => "Würde {} zur Konfiguration hinzufügen",
+        "Added {} to the config" => "{} zur Konfiguration hinzugefügt",
+        "Would generate a database with {} lot at {}" => {
+            "Datenbank mit {} Posten würde erstellt in {}"
+        }
+        "Would generate a database with {} lots at {}" => {
+            "Datenbank mit {} Posten würde erstellt in {}"
+        }
+        "Would write labels to {}" => "Etiketten würden geschrieben nach {}",
+        "Wrote {} label to {}" => "{} Etikett geschrieben nach {}",
+        "Wrote {} labels to {}" => "{} Etiketten geschrieben nach {}",
+        "Importing bundle from {} would make these changes:" => {
+            "Der Import des Pakets aus {} würde diese Änderungen vornehmen:"
+        }
+
+        // dry run
+        "DRY RUN: changes are not written to the database" => {
+            "PROBELAUF: Änderungen werden nicht in die Datenbank geschrieben"
+        }
+        "Dry run, the database was not modified. These changes would have been made:" => {
+            "Probelauf, die Datenbank wurde nicht verändert. Diese Änderungen wären vorgenommen worden:"
+        }
+        "Press any key to continue" => "Beliebige Taste drücken, um fortzufahren",
+        "y: yes" => "y: ja",
+        "n: no" => "n: nein",
+        "(Enter: the bold answer)" => "(Enter: die fette Antwort)",
+        "(Enter: the answer in brackets)" => "(Enter: die Antwort in Klammern)",
+        "(Enter: the answer without !)" => "(Enter: die Antwort ohne !)",
+        "Type \"{}\" to confirm:" => "Zum Bestätigen \"{}\" eingeben:",
+        "Removed part {}: {}" => "Teil {} entfernt: {}",
+        "Changed part {}:" => "Teil {} geändert:",
+        "Added part {}: {}" => "Teil {} hinzugefügt: {}",
+
+        // sync
+        "Item" => "Teil",
+        "Name" => "Name",
+        "Alternative IDs" => "Alternative IDs",
+        "Locations" => "Orte",
+        "Price" => "Preis",
+        "Details" => "Details",
+        "Deleted" => "Gelöscht",
+        "{} of part {} was changed both here and on disk.\n\nHere:\n{}\n\nOn disk:\n{}" => {
+            "{} von Teil {} wurde hier und auf der Festplatte geändert.\n\nHier:\n{}\n\nAuf der Festplatte:\n{}"
+        }
+        "Part {} was removed on disk." => "Teil {} wurde auf der Festplatte entfernt.",
+        "The database was changed on disk during this session." => {
+            "Die Datenbank wurde während dieser Sitzung auf der Festplatte geändert."
+        }
+        "Keep the version from this session? Otherwise the version on disk is used." => {
+            "Version aus dieser Sitzung behalten? Sonst wird die Version auf der Festplatte verwendet."
+        }
+        "Merged changes made on disk, {} conflict was resolved." => {
+            "Änderungen von der Festplatte übernommen, {} Konflikt wurde gelöst."
+        }
+        "Merged changes made on disk, {} conflicts were resolved." => {
+            "Änderungen von der Festplatte übernommen, {} Konflikte wurden gelöst."
+        }
+
+        // clipboard
+        "Copied part ID {} to the clipboard." => "Teilenummer {} in die Zwischenablage kopiert.",
+        "Copied {} part to the clipboard." => "{} Teil in die Zwischenablage kopiert.",
+        "Copied {} parts to the clipboard." => "{} Teile in die Zwischenablage kopiert.",
+        "Enter a filter like \"color=red, brick\", or the name of a saved filter:" => {
+            "Gib einen Filter wie \"color=red, brick\" oder den Namen eines gespeicherten Filters ein:"
+        }
+        "The clipboard does not contain a part ID: {}" => {
+            "Die Zwischenablage enthält keine Teilenummer: {}"
+        }
+
+        // macros
+        "RECORDING: press Q to stop recording the macro" => {
+            "AUFNAHME: drücke Q, um die Makroaufnahme zu beenden"
+        }
+        "Enter a name to save the macro, or leave it empty to keep it for this session only:" => {
+            "Gib einen Namen ein, um das Makro zu speichern, oder lass ihn leer, um es nur für diese Sitzung zu behalten:"
+        }
+        "Recorded a macro with {} step." => "Makro mit {} Schritt aufgenommen.",
+        "Recorded a macro with {} steps." => "Makro mit {} Schritten aufgenommen.",
+        "Saved macro {}." => "Makro {} gespeichert.",
+        "Last recorded macro" => "Zuletzt aufgenommenes Makro",
+        "No macros have been recorded yet." => "Es wurden noch keine Makros aufgenommen.",
+        "Select a macro to play:" => "Wähle ein Makro zum Abspielen:",
+        "How many times should the macro be played?" => "Wie oft soll das Makro abgespielt werden?",
+
+        // widget
+        "Recently viewed" => "Zuletzt angesehen",
+        "Low stock" => "Geringer Bestand",
+        "Statistics" => "Statistik",
+        "Saved filters" => "Gespeicherte Filter",
+        "No items have been viewed yet" => "Es wurden noch keine Teile angesehen",
+        "No lots have fewer than {} pieces" => "Keine Lose haben weniger als {} Teile",
+        "{} {}, {} at {}: {}" => "{} {}, {} bei {}: {}",
+        "and {} more lot" => "und {} weiteres Los",
+        "and {} more lots" => "und {} weitere Lose",
+        "No filters have been saved in the config" => {
+            "In der Konfiguration wurden keine Filter gespeichert"
+        }
+        "{} part matches {}" => "{} Teil passt zu {}",
+        "{} parts match {}" => "{} Teile passen zu {}",
+        "The saved filter {} is invalid" => "Der gespeicherte Filter {} ist ungültig",
+        "Parts: {}, counted pieces: {}, locations: {}" => "Teile: {}, gezählte Teile: {}, Orte: {}",
+        "Goals" => "Ziele",
+        "No goals have been defined in the config" => {
+            "In der Konfiguration wurden keine Ziele festgelegt"
+        }
+
+        // goal
+        "[{}{}] {}% {} ({} of {})" => "[{}{}] {}% {} ({} von {})",
+
+        // set
+        "{} {}: {}% ({} of {} pieces)" => "{} {}: {}% ({} von {} Teilen)",
+        "Completion of incomplete sets, closest first" => {
+            "Vollständigkeit unvollständiger Sets, fast vollständige zuerst"
+        }
+        "Completion of incomplete sets, by set number" => {
+            "Vollständigkeit unvollständiger Sets, nach Setnummer"
+        }
+        "Spare parts are needed for completion." => "Ersatzteile sind für die Vollständigkeit nötig.",
+        "Spare parts are not needed for completion." => {
+            "Ersatzteile sind für die Vollständigkeit nicht nötig."
+        }
+        "No incomplete sets have been added to the config" => {
+            "In der Konfiguration wurden keine unvollständigen Sets eingetragen"
+        }
+        "View Sets" => "Sets anzeigen",
+        "Part-out value of {} {}: {}" => "Zerlegungswert von {} {}: {}",
+        "instructions" => "Bauanleitung",
+        "sticker sheet" => "Stickerbogen",
+        "box" => "Karton",
+        "new" => "neu",
+        "used" => "gebraucht",
+        "damaged" => "beschädigt",
+        "missing" => "fehlt",
+        "Resale readiness of {} {}:" => "Verkaufsbereitschaft von {} {}:",
+        "complete" => "vollständig",
+        "{} of {} pieces" => "{} von {} Teilen",
+        "Parts: {}" => "Teile: {}",
+        "The set is ready to be sold." => "Das Set ist bereit zum Verkauf.",
+        "Missing: {}" => "Es fehlt: {}",
+        "Sealed price: {}" => "Preis versiegelt: {}",
+        "Part-out minus sealed: {}" => "Zerlegungswert minus versiegelt: {}",
+        "The sealed price of the set is not known" => "Der Preis des versiegelten Sets ist unbekannt",
+        "({} part has no price in the home currency and is not included)" => {
+            "({} Teil hat keinen Preis in der Heimatwährung und ist nicht enthalten)"
+        }
+        "({} parts have no price in the home currency and are not included)" => {
+            "({} Teile haben keinen Preis in der Heimatwährung und sind nicht enthalten)"
+        }
+        "Parts contributing the most:" => "Teile mit dem größten Anteil:",
+        "Enter the number of the set:" => "Gib die Nummer des Sets ein:",
+        "Set {} has not been added to the config" => {
+            "Set {} wurde nicht in der Konfiguration eingetragen"
+        }
+
+        // consolidate
+        "Part {} {}: {} lots of {} at {}" => "Teil {} {}: {} Lose von {} bei {}",
+        "No duplicate lots were found." => "Es wurden keine doppelten Lose gefunden.",
+        "These lots have the same color group, condition and location as another lot:" => {
+            "Diese Lose haben dieselbe Farbgruppe, denselben Zustand und denselben Ort wie ein anderes Los:"
+        }
+        "Merge duplicate lots" => "Doppelte Lose zusammenführen",
+        "Merge the duplicate lots? Their quantities are added up." => {
+            "Doppelte Lose zusammenführen? Ihre Anzahlen werden addiert."
+        }
+        "Merged the duplicate lots of {} item." => "Doppelte Lose von {} Teil zusammengeführt.",
+        "Merged the duplicate lots of {} items." => "Doppelte Lose von {} Teilen zusammengeführt.",
+
+        // check
+        "Part {} has no name." => "Teil {} hat keinen Namen.",
+        "The lot of {} of part {} has no location." => "Das Los von {} von Teil {} hat keinen Ort.",
+        "Part {} has {} lots of {} at {}." => "Teil {} hat {} Lose von {} bei {}.",
+        "Part {} is listed as its own alternative ID." => {
+            "Teil {} ist als seine eigene alternative ID eingetragen."
+        }
+        "ID {} belongs to part {} but is also an alternative ID of part {}." => {
+            "ID {} gehört zu Teil {}, ist aber auch eine alternative ID von Teil {}."
+        }
+        "Part {} is stored with all colors together but also by color group." => {
+            "Teil {} wird mit allen Farben zusammen, aber auch nach Farbgruppe aufbewahrt."
+        }
+        "{} in the config refers to part {}, which is not in the database." => {
+            "{} in der Konfiguration verweist auf Teil {}, das nicht in der Datenbank ist."
+        }
+        "Give the part a name?" => "Dem Teil einen Namen geben?",
+        "Give the lots IDs?" => "Den Posten IDs geben?",
+        "Lots of part {} have no ID." => "Posten von Teil {} haben keine ID.",
+        "Remove the lot?" => "Das Los entfernen?",
+        "Merge the lots and add up their quantities?" => {
+            "Die Lose zusammenführen und ihre Anzahlen addieren?"
+        }
+        "Remove the alternative ID?" => "Die alternative ID entfernen?",
+        "Set {}" => "Set {}",
+        "Goal {}" => "Ziel {}",
+        "No problems were found in the database." => "In der Datenbank wurden keine Probleme gefunden.",
+        "Repaired: {}" => "Behoben: {}",
+        "{} problem was found and {} repaired." => "{} Problem wurde gefunden und {} behoben.",
+        "{} problems were found and {} repaired." => "{} Probleme wurden gefunden und {} behoben.",
+        "Enter the name:" => "Gib den Namen ein:",
+
+        // adjustment
+        "built into a (m)OC" => "in ein MOC verbaut (m)",
+        "(s)old" => "verkauft (s)",
+        "(l)ost" => "verloren (l)",
+        "(f)ound" => "gefunden (f)",
+        "(b)ought" => "gekauft (b)",
+        "(n)o reason" => "kein Grund (n)",
+        "built into MOCs" => "in MOCs verbaut",
+        "sold" => "verkauft",
+        "lost" => "verloren",
+        "found" => "gefunden",
+        "bought" => "gekauft",
+        "no reason" => "kein Grund",
+        "Why did the count change?" => "Warum hat sich die Anzahl geändert?",
+        "No changes to quantities have been recorded yet." => {
+            "Es wurden noch keine Änderungen von Anzahlen aufgezeichnet."
+        }
+        "Changes to quantities by month and reason:" => "Änderungen der Anzahlen nach Monat und Grund:",
+
+        // sale
+        "Asking price of ({}, {}): {} -> {}" => "Angebotspreis von ({}, {}): {} -> {}",
+        "Not listed" => "Nicht angeboten",
+        "listed for {} each" => "angeboten für je {}",
+        "Select color group to list for sale:" => "Wähle die Farbgruppe, die angeboten werden soll:",
+        "Enter the asking price per piece, e.g. \"0.05 EUR\". Leave empty to stop listing:" => {
+            "Gib den Angebotspreis pro Teil ein, z.B. \"0.05 EUR\". Leer lassen, um das Angebot zu beenden:"
+        }
+        "Select color group that was sold from:" => "Wähle die Farbgruppe, aus der verkauft wurde:",
+        "How many pieces were sold?" => "Wie viele Teile wurden verkauft?",
+        "Who was the buyer?" => "Wer war der Käufer?",
+        "Enter the total price paid, e.g. \"1.50 EUR\":" => {
+            "Gib den gezahlten Gesamtpreis ein, z.B. \"1.50 EUR\":"
+        }
+        "The sale is recorded when the changes are saved." => {
+            "Der Verkauf wird erfasst, wenn die Änderungen gespeichert werden."
+        }
+        "No sales have been recorded yet." => "Es wurden noch keine Verkäufe erfasst.",
+        "Revenue from sales by month:" => "Verkaufserlöse nach Monat:",
+        "{} sale for {}" => "{} Verkauf für {}",
+        "{} sales for {}" => "{} Verkäufe für {}",
+        "({} without an exchange rate to the home currency)" => {
+            "({} ohne Wechselkurs zur Heimatwährung)"
+        }
+
+        // shipping
+        "Weight: {}" => "Gewicht: {}",
+        "({} without a known weight is not included)" => {
+            "({} ohne bekanntes Gewicht ist nicht enthalten)"
+        }
+        "({} without a known weight are not included)" => {
+            "({} ohne bekanntes Gewicht sind nicht enthalten)"
+        }
+        "Estimated shipping: {}" => "Geschätzter Versand: {}",
+        "No shipping rate covers a weight of {}" => "Kein Versandtarif deckt ein Gewicht von {} ab",
+
+        // order
+        "Part {} is not in the database" => "Teil {} ist nicht in der Datenbank",
+        "Part {} has no lot for color {}, add the {} pieces by hand" => {
+            "Teil {} hat keinen Posten für Farbe {}, füge die {} Teile von Hand hinzu"
+        }
+        "The pieces of part {} in {} are not counted, add the {} pieces by hand" => {
+            "Die Teile von {} in {} sind nicht gezählt, füge die {} Teile von Hand hinzu"
+        }
+        "Taking in order {} would make these changes:" => {
+            "Die Aufnahme der Bestellung {} würde diese Änderungen vornehmen:"
+        }
+        "{} lot was changed by taking in order {}." => {
+            "{} Posten wurde durch die Aufnahme der Bestellung {} geändert."
+        }
+        "{} lots were changed by taking in order {}." => {
+            "{} Posten wurden durch die Aufnahme der Bestellung {} geändert."
+        }
+        "Resumed taking in the order after {} line." => {
+            "Die Aufnahme der Bestellung wurde nach {} Zeile fortgesetzt."
+        }
+        "Resumed taking in the order after {} lines." => {
+            "Die Aufnahme der Bestellung wurde nach {} Zeilen fortgesetzt."
+        }
+        "Part {} was not found on Rebrickable." => "Teil {} wurde auf Rebrickable nicht gefunden.",
+        "Produced from {} to {}" => "Hergestellt von {} bis {}",
+        "Category: {}" => "Kategorie: {}",
+        "({} purchase without an exchange rate is not included)" => "({} Kauf ohne Wechselkurs ist nicht enthalten)",
+        "({} purchases without an exchange rate are not included)" => "({} Käufe ohne Wechselkurs sind nicht enthalten)",
+        "Budget" => "Budget",
+        "Enter the total price paid, e.g. \"25 EUR\":" => "Gib den bezahlten Gesamtpreis ein, z. B. \"25 EUR\":",
+        "Logged the purchase {}." => "Der Kauf {} wurde eingetragen.",
+        "No monthly budget has been set in the config" => "In der Konfiguration ist kein monatliches Budget festgelegt",
+        "Order {}" => "Bestellung {}",
+        "Spent {} of {} this month, {} left" => "Diesen Monat {} von {} ausgegeben, {} übrig",
+        "Spent {} of {} this month, {} over the budget" => "Diesen Monat {} von {} ausgegeben, {} über dem Budget",
+        "The budget of {} has no exchange rate to {}" => "Das Budget von {} hat keinen Wechselkurs zu {}",
+        "The purchases could not be read: {}" => "Die Käufe konnten nicht gelesen werden: {}",
+        "What was bought, and from whom?" => "Was wurde gekauft, und von wem?",
+        "When was it bought?" => "Wann wurde es gekauft?",
+        "log (m)oney spent on a purchase of parts" => "Geld für einen Kauf von Teilen eintragen (m)",
+        "{}: {} for {}" => "{}: {} für {}",
+        "Change in value" => "Änderung des Werts",
+        "Lots added" => "Hinzugefügte Lose",
+        "Money spent" => "Ausgaben",
+        "Sent the summary." => "Die Zusammenfassung wurde gesendet.",
+        "Summary of {}" => "Zusammenfassung von {}",
+        "Would write the summary to {}" => "Würde die Zusammenfassung nach {} schreiben",
+        "Wrote the summary to {}" => "Die Zusammenfassung wurde nach {} geschrieben",
+        "{} lot with {} counted pieces" => "{} Los mit {} gezählten Teilen",
+        "{} lots with {} counted pieces" => "{} Lose mit {} gezählten Teilen",
+        "Count the lots added in:" => "Zähle die Lose, die hinzugefügt wurden:",
+        "Days: {}" => "Tage: {}",
+        "From which day?" => "Ab welchem Tag?",
+        "No changes to quantities were recorded on these days." => "An diesen Tagen wurden keine Änderungen der Mengen aufgezeichnet.",
+        "No sales were made on these days." => "An diesen Tagen gab es keine Verkäufe.",
+        "Only the lots added from {} are counted." => "Nur die vom {} hinzugefügten Lose werden gezählt.",
+        "Show the changes made in:" => "Zeige die Änderungen aus:",
+        "Show the sales made in:" => "Zeige die Verkäufe aus:",
+        "To which day?" => "Bis zu welchem Tag?",
+        "Total: {}" => "Gesamt: {}",
+        "all time" => "die ganze Zeit",
+        "count only the lots added in a date (r)ange" => "nur die Lose eines Zeitraums zählen (r)",
+        "last month" => "letzter Monat",
+        "last year" => "letztes Jahr",
+        "other days" => "andere Tage",
+        "this month" => "dieser Monat",
+        "this year" => "dieses Jahr",
+        "{} to {}" => "{} bis {}",
+        "Mo  Tu  We  Th  Fr  Sa  Su" => "Mo  Di  Mi  Do  Fr  Sa  So",
+        "Select the lot that was bought:" => "Wähle das Los, das gekauft wurde:",
+        "When was the sale?" => "Wann war der Verkauf?",
+        "When were the pieces bought?" => "Wann wurden die Teile gekauft?",
+        "a day earlier" => "einen Tag früher",
+        "a day later" => "einen Tag später",
+        "a week earlier" => "eine Woche früher",
+        "a week later" => "eine Woche später",
+        "choose the date" => "das Datum wählen",
+        "mar(k) when the pieces of a lot were bought" => "das Kaufdatum eines Loses eintragen (k)",
+        "the next month" => "der nächste Monat",
+        "the previous month" => "der vorige Monat",
+        "today" => "heute",
+        "type a date, like yesterday, -3 or 2024-05-01" => "ein Datum eingeben, wie gestern, -3 oder 2024-05-01",
+        "yesterday" => "gestern",
+        "{} is not a date" => "{} ist kein Datum",
+        "Assembly of part {}" => "Zusammensetzen von Teil {}",
+        "Database repairs" => "Reparaturen der Datenbank",
+        "Disassembly of part {}" => "Zerlegen von Teil {}",
+        "Import of order {}" => "Import der Bestellung {}",
+        "Import of {}" => "Import von {}",
+        "No transactions have been recorded yet." => "Es wurden noch keine Transaktionen aufgezeichnet.",
+        "Part-out of set {}" => "Zerlegung von Set {}",
+        "There is nothing to undo." => "Es gibt nichts rückgängig zu machen.",
+        "Undid {}" => "Rückgängig gemacht: {}",
+        "Undoing {} would make these changes:" => "{} rückgängig zu machen würde diese Änderungen vornehmen:",
+        "{} part changed" => "{} Teil geändert",
+        "{} parts changed" => "{} Teile geändert",
+        "(p)arted out of a set" => "aus einem Set zerlegt (p)",
+        "Are the spare parts parted out as well?" => "Werden die Ersatzteile auch zerlegt?",
+        "How many pieces are bagged? The set has {}. Enter 0 to skip the part, or press Escape to stop:" => "Wie viele Teile sind eingetütet? Das Set hat {}. Gib 0 ein, um das Teil zu überspringen, oder drücke Escape zum Aufhören:",
+        "Nothing was parted out of set {}." => "Aus Set {} wurde nichts zerlegt.",
+        "Part {} was skipped." => "Teil {} wurde übersprungen.",
+        "Part {}: {}" => "Teil {}: {}",
+        "Parted out {} piece of set {} into {} parts." => "{} Teil von Set {} in {} Teile zerlegt.",
+        "Parted out {} pieces of set {} into {} parts." => "{} Teile von Set {} in {} Teile zerlegt.",
+        "Parting out set {}: {}, part {} of {}" => "Set {} wird zerlegt: {}, Teil {} von {}",
+        "Save the part-out of set {}?" => "Die Zerlegung von Set {} speichern?",
+        "The inventory of set {} is not known, import it with `tuibricks set import`" => "Das Inventar von Set {} ist nicht bekannt, importiere es mit `tuibricks set import`",
+        "The part-out of set {} was discarded." => "Die Zerlegung von Set {} wurde verworfen.",
+        "part out a set, (w)orking through its inventory" => "ein Set zerlegen und dabei sein Inventar durchgehen (w)",
+        "parted out of sets" => "aus Sets zerlegt",
+        "Select the first lot to merge:" => "Wähle den ersten zusammenzulegenden Posten:",
+        "Select the location of the merged lot:" => "Wähle den Ort des zusammengelegten Postens:",
+        "Select the lot to merge it with:" => "Wähle den Posten, mit dem er zusammengelegt wird:",
+        "The lots are in different conditions. Select the condition of the merged lot:" => "Die Posten sind in unterschiedlichem Zustand. Wähle den Zustand des zusammengelegten Postens:",
+        "The lots have different notes. Select the notes to keep:" => "Die Posten haben unterschiedliche Notizen. Wähle die zu behaltenden Notizen:",
+        "There is no other lot of {} to merge with" => "Es gibt keinen anderen Posten von {} zum Zusammenlegen",
+        "The lots could not be merged" => "Die Posten konnten nicht zusammengelegt werden",
+        "m(e)rge two lots of the same color group" => "zwei Posten derselben Farbgruppe zusamm(e)nlegen",
+        "merged from {} other lot" => "zusammengelegt aus {} anderem Posten",
+        "merged from {} other lots" => "zusammengelegt aus {} anderen Posten",
+        "Enter the asking price per piece of the new lot. Leave empty if it is not listed:" => "Gib den Angebotspreis pro Teil des neuen Postens ein. Leer lassen, wenn er nicht angeboten wird:",
+        "Enter the location of the new lot:" => "Gib den Ort des neuen Postens ein:",
+        "Enter the number of pieces to move:" => "Gib die Anzahl der zu verschiebenden Teile ein:",
+        "Select the condition of the new lot:" => "Wähle den Zustand des neuen Postens:",
+        "Select the lot to divide:" => "Wähle den zu teilenden Posten:",
+        "The lot cannot be divided: it needs more than {} counted pieces, and {} must not have a lot of {} already." => "Der Posten kann nicht geteilt werden: Er braucht mehr als {} gezählte Teile, und {} darf noch keinen Posten von {} haben.",
+        "di(v)ide a lot, moving some of its pieces to another location" => "Posten tei(v)len, wobei einige Teile an einen anderen Ort kommen",
+        "split off from a lot that is gone" => "abgeteilt von einem Posten, den es nicht mehr gibt",
+        "split off from the lot at {}" => "abgeteilt vom Posten bei {}",
+        "the same condition" => "derselbe Zustand",
+        "Not saved, the item breaks {} rule: {}" => "Nicht gespeichert, das Teil verletzt {} Regel: {}",
+        "Not saved, the item breaks {} rules: {}" => "Nicht gespeichert, das Teil verletzt {} Regeln: {}",
+        "Part {} breaks the rule: {}" => "Teil {} verletzt die Regel: {}",
+        "Enter the {}. Leave empty if unknown:" => "Gib {} ein. Leer lassen, falls unbekannt:",
+        "Leave empty" => "Leer lassen",
+        "Select the field to edit" => "Wähle das zu bearbeitende Feld",
+        "edit the custom (f)ields of this item" => "bearbeite die eigenen (f)elder dieses Teils",
+        "yes" => "ja",
+        "no" => "nein",
+        "{} is not a valid {}" => "{} ist kein gültiger Wert für {}",
+        "Filled in the year of release of {} set." => "Das Erscheinungsjahr von {} Set ergänzt.",
+        "Filled in the years of release of {} sets." => "Die Erscheinungsjahre von {} Sets ergänzt.",
+        "Produced: {}" => "Produziert: {}",
+        "Produced: {} -> {}" => "Produziert: {} -> {}",
+        "It comes in: {}" => "Erhältlich in: {}",
+        "Part {} was never produced in {}, according to Rebrickable." => { "Teil {} wurde laut Rebrickable nie in {} hergestellt." },
+        "Part {} was not placed." => "Teil {} wurde nicht eingeräumt.",
+        "The color group was not added." => "Die Farbgruppe wurde nicht hinzugefügt.",
+        "Use the color anyway?" => "Die Farbe trotzdem verwenden?",
+        "All sets have their paper instructions." => { "Alle Sets haben ihre gedruckte Bauanleitung." },
+        "Sets missing their paper instructions:" => { "Sets ohne gedruckte Bauanleitung:" },
+        "no instructions" => "keine Bauanleitung",
+        "only a PDF" => "nur als PDF",
+        "paper instructions" => "gedruckte Bauanleitung",
+        "Added set {} {}" => "Set {} {} hinzugefügt",
+        "Brickset username:" => "Brickset-Benutzername:",
+        "Logged in as {}." => "Als {} angemeldet.",
+        "Marked set {} as owned on Brickset" => { "Set {} auf Brickset als im Besitz markiert" },
+        "Password:" => "Passwort:",
+        "Set {} was not found on Brickset" => "Set {} wurde auf Brickset nicht gefunden",
+        "Their inventories can be added with `tuibricks set import`." => { "Ihre Inventare können mit `tuibricks set import` hinzugefügt werden." },
+        "Would add set {} {}" => "Würde Set {} {} hinzufügen",
+        "Would mark set {} as owned on Brickset" => { "Würde Set {} auf Brickset als im Besitz markieren" },
+        "{} set owned on Brickset, {} added here." => { "{} Set auf Brickset im Besitz, {} hier hinzugefügt." },
+        "{} sets owned on Brickset, {} added here." => { "{} Sets auf Brickset im Besitz, {} hier hinzugefügt." },
+        "Group {} is not a part on BrickLink, it is left out" => {
+            "Gruppe {} ist auf BrickLink kein Teil und wird ausgelassen"
+        }
+        "BrickStore will not know color {} of part {}" => { "BrickStore wird Farbe {} von Teil {} nicht kennen" },
+        "Exported the lots to {}" => "Posten nach {} exportiert",
+        "The pieces of part {} in {} are not counted, it is left out" => { "Die Teile von Teil {} in {} sind nicht gezählt und werden ausgelassen" },
+        "Would export the lots to {}" => "Würde die Posten nach {} exportieren",
+        "Condition of ({}, {}): {} -> {}" => "Zustand von ({}, {}): {} -> {}",
+        "Imported {} lot from {} into {}." => "{} Posten aus {} nach {} importiert.",
+        "Imported {} lots from {} into {}." => "{} Posten aus {} nach {} importiert.",
+        "Importing {} would make these changes:" => { "Das Importieren von {} würde diese Änderungen vornehmen:" },
+        "Notes of ({}, {}): {} -> {}" => "Notizen von ({}, {}): {} -> {}",
+        "Part {} {} has an ID that is not a number" => { "Teil {} {} hat eine ID, die keine Zahl ist" },
+        "{} {} is not a part" => "{} {} ist kein Teil",
+        "Catalogued {} piece." => "{} Teil katalogisiert.",
+        "Catalogued {} pieces." => "{} Teile katalogisiert.",
+        "Put pieces into {} lot, {} of them new." => { "Teile in {} Posten gelegt, {} davon neu." },
+        "Put pieces into {} lots, {} of them new." => { "Teile in {} Posten gelegt, {} davon neu." },
+        "Sorted for {}." => "{} lang sortiert.",
+        "That is {} lots and {} pieces an hour." => { "Das sind {} Posten und {} Teile pro Stunde." },
+        "{} pieces an hour" => "{} Teile pro Stunde",
+        "Commands used:" => "Verwendete Befehle:",
+        "Time in modes:" => "Zeit in Modi:",
+        "Usage is not logged, set usage_log to true in the config to log it." => {
+            "Die Nutzung wird nicht aufgezeichnet, setze usage_log in der Konfiguration auf true."
+        },
+        "view how much commands and modes are (u)sed" => "Nutzung von Befehlen und Modi anzeigen (u)",
+        "API key:" => "API-Schlüssel:",
+        "An API key can be made at {}" => "Ein API-Schlüssel kann unter {} erstellt werden",
+        "Removed the key written in the config." => "Der Schlüssel in der Konfiguration wurde entfernt.",
+        "Saved the key to {}" => "Schlüssel in {} gespeichert",
+        "The key was accepted." => "Der Schlüssel wurde angenommen.",
+        "Would save the key to {}" => "Würde den Schlüssel in {} speichern",
+        "Passphrase of the database:" => "Passwort der Datenbank:",
+        "New passphrase to encrypt the database with:" => {
+            "Neues Passwort, mit dem die Datenbank verschlüsselt wird:"
+        },
+        "Repeat the passphrase:" => "Passwort wiederholen:",
+        "Answer y for yes or n for no, or nothing for no:" => {
+            "Antworte y für ja oder n für nein, oder nichts für nein:"
+        },
+        "Answer y for yes or n for no, or nothing for yes:" => {
+            "Antworte y für ja oder n für nein, oder nichts für ja:"
+        },
+        "Answer y for yes or n for no:" => "Antworte y für ja oder n für nein:",
+        "Command key, or an empty line to cancel:" => {
+            "Befehlstaste, oder eine leere Zeile zum Abbrechen:"
+        },
+        "Currently: {}" => "Derzeit: {}",
+        "Enter the key of a choice:" => "Gib die Taste einer Auswahl ein:",
+        "Enter the number of a choice:" => "Gib die Nummer einer Auswahl ein:",
+        "Search for:" => "Suche nach:",
+        "{} is not an answer to this" => "{} ist keine Antwort darauf",
+        "{} does nothing here" => "{} bewirkt hier nichts",
+        "Count of color group {} changed from {} to {}." => {
+            "Anzahl der Farbgruppe {} von {} auf {} geändert."
+        },
+        "Select color group to add {} piece to:" => {
+            "Wähle die Farbgruppe, zu der {} Teil hinzugefügt wird:"
+        },
+        "Select color group to add {} pieces to:" => {
+            "Wähle die Farbgruppe, zu der {} Teile hinzugefügt werden:"
+        },
+        "Select color group to remove {} piece from:" => {
+            "Wähle die Farbgruppe, aus der {} Teil entfernt wird:"
+        },
+        "Select color group to remove {} pieces from:" => {
+            "Wähle die Farbgruppe, aus der {} Teile entfernt werden:"
+        },
+        "There is only {} piece in color group {}" => "Es gibt nur {} Teil in Farbgruppe {}",
+        "There are only {} pieces in color group {}" => "Es gibt nur {} Teile in Farbgruppe {}",
+        "add pieces to a lot (+), type a count first like 5+ for more" => {
+            "Teile zu einem Posten hinzufügen (+), mit vorangestellter Anzahl wie 5+ für mehr"
+        },
+        "remove pieces from a lot (-), type a count first like 12- for more" => {
+            "Teile aus einem Posten entfernen (-), mit vorangestellter Anzahl wie 12- für mehr"
+        },
+        "Count of {} {} updated." => "Anzahl von {} {} aktualisiert.",
+        "Moved {} {} to {}." => "{} {} nach {} verschoben.",
+        "New location of {} {}:" => "Neuer Ort von {} {}:",
+        "Number of pieces of {} {}:" => "Anzahl der Teile von {} {}:",
+        "Select a color group of {}:" => "Wähle eine Farbgruppe von {}:",
+        "Up/Down, PgUp/PgDn: scroll  Enter: select  c: count  m: move  Esc: cancel" => {
+            "Hoch/Runter, Bild hoch/runter: blättern  Enter: wählen  c: zählen  m: verschieben  Esc: abbrechen"
+        },
+        "{} has no lots" => "{} hat keine Posten",
+        "Overview" => "Übersicht",
+        "Lots" => "Posten",
+        "Sales" => "Verkäufe",
+        "History" => "Verlauf",
+        "No sales are recorded for this part" => "Für dieses Teil sind keine Verkäufe erfasst",
+        "No changes to the pieces are recorded for this part" => {
+            "Für dieses Teil sind keine Änderungen der Teile erfasst"
+        },
+        "show or hide the section with this number" => {
+            "den Abschnitt mit dieser Nummer ein- oder ausblenden"
+        },
+        "{}: {} {} to {} for {}" => "{}: {} {} an {} für {}",
+        "({} columns hidden to the left, {} to the right, h/l: pan)" => {
+            "({} Spalten links verborgen, {} rechts, h/l: verschieben)"
+        },
+        "Up/Down, PgUp/PgDn: scroll  Enter: select  Esc: cancel" => {
+            "Hoch/Runter, Bild hoch/runter: blättern  Enter: wählen  Esc: abbrechen"
+        },
+        "Total: {} parts, {} counted pieces, worth {}" => {
+            "Gesamt: {} Teile, {} gezählte Stücke, Wert {}"
+        },
+        "(d)one" => "fertig (d)",
+        "At least one column must be shown." => "Mindestens eine Spalte muss angezeigt werden.",
+        "Color" => "Farbe",
+        "Filters that are not saved" => "Nicht gespeicherte Filter",
+        "ID" => "ID",
+        "Location" => "Ort",
+        "Pieces" => "Teile",
+        "Saved filter {}" => "Gespeicherter Filter {}",
+        "The columns were saved." => "Die Spalten wurden gespeichert.",
+        "The columns are shown for this session only, as nothing is saved in read-only mode." => {
+            "Die Spalten werden nur in dieser Sitzung gezeigt, da im schreibgeschützten Modus nichts gespeichert wird."
+        }
+        "Toggle the columns to show:" => "Die anzuzeigenden Spalten umschalten:",
+        "Value" => "Wert",
+        "Which list should the columns be chosen for?" => "Für welche Liste sollen die Spalten gewählt werden?",
+        "choose the columns of the (t)able of parts matching a filter" => {
+            "Spalten der Tabelle der zu einem Filter passenden Teile wählen (t)"
+        },
+        "search by (f)ilter" => "nach Filter suchen (f)",
+        "Lots by time since they were last changed:" => "Posten nach Zeit seit der letzten Änderung:",
+        "under a month" => "unter einem Monat",
+        "1 to 6 months" => "1 bis 6 Monate",
+        "6 to 12 months" => "6 bis 12 Monate",
+        "1 to 2 years" => "1 bis 2 Jahre",
+        "over 2 years" => "über 2 Jahre",
+        "not known" => "unbekannt",
+        "list the lots not changed for over two years, (s)tale lots" => {
+            "Posten auflisten, die seit über zwei Jahren unverändert sind (s)"
+        },
+        "No lots have gone unchanged for over two years." => {
+            "Keine Posten sind seit über zwei Jahren unverändert."
+        },
+        "These lots have not changed for over two years and may be worth selling or sorting anew:" => {
+            "Diese Posten sind seit über zwei Jahren unverändert und könnten verkauft oder neu sortiert werden:"
+        },
+        "last changed {}" => "zuletzt geändert {}",
+        "added {}" => "hinzugefügt {}",
+        "modified {}" => "geändert {}",
+        "bought {}" => "gekauft {}",
+        "Done, {} item was changed." => "Fertig, {} Teil wurde geändert.",
+        "Done, {} items were changed." => "Fertig, {} Teile wurden geändert.",
+        "Running {}..." => "{} wird ausgeführt...",
+        "Would write the viewer to {}" => "Würde die Ansicht nach {} schreiben",
+        "Wrote {} part to {}" => "{} Teil nach {} geschrieben",
+        "Wrote {} parts to {}" => "{} Teile nach {} geschrieben",
+        "Pulled {} changed item from the server." => "{} geändertes Teil vom Server geholt.",
+        "Pulled {} changed items from the server." => "{} geänderte Teile vom Server geholt.",
+        "Pushed {} changed item to the server." => "{} geändertes Teil an den Server gesendet.",
+        "Pushed {} changed items to the server." => "{} geänderte Teile an den Server gesendet.",
+        "Serving the database on {}" => "Die Datenbank wird auf {} bereitgestellt",
+        "Syncing would make these changes:" => "Das Synchronisieren würde diese Änderungen vornehmen:",
+        "{} conflict was found, the version on this machine was kept." => {
+            "{} Konflikt wurde gefunden, die Version auf diesem Rechner wurde behalten."
+        },
+        "{} conflicts were found, the versions on this machine were kept." => {
+            "{} Konflikte wurden gefunden, die Versionen auf diesem Rechner wurden behalten."
+        },
+        "Changes to quantities made by {} by month and reason:" => {
+            "Änderungen der Anzahlen von {} nach Monat und Grund:"
+        },
+        "Show the changes made by:" => "Änderungen anzeigen von:",
+        "everyone" => "allen",
+        "Move this lot?" => "Dieses Los verschieben?",
+        "No lots can be moved to make room in overfull locations." => {
+            "Es können keine Lose verschoben werden, um in überfüllten Orten Platz zu schaffen."
+        },
+        "Proposed move {} of {}" => "Vorgeschlagene Verschiebung {} von {}",
+        "move lots out of overfull (l)ocations" => "Lose aus überfüllten Orten verschieben (l)",
+        "{} lot was proposed to be moved, {} were moved." => {
+            "{} Los wurde zum Verschieben vorgeschlagen, {} wurden verschoben."
+        },
+        "{} lots were proposed to be moved, {} were moved." => {
+            "{} Lose wurden zum Verschieben vorgeschlagen, {} wurden verschoben."
+        },
+        "Filled: {}" => "Belegt: {}",
+        "Location {} would be overfull: {}" => "Ort {} wäre überfüllt: {}",
+        "and {} uncounted lot" => "und {} ungezähltes Los",
+        "and {} uncounted lots" => "und {} ungezählte Lose",
+        "{} of {} pieces ({}%)" => "{} von {} Teilen ({}%)",
+        "{} piece, no capacity is set" => "{} Teil, keine Kapazität festgelegt",
+        "{} pieces, no capacity is set" => "{} Teile, keine Kapazität festgelegt",
+        "sort the parts of an intake (b)in into their locations" => {
+            "Teile einer Eingangskiste an ihre Orte einsortieren (b)"
+        }
+        "Sorting a bin, {} part placed so far" => "Sortiere eine Kiste, bisher {} Teil eingeordnet",
+        "Sorting a bin, {} parts placed so far" => "Sortiere eine Kiste, bisher {} Teile eingeordnet",
+        "Enter the part ID of the next part, or press Escape when the bin is empty:" => {
+            "Gib die Teile-ID des nächsten Teils ein, oder drücke Escape, wenn die Kiste leer ist:"
+        }
+        "Select the color group of part {}:" => "Wähle die Farbgruppe von Teil {}:",
+        "How many pieces are there?" => "Wie viele Stück sind es?",
+        "No location can be suggested for this part." => {
+            "Für dieses Teil kann kein Ort vorgeschlagen werden."
+        }
+        "Suggested locations:" => "Vorgeschlagene Orte:",
+        "Enter the location to store the pieces in:" => "Gib den Ort ein, an dem die Teile gelagert werden:",
+        "The part was not placed, as no location was given." => {
+            "Das Teil wurde nicht eingeordnet, da kein Ort angegeben wurde."
+        }
+        "Placed part {} in {}." => "Teil {} wurde in {} eingeordnet.",
+        "Placed {} part from the bin." => "{} Teil aus der Kiste wurde eingeordnet.",
+        "Placed {} parts from the bin." => "{} Teile aus der Kiste wurden eingeordnet.",
+        "already stored there" => "bereits dort gelagert",
+        "stored there in another color group" => "dort in einer anderen Farbgruppe gelagert",
+        "a similar part is stored there ({})" => "ein ähnliches Teil ist dort gelagert ({})",
+        "part {} is stored there" => "Teil {} ist dort gelagert",
+        "Similar parts you own:" => "Ähnliche Teile, die du besitzt:",
+        "show the similar part with this number" => "ähnliches Teil mit dieser Nummer anzeigen",
+        "substitute" => "Ersatz",
+        "same mold" => "gleiche Form",
+        "similar size" => "ähnliche Größe",
+        "Size: {}" => "Größe: {}",
+        "Size: {} -> {}" => "Größe: {} -> {}",
+        "edit the si(z)e of the part in studs and its height" => {
+            "Größe des Teils in Noppen und seine Höhe bearbeiten (z)"
+        }
+        "Enter the size in studs and the height in bricks, e.g. \"1 x 4 x 1/3\" for a plate. Leave empty if unknown:" => {
+            "Gib die Größe in Noppen und die Höhe in Steinen ein, z. B. \"1 x 4 x 1/3\" für eine Platte. Leer lassen, wenn unbekannt:"
+        }
+        "{} is not a valid size" => "{} ist keine gültige Größe",
+        "(a)ssembled or taken apart" => "zusammengesetzt oder zerlegt (a)",
+        "assembled or taken apart" => "zusammengesetzt oder zerlegt",
+        "(j)oin components into assemblies" => "Bestandteile zu Baugruppen zusammensetzen (j)",
+        "take assemblies apart into their components (x)" => {
+            "Baugruppen in ihre Bestandteile zerlegen (x)"
+        }
+        "edit the parts this assembly is b(u)ilt from" => {
+            "Teile bearbeiten, aus denen diese Baugruppe besteht (u)"
+        }
+        "Components: {}" => "Bestandteile: {}",
+        "Components: {} -> {}" => "Bestandteile: {} -> {}",
+        "Enter the parts this assembly is made of, e.g. \"3482 x 1, 3483 x 1\". Leave empty if it is not an assembly:" => {
+            "Gib die Teile ein, aus denen diese Baugruppe besteht, z. B. \"3482 x 1, 3483 x 1\". Leer lassen, wenn es keine Baugruppe ist:"
+        }
+        "{} are not valid components" => "{} sind keine gültigen Bestandteile",
+        "Select color group of the assemblies:" => "Wähle die Farbgruppe der Baugruppen:",
+        "How many assemblies were put together?" => "Wie viele Baugruppen wurden zusammengesetzt?",
+        "How many assemblies were taken apart?" => "Wie viele Baugruppen wurden zerlegt?",
+        "Put together {} assembly from its components." => {
+            "{} Baugruppe wurde aus ihren Bestandteilen zusammengesetzt."
+        }
+        "Put together {} assemblies from their components." => {
+            "{} Baugruppen wurden aus ihren Bestandteilen zusammengesetzt."
+        }
+        "Took apart {} assembly into its components." => {
+            "{} Baugruppe wurde in ihre Bestandteile zerlegt."
+        }
+        "Took apart {} assemblies into their components." => {
+            "{} Baugruppen wurden in ihre Bestandteile zerlegt."
+        }
+        "Base mold: {}" => "Grundform: {}",
+        "Base mold: {} -> {}" => "Grundform: {} -> {}",
+        "Enter the part ID of the plain part this part is a print or pattern of. Leave empty if it is not decorated:" => {
+            "Gib die Teile-ID des unbedruckten Teils ein, dessen Druck oder Muster dieses Teil ist. Leer lassen, wenn es nicht verziert ist:"
+        }
+        "edit the base mold of a prin(t)ed or patterned part" => {
+            "Grundform eines bedruckten oder gemusterten Teils bearbeiten (t)"
+        }
+        "{} base mold matches {}:" => "{} Grundform passt zu {}:",
+        "{} base molds match {}:" => "{} Grundformen passen zu {}:",
+        "{} decorated variant" => "{} verzierte Variante",
+        "{} decorated variants" => "{} verzierte Varianten",
+        "{} is not a valid base mold" => "{} ist keine gültige Grundform",
+        "Category: {} -> {}" => "Kategorie: {} -> {}",
+        "Looked up {} part, filled in {} names, {} categories and {} years of production." => {
+            "{} Teil nachgeschlagen, {} Namen, {} Kategorien und {} Produktionsjahre ergänzt."
+        }
+        "Looked up {} parts, filled in {} names, {} categories and {} years of production." => {
+            "{} Teile nachgeschlagen, {} Namen, {} Kategorien und {} Produktionsjahre ergänzt."
+        }
+        "Looking up {} ({} of {})" => "Schlage {} nach ({} von {})",
+        "Not found: {}" => "Nicht gefunden: {}",
+        "{} part still has no weight per piece, weigh a lot to fill it in." => {
+            "{} Teil hat noch kein Gewicht pro Stück, wiege einen Posten, um es zu ergänzen."
+        }
+        "{} parts still have no weight per piece, weigh a lot to fill them in." => {
+            "{} Teile haben noch kein Gewicht pro Stück, wiege einen Posten, um es zu ergänzen."
+        }
+        "Pushing would make these changes to the store:" => {
+            "Das Hochladen würde diese Änderungen am Shop vornehmen:"
+        }
+        "The store is up to date." => "Der Shop ist auf dem neuesten Stand.",
+        "Made {} change to the store." => "{} Änderung am Shop vorgenommen.",
+        "Made {} changes to the store." => "{} Änderungen am Shop vorgenommen.",
+        "Add {}: {} pieces at {}" => "{} hinzufügen: {} Stück zu {}",
+        "Update {}: {}" => "{} ändern: {}",
+        "{} to {} pieces" => "{} auf {} Stück",
+        "price {} to {}" => "Preis {} auf {}",
+        "remarks \"{}\" to \"{}\"" => "Bemerkungen \"{}\" auf \"{}\"",
+        "The lot of part {} {} at {} is left out, as {}" => {
+            "Der Posten von Teil {} {} bei {} wird ausgelassen, da {}"
+        }
+        "groups are not parts on BrickLink" => "Gruppen auf BrickLink keine Teile sind",
+        "{} is not a color on BrickLink" => "{} auf BrickLink keine Farbe ist",
+        "it has no condition" => "er keinen Zustand hat",
+        "its pieces are not counted" => "seine Teile nicht gezählt sind",
+        "its price has no exchange rate to the home currency" => {
+            "es für seinen Preis keinen Wechselkurs zur Heimatwährung gibt"
+        }
+        "The keys of a store can be made at {}" => "Die Schlüssel eines Shops können unter {} erstellt werden",
+        "Consumer key:" => "Consumer Key:",
+        "Consumer secret:" => "Consumer Secret:",
+        "Token value:" => "Token Value:",
+        "Token secret:" => "Token Secret:",
+        "The keys were accepted." => "Die Schlüssel wurden angenommen.",
+        "Saved the keys to {}" => "Schlüssel in {} gespeichert",
+        "Would save the keys to {}" => "Würde die Schlüssel in {} speichern",
+        _ => return None,
+    };
+    Some(translated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill("Part {} at {}", &[&3001, &"B1A3"]),
+            "Part 3001 at B1A3"
+        );
+        assert_eq!(fill("No args", &[]), "No args");
+    }
+
+    #[test]
+    fn test_format_decimal() {
+        assert_eq!(group_digits("123456", ','), "123,456");
+        assert_eq!(group_digits("12", ','), "12");
+        assert_eq!(format_decimal_with(1234.5, 2, '.', ','), "1.234,50");
+        assert_eq!(format_decimal_with(-1234567.0, 0, ',', '.'), "-1,234,567");
+    }
+}
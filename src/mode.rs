@@ -1,13 +1,21 @@
 use std::fmt;
 
+use strum::IntoEnumIterator;
+
 use term_lib::command::CmdList;
 use term_lib::display;
 
 use crate::cmd::{Cmd, MultiCmd};
 use crate::data::{DatabaseStats, Item};
 use crate::error::Result;
+use crate::field;
+use crate::locale::tr;
+use crate::section::Section;
+use crate::set::{Completion, SortBy};
 
+/// There is only one mode at a time, so the items in it are not boxed.
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Mode {
     Default {
         info: Option<String>,
@@ -24,9 +32,37 @@ pub enum Mode {
     ViewStatistics {
         stats: DatabaseStats,
     },
+    ViewSetCompletion {
+        sets: Vec<Completion>,
+        sort_by: SortBy,
+        /// Whether the spare parts of the sets are needed for completion.
+        include_spares: bool,
+    },
 }
 
 impl Mode {
+    /// Returns the mode showing the message, where the mode has room for one.
+    pub fn with_msg(&self, msg: String) -> Mode {
+        let mut mode = self.clone();
+        match &mut mode {
+            Mode::Default { info } => *info = Some(msg),
+            Mode::DisplayItem { msg: m, .. } | Mode::EditItem { msg: m, .. } => *m = Some(msg),
+            Mode::ViewStatistics { .. } | Mode::ViewSetCompletion { .. } => {}
+        }
+        mode
+    }
+
+    /// The name of the kind of mode, used in the usage log.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Mode::Default { .. } => "Default",
+            Mode::DisplayItem { .. } => "DisplayItem",
+            Mode::EditItem { .. } => "EditItem",
+            Mode::ViewStatistics { .. } => "ViewStatistics",
+            Mode::ViewSetCompletion { .. } => "ViewSetCompletion",
+        }
+    }
+
     pub fn get_possible_cmds(&self) -> CmdList<Cmd> {
         use Cmd::*;
         use Mode::*;
@@ -34,19 +70,48 @@ impl Mode {
             Default { .. } => CmdList::new(vec![
                 AddItem,
                 AddGroup,
+                SortBin,
+                Rebalance,
                 MCmd(MultiCmd::SearchItem),
                 Quit,
                 ViewStats,
+                ViewSets,
+                PartOutValue,
+                PartOutSet,
+                ConsolidateLots,
+                CheckDatabase,
+                ViewAdjustments,
+                ViewRevenue,
+                LogPurchase,
+                ViewUsage,
+                ChooseColumns,
+                CopyPartList,
+                StartRecording,
+                PlayMacro,
             ]),
-            DisplayItem { .. } => CmdList::new(vec![
-                AddItem,
-                AddGroup,
-                MCmd(MultiCmd::SearchItem),
-                Quit,
-                Edit,
-                ViewStats,
-                Bricklink,
-            ]),
+            DisplayItem { item, .. } => {
+                let mut cmds = vec![
+                    AddItem,
+                    AddGroup,
+                    MCmd(MultiCmd::SearchItem),
+                    Quit,
+                    Edit,
+                    ViewStats,
+                    ViewSets,
+                    PartOutValue,
+                    PartOutSet,
+                    OpenPartPage,
+                    CopyPartID,
+                    StartRecording,
+                    PlayMacro,
+                ];
+                if !item.get_components().is_empty() && !item.get_locations().is_empty() {
+                    cmds.push(Assemble);
+                    cmds.push(Disassemble);
+                }
+                cmds.extend(Section::iter().map(ToggleSection));
+                CmdList::new(cmds)
+            }
             EditItem {
                 old_item, new_item, ..
             } => {
@@ -54,20 +119,50 @@ impl Mode {
                     SaveEdit,
                     QuitEdit,
                     EditName,
+                    EditPrice,
+                    EditPieceWeight,
                     MCmd(MultiCmd::AddToItem),
                     MCmd(MultiCmd::RemoveFromItem),
                     DeleteItem,
-                    Bricklink,
+                    OpenPartPage,
+                    StartRecording,
+                    PlayMacro,
                 ];
                 if !old_item.is_group() {
                     cmds.push(EditPartID);
+                    cmds.push(EditBaseMold);
+                    cmds.push(EditComponents);
+                    cmds.push(EditDimensions);
+                }
+                if !field::get_fields().is_empty() {
+                    cmds.push(EditFields);
                 }
                 if new_item.get_locations().len() != 0 {
                     cmds.push(MoveColorGroup);
+                    cmds.push(EditQuantity);
+                    cmds.push(ListForSale);
+                    cmds.push(RecordSale);
+                    cmds.push(WeighLot);
+                    cmds.push(SplitLot);
+                    cmds.push(EditPurchased);
+                    if new_item.get_locations().len() > 1 {
+                        cmds.push(MergeLots);
+                    }
+                    cmds.push(AddPieces);
+                    cmds.push(RemovePieces);
                 }
                 CmdList::new(cmds)
             }
-            ViewStatistics { .. } => CmdList::new(vec![QuitStats]),
+            ViewStatistics { .. } => CmdList::new(vec![
+                ToggleListedLots,
+                ToggleWantedParts,
+                ViewStaleLots,
+                ChooseStatsRange,
+                QuitStats,
+            ]),
+            ViewSetCompletion { .. } => {
+                CmdList::new(vec![SortSets, ToggleSpareParts, ResaleReadiness, QuitSets])
+            }
         }
     }
 
@@ -76,7 +171,7 @@ impl Mode {
         use Mode::*;
         match self {
             Default { info } => {
-                display::header(w, "Welcome to TUI bricks")?;
+                display::header(w, tr!("Welcome to TUI bricks"))?;
                 if let Some(info) = info {
                     display::iter(w, info.split("\n"))?;
                 }
@@ -88,19 +183,14 @@ impl Mode {
                     if item.is_group() {
                         display::header(
                             w,
-                            &format!(
-                                "Viewing item group with group ID {}",
-                                item.get_id() & !(1 << 31)
-                            ),
+                            &tr!("Viewing item group with group ID {}", item.get_id()),
                         )?;
                     } else {
-                        display::header(
-                            w,
-                            &format!("Viewing item with part ID {}", item.get_id()),
-                        )?;
+                        display::header(w, &tr!("Viewing item with part ID {}", item.get_id()))?;
                     }
                 }
-                display::iter(w, item.to_string().split("\n"))?;
+                // The item itself is shown in sections by the state, which knows which ones are
+                // expanded.
             }
             EditItem { new_item, msg, .. } => {
                 if let Some(msg) = msg {
@@ -109,15 +199,12 @@ impl Mode {
                     if new_item.is_group() {
                         display::header(
                             w,
-                            &format!(
-                                "Now editing item with part ID {}",
-                                new_item.get_id() & !(1 << 31)
-                            ),
+                            &tr!("Now editing item group with group ID {}", new_item.get_id()),
                         )?;
                     } else {
                         display::header(
                             w,
-                            &format!("Now editing item with part ID {}", new_item.get_id()),
+                            &tr!("Now editing item with part ID {}", new_item.get_id()),
                         )?;
                     }
                 }
@@ -126,19 +213,72 @@ impl Mode {
             ViewStatistics { stats } => {
                 display::iter(w, stats.to_string().split("\n"))?;
             }
+            ViewSetCompletion {
+                sets,
+                sort_by,
+                include_spares,
+            } => {
+                match sort_by {
+                    SortBy::Completion => {
+                        display::header(w, tr!("Completion of incomplete sets, closest first"))?
+                    }
+                    SortBy::Number => {
+                        display::header(w, tr!("Completion of incomplete sets, by set number"))?
+                    }
+                }
+                if *include_spares {
+                    display::line(w, tr!("Spare parts are needed for completion."))?;
+                } else {
+                    display::line(w, tr!("Spare parts are not needed for completion."))?;
+                }
+                if sets.is_empty() {
+                    display::line(w, tr!("No incomplete sets have been added to the config"))?;
+                }
+                display::iter(w, sets.iter())?;
+            }
         }
         Ok(())
     }
 }
 
+/// What the interactive program starts in, given with `--mode` in place of the default menu.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StartMode {
+    /// Asks what to search by right away.
+    Search,
+    Statistics,
+    Sets,
+}
+
+impl StartMode {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "search" => Some(StartMode::Search),
+            "stats" | "statistics" => Some(StartMode::Statistics),
+            "sets" => Some(StartMode::Sets),
+            _ => None,
+        }
+    }
+
+    /// The command that is run to get to the mode from the default menu.
+    pub fn get_cmd(&self) -> Cmd {
+        match self {
+            StartMode::Search => Cmd::MCmd(MultiCmd::SearchItem),
+            StartMode::Statistics => Cmd::ViewStats,
+            StartMode::Sets => Cmd::ViewSets,
+        }
+    }
+}
+
 impl fmt::Display for Mode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Mode::*;
         match self {
-            Default { .. } => write!(f, "Default"),
-            DisplayItem { .. } => write!(f, "Display Item"),
-            EditItem { .. } => write!(f, "Edit Item"),
-            ViewStatistics { .. } => write!(f, "View Statistics"),
+            Default { .. } => write!(f, "{}", tr!("Default")),
+            DisplayItem { .. } => write!(f, "{}", tr!("Display Item")),
+            EditItem { .. } => write!(f, "{}", tr!("Edit Item")),
+            ViewStatistics { .. } => write!(f, "{}", tr!("View Statistics")),
+            ViewSetCompletion { .. } => write!(f, "{}", tr!("View Sets")),
         }
     }
 }
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+/// The keys of an API that signs its requests with OAuth 1.0a, like that of BrickLink, of which
+/// two stand for the program and two for the access given to it. Kept in the secrets file.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct OAuthKeys {
+    pub consumer_key: String,
+    pub consumer_secret: String,
+    pub token: String,
+    pub token_secret: String,
+}
+
+/// Percent-encodes everything but the unreserved characters, as signatures are made of.
+pub fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// SHA-1 is only needed for signing, so it is written out here instead of depending on a crate.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, word) in digest.chunks_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+fn hmac_sha1(key: &[u8], message: &[u8]) -> [u8; 20] {
+    let mut block = [0u8; 64];
+    if key.len() > block.len() {
+        block[..20].copy_from_slice(&sha1(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| -> Vec<u8> { block.iter().map(|b| b ^ byte).collect() };
+    let mut inner = pad(0x36);
+    inner.extend_from_slice(message);
+    let mut outer = pad(0x5c);
+    outer.extend_from_slice(&sha1(&inner));
+    sha1(&outer)
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut s = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+/// The Authorization header of a request to the URL, which is given without its query, with the
+/// parameters of the query signed along with those of OAuth.
+pub fn get_authorization(
+    keys: &OAuthKeys,
+    method: &str,
+    url: &str,
+    query: &[(&str, &str)],
+    nonce: &str,
+    timestamp: u64,
+) -> String {
+    let timestamp = timestamp.to_string();
+    let oauth = [
+        ("oauth_consumer_key", keys.consumer_key.as_str()),
+        ("oauth_nonce", nonce),
+        ("oauth_signature_method", "HMAC-SHA1"),
+        ("oauth_timestamp", &timestamp),
+        ("oauth_token", keys.token.as_str()),
+        ("oauth_version", "1.0"),
+    ];
+    let mut params: Vec<(String, String)> = oauth
+        .iter()
+        .chain(query)
+        .map(|(name, value)| (encode(name), encode(value)))
+        .collect();
+    params.sort();
+    let params: Vec<String> = params
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    let base = format!("{}&{}&{}", method, encode(url), encode(&params.join("&")));
+    let key = format!(
+        "{}&{}",
+        encode(&keys.consumer_secret),
+        encode(&keys.token_secret)
+    );
+    let signature = to_base64(&hmac_sha1(key.as_bytes(), base.as_bytes()));
+
+    let fields: Vec<String> = oauth
+        .iter()
+        .chain([&("oauth_signature", signature.as_str())])
+        .map(|(name, value)| format!("{}=\"{}\"", name, encode(value)))
+        .collect();
+    format!("OAuth realm=\"\", {}", fields.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        let hex =
+            |digest: [u8; 20]| -> String { digest.iter().map(|b| format!("{:02x}", b)).collect() };
+        assert_eq!(
+            hex(sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(hmac_sha1(b"Jefe", b"what do ya want for nothing?")),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+        assert_eq!(to_base64(b"ab"), "YWI=");
+        assert_eq!(encode("a b&c~"), "a%20b%26c~");
+
+        // The example of the OAuth 1.0 specification.
+        let keys = OAuthKeys {
+            consumer_key: "dpf43f3p2l4k3l03".to_string(),
+            consumer_secret: "kd94hf93k423kf44".to_string(),
+            token: "nnch734d00sl2jdk".to_string(),
+            token_secret: "pfkkdhi9sl3r4s00".to_string(),
+        };
+        let authorization = get_authorization(
+            &keys,
+            "GET",
+            "http://photos.example.net/photos",
+            &[("file", "vacation.jpg"), ("size", "original")],
+            "kllo9940pd9333jh",
+            1191242096,
+        );
+        assert!(authorization.contains("oauth_signature=\"tR3%2BTy81lMeYAr%2FFid0kMTYa%2FWM%3D\""));
+    }
+}
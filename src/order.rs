@@ -0,0 +1,461 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::adjustment::{self, Adjustment, Reason};
+use crate::color;
+use crate::currency::{self, Amount, Price};
+use crate::data::{ColorGroup, Item};
+use crate::error::{Error, Result};
+use crate::io;
+use crate::locale::tr;
+use crate::timestamp::Timestamp;
+use crate::types::{PartId, Quantity};
+
+/// Parts received in an order, as read from a downloaded order file.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Order {
+    number: String,
+    /// Seconds since the unix epoch when the order was taken in.
+    #[serde(default)]
+    time: u64,
+    lines: Vec<OrderLine>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct OrderLine {
+    id: PartId,
+    /// The color as given in the order file.
+    color: String,
+    quantity: u32,
+    /// The price paid per piece.
+    price: Option<Price>,
+}
+
+impl Order {
+    pub fn get_number(&self) -> &str {
+        &self.number
+    }
+
+    pub fn get_time(&self) -> Timestamp {
+        Timestamp::from_secs(self.time)
+    }
+
+    /// The price paid for the lines with a price, which is none if no line has one. Lines are
+    /// priced in the currency of the order.
+    pub fn get_total(&self) -> Option<Price> {
+        let mut prices = self.lines.iter().filter_map(|line| {
+            line.price.as_ref().map(|price| {
+                (
+                    price.get_amount().times(line.quantity),
+                    price.get_currency(),
+                )
+            })
+        });
+        let (mut total, currency) = prices.next()?;
+        for (amount, _) in prices {
+            total += amount;
+        }
+        Some(Price::new(total, currency))
+    }
+}
+
+/// Returns the text between the first pair of the given tags, e.g. `<QTY>4</QTY>`.
+pub fn get_tag<'a>(element: &'a str, tag: &str) -> Option<&'a str> {
+    let start = element.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + element[start..].find(&format!("</{}>", tag))?;
+    Some(element[start..end].trim())
+}
+
+fn parse_id(id: &str) -> Result<PartId> {
+    id.trim()
+        .parse()
+        .ok()
+        .and_then(PartId::new)
+        .ok_or_else(|| Error::InvalidOrder {
+            msg: format!("{} is not a valid part ID", id),
+        })
+}
+
+fn parse_quantity(quantity: &str) -> Result<u32> {
+    Quantity::parse(quantity)
+        .map(|quantity| quantity.get())
+        .ok_or_else(|| Error::InvalidOrder {
+            msg: format!("{} is not a valid number of pieces", quantity),
+        })
+}
+
+/// Parses an order downloaded from BrickLink as XML.
+fn parse_xml(contents: &str) -> Result<Order> {
+    let number = get_tag(contents, "ORDERID").unwrap_or_default().to_string();
+    let currency = get_tag(contents, "BASECURRENCYCODE")
+        .unwrap_or_else(|| currency::get_home_currency())
+        .to_string();
+
+    let mut lines = Vec::new();
+    for element in contents.split("<ITEM>").skip(1) {
+        let item_id = get_tag(element, "ITEMID").unwrap_or_default();
+        lines.push(OrderLine {
+            id: parse_id(item_id)?,
+            color: get_tag(element, "COLOR").unwrap_or_default().to_string(),
+            quantity: parse_quantity(get_tag(element, "QTY").unwrap_or_default())?,
+            price: get_tag(element, "PRICE")
+                .and_then(Amount::parse)
+                .map(|amount| Price::new(amount, &currency)),
+        });
+    }
+    Ok(Order {
+        number,
+        time: 0,
+        lines,
+    })
+}
+
+/// Parses an order as CSV with a header naming the part, color, quantity and optionally price
+/// columns, as exported by Rebrickable. The price is in the home currency.
+fn parse_csv(contents: &str, number: String) -> Result<Order> {
+    let mut rows = contents.lines().filter(|row| !row.trim().is_empty());
+    let header: Vec<String> = rows
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let (Some(part), Some(quantity)) = (column("part"), column("quantity")) else {
+        return Err(Error::InvalidOrder {
+            msg: "the header has no part and quantity columns".to_string(),
+        });
+    };
+    let (color, price) = (column("color"), column("price"));
+
+    let mut lines = Vec::new();
+    for row in rows {
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        let field = |i: Option<usize>| i.and_then(|i| fields.get(i)).copied();
+        lines.push(OrderLine {
+            id: parse_id(field(Some(part)).unwrap_or_default())?,
+            color: field(color).unwrap_or_default().to_string(),
+            quantity: parse_quantity(field(Some(quantity)).unwrap_or_default())?,
+            price: field(price)
+                .and_then(Amount::parse)
+                .map(|amount| Price::new(amount, currency::get_home_currency())),
+        });
+    }
+    Ok(Order {
+        number,
+        time: 0,
+        lines,
+    })
+}
+
+/// Reads an order file, which is parsed as BrickLink XML if it has the xml extension and as CSV
+/// otherwise. Orders in CSV files are numbered by the name of the file.
+pub fn read(path: &Path) -> Result<Order> {
+    let contents = fs::read_to_string(path)?;
+    let mut order = if path.extension().is_some_and(|ext| ext == "xml") {
+        parse_xml(&contents)?
+    } else {
+        let number = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        parse_csv(&contents, number)?
+    };
+    order.time = adjustment::now();
+    Ok(order)
+}
+
+/// Finds the index of the lot the pieces of the order line go into. Items with several lots must
+/// have one that is named after the color, or an alias of it, or that holds all colors, and the
+/// first of those is taken.
+fn find_lot(item: &Item, color: &str) -> Option<usize> {
+    let color = color::resolve(color);
+    let lots = item.get_locations();
+    if lots.len() == 1 {
+        return Some(0);
+    }
+    lots.iter()
+        .position(|lot| match lot.get_color_group() {
+            ColorGroup::Other(name) => color::resolve(name).eq_ignore_ascii_case(color),
+            _ => false,
+        })
+        .or_else(|| {
+            lots.iter()
+                .position(|lot| *lot.get_color_group() == ColorGroup::All)
+        })
+}
+
+/// Adds the received pieces to the items. Items without a price get the price paid. Returns the
+/// quantity changes, and a message for each line that has to be added by hand because the part is
+/// missing, it is unclear which lot the pieces go into, or the lot is not counted.
+pub fn take_in(order: &Order, items: &mut [Item]) -> (Vec<Adjustment>, Vec<String>) {
+    let mut adjustments = Vec::new();
+    let mut skipped = Vec::new();
+    for line in order.lines.iter() {
+        let item = items
+            .iter_mut()
+            .find(|item| item.get_id() == line.id || item.get_alternative_ids().contains(&line.id));
+        let Some(item) = item else {
+            skipped.push(tr!("Part {} is not in the database", line.id));
+            continue;
+        };
+        let Some(i) = find_lot(item, &line.color) else {
+            skipped.push(tr!(
+                "Part {} has no lot for color {}, add the {} pieces by hand",
+                line.id,
+                line.color,
+                line.quantity
+            ));
+            continue;
+        };
+
+        let lot = &item.get_locations()[i];
+        let color_group = lot.get_color_group().clone();
+        let Some(quantity) = lot.get_quantity() else {
+            skipped.push(tr!(
+                "The pieces of part {} in {} are not counted, add the {} pieces by hand",
+                line.id,
+                color_group,
+                line.quantity
+            ));
+            continue;
+        };
+
+        let old_item = item.clone();
+        item.set_lot_quantity(i, Some(Quantity::new(quantity.get() + line.quantity)));
+        let purchased = match order.time {
+            0 => Timestamp::now(),
+            time => Timestamp::from_secs(time),
+        };
+        item.set_lot_purchased(i, Some(purchased));
+        if item.get_price().is_none() {
+            item.set_price(line.price.clone());
+        }
+        let reasons = BTreeMap::from([(color_group, Reason::Bought)]);
+        adjustments.append(&mut adjustment::get_adjustments(&old_item, item, &reasons));
+    }
+    (adjustments, skipped)
+}
+
+/// Lines are taken in and checkpointed in batches of this size.
+const CHECKPOINT_EVERY: usize = 500;
+
+/// The progress of taking in an order, saved after every batch of lines so that an interrupted
+/// import can be resumed. The database itself is only written when the whole order is taken in.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Checkpoint {
+    number: String,
+    lines_done: usize,
+    /// The items of the lines taken in so far, as they are after taking them in.
+    items: Vec<Item>,
+    adjustments: Vec<Adjustment>,
+    skipped: Vec<String>,
+}
+
+pub fn get_checkpoint_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("import")
+}
+
+fn read_checkpoint(path: &Path) -> Result<Option<Checkpoint>> {
+    match io::read_private(path) {
+        Ok(contents) => Ok(Some(
+            serde_yaml::from_str(&contents).map_err(term_lib::Error::from)?,
+        )),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes the checkpoint to a temporary file first, so that an interruption leaves either the old
+/// or the new checkpoint. It is encrypted like the database, as it holds the items.
+fn write_checkpoint(path: &Path, checkpoint: &Checkpoint) -> Result<()> {
+    let tmp_path = path.with_extension("import.tmp");
+    let contents = serde_yaml::to_string(checkpoint).map_err(term_lib::Error::from)?;
+    io::write_private(&tmp_path, &contents)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Takes in the order like `take_in`, checkpointing the progress after every batch of lines. A
+/// checkpoint of the same order is resumed from, unless starting over, and is removed by
+/// `finish_import` once the changes are written. Returns the line the import was resumed from.
+pub fn take_in_resumable(
+    order: &Order,
+    items: &mut Vec<Item>,
+    checkpoint_path: &Path,
+    start_over: bool,
+) -> Result<(Vec<Adjustment>, Vec<String>, usize)> {
+    let mut checkpoint = match read_checkpoint(checkpoint_path)? {
+        Some(checkpoint) if !start_over && checkpoint.number != order.number => {
+            return Err(Error::ImportInProgress {
+                number: checkpoint.number,
+            })
+        }
+        Some(checkpoint) if !start_over => checkpoint,
+        _ => Checkpoint {
+            number: order.number.clone(),
+            lines_done: 0,
+            items: Vec::new(),
+            adjustments: Vec::new(),
+            skipped: Vec::new(),
+        },
+    };
+    let resumed_from = checkpoint.lines_done.min(order.lines.len());
+    for item in checkpoint.items.iter() {
+        match items.iter_mut().find(|i| i.get_id() == item.get_id()) {
+            Some(old) => *old = item.clone(),
+            None => items.push(item.clone()),
+        }
+    }
+
+    for lines in order.lines[resumed_from..].chunks(CHECKPOINT_EVERY) {
+        let batch = Order {
+            number: order.number.clone(),
+            time: order.time,
+            lines: lines.to_vec(),
+        };
+        let (mut adjustments, mut skipped) = take_in(&batch, items);
+        checkpoint.adjustments.append(&mut adjustments);
+        checkpoint.skipped.append(&mut skipped);
+        for line in lines {
+            let item = items.iter().find(|item| {
+                item.get_id() == line.id || item.get_alternative_ids().contains(&line.id)
+            });
+            let Some(item) = item else {
+                continue;
+            };
+            match checkpoint
+                .items
+                .iter_mut()
+                .find(|i| i.get_id() == item.get_id())
+            {
+                Some(old) => *old = item.clone(),
+                None => checkpoint.items.push(item.clone()),
+            }
+        }
+        checkpoint.lines_done += lines.len();
+        write_checkpoint(checkpoint_path, &checkpoint)?;
+    }
+    Ok((checkpoint.adjustments, checkpoint.skipped, resumed_from))
+}
+
+/// Removes the checkpoint after the order was taken in.
+pub fn finish_import(checkpoint_path: &Path) -> Result<()> {
+    match fs::remove_file(checkpoint_path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+pub fn get_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("orders")
+}
+
+/// Appends the order to the log of orders taken in.
+pub fn append(path: &Path, order: &Order) -> Result<()> {
+    let appended = format!(
+        "---\n{}",
+        serde_yaml::to_string(order).map_err(term_lib::Error::from)?
+    );
+    io::append_private(path, &appended)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_take_in() {
+        let xml = "<ORDERS><ORDER><ORDERID>1234</ORDERID><BASECURRENCYCODE>EUR</BASECURRENCYCODE>\
+            <ITEM><ITEMTYPE>P</ITEMTYPE><ITEMID>3001</ITEMID><COLOR>11</COLOR><QTY>4</QTY>\
+            <PRICE>0.10</PRICE></ITEM>\
+            <ITEM><ITEMTYPE>P</ITEMTYPE><ITEMID>3003</ITEMID><COLOR>5</COLOR><QTY>2</QTY></ITEM>\
+            </ORDER></ORDERS>";
+        let order = parse_xml(xml).unwrap();
+        assert_eq!(order.get_number(), "1234");
+        assert_eq!(order.lines.len(), 2);
+        assert_eq!(
+            order.get_total(),
+            Some(Price::new(Amount::from_cents(40), "EUR"))
+        );
+
+        let csv = "Part,Color,Quantity\n3001,11,4\n3003,5,2\n";
+        let mut csv_order = parse_csv(csv, "1234".to_string()).unwrap();
+        csv_order.lines[0].price = Some(Price::new(Amount::from_cents(10), "EUR"));
+        assert_eq!(csv_order, order);
+
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::All, "A1".to_string());
+        item.set_quantity(&ColorGroup::All, Some(Quantity::new(0)));
+        let mut items = vec![item];
+        let (adjustments, skipped) = take_in(&order, &mut items);
+        assert_eq!(adjustments.len(), 1);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(items[0].get_quantity(), Quantity::new(4));
+        assert_eq!(
+            items[0].get_price(),
+            Some(&Price::new(Amount::from_cents(10), "EUR"))
+        );
+
+        // Of several lots holding all colors, the pieces go into the first one.
+        let mut lots = items[0].get_locations().to_vec();
+        lots.push(lots[0].clone());
+        items[0].set_locations(lots);
+        items[0].set_lot_quantity(1, Some(Quantity::new(1)));
+        items[0].set_lot_purchased(1, None);
+        take_in(&order, &mut items);
+        let lots = items[0].get_locations();
+        assert_eq!(lots[0].get_quantity(), Some(Quantity::new(8)));
+        assert!(lots[0].get_purchased().is_some());
+        assert_eq!(lots[1].get_quantity(), Some(Quantity::new(1)));
+        assert_eq!(lots[1].get_purchased(), None);
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let path = PathBuf::from("test_resume.import");
+        let line = |quantity| OrderLine {
+            id: PartId::new(3001).unwrap(),
+            color: String::new(),
+            quantity,
+            price: None,
+        };
+        let mut order = Order {
+            number: "1234".to_string(),
+            time: 0,
+            lines: vec![line(1); CHECKPOINT_EVERY + 1],
+        };
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::All, "A1".to_string());
+        item.set_quantity(&ColorGroup::All, Some(Quantity::new(0)));
+
+        // Interrupted after the first batch, before the last line.
+        let last = order.lines.pop().unwrap();
+        let mut items = vec![item.clone()];
+        take_in_resumable(&order, &mut items, &path, false).unwrap();
+        order.lines.push(last);
+
+        let mut items = vec![item.clone()];
+        let (adjustments, _, resumed_from) =
+            take_in_resumable(&order, &mut items, &path, false).unwrap();
+        let other = Order {
+            number: "5678".to_string(),
+            ..order.clone()
+        };
+        let in_progress = take_in_resumable(&other, &mut vec![item], &path, false);
+        finish_import(&path).unwrap();
+
+        assert_eq!(resumed_from, CHECKPOINT_EVERY);
+        assert_eq!(adjustments.len(), CHECKPOINT_EVERY + 1);
+        assert_eq!(
+            items[0].get_quantity(),
+            Quantity::new(CHECKPOINT_EVERY as u32 + 1)
+        );
+        assert!(matches!(in_progress, Err(Error::ImportInProgress { .. })));
+    }
+}
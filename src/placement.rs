@@ -0,0 +1,145 @@
+use std::fmt;
+
+use crate::data::{ColorGroup, Item, Lot};
+use crate::locale::tr;
+use crate::similar::{self, Relation};
+use crate::types::PartId;
+
+/// How many locations are suggested for a part.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Parts whose IDs are at most this far apart are often of the same kind, e.g. 3001 and 3003.
+const NEARBY_IDS: u32 = 10;
+
+/// Why a location is suggested for a part.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Basis {
+    /// The part is already stored there in the color group.
+    SameLot,
+    /// The part is stored there in another color group.
+    OtherColorGroup,
+    /// A similar part is stored there.
+    Similar(Relation),
+    /// A part with a nearby part ID is stored there.
+    NearbyId(PartId),
+}
+
+impl fmt::Display for Basis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let basis = match self {
+            Basis::SameLot => tr!("already stored there").to_string(),
+            Basis::OtherColorGroup => tr!("stored there in another color group").to_string(),
+            Basis::Similar(relation) => tr!("a similar part is stored there ({})", relation),
+            Basis::NearbyId(id) => tr!("part {} is stored there", id),
+        };
+        write!(f, "{}", basis)
+    }
+}
+
+/// A location to store pieces of a part in.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Suggestion {
+    pub location: String,
+    pub basis: Basis,
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.basis)
+    }
+}
+
+/// The lots of the item with those in the color group first, as they are better suggestions.
+fn by_color_group<'a>(item: &'a Item, color_group: &ColorGroup) -> Vec<&'a Lot> {
+    let mut lots: Vec<&Lot> = item.get_locations().iter().collect();
+    lots.sort_by_key(|lot| lot.get_color_group() != color_group);
+    lots
+}
+
+/// Suggests where to store pieces of the part in the color group, best first, from where the part
+/// and similar parts are stored. Parts not in the database are given as a new item.
+pub fn suggest(
+    items: &[Item],
+    item: &Item,
+    color_group: &ColorGroup,
+    substitutes: &[Vec<PartId>],
+) -> Vec<Suggestion> {
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    let mut add = |location: &String, basis| {
+        if !suggestions.iter().any(|s| &s.location == location) {
+            suggestions.push(Suggestion {
+                location: location.clone(),
+                basis,
+            });
+        }
+    };
+    for lot in by_color_group(item, color_group) {
+        let basis = if lot.get_color_group() == color_group {
+            Basis::SameLot
+        } else {
+            Basis::OtherColorGroup
+        };
+        add(lot.get_location(), basis);
+    }
+    for (other, relation) in similar::find(items, item, substitutes) {
+        for lot in by_color_group(other, color_group) {
+            add(lot.get_location(), Basis::Similar(relation));
+        }
+    }
+    let number = item.get_id().get_number();
+    let mut nearby: Vec<&Item> = items
+        .iter()
+        .filter(|other| !other.is_group() && other.get_id() != item.get_id())
+        .filter(|other| other.get_id().get_number().abs_diff(number) <= NEARBY_IDS)
+        .collect();
+    nearby.sort_by_key(|other| other.get_id().get_number().abs_diff(number));
+    for other in nearby {
+        for lot in by_color_group(other, color_group) {
+            add(lot.get_location(), Basis::NearbyId(other.get_id()));
+        }
+    }
+
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest() {
+        let id = |id| PartId::new(id).unwrap();
+        let red = ColorGroup::Other("Red".to_string());
+        let item = |number, lots: &[(&ColorGroup, &str)]| {
+            let mut item = Item::new(id(number), number.to_string());
+            for (color_group, location) in lots {
+                item.add_color_group((*color_group).clone(), location.to_string());
+            }
+            item
+        };
+        let brick = item(3001, &[(&ColorGroup::Basic, "A1"), (&red, "A2")]);
+        let items = vec![
+            brick.clone(),
+            item(3003, &[(&red, "B1"), (&ColorGroup::Basic, "A1")]),
+            item(3040, &[(&red, "C1")]),
+        ];
+
+        let locations = |item: &Item| -> Vec<String> {
+            suggest(&items, item, &red, &[])
+                .into_iter()
+                .map(|s| s.location)
+                .collect()
+        };
+        assert_eq!(locations(&brick), vec!["A2", "A1", "B1"]);
+        assert_eq!(
+            locations(&Item::new(id(3002), String::new())),
+            vec!["A2", "A1", "B1"]
+        );
+        assert!(locations(&Item::new(id(60000), String::new())).is_empty());
+
+        let suggestions = suggest(&items, &brick, &red, &[]);
+        assert_eq!(suggestions[0].basis, Basis::SameLot);
+        assert_eq!(suggestions[2].basis, Basis::NearbyId(id(3003)));
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+/// The size the terminal is taken to have when it can not be asked for it.
+const DEFAULT_SIZE: (usize, usize) = (80, 24);
+
+/// Whether the output shows styles given as escape sequences. They are left out when the output is
+/// piped or redirected to a file. The legacy console of Windows only shows them once it is told
+/// to, which fails on versions before Windows 10.
+pub fn supports_styles() -> bool {
+    if !std::io::stdout().is_terminal() {
+        return false;
+    }
+    #[cfg(windows)]
+    return crossterm::ansi_support::supports_ansi();
+    #[cfg(not(windows))]
+    return true;
+}
+
+/// The columns and rows of the terminal. Consoles on Windows give a size of zero when the output
+/// is redirected, which is treated like not knowing the size.
+pub fn get_size() -> (usize, usize) {
+    to_size(crossterm::terminal::size().ok())
+}
+
+fn to_size(size: Option<(u16, u16)>) -> (usize, usize) {
+    match size {
+        Some((cols, rows)) if cols != 0 && rows != 0 => (cols as usize, rows as usize),
+        _ => DEFAULT_SIZE,
+    }
+}
+
+/// Which of Ctrl and Alt were held for the character. Windows reports characters typed with
+/// AltGr, like `@` or `{` on many European layouts, as typed with Ctrl and Alt, so those are taken
+/// as typed without modifiers. Letters keep both, as AltGr does not give them.
+pub fn get_modifiers(c: char, ctrl: bool, alt: bool) -> (bool, bool) {
+    if ctrl && alt && !c.is_ascii_alphabetic() {
+        (false, false)
+    } else {
+        (ctrl, alt)
+    }
+}
+
+/// The path written in the config, where a leading `~` stands for the home directory on every
+/// platform, followed by either kind of slash.
+pub fn expand_path(path: &str) -> PathBuf {
+    let home = directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    expand_home(path, home.as_deref())
+}
+
+fn expand_home(path: &str, home: Option<&Path>) -> PathBuf {
+    let Some(home) = home else {
+        return PathBuf::from(path);
+    };
+    match path.strip_prefix('~') {
+        Some("") => home.to_path_buf(),
+        Some(rest) if rest.starts_with(['/', '\\']) => {
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform() {
+        let home = Path::new("home");
+        assert_eq!(expand_home("~", Some(home)), home);
+        assert_eq!(expand_home("~/lego.yml", Some(home)), home.join("lego.yml"));
+        assert_eq!(
+            expand_home("~\\lego.yml", Some(home)),
+            home.join("lego.yml")
+        );
+        assert_eq!(expand_home("~lego.yml", Some(home)), Path::new("~lego.yml"));
+        assert_eq!(expand_home("~/lego.yml", None), Path::new("~/lego.yml"));
+        assert_eq!(
+            expand_home("C:\\lego.yml", Some(home)),
+            Path::new("C:\\lego.yml")
+        );
+
+        assert_eq!(get_modifiers('@', true, true), (false, false));
+        assert_eq!(get_modifiers('d', true, true), (true, true));
+        assert_eq!(get_modifiers('d', true, false), (true, false));
+
+        assert_eq!(to_size(Some((120, 40))), (120, 40));
+        assert_eq!(to_size(Some((0, 0))), DEFAULT_SIZE);
+        assert_eq!(to_size(None), DEFAULT_SIZE);
+    }
+}
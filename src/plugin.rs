@@ -0,0 +1,162 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::Item;
+use crate::error::{Error, Result};
+
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+/// What a plugin is given to work on.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// The item shown.
+    Item,
+    /// The items matching a filter that is asked for.
+    Filter,
+    /// All items.
+    Database,
+}
+
+/// An external program run as a command, which is given the items of its scope as JSON on stdin
+/// and may answer with JSON on stdout, see `Output`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Plugin {
+    key: char,
+    /// What the plugin does, shown in the list of commands like `print (l)abels`.
+    label: String,
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+    scope: Scope,
+    /// Whether the items the plugin answers with are written to the database. Such plugins are
+    /// disabled in read-only sessions.
+    #[serde(default)]
+    changes_items: bool,
+}
+
+impl Plugin {
+    pub fn get_key(&self) -> char {
+        self.key
+    }
+
+    pub fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn get_scope(&self) -> Scope {
+        self.scope
+    }
+
+    pub fn changes_items(&self) -> bool {
+        self.changes_items
+    }
+}
+
+/// Sets the plugins used for the rest of the session. Can only be set once.
+pub fn init(plugins: Vec<Plugin>) {
+    let _ = PLUGINS.set(plugins);
+}
+
+pub fn get_plugins() -> &'static [Plugin] {
+    PLUGINS.get().map_or(&[], Vec::as_slice)
+}
+
+pub fn get(i: usize) -> Option<&'static Plugin> {
+    get_plugins().get(i)
+}
+
+#[derive(Serialize)]
+struct Input<'a> {
+    scope: Scope,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filter: Option<&'a str>,
+    items: &'a [&'a Item],
+}
+
+/// The answer of a plugin, where both fields may be left out.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+pub struct Output {
+    /// Shown when the plugin is done.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Items to add or replace, for plugins that change items.
+    #[serde(default)]
+    pub items: Vec<Item>,
+}
+
+/// Runs the plugin on the items, with the filter that matched them for plugins of that scope.
+pub fn run(plugin: &Plugin, filter: Option<&str>, items: &[&Item]) -> Result<Output> {
+    let failed = |msg: String| Error::PluginFailed {
+        label: plugin.label.clone(),
+        msg,
+    };
+    let input = Input {
+        scope: plugin.scope,
+        filter,
+        items,
+    };
+    let input = serde_json::to_vec(&input).map_err(|e| failed(e.to_string()))?;
+
+    let mut child = Command::new(&plugin.command)
+        .args(&plugin.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| failed(e.to_string()))?;
+    // The input is written from another thread so that plugins answering before they have read
+    // all of it do not block. Plugins that do not read it at all close stdin, which is fine.
+    let writer = child.stdin.take().map(|mut stdin| {
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        })
+    });
+    let output = child.wait_with_output()?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(failed(format!("{}: {}", output.status, stderr.trim())));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        return Ok(Output::default());
+    }
+    serde_json::from_str(&stdout).map_err(|e| failed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PartId;
+
+    #[test]
+    fn test_run() {
+        let plugin = |script: &str| Plugin {
+            key: 'x',
+            label: "test".to_string(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            scope: Scope::Filter,
+            changes_items: false,
+        };
+        let item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+
+        let echo =
+            plugin("grep -c '\"filter\":\"brick\"' > /dev/null && echo '{\"message\":\"hi\"}'");
+        let output = run(&echo, Some("brick"), &[&item]).unwrap();
+        assert_eq!(output.message.as_deref(), Some("hi"));
+        assert_eq!(run(&plugin("true"), None, &[]).unwrap(), Output::default());
+        assert!(matches!(
+            run(&plugin("echo oops >&2; exit 3"), None, &[]),
+            Err(Error::PluginFailed { .. })
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::data::{ColorGroup, Item};
+use crate::locale::tr_n;
+use crate::types::PartId;
+
+/// Moving a lot from an overfull location to one with room for it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Move {
+    pub part_id: PartId,
+    /// The index of the lot among the lots of the part.
+    pub lot: usize,
+    pub color_group: ColorGroup,
+    pub from: String,
+    pub to: String,
+    pub pieces: u32,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pieces = tr_n!("{} piece", "{} pieces", self.pieces as usize);
+        write!(
+            f,
+            "{} {} ({}): {} -> {}",
+            self.part_id, self.color_group, pieces, self.from, self.to
+        )
+    }
+}
+
+/// Proposes moves of whole lots out of the locations holding more pieces than their capacity,
+/// into locations with room to spare, until no location is overfull or no lot fits anywhere.
+/// Locations already holding the part are preferred, then those with the most room. Lots whose
+/// number of pieces is not known are never moved.
+pub fn propose(items: &[Item], get_capacity: impl Fn(&str) -> Option<u32>) -> Vec<Move> {
+    let mut fill: BTreeMap<&str, u32> = BTreeMap::new();
+    for item in items {
+        for lot in item.get_locations() {
+            let pieces = lot
+                .get_quantity()
+                .or_else(|| item.get_estimated_quantity(lot))
+                .map_or(0, |q| q.get());
+            *fill.entry(lot.get_location()).or_insert(0) += pieces;
+        }
+    }
+    let capacities: BTreeMap<&str, u32> = fill
+        .keys()
+        .filter_map(|location| Some((*location, get_capacity(location)?)))
+        .collect();
+    let excess = |fill: &BTreeMap<&str, u32>, location: &str| {
+        capacities
+            .get(location)
+            .map_or(0, |capacity| fill[location].saturating_sub(*capacity))
+    };
+
+    let mut overfull: Vec<&str> = capacities
+        .keys()
+        .copied()
+        .filter(|location| excess(&fill, location) > 0)
+        .collect();
+    overfull.sort_by_key(|location| std::cmp::Reverse(excess(&fill, location)));
+
+    let mut moves = Vec::new();
+    for from in overfull {
+        // The largest lots are moved first, so that as few lots as possible are moved.
+        let mut lots: Vec<(&Item, usize, &ColorGroup, u32)> = items
+            .iter()
+            .flat_map(|item| {
+                item.get_locations()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, lot)| lot.get_location() == from)
+                    .filter_map(move |(i, lot)| {
+                        let pieces = lot
+                            .get_quantity()
+                            .or_else(|| item.get_estimated_quantity(lot))?
+                            .get();
+                        Some((item, i, lot.get_color_group(), pieces))
+                    })
+            })
+            .collect();
+        lots.sort_by_key(|(_, _, _, pieces)| std::cmp::Reverse(*pieces));
+
+        for (item, i, color_group, pieces) in lots {
+            if excess(&fill, from) == 0 {
+                break;
+            }
+            let holds_part = |location: &str| {
+                item.get_locations()
+                    .iter()
+                    .any(|l| l.get_location() == location)
+            };
+            let to = capacities
+                .iter()
+                .filter(|(location, capacity)| {
+                    **location != from && fill[*location] + pieces <= **capacity
+                })
+                .max_by_key(|(location, capacity)| {
+                    (holds_part(location), **capacity - fill[*location])
+                })
+                .map(|(location, _)| *location);
+            let Some(to) = to else {
+                continue;
+            };
+            *fill.get_mut(from).unwrap() -= pieces;
+            *fill.get_mut(to).unwrap() += pieces;
+            moves.push(Move {
+                part_id: item.get_id(),
+                lot: i,
+                color_group: color_group.clone(),
+                from: from.to_string(),
+                to: to.to_string(),
+                pieces,
+            });
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Lot;
+    use crate::types::Quantity;
+
+    #[test]
+    fn test_propose() {
+        let item = |id, lots: &[(&str, ColorGroup, u32)]| {
+            let mut item = Item::new(PartId::new(id).unwrap(), String::new());
+            for (location, color_group, pieces) in lots {
+                item.add_color_group(color_group.clone(), location.to_string());
+                item.set_quantity(color_group, Some(Quantity::new(*pieces)));
+            }
+            item
+        };
+        let items = vec![
+            item(
+                3001,
+                &[("A1", ColorGroup::Basic, 80), ("B1", ColorGroup::Grey, 10)],
+            ),
+            item(3002, &[("A1", ColorGroup::Basic, 30)]),
+            item(
+                3003,
+                &[("B1", ColorGroup::Basic, 20), ("C1", ColorGroup::Grey, 5)],
+            ),
+        ];
+        let capacity = |location: &str| match location {
+            "A1" => Some(100),
+            "B1" | "C1" => Some(60),
+            _ => None,
+        };
+        let moves = propose(&items, capacity);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].part_id, PartId::new(3002).unwrap());
+        assert_eq!((moves[0].from.as_str(), moves[0].to.as_str()), ("A1", "C1"));
+        assert_eq!(moves[0].lot, 0);
+
+        // Of the lots of a color group, only the one in the overfull location is moved.
+        let mut items = items;
+        let mut lots = vec![Lot::new(ColorGroup::Basic, "B1".to_string())];
+        lots.extend_from_slice(items[1].get_locations());
+        items[1].set_locations(lots);
+        let moves = propose(&items, capacity);
+        assert_eq!(moves.len(), 1);
+        assert_eq!((moves[0].lot, moves[0].to.as_str()), (1, "B1"));
+        let mut moved = items[1].clone();
+        moved.move_lot(moves[0].lot, moves[0].to.clone());
+        assert_eq!(moved.get_locations()[0], items[1].get_locations()[0]);
+        assert_eq!(moved.get_locations()[1].get_location(), "B1");
+        assert_eq!(
+            moved.get_locations()[1].get_quantity(),
+            Some(Quantity::new(30))
+        );
+
+        // Nothing fits when the other locations are too small.
+        let small = |location: &str| (location == "A1").then_some(100).or(Some(31));
+        assert!(propose(&items, small).is_empty());
+        assert!(propose(&items, |_: &str| None).is_empty());
+    }
+}
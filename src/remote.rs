@@ -0,0 +1,383 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{self, Item, RawDatabase};
+use crate::envelope;
+use crate::error::{Error, Result};
+use crate::io;
+use crate::locale::tr_n;
+use crate::sync::{self, Conflict};
+
+const ITEMS_PATH: &str = "/items";
+
+/// Where the sync server listens and where clients find it, with the token both sides use.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct RemoteConfig {
+    /// The address `serve` listens on, like `0.0.0.0:8642` to be reachable from other machines.
+    #[serde(default = "default_listen")]
+    listen: String,
+    /// The server that `sync` pulls from and pushes to, like `http://192.168.1.10:8642`.
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+fn default_listen() -> String {
+    "127.0.0.1:8642".to_string()
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            listen: default_listen(),
+            url: None,
+            token: None,
+        }
+    }
+}
+
+fn sync_error(msg: impl ToString) -> Error {
+    Error::SyncError {
+        msg: msg.to_string(),
+    }
+}
+
+impl RemoteConfig {
+    pub fn get_listen(&self) -> &str {
+        &self.listen
+    }
+
+    fn get_token(&self) -> Result<&str> {
+        self.token
+            .as_deref()
+            .ok_or_else(|| sync_error("there is no token for the sync server in the config"))
+    }
+
+    fn get_items_url(&self) -> Result<String> {
+        let url = self
+            .url
+            .as_deref()
+            .ok_or_else(|| sync_error("there is no URL of the sync server in the config"))?;
+        Ok(format!("{}{}", url.trim_end_matches('/'), ITEMS_PATH))
+    }
+}
+
+/// Identifies the contents of a database, so that pushes made from an outdated version are
+/// refused.
+fn get_version(items: &RawDatabase) -> Result<String> {
+    let mut hasher = DefaultHasher::new();
+    serde_yaml::to_string(items)
+        .map_err(term_lib::Error::from)?
+        .hash(&mut hasher);
+    Ok(format!("\"{:016x}\"", hasher.finish()))
+}
+
+/// A request to a server, taken apart from its connection.
+pub struct Request<'a> {
+    pub method: &'a str,
+    /// The path with the query, like `/search?filter=red`.
+    pub path: &'a str,
+    pub authorization: Option<&'a str>,
+    pub if_match: Option<&'a str>,
+    pub body: &'a str,
+}
+
+impl Request<'_> {
+    /// Whether the request carries the token.
+    pub fn has_token(&self, token: &str) -> bool {
+        self.authorization == Some(format!("Bearer {}", token).as_str())
+    }
+}
+
+pub struct Reply {
+    pub status: u16,
+    pub body: String,
+    pub content_type: &'static str,
+    /// The version of the items, given as the ETag.
+    pub version: Option<String>,
+}
+
+impl Reply {
+    pub fn status(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+            content_type: "text/plain; charset=utf-8",
+            version: None,
+        }
+    }
+
+    /// Answers with the value as JSON in an envelope of the kind, see `envelope`.
+    pub fn json(kind: &str, value: &impl Serialize) -> Result<Self> {
+        Ok(Self {
+            status: 200,
+            body: envelope::to_json(kind, value)?,
+            content_type: "application/json",
+            version: None,
+        })
+    }
+}
+
+/// Answers a request to get the items, or to replace them with a version made from the current
+/// one, which is given as its ETag.
+fn handle(db_path: &Path, token: &str, request: Request) -> Result<Reply> {
+    if !request.has_token(token) {
+        return Ok(Reply::status(401, "unauthorized"));
+    }
+    if request.path != ITEMS_PATH {
+        return Ok(Reply::status(404, "not found"));
+    }
+
+    let items = data::read_items(db_path)?;
+    let version = get_version(&items)?;
+    match request.method {
+        "GET" => Ok(Reply {
+            status: 200,
+            body: serde_yaml::to_string(&items).map_err(term_lib::Error::from)?,
+            content_type: "application/yaml",
+            version: Some(version),
+        }),
+        "PUT" if request.if_match != Some(version.as_str()) => Ok(Reply::status(
+            412,
+            "the items changed since they were pulled",
+        )),
+        "PUT" => {
+            let Ok(items) = io::parse_yaml::<RawDatabase>(request.body) else {
+                return Ok(Reply::status(400, "the items could not be read"));
+            };
+            data::write_items(db_path, &items)?;
+            Ok(Reply {
+                version: Some(get_version(&items)?),
+                ..Reply::status(200, "")
+            })
+        }
+        _ => Ok(Reply::status(405, "method not allowed")),
+    }
+}
+
+/// Serves the database over HTTP to clients with the token until the program is stopped.
+pub fn serve(db_path: &Path, config: &RemoteConfig) -> Result<()> {
+    let token = config.get_token()?;
+    listen(&config.listen, |request| handle(db_path, token, request))
+}
+
+/// Answers the requests to the address until the program is stopped. Errors in handling a request
+/// are sent as its reply.
+pub fn listen(address: &str, mut handle: impl FnMut(Request) -> Result<Reply>) -> Result<()> {
+    let server = tiny_http::Server::http(address).map_err(sync_error)?;
+    for mut request in server.incoming_requests() {
+        let header = |name: &'static str| {
+            request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv(name))
+                .map(|header| header.value.to_string())
+        };
+        let (authorization, if_match) = (header("Authorization"), header("If-Match"));
+        let (method, path) = (request.method().to_string(), request.url().to_string());
+        let mut body = String::new();
+        if let Err(e) = request.as_reader().read_to_string(&mut body) {
+            log::warn!("could not read request: {}", e);
+            continue;
+        }
+
+        let reply = handle(Request {
+            method: &method,
+            path: &path,
+            authorization: authorization.as_deref(),
+            if_match: if_match.as_deref(),
+            body: &body,
+        })
+        .unwrap_or_else(|e| Reply::status(500, &e.to_string()));
+        log::info!("{} {} {}", method, path, reply.status);
+
+        let mut response =
+            tiny_http::Response::from_string(reply.body).with_status_code(reply.status);
+        let headers = [
+            ("Content-Type", Some(reply.content_type.to_string())),
+            ("ETag", reply.version),
+        ];
+        for (name, value) in headers {
+            if let Some(Ok(header)) = value.map(|value| tiny_http::Header::from_bytes(name, value))
+            {
+                response.add_header(header);
+            }
+        }
+        if let Err(e) = request.respond(response) {
+            log::warn!("could not answer request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// The items as they were after the last sync, which the changes on both sides are merged from.
+pub fn get_base_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("sync-base")
+}
+
+fn read_base(db_path: &Path) -> Result<RawDatabase> {
+    match io::read_contents_from_yaml(get_base_path(db_path)) {
+        Err(term_lib::Error::IOError(e)) if e.kind() == ErrorKind::NotFound => {
+            Ok(RawDatabase::default())
+        }
+        base => Ok(base?),
+    }
+}
+
+/// Counts the items that are added, changed or deleted going from the old to the new items.
+fn count_changes(old: &[Item], new: &[Item]) -> usize {
+    let find = |items: &'_ [Item], id| items.iter().find(|item| item.get_id() == id).cloned();
+    let ids: BTreeSet<_> = old.iter().chain(new).map(Item::get_id).collect();
+    ids.into_iter()
+        .filter(|&id| find(old, id) != find(new, id))
+        .count()
+}
+
+/// What syncing with the server changed on either side.
+#[derive(Debug)]
+pub struct Outcome {
+    pub pulled: usize,
+    pub pushed: usize,
+    /// Items changed differently on both sides, where the version on this machine is kept.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for conflict in self.conflicts.iter() {
+            writeln!(f, "{}\n", conflict)?;
+        }
+        writeln!(
+            f,
+            "{}",
+            tr_n!(
+                "Pulled {} changed item from the server.",
+                "Pulled {} changed items from the server.",
+                self.pulled
+            )
+        )?;
+        writeln!(
+            f,
+            "{}",
+            tr_n!(
+                "Pushed {} changed item to the server.",
+                "Pushed {} changed items to the server.",
+                self.pushed
+            )
+        )?;
+        if !self.conflicts.is_empty() {
+            writeln!(
+                f,
+                "{}",
+                tr_n!(
+                    "{} conflict was found, the version on this machine was kept.",
+                    "{} conflicts were found, the versions on this machine were kept.",
+                    self.conflicts.len()
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Pulls the items from the server, merges them with the local ones and pushes the merged items
+/// back. The push is refused if the server changed in the meantime, so nothing is lost.
+pub fn sync(db_path: &Path, config: &RemoteConfig, dry_run: bool) -> Result<Outcome> {
+    let url = config.get_items_url()?;
+    let authorization = format!("Bearer {}", config.get_token()?);
+    let send_error = |e: ureq::Error| sync_error(e);
+
+    let response = ureq::get(&url)
+        .set("Authorization", &authorization)
+        .call()
+        .map_err(send_error)?;
+    let version = response
+        .header("ETag")
+        .ok_or_else(|| sync_error("the server did not give the version of the items"))?
+        .to_string();
+    let remote: RawDatabase = io::parse_yaml(&response.into_string()?)?;
+
+    let local = data::read_items(db_path)?;
+    let (merged, conflicts) = sync::merge(&read_base(db_path)?, &local, &remote);
+    let outcome = Outcome {
+        pulled: count_changes(&local, &merged),
+        pushed: count_changes(&remote, &merged),
+        conflicts,
+    };
+    if dry_run {
+        return Ok(outcome);
+    }
+
+    let merged = RawDatabase::new(merged);
+    if outcome.pushed != 0 {
+        let body = serde_yaml::to_string(&merged).map_err(term_lib::Error::from)?;
+        match ureq::put(&url)
+            .set("Authorization", &authorization)
+            .set("If-Match", &version)
+            .send_string(&body)
+        {
+            Ok(_) => {}
+            Err(ureq::Error::Status(412, _)) => {
+                return Err(sync_error(
+                    "the items on the server changed while syncing, sync again",
+                ))
+            }
+            Err(e) => return Err(send_error(e)),
+        }
+    }
+    if outcome.pulled != 0 {
+        data::write_items(db_path, &merged)?;
+    }
+    io::write_yaml_and_contents(get_base_path(db_path), &merged)?;
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PartId;
+
+    #[test]
+    fn test_handle() {
+        let path = PathBuf::from("test_remote.yml");
+        let item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        data::write_items(&path, &RawDatabase::new(vec![item.clone()])).unwrap();
+        let request = |method, authorization, if_match, body| Request {
+            method,
+            path: ITEMS_PATH,
+            authorization,
+            if_match,
+            body,
+        };
+        let auth = Some("Bearer secret");
+
+        let reply = handle(&path, "secret", request("GET", None, None, "")).unwrap();
+        assert_eq!(reply.status, 401);
+        let pulled = handle(&path, "secret", request("GET", auth, None, "")).unwrap();
+        assert_eq!(pulled.status, 200);
+        let version = pulled.version.unwrap();
+
+        let mut changed = item.clone();
+        changed.set_name("Brick 2x4");
+        let body = serde_yaml::to_string(&RawDatabase::new(vec![changed.clone()])).unwrap();
+        let stale = handle(&path, "secret", request("PUT", auth, Some("\"0\""), &body)).unwrap();
+        assert_eq!(stale.status, 412);
+        let pushed = handle(&path, "secret", request("PUT", auth, Some(&version), &body)).unwrap();
+        assert_eq!(pushed.status, 200);
+        assert_ne!(pushed.version.unwrap(), version);
+
+        let items = data::read_items(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(*items, vec![changed.clone()]);
+        assert_eq!(count_changes(&[item], &[changed.clone()]), 1);
+        assert_eq!(count_changes(&[], &[changed.clone()]), 1);
+        assert_eq!(count_changes(&[changed.clone()], &[changed]), 0);
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{self, Database, Item};
+use crate::envelope;
+use crate::error::Result;
+use crate::filter::Filter;
+use crate::link;
+use crate::remote::{self, Reply, Request};
+use crate::types::PartId;
+
+/// Where the API of a running session listens, and the token that allows changes through it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct RestConfig {
+    #[serde(default = "default_listen")]
+    listen: String,
+    /// Changes are only accepted with this token, and not at all without one.
+    #[serde(default)]
+    token: Option<String>,
+}
+
+fn default_listen() -> String {
+    "127.0.0.1:8643".to_string()
+}
+
+impl Default for RestConfig {
+    fn default() -> Self {
+        Self {
+            listen: default_listen(),
+            token: None,
+        }
+    }
+}
+
+/// Returns the value of the parameter in a query like `filter=color%3Dred&x=1`.
+fn get_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        (key == name).then(|| link::decode(&value.replace('+', " ")))?
+    })
+}
+
+/// Answers the requests of the API, reading from and writing to the database on disk, where the
+/// session merges in changes made through the API as it does with other changes made on disk.
+fn handle(db_path: &Path, token: Option<&str>, request: Request) -> Result<Reply> {
+    let (path, query) = request.path.split_once('?').unwrap_or((request.path, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let parse_id = |id: &str| id.parse().ok().and_then(PartId::new);
+    match (request.method, segments.as_slice()) {
+        ("GET", ["search"]) => {
+            let filter = get_param(query, "filter").unwrap_or_default();
+            let filter = match Filter::parse(&filter) {
+                Ok(filter) => filter,
+                Err(e) => return Ok(Reply::status(400, &e.to_string())),
+            };
+            let items = data::read_items(db_path)?;
+            let matching: Vec<&Item> = items.iter().filter(|item| filter.matches(item)).collect();
+            Reply::json("items", &matching)
+        }
+        ("GET", ["items", id]) => {
+            let items = data::read_items(db_path)?;
+            match parse_id(id).and_then(|id| items.iter().find(|item| item.get_id() == id)) {
+                Some(item) => Reply::json("item", item),
+                None => Ok(Reply::status(404, "part not found")),
+            }
+        }
+        ("GET", ["stats"]) => {
+            let db = Database::new(db_path.to_path_buf())?;
+            Reply::json("stats", &db.get_stats(true, None))
+        }
+        ("PUT", ["items", id]) => {
+            if !token.is_some_and(|token| request.has_token(token)) {
+                return Ok(Reply::status(401, "unauthorized"));
+            }
+            let item = match envelope::from_json::<Item>("item", request.body) {
+                Ok(item) if Some(item.get_id()) == parse_id(id) => item,
+                _ => {
+                    return Ok(Reply::status(
+                        400,
+                        "expected the part with the ID as JSON in an item envelope",
+                    ))
+                }
+            };
+            let mut db = Database::new(db_path.to_path_buf())?;
+            match db.find(item.get_id()).cloned() {
+                Some(old_item) => db.update_item(&old_item, &item)?,
+                None => db.add_item(item.clone())?,
+            }
+            db.write()?;
+            Reply::json("item", &item)
+        }
+        ("GET" | "PUT", _) => Ok(Reply::status(404, "not found")),
+        _ => Ok(Reply::status(405, "method not allowed")),
+    }
+}
+
+/// Serves the API next to the session until the program exits. Changes are refused in read-only
+/// sessions.
+pub fn spawn(db_path: PathBuf, config: RestConfig, read_only: bool) {
+    thread::spawn(move || {
+        let token = config.token.filter(|_| !read_only);
+        let handle = |request: Request| handle(&db_path, token.as_deref(), request);
+        if let Err(e) = remote::listen(&config.listen, handle) {
+            log::error!("the API stopped: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::RawDatabase;
+
+    #[test]
+    fn test_handle() {
+        let path = PathBuf::from("test_rest.yml");
+        let item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        data::write_items(&path, &RawDatabase::new(vec![item.clone()])).unwrap();
+        let request = |method, path, authorization, body| Request {
+            method,
+            path,
+            authorization,
+            if_match: None,
+            body,
+        };
+        let get = |target| handle(&path, None, request("GET", target, None, "")).unwrap();
+
+        assert_eq!(
+            get_param("a=1&filter=name%3Dbrick+2", "filter").unwrap(),
+            "name=brick 2"
+        );
+        let found = get("/search?filter=name%3Dbrick");
+        assert_eq!(found.status, 200);
+        assert!(found.body.contains("Brick 2 x 4"));
+        let none = get("/search?filter=name%3Dplate").body;
+        assert!(envelope::from_json::<Vec<Item>>("items", &none)
+            .unwrap()
+            .is_empty());
+        assert_eq!(get("/search?filter=by%3Dcolor").status, 400);
+        assert_eq!(get("/items/3001").status, 200);
+        assert_eq!(get("/items/3002").status, 404);
+        assert_eq!(get("/stats").status, 200);
+
+        let mut changed = item.clone();
+        changed.set_name("Brick 2x4");
+        let body = envelope::to_json("item", &changed).unwrap();
+        let put = |token, authorization| {
+            handle(
+                &path,
+                token,
+                request("PUT", "/items/3001", authorization, &body),
+            )
+            .unwrap()
+        };
+        assert_eq!(put(None, Some("Bearer secret")).status, 401);
+        assert_eq!(put(Some("secret"), None).status, 401);
+        assert_eq!(put(Some("secret"), Some("Bearer secret")).status, 200);
+
+        let items = data::read_items(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let _ = std::fs::remove_file(crate::journal::get_path(&path));
+        assert_eq!(*items, vec![changed]);
+    }
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::Item;
+use crate::error::Result;
+use crate::filter::Filter;
+
+/// A rule of the user for their data, which items are checked against when they are saved and in
+/// the check of the database, e.g. `{name: Parts for sale have a price, filter: "for sale=yes",
+/// require: has=price}`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Rule {
+    name: String,
+    /// The items the rule applies to, see `Filter`. The rule applies to all items if it is empty.
+    #[serde(default)]
+    filter: String,
+    /// The filter the items the rule applies to must match, e.g. `pieces<10000`.
+    require: String,
+}
+
+impl Rule {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the rule applies to the item but the item does not have what it requires. Groups
+    /// are only checked against rules with a filter.
+    pub fn is_broken_by(&self, item: &Item) -> Result<bool> {
+        if item.is_group() && self.filter.trim().is_empty() {
+            return Ok(false);
+        }
+        Ok(Filter::parse(&self.filter)?.matches(item)
+            && !Filter::parse(&self.require)?.matches(item))
+    }
+}
+
+/// Checks that the filters of the rules are valid, so that they can be checked later on.
+pub fn validate(rules: &[Rule]) -> Result<()> {
+    for rule in rules.iter() {
+        Filter::parse(&rule.filter)?;
+        Filter::parse(&rule.require)?;
+    }
+    Ok(())
+}
+
+/// The names of the rules the item breaks.
+pub fn get_broken<'a>(rules: &'a [Rule], item: &Item) -> Vec<&'a str> {
+    rules
+        .iter()
+        .filter(|rule| rule.is_broken_by(item).unwrap_or(false))
+        .map(Rule::get_name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::{PartId, Quantity};
+
+    #[test]
+    fn test_rules() {
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            "- {name: Every part has a location, require: has=location}\n\
+            - {name: No huge lots, require: pieces<10000}\n\
+            - {name: Red parts have a price, filter: color=red, require: has=price}\n",
+        )
+        .unwrap();
+        assert!(validate(&rules).is_ok());
+
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        assert_eq!(get_broken(&rules, &item), vec!["Every part has a location"]);
+
+        item.add_color_group(ColorGroup::Other("Red".to_string()), "A3".to_string());
+        let mut lots = item.get_locations().to_vec();
+        lots[0].set_quantity(Some(Quantity::new(10000)));
+        item.set_locations(lots);
+        assert_eq!(
+            get_broken(&rules, &item),
+            vec!["No huge lots", "Red parts have a price"]
+        );
+
+        let group = Item::new(PartId::group(1).unwrap(), "Loose bricks".to_string());
+        assert!(get_broken(&rules, &group).is_empty());
+
+        let invalid: Vec<Rule> =
+            serde_yaml::from_str("- {name: Bad, require: \"size=2\"}").unwrap();
+        assert!(validate(&invalid).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use term_lib::Result;
+
+use crate::adjustment;
+use crate::currency::{self, Amount, Price};
+use crate::data::ColorGroup;
+use crate::io;
+use crate::locale::{tr, tr_n};
+use crate::timestamp::Timestamp;
+use crate::types::PartId;
+
+/// A sale of pieces from a lot, kept in a log next to the database file.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Sale {
+    /// Seconds since the unix epoch.
+    time: u64,
+    id: PartId,
+    color_group: ColorGroup,
+    quantity: u32,
+    buyer: String,
+    /// The total price paid for the pieces.
+    price: Price,
+}
+
+impl Sale {
+    pub fn new(
+        id: PartId,
+        color_group: ColorGroup,
+        quantity: u32,
+        buyer: String,
+        price: Price,
+        time: Timestamp,
+    ) -> Self {
+        Self {
+            time: time.get_secs(),
+            id,
+            color_group,
+            quantity,
+            buyer,
+            price,
+        }
+    }
+
+    pub fn get_id(&self) -> PartId {
+        self.id
+    }
+
+    pub fn get_time(&self) -> Timestamp {
+        Timestamp::from_secs(self.time)
+    }
+}
+
+impl fmt::Display for Sale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr!(
+                "{}: {} {} to {} for {}",
+                adjustment::fmt_time(self.time),
+                tr_n!("{} piece", "{} pieces", self.quantity as usize),
+                self.color_group,
+                self.buyer,
+                self.price
+            )
+        )
+    }
+}
+
+pub fn get_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("sales")
+}
+
+pub fn append(path: &Path, sales: &[Sale]) -> Result<()> {
+    let mut appended = String::new();
+    for sale in sales {
+        appended.push_str("---\n");
+        appended.push_str(&serde_yaml::to_string(sale)?);
+    }
+    io::append_private(path, &appended)?;
+    Ok(())
+}
+
+/// Reads the log, which is empty if there is none.
+pub fn read(path: &Path) -> Result<Vec<Sale>> {
+    let contents = match io::read_private(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut sales = Vec::new();
+    if contents.trim().is_empty() {
+        return Ok(sales);
+    }
+    for document in serde_yaml::Deserializer::from_str(&contents) {
+        sales.push(Sale::deserialize(document)?);
+    }
+    Ok(sales)
+}
+
+#[derive(Default)]
+struct Month {
+    revenue: Amount,
+    num_sales: usize,
+    num_unconverted: usize,
+}
+
+impl Month {
+    fn add(&mut self, sale: &Sale) {
+        self.num_sales += 1;
+        match sale.price.to_home_currency() {
+            Some(price) => self.revenue += price.get_amount(),
+            None => self.num_unconverted += 1,
+        }
+    }
+
+    fn describe(&self) -> String {
+        let mut line = tr_n!(
+            "{} sale for {}",
+            "{} sales for {}",
+            self.num_sales,
+            Price::new(self.revenue, currency::get_home_currency())
+        );
+        if self.num_unconverted != 0 {
+            line.push(' ');
+            line.push_str(&tr!(
+                "({} without an exchange rate to the home currency)",
+                self.num_unconverted
+            ));
+        }
+        line
+    }
+}
+
+/// Sums up the revenue in the home currency by month, one line per month.
+pub fn report(sales: &[Sale]) -> Vec<String> {
+    let mut months: BTreeMap<(i64, u32), Month> = BTreeMap::new();
+    for sale in sales {
+        months
+            .entry(adjustment::get_month(sale.time))
+            .or_default()
+            .add(sale);
+    }
+
+    months
+        .iter()
+        .map(|((year, number), month)| format!("{}-{:02}: {}", year, number, month.describe()))
+        .collect()
+}
+
+/// Sums up the revenue of all the sales in the home currency.
+pub fn summarize(sales: &[Sale]) -> String {
+    let mut total = Month::default();
+    sales.iter().for_each(|sale| total.add(sale));
+    total.describe()
+}
+
+pub fn total(sales: &[Sale]) -> String {
+    tr!("Total: {}", summarize(sales))
+}
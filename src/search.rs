@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::data::Item;
+use crate::types::PartId;
+
+/// Normalizes a string for searching, such that comparisons ignore case and diacritics, e.g.
+/// "Blaugrün" and "blaugrun" both normalize to "blaugrun".
+pub fn normalize(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.nfd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            'ß' | 'ẞ' => res.push_str("ss"),
+            c => res.extend(c.to_lowercase()),
+        }
+    }
+    res
+}
+
+/// Lookup tables for the searches, kept up to date as items are added and removed so that they
+/// never have to be rebuilt from the whole database.
+#[derive(Debug, Default)]
+pub struct Index {
+    /// Main and alternative IDs to the main ID of the item.
+    ids: HashMap<PartId, PartId>,
+    /// Normalized names to the IDs of the items with that name.
+    names: BTreeMap<String, BTreeSet<PartId>>,
+    /// Locations to the IDs of the items stored there.
+    locations: BTreeMap<String, BTreeSet<PartId>>,
+}
+
+impl Index {
+    pub fn new(items: &[Item]) -> Self {
+        let mut index = Self::default();
+        for item in items {
+            index.insert(item);
+        }
+        index
+    }
+
+    pub fn insert(&mut self, item: &Item) {
+        let id = item.get_id();
+        self.ids.insert(id, id);
+        for alt_id in item.get_alternative_ids() {
+            self.ids.entry(*alt_id).or_insert(id);
+        }
+        self.names
+            .entry(normalize(item.get_name()))
+            .or_default()
+            .insert(id);
+        for lot in item.get_locations() {
+            self.locations
+                .entry(lot.get_location().clone())
+                .or_default()
+                .insert(id);
+        }
+    }
+
+    /// Removes the item, which must be the same as when it was inserted.
+    pub fn remove(&mut self, item: &Item) {
+        let id = item.get_id();
+        self.ids.remove(&id);
+        for alt_id in item.get_alternative_ids() {
+            if self.ids.get(alt_id) == Some(&id) {
+                self.ids.remove(alt_id);
+            }
+        }
+        remove_from(&mut self.names, &normalize(item.get_name()), id);
+        for lot in item.get_locations() {
+            remove_from(&mut self.locations, lot.get_location(), id);
+        }
+    }
+
+    /// Returns the main ID of the item with the given main or alternative ID.
+    pub fn get_id(&self, id: PartId) -> Option<PartId> {
+        self.ids.get(&id).copied()
+    }
+
+    /// Returns the ID of an item with the name, ignoring case and diacritics.
+    pub fn get_by_name(&self, name: &str) -> Option<PartId> {
+        self.names
+            .get(&normalize(name))
+            .and_then(|ids| ids.first())
+            .copied()
+    }
+
+    pub fn get_locations(&self) -> impl Iterator<Item = &String> {
+        self.locations.keys()
+    }
+
+    pub fn contains_location(&self, loc: &str) -> bool {
+        self.locations.contains_key(loc)
+    }
+
+    pub fn get_at_location(&self, loc: &str) -> Option<&BTreeSet<PartId>> {
+        self.locations.get(loc)
+    }
+}
+
+/// The pieces of a base mold and of the printed and patterned parts based on it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MoldCount {
+    pub mold: PartId,
+    /// The pieces of all the parts together.
+    pub quantity: u32,
+    /// The decorated parts based on the mold.
+    pub variants: Vec<PartId>,
+}
+
+/// Counts the items by base mold, where parts without a base mold are their own mold.
+pub fn roll_up(items: &[&Item]) -> Vec<MoldCount> {
+    let mut molds: BTreeMap<PartId, MoldCount> = BTreeMap::new();
+    for item in items {
+        let mold = item.get_base_mold().unwrap_or(item.get_id());
+        let count = molds.entry(mold).or_insert_with(|| MoldCount {
+            mold,
+            quantity: 0,
+            variants: Vec::new(),
+        });
+        count.quantity += item.get_quantity().get();
+        if mold != item.get_id() {
+            count.variants.push(item.get_id());
+        }
+    }
+    molds.into_values().collect()
+}
+
+/// Lists the parts whose ID or name contains the text, with one line for each of their lots, so
+/// that the lines can be filtered further like with `grep`.
+pub fn list_matches(items: &[Item], text: &str) -> Vec<String> {
+    let text = normalize(text.trim());
+    let mut lines = Vec::new();
+    for item in items {
+        let ids = std::iter::once(item.get_id()).chain(item.get_alternative_ids().iter().copied());
+        let matches = ids
+            .map(|id| id.to_string())
+            .chain([normalize(item.get_name())])
+            .any(|s| s.contains(&text));
+        if !matches {
+            continue;
+        }
+        let part = format!("{} {}", item.get_id(), item.get_name());
+        if item.get_locations().is_empty() {
+            lines.push(part.clone());
+        }
+        for lot in item.get_locations() {
+            lines.push(format!("{}  {}", part, lot));
+        }
+    }
+    lines
+}
+
+fn remove_from(map: &mut BTreeMap<String, BTreeSet<PartId>>, key: &str, id: PartId) {
+    if let Some(ids) = map.get_mut(key) {
+        ids.remove(&id);
+        if ids.is_empty() {
+            map.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::Quantity;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Blaugrün"), normalize("blaugrun"));
+        assert_eq!(normalize("Straße"), "strasse");
+        assert_eq!(normalize("Plate 1 x 2"), "plate 1 x 2");
+    }
+
+    #[test]
+    fn test_list_matches() {
+        let id = |id| PartId::new(id).unwrap();
+        let mut brick = Item::new(id(3001), "Brick 2 x 4".to_string());
+        brick.add_color_group(ColorGroup::Basic, "A1".to_string());
+        brick.add_color_group(ColorGroup::Translucent, "A2".to_string());
+        let plate = Item::new(id(3020), "Plate 2 x 4".to_string());
+        let items = [brick, plate];
+
+        let lines = list_matches(&items, "3001");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("3001 Brick 2 x 4  "));
+        assert!(lines[1].ends_with("A2"));
+        assert_eq!(list_matches(&items, "PLATE"), vec!["3020 Plate 2 x 4"]);
+        assert!(list_matches(&items, "tile").is_empty());
+    }
+
+    #[test]
+    fn test_index() {
+        let id = |id| PartId::new(id).unwrap();
+        let mut item = Item::new(id(3001), "Brick 2 x 4".to_string());
+        item.add_alt_id(id(300101));
+        item.add_color_group(ColorGroup::All, "A1".to_string());
+
+        let mut index = Index::new(&[item.clone()]);
+        assert_eq!(index.get_id(id(300101)), Some(id(3001)));
+        assert_eq!(index.get_by_name("brick 2 x 4"), Some(id(3001)));
+        assert!(index.contains_location("A1"));
+
+        index.remove(&item);
+        item.set_name("Brick 2x4");
+        index.insert(&item);
+        assert_eq!(index.get_by_name("Brick 2 x 4"), None);
+        assert_eq!(index.get_by_name("Brick 2x4"), Some(id(3001)));
+
+        index.remove(&item);
+        assert_eq!(index.get_id(id(3001)), None);
+        assert!(!index.contains_location("A1"));
+    }
+
+    #[test]
+    fn test_roll_up() {
+        let id = |id| PartId::new(id).unwrap();
+        let item = |number, mold: Option<u32>, quantity| {
+            let mut item = Item::new(id(number), number.to_string());
+            item.set_base_mold(mold.map(id));
+            item.add_color_group(ColorGroup::All, "A1".to_string());
+            item.set_quantity(&ColorGroup::All, Some(Quantity::new(quantity)));
+            item
+        };
+        let items = [
+            item(3068, None, 10),
+            item(306801, Some(3068), 2),
+            item(306802, Some(3068), 3),
+            item(3001, None, 5),
+        ];
+        let items: Vec<&Item> = items.iter().collect();
+        assert_eq!(
+            roll_up(&items),
+            vec![
+                MoldCount {
+                    mold: id(3001),
+                    quantity: 5,
+                    variants: vec![],
+                },
+                MoldCount {
+                    mold: id(3068),
+                    quantity: 15,
+                    variants: vec![id(306801), id(306802)],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{ErrorKind, IsTerminal, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::io;
+use crate::oauth::OAuthKeys;
+use crate::site::Site;
+
+/// Credentials kept out of the config in a file that only the user can read, so that the config
+/// can be synced or shared without giving them away.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct Secrets {
+    #[serde(default)]
+    api_keys: BTreeMap<Site, String>,
+    /// What the site gave when logging in, which stands for the user in requests.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    user_hashes: BTreeMap<Site, String>,
+    /// The keys of sites that sign requests with OAuth, like a store on BrickLink.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    oauth_keys: BTreeMap<Site, OAuthKeys>,
+}
+
+impl Secrets {
+    pub fn get_api_keys(&self) -> &BTreeMap<Site, String> {
+        &self.api_keys
+    }
+
+    pub fn set_api_key(&mut self, site: Site, key: String) {
+        self.api_keys.insert(site, key);
+    }
+
+    pub fn get_user_hash(&self, site: Site) -> Option<&str> {
+        self.user_hashes.get(&site).map(String::as_str)
+    }
+
+    pub fn set_user_hash(&mut self, site: Site, hash: String) {
+        self.user_hashes.insert(site, hash);
+    }
+
+    pub fn get_oauth_keys(&self, site: Site) -> Option<&OAuthKeys> {
+        self.oauth_keys.get(&site)
+    }
+
+    pub fn set_oauth_keys(&mut self, site: Site, keys: OAuthKeys) {
+        self.oauth_keys.insert(site, keys);
+    }
+}
+
+/// Reads the secrets, which are empty if there is no file yet.
+pub fn read(path: &Path) -> Result<Secrets> {
+    match io::read_contents_from_yaml(path) {
+        Ok(secrets) => {
+            warn_if_shared(path);
+            Ok(secrets)
+        }
+        Err(term_lib::Error::IOError(e)) if e.kind() == ErrorKind::NotFound => {
+            Ok(Secrets::default())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes the secrets to a file that only the user can read and write.
+pub fn write(path: &Path, secrets: &Secrets) -> Result<()> {
+    let contents = serde_yaml::to_string(secrets).map_err(term_lib::Error::from)?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode is only used for new files.
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+fn warn_if_shared(path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(path).map_or(0, |m| m.permissions().mode());
+        if mode & 0o077 != 0 {
+            log::warn!(
+                "{} can be read by other users, it should have mode 600",
+                path.display()
+            );
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// Asks for a line on the terminal, like a user name, showing what is typed.
+pub fn ask_visible(prompt: &str) -> Result<String> {
+    let mut err = std::io::stderr();
+    write!(err, "{} ", prompt)?;
+    err.flush()?;
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+/// Asks for a secret on the terminal without showing what is typed. When the answers are piped
+/// in, it is the next line.
+pub fn ask(prompt: &str) -> Result<String> {
+    let mut err = std::io::stderr();
+    write!(err, "{} ", prompt)?;
+    err.flush()?;
+    if !std::io::stdin().is_terminal() {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        return Ok(line.trim_end_matches(['\n', '\r']).to_string());
+    }
+
+    crossterm::terminal::enable_raw_mode()?;
+    let secret = read_hidden();
+    crossterm::terminal::disable_raw_mode()?;
+    writeln!(err)?;
+    secret
+}
+
+fn read_hidden() -> Result<String> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+
+    let mut secret = String::new();
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Enter => return Ok(secret),
+            KeyCode::Backspace => {
+                secret.pop();
+            }
+            KeyCode::Esc => return Err(term_lib::Error::Quit.into()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Err(term_lib::Error::Quit.into())
+            }
+            KeyCode::Char(c) => secret.push(c),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secrets() {
+        let path = Path::new("test_secrets_roundtrip.yml");
+        assert_eq!(read(path).unwrap(), Secrets::default());
+
+        let mut secrets = Secrets::default();
+        secrets.set_api_key(Site::Rebrickable, "abc123".to_string());
+        secrets.set_user_hash(Site::Brickset, "def456".to_string());
+        write(path, &secrets).unwrap();
+        let read_back = read(path);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_file(path).unwrap();
+        assert_eq!(read_back.unwrap(), secrets);
+    }
+}
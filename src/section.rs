@@ -0,0 +1,161 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::adjustment::Adjustment;
+use crate::data::Item;
+use crate::locale::tr;
+use crate::sale::Sale;
+
+/// The parts of the item display that can be shown or hidden, each toggled by its number.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, EnumIter)]
+pub enum Section {
+    Overview,
+    Lots,
+    /// The sales of the part recorded in the sale log.
+    Sales,
+    /// The changes to the number of pieces of the part recorded in the adjustment log.
+    History,
+}
+
+/// The number of sections, whose numbers come before those of the similar parts.
+pub const NUM_SECTIONS: usize = 4;
+
+impl Section {
+    pub fn get_number(&self) -> usize {
+        Section::iter()
+            .position(|section| section == *self)
+            .unwrap()
+            + 1
+    }
+
+    pub fn get_char(&self) -> char {
+        char::from_digit(self.get_number() as u32, 10).unwrap_or('?')
+    }
+
+    /// Whether the section is shown when an item is viewed for the first time.
+    pub fn is_expanded_by_default(&self) -> bool {
+        matches!(self, Section::Overview | Section::Lots)
+    }
+
+    /// Whether the section needs the sale log to be read.
+    pub fn uses_sales(&self) -> bool {
+        *self == Section::Sales
+    }
+
+    /// Whether the section needs the adjustment log to be read.
+    pub fn uses_adjustments(&self) -> bool {
+        *self == Section::History
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Section::Overview => tr!("Overview"),
+            Section::Lots => tr!("Lots"),
+            Section::Sales => tr!("Sales"),
+            Section::History => tr!("History"),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The sections shown when an item is viewed for the first time.
+pub fn default_expanded() -> BTreeSet<Section> {
+    Section::iter()
+        .filter(Section::is_expanded_by_default)
+        .collect()
+}
+
+/// Describes the item in its sections, where only the sections that are expanded show more than
+/// their heading. The sales and adjustments are those of all parts, of which the ones of the item
+/// are shown.
+pub fn describe(
+    item: &Item,
+    expanded: &BTreeSet<Section>,
+    sales: &[Sale],
+    adjustments: &[Adjustment],
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for section in Section::iter() {
+        let is_expanded = expanded.contains(&section);
+        let mark = if is_expanded { '-' } else { '+' };
+        lines.push(format!("[{}] {} {}", mark, section.get_number(), section));
+        if !is_expanded {
+            continue;
+        }
+
+        let contents = match section {
+            Section::Overview => item.describe_overview(),
+            Section::Lots => item.describe_lots(),
+            Section::Sales => {
+                let sales: Vec<String> = sales
+                    .iter()
+                    .filter(|sale| sale.get_id() == item.get_id())
+                    .map(Sale::to_string)
+                    .collect();
+                if sales.is_empty() {
+                    tr!("No sales are recorded for this part").to_string()
+                } else {
+                    sales.join("\n")
+                }
+            }
+            Section::History => {
+                let adjustments: Vec<String> = adjustments
+                    .iter()
+                    .filter(|adjustment| adjustment.get_id() == item.get_id())
+                    .map(Adjustment::to_string)
+                    .collect();
+                if adjustments.is_empty() {
+                    tr!("No changes to the pieces are recorded for this part").to_string()
+                } else {
+                    adjustments.join("\n")
+                }
+            }
+        };
+        lines.extend(
+            contents
+                .trim_end()
+                .split('\n')
+                .map(|line| format!("  {}", line)),
+        );
+        lines.push(String::new());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::PartId;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(Section::iter().count(), NUM_SECTIONS);
+        assert_eq!(Section::History.get_char(), '4');
+
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        let lines = describe(&item, &default_expanded(), &[], &[]);
+        assert_eq!(lines[0], "[-] 1 Overview");
+        assert!(lines.contains(&"  Name: Brick 2 x 4".to_string()));
+        assert!(lines.contains(&"[+] 3 Sales".to_string()));
+        assert_eq!(lines.last().unwrap(), "[+] 4 History");
+
+        let lines = describe(&item, &BTreeSet::from([Section::Sales]), &[], &[]);
+        assert_eq!(
+            lines,
+            vec![
+                "[+] 1 Overview",
+                "[+] 2 Lots",
+                "[-] 3 Sales",
+                "  No sales are recorded for this part",
+                "",
+                "[+] 4 History",
+            ]
+        );
+    }
+}
@@ -0,0 +1,648 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::currency::{self, Amount, Price};
+use crate::data::Database;
+use crate::error::{Error, Result};
+use crate::goal;
+use crate::locale::{self, tr, tr_n};
+use crate::shipping::Weight;
+use crate::types::{PartId, Years};
+
+/// An owned set defined in the config, given by the parts of its inventory.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Set {
+    number: String,
+    name: String,
+    /// Complete sets are left out of the completion view.
+    #[serde(default)]
+    complete: bool,
+    /// The current price of the set sealed.
+    #[serde(default)]
+    price: Option<Price>,
+    parts: BTreeMap<PartId, u32>,
+    /// The spare parts that come with the set besides the parts of the main build.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    spare_parts: BTreeMap<PartId, u32>,
+    /// The condition of what belongs to the set besides the parts. Sets are taken to come with
+    /// instructions and a box, but only with a sticker sheet if it is listed.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    extras: BTreeMap<Extra, Condition>,
+    /// Whether the instructions are kept as a PDF, which matters for sets without the paper ones.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pdf_instructions: bool,
+    /// The years the set was released in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    produced: Option<Years>,
+}
+
+/// What belongs to a set besides the parts.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Extra {
+    Instructions,
+    StickerSheet,
+    Box,
+}
+
+impl Extra {
+    /// Whether every set is taken to come with it.
+    fn is_expected(self) -> bool {
+        match self {
+            Extra::Instructions | Extra::Box => true,
+            Extra::StickerSheet => false,
+        }
+    }
+}
+
+impl fmt::Display for Extra {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Extra::Instructions => tr!("instructions"),
+            Extra::StickerSheet => tr!("sticker sheet"),
+            Extra::Box => tr!("box"),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    New,
+    Used,
+    Damaged,
+    Missing,
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Condition::New => tr!("new"),
+            Condition::Used => tr!("used"),
+            Condition::Damaged => tr!("damaged"),
+            Condition::Missing => tr!("missing"),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Which instructions are kept for a set.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Instructions {
+    None,
+    Pdf,
+    Physical,
+}
+
+impl fmt::Display for Instructions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Instructions::None => tr!("no instructions"),
+            Instructions::Pdf => tr!("only a PDF"),
+            Instructions::Physical => tr!("paper instructions"),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Set {
+    /// A set whose inventory is not known yet, which can be added with `tuibricks set import`.
+    pub fn new(number: String, name: String) -> Self {
+        Set {
+            number,
+            name,
+            complete: false,
+            price: None,
+            parts: BTreeMap::new(),
+            spare_parts: BTreeMap::new(),
+            extras: BTreeMap::new(),
+            pdf_instructions: false,
+            produced: None,
+        }
+    }
+
+    pub fn get_number(&self) -> &str {
+        &self.number
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_produced(&self) -> Option<Years> {
+        self.produced
+    }
+
+    pub fn set_produced(&mut self, produced: Option<Years>) {
+        self.produced = produced;
+    }
+
+    pub fn get_parts(&self) -> &BTreeMap<PartId, u32> {
+        &self.parts
+    }
+
+    pub fn get_spare_parts(&self) -> &BTreeMap<PartId, u32> {
+        &self.spare_parts
+    }
+
+    /// The parts of the main build, and the spare parts if they are included.
+    pub fn get_needed_parts(&self, include_spares: bool) -> BTreeMap<PartId, u32> {
+        let mut parts = self.parts.clone();
+        if include_spares {
+            for (id, quantity) in self.spare_parts.iter() {
+                *parts.entry(*id).or_default() += quantity;
+            }
+        }
+        parts
+    }
+
+    /// The paper instructions are only had if they are listed as an extra that is not missing.
+    pub fn get_instructions(&self) -> Instructions {
+        match self.extras.get(&Extra::Instructions) {
+            Some(condition) if *condition != Condition::Missing => Instructions::Physical,
+            _ if self.pdf_instructions => Instructions::Pdf,
+            _ => Instructions::None,
+        }
+    }
+
+    /// Whether the given set number refers to this set, where the variant after the dash may be
+    /// left out, e.g. both "10265" and "10265-1" match "10265-1".
+    pub fn matches_number(&self, number: &str) -> bool {
+        let number = number.trim();
+        self.number == number || self.number.split('-').next() == Some(number)
+    }
+}
+
+/// Parses the inventory of a set as CSV with a header naming the part, quantity and optionally
+/// "is spare" columns, as exported by Rebrickable. Quantities of the same part in different colors
+/// are added up.
+pub fn parse_inventory(contents: &str, number: &str) -> Result<Set> {
+    let mut rows = contents.lines().filter(|row| !row.trim().is_empty());
+    let header: Vec<String> = rows
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let (Some(part), Some(quantity)) = (column("part"), column("quantity")) else {
+        return Err(Error::InvalidInventory {
+            msg: "the header has no part and quantity columns".to_string(),
+        });
+    };
+    let is_spare = column("is spare");
+
+    let mut parts = BTreeMap::new();
+    let mut spare_parts = BTreeMap::new();
+    for row in rows {
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        let field = |i: usize| fields.get(i).copied().unwrap_or_default();
+        let id = field(part)
+            .parse()
+            .ok()
+            .and_then(PartId::new)
+            .ok_or_else(|| Error::InvalidInventory {
+                msg: format!("{} is not a valid part ID", field(part)),
+            })?;
+        let count: u32 = field(quantity)
+            .parse()
+            .map_err(|_| Error::InvalidInventory {
+                msg: format!("{} is not a valid number of pieces", field(quantity)),
+            })?;
+        let spare = is_spare.is_some_and(|i| field(i).eq_ignore_ascii_case("true"));
+        let parts = if spare { &mut spare_parts } else { &mut parts };
+        *parts.entry(id).or_default() += count;
+    }
+
+    Ok(Set {
+        number: number.to_string(),
+        name: number.to_string(),
+        complete: false,
+        price: None,
+        parts,
+        spare_parts,
+        extras: BTreeMap::new(),
+        pdf_instructions: false,
+        produced: None,
+    })
+}
+
+/// The number of parts listed as contributing the most to the part-out value.
+const TOP_PARTS: usize = 5;
+
+/// The value of the parts of a set if it was parted out, compared to the price of the set sealed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PartOut {
+    number: String,
+    name: String,
+    /// The value of the priced parts in the home currency.
+    value: Amount,
+    sealed: Option<Price>,
+    /// The parts contributing the most to the value, with their quantity and value.
+    top_parts: Vec<(PartId, String, u32, Amount)>,
+    /// Number of parts without a price in the home currency.
+    unpriced: usize,
+}
+
+/// Computes the part-out value of the set from the prices of the parts in the database.
+pub fn get_part_out(set: &Set, db: &Database) -> PartOut {
+    let mut value = Amount::ZERO;
+    let mut unpriced = 0;
+    let mut parts = Vec::new();
+    for (id, quantity) in set.parts.iter() {
+        let item = db.get_item_by_id(*id).ok();
+        let price = item
+            .and_then(|item| item.get_price())
+            .and_then(|price| price.to_home_currency());
+        let Some(price) = price else {
+            unpriced += 1;
+            continue;
+        };
+        let part_value = price.get_amount().times(*quantity);
+        value += part_value;
+        let name = item
+            .map(|item| item.get_name().to_string())
+            .unwrap_or_default();
+        parts.push((*id, name, *quantity, part_value));
+    }
+    parts.sort_by_key(|part| std::cmp::Reverse(part.3));
+    parts.truncate(TOP_PARTS);
+
+    PartOut {
+        number: set.number.clone(),
+        name: set.name.clone(),
+        value,
+        sealed: set.price.clone(),
+        top_parts: parts,
+        unpriced,
+    }
+}
+
+/// Computes the weight of the parts of the set from the weights per piece in the database.
+pub fn get_weight(set: &Set, db: &Database) -> Weight {
+    let mut weight = Weight::default();
+    for (id, quantity) in set.parts.iter() {
+        let item = db.get_item_by_id(*id).ok();
+        weight.add(item.and_then(|item| item.get_weight(*quantity)));
+    }
+    weight
+}
+
+impl fmt::Display for PartOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let home = currency::get_home_currency();
+        write!(
+            f,
+            "{}",
+            tr!(
+                "Part-out value of {} {}: {}",
+                self.number,
+                self.name,
+                Price::new(self.value, home)
+            )
+        )?;
+        match self
+            .sealed
+            .as_ref()
+            .and_then(|price| price.to_home_currency())
+        {
+            Some(sealed) => write!(
+                f,
+                "\n{}\n{}",
+                tr!("Sealed price: {}", sealed),
+                tr!(
+                    "Part-out minus sealed: {}",
+                    Price::new(self.value - sealed.get_amount(), home)
+                )
+            )?,
+            None => write!(f, "\n{}", tr!("The sealed price of the set is not known"))?,
+        }
+        if self.unpriced != 0 {
+            write!(
+                f,
+                "\n{}",
+                tr_n!(
+                    "({} part has no price in the home currency and is not included)",
+                    "({} parts have no price in the home currency and are not included)",
+                    self.unpriced,
+                )
+            )?;
+        }
+        if !self.top_parts.is_empty() {
+            write!(f, "\n\n{}", tr!("Parts contributing the most:"))?;
+        }
+        for (id, name, quantity, value) in self.top_parts.iter() {
+            write!(
+                f,
+                "\n  {} x {} {}: {}",
+                locale::fmt_count(*quantity as usize),
+                id,
+                name,
+                Price::new(*value, home)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a set has everything it takes to be sold as a full set.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Readiness {
+    number: String,
+    name: String,
+    /// The owned and total pieces, or none if the set is complete.
+    pieces: Option<(u32, u32)>,
+    extras: Vec<(Extra, Condition)>,
+}
+
+impl Readiness {
+    fn get_missing(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+        if let Some((owned, total)) = self.pieces {
+            if owned < total {
+                missing.push(tr_n!("{} piece", "{} pieces", (total - owned) as usize));
+            }
+        }
+        for (extra, condition) in self.extras.iter() {
+            if *condition == Condition::Missing {
+                missing.push(extra.to_string());
+            }
+        }
+        missing
+    }
+}
+
+/// The extras the set comes with, where the expected ones that are not listed are missing.
+fn get_extras(set: &Set) -> Vec<(Extra, Condition)> {
+    Extra::iter()
+        .filter_map(|extra| match set.extras.get(&extra) {
+            Some(condition) => Some((extra, *condition)),
+            None if extra.is_expected() => Some((extra, Condition::Missing)),
+            None => None,
+        })
+        .collect()
+}
+
+/// Computes the resale readiness of the set from its extras and the parts in the database.
+pub fn get_readiness(set: &Set, db: &Database) -> Readiness {
+    Readiness {
+        number: set.number.clone(),
+        name: set.name.clone(),
+        pieces: (!set.complete).then(|| goal::get_parts_progress(db, &set.parts)),
+        extras: get_extras(set),
+    }
+}
+
+impl fmt::Display for Readiness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr!("Resale readiness of {} {}:", self.number, self.name)
+        )?;
+        let pieces = match self.pieces {
+            None => tr!("complete").to_string(),
+            Some((owned, total)) => tr!(
+                "{} of {} pieces",
+                locale::fmt_count(owned as usize),
+                locale::fmt_count(total as usize)
+            ),
+        };
+        write!(f, "\n  {}", tr!("Parts: {}", pieces))?;
+        for (extra, condition) in self.extras.iter() {
+            write!(f, "\n  {}: {}", extra, condition)?;
+        }
+
+        let missing = self.get_missing();
+        if missing.is_empty() {
+            write!(f, "\n\n{}", tr!("The set is ready to be sold."))
+        } else {
+            write!(f, "\n\n{}", tr!("Missing: {}", missing.join(", ")))
+        }
+    }
+}
+
+/// The page on the LEGO site where the instructions of the set can be downloaded as PDFs.
+pub fn get_instructions_page(number: &str) -> String {
+    let base = number.split('-').next().unwrap_or(number);
+    format!("https://www.lego.com/service/buildinginstructions/{}", base)
+}
+
+/// The sets without paper instructions, those without any instructions first.
+pub fn get_missing_instructions(sets: &[Set]) -> Vec<&Set> {
+    let mut missing: Vec<&Set> = sets
+        .iter()
+        .filter(|set| set.get_instructions() != Instructions::Physical)
+        .collect();
+    missing.sort_by_key(|set| set.get_instructions());
+    missing
+}
+
+/// Reports the sets missing instructions with where to get the PDFs, which are the page on the
+/// LEGO site and the PDFs found for the set on Brickset, given by set number.
+pub fn describe_missing_instructions(
+    missing: &[&Set],
+    pdfs: &BTreeMap<String, Vec<String>>,
+) -> String {
+    if missing.is_empty() {
+        return tr!("All sets have their paper instructions.").to_string();
+    }
+    let mut lines = vec![tr!("Sets missing their paper instructions:").to_string()];
+    for set in missing.iter() {
+        lines.push(format!(
+            "  {} {}: {}",
+            set.number,
+            set.name,
+            set.get_instructions()
+        ));
+        lines.push(format!("    {}", get_instructions_page(&set.number)));
+        for pdf in pdfs.get(&set.number).into_iter().flatten() {
+            lines.push(format!("    {}", pdf));
+        }
+    }
+    lines.join("\n")
+}
+
+/// How many of the pieces of an incomplete set are owned loose.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Completion {
+    number: String,
+    name: String,
+    owned: u32,
+    total: u32,
+}
+
+impl Completion {
+    fn get_ratio(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.owned as f64 / self.total as f64
+        }
+    }
+}
+
+impl fmt::Display for Completion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr!(
+                "{} {}: {}% ({} of {} pieces)",
+                self.number,
+                self.name,
+                locale::fmt_decimal(self.get_ratio() * 100.0, 1),
+                locale::fmt_count(self.owned as usize),
+                locale::fmt_count(self.total as usize),
+            )
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SortBy {
+    /// The sets closest to completion first.
+    Completion,
+    Number,
+}
+
+impl SortBy {
+    pub fn toggle(self) -> Self {
+        match self {
+            SortBy::Completion => SortBy::Number,
+            SortBy::Number => SortBy::Completion,
+        }
+    }
+}
+
+/// Computes the completion of every incomplete set, where the spare parts are only needed if they
+/// are included.
+pub fn get_completions(sets: &[Set], db: &Database, include_spares: bool) -> Vec<Completion> {
+    sets.iter()
+        .filter(|set| !set.complete)
+        .map(|set| {
+            let parts = set.get_needed_parts(include_spares);
+            let (owned, total) = goal::get_parts_progress(db, &parts);
+            Completion {
+                number: set.number.clone(),
+                name: set.name.clone(),
+                owned,
+                total,
+            }
+        })
+        .collect()
+}
+
+pub fn sort(completions: &mut [Completion], sort_by: SortBy) {
+    match sort_by {
+        SortBy::Completion => completions.sort_by(|a, b| b.get_ratio().total_cmp(&a.get_ratio())),
+        SortBy::Number => completions.sort_by(|a, b| a.number.cmp(&b.number)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completion(number: &str, owned: u32, total: u32) -> Completion {
+        Completion {
+            number: number.to_string(),
+            name: String::new(),
+            owned,
+            total,
+        }
+    }
+
+    #[test]
+    fn test_matches_number() {
+        let set: Set =
+            serde_yaml::from_str("number: 10265-1\nname: Ford Mustang\nparts:\n  3001: 2").unwrap();
+        assert!(set.matches_number("10265-1"));
+        assert!(set.matches_number(" 10265"));
+        assert!(!set.matches_number("10265-2"));
+    }
+
+    #[test]
+    fn test_parse_inventory() {
+        let csv = "Part,Color,Quantity,Is Spare\n3001,4,2,False\n3001,1,1,False\n3024,4,1,True\n";
+        let set = parse_inventory(csv, "6020-1").unwrap();
+        let id = |id| PartId::new(id).unwrap();
+        assert_eq!(set.parts, BTreeMap::from([(id(3001), 3)]));
+        assert_eq!(set.spare_parts, BTreeMap::from([(id(3024), 1)]));
+        assert_eq!(
+            set.get_needed_parts(true),
+            BTreeMap::from([(id(3001), 3), (id(3024), 1)])
+        );
+        assert!(parse_inventory("Part,Color\n3001,4\n", "6020-1").is_err());
+    }
+
+    #[test]
+    fn test_readiness() {
+        let set: Set = serde_yaml::from_str(
+            "number: 6020-1\nname: Magic Shop\ncomplete: true\nparts: {}\nextras:\n  instructions: used\n  sticker_sheet: new",
+        )
+        .unwrap();
+        let readiness = Readiness {
+            number: set.number.clone(),
+            name: set.name.clone(),
+            pieces: Some((12, 12)),
+            extras: get_extras(&set),
+        };
+        assert_eq!(
+            readiness.extras,
+            vec![
+                (Extra::Instructions, Condition::Used),
+                (Extra::StickerSheet, Condition::New),
+                (Extra::Box, Condition::Missing),
+            ]
+        );
+        assert_eq!(readiness.get_missing(), vec!["box".to_string()]);
+    }
+
+    #[test]
+    fn test_missing_instructions() {
+        let sets: Vec<Set> = serde_yaml::from_str(
+            "- {number: 6020-1, name: Magic Shop, parts: {}, extras: {instructions: used}}\n\
+            - {number: 10265-1, name: Ford Mustang, parts: {}, pdf_instructions: true}\n\
+            - {number: 42083-1, name: Bugatti Chiron, parts: {}, extras: {instructions: missing}}",
+        )
+        .unwrap();
+        let missing = get_missing_instructions(&sets);
+        assert_eq!(missing, vec![&sets[2], &sets[1]]);
+
+        let pdfs = BTreeMap::from([(
+            "42083-1".to_string(),
+            vec!["https://example.com/6209331.pdf".to_string()],
+        )]);
+        assert_eq!(
+            describe_missing_instructions(&missing, &pdfs),
+            "Sets missing their paper instructions:\n  \
+            42083-1 Bugatti Chiron: no instructions\n    \
+            https://www.lego.com/service/buildinginstructions/42083\n    \
+            https://example.com/6209331.pdf\n  \
+            10265-1 Ford Mustang: only a PDF\n    \
+            https://www.lego.com/service/buildinginstructions/10265"
+        );
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut completions = vec![
+            completion("10265-1", 10, 100),
+            completion("6020-1", 90, 100),
+            completion("10030-1", 0, 0),
+        ];
+
+        sort(&mut completions, SortBy::Completion);
+        let numbers: Vec<_> = completions.iter().map(|c| c.number.as_str()).collect();
+        assert_eq!(numbers, vec!["10030-1", "6020-1", "10265-1"]);
+
+        sort(&mut completions, SortBy::Number);
+        let numbers: Vec<_> = completions.iter().map(|c| c.number.as_str()).collect();
+        assert_eq!(numbers, vec!["10030-1", "10265-1", "6020-1"]);
+    }
+}
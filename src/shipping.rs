@@ -0,0 +1,108 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::currency::Price;
+use crate::locale::{self, tr, tr_n};
+
+/// The price of shipping a parcel weighing at most the given number of grams.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ShippingRate {
+    max_weight: f64,
+    price: Price,
+}
+
+/// The total weight of some parts or lots, with how many of them have no known weight.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Weight {
+    grams: f64,
+    unknown: usize,
+}
+
+impl Weight {
+    pub fn add(&mut self, grams: Option<f64>) {
+        match grams {
+            Some(grams) => self.grams += grams,
+            None => self.unknown += 1,
+        }
+    }
+}
+
+/// Formats a weight in grams, or in kilograms from one kilogram.
+pub fn fmt_weight(grams: f64) -> String {
+    if grams >= 1000.0 {
+        format!("{} kg", locale::fmt_decimal(grams / 1000.0, 2))
+    } else {
+        format!("{} g", locale::fmt_decimal(grams, 1))
+    }
+}
+
+impl fmt::Display for Weight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", tr!("Weight: {}", fmt_weight(self.grams)))?;
+        if self.unknown != 0 {
+            write!(
+                f,
+                " {}",
+                tr_n!(
+                    "({} without a known weight is not included)",
+                    "({} without a known weight are not included)",
+                    self.unknown
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the cheapest rate for a parcel of the given weight.
+pub fn estimate(rates: &[ShippingRate], grams: f64) -> Option<&Price> {
+    rates
+        .iter()
+        .filter(|rate| rate.max_weight >= grams)
+        .min_by(|a, b| a.max_weight.total_cmp(&b.max_weight))
+        .map(|rate| &rate.price)
+}
+
+/// Describes the weight and, if any shipping rates are configured, the estimated shipping cost.
+pub fn describe(weight: &Weight, rates: &[ShippingRate]) -> String {
+    if rates.is_empty() {
+        return weight.to_string();
+    }
+    let shipping = match estimate(rates, weight.grams) {
+        Some(price) => tr!("Estimated shipping: {}", price),
+        None => tr!(
+            "No shipping rate covers a weight of {}",
+            fmt_weight(weight.grams)
+        ),
+    };
+    format!("{}\n{}", weight, shipping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::currency::Amount;
+
+    #[test]
+    fn test_estimate() {
+        let rate = |max_weight, amount| ShippingRate {
+            max_weight,
+            price: Price::new(Amount::from_cents(amount), "EUR"),
+        };
+        let rates = vec![rate(1000.0, 500), rate(100.0, 150), rate(500.0, 300)];
+        assert_eq!(
+            estimate(&rates, 80.0),
+            Some(&Price::new(Amount::from_cents(150), "EUR"))
+        );
+        assert_eq!(
+            estimate(&rates, 100.0),
+            Some(&Price::new(Amount::from_cents(150), "EUR"))
+        );
+        assert_eq!(
+            estimate(&rates, 120.0),
+            Some(&Price::new(Amount::from_cents(300), "EUR"))
+        );
+        assert_eq!(estimate(&rates, 2000.0), None);
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+
+use crate::data::Item;
+use crate::locale::tr;
+use crate::types::PartId;
+
+/// How many similar parts are shown with an item, which are jumped to with the keys from 1.
+pub const MAX_SIMILAR: usize = 5;
+
+/// How a similar part is related to the item.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Relation {
+    /// They are listed as substitutes in the config.
+    Substitute,
+    /// One is a print or pattern of the other, or both are of the same base mold.
+    SameMold,
+    /// They are in the same category and have about the same size.
+    SimilarSize,
+}
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Relation::Substitute => tr!("substitute"),
+            Relation::SameMold => tr!("same mold"),
+            Relation::SimilarSize => tr!("similar size"),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+fn get_relation(item: &Item, other: &Item, substitutes: &[Vec<PartId>]) -> Option<Relation> {
+    let (id, other_id) = (item.get_id(), other.get_id());
+    if substitutes
+        .iter()
+        .any(|group| group.contains(&id) && group.contains(&other_id))
+    {
+        return Some(Relation::Substitute);
+    }
+    let mold = item.get_base_mold().unwrap_or(id);
+    if other.get_base_mold().unwrap_or(other_id) == mold {
+        return Some(Relation::SameMold);
+    }
+    let same_category =
+        item.get_category().is_some() && item.get_category() == other.get_category();
+    match (item.get_dimensions(), other.get_dimensions()) {
+        (Some(dims), Some(other_dims)) if same_category && dims.is_similar(&other_dims) => {
+            Some(Relation::SimilarSize)
+        }
+        _ => None,
+    }
+}
+
+/// The owned parts related to the item, closest first.
+pub fn find<'a>(
+    items: &'a [Item],
+    item: &Item,
+    substitutes: &[Vec<PartId>],
+) -> Vec<(&'a Item, Relation)> {
+    let mut similar: Vec<(&Item, Relation)> = items
+        .iter()
+        .filter(|other| other.get_id() != item.get_id() && !other.get_locations().is_empty())
+        .filter_map(|other| Some((other, get_relation(item, other, substitutes)?)))
+        .collect();
+    similar.sort_by_key(|(other, relation)| (*relation, other.get_id()));
+    similar.truncate(MAX_SIMILAR);
+    similar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::dimensions::Dimensions;
+
+    #[test]
+    fn test_find() {
+        let id = |id| PartId::new(id).unwrap();
+        let item = |number, dims: &str| {
+            let mut item = Item::new(id(number), number.to_string());
+            item.set_category(Some("Tiles".to_string()));
+            item.set_dimensions(Dimensions::parse(dims));
+            item.add_color_group(ColorGroup::All, "A1".to_string());
+            item
+        };
+        let tile = item(3068, "2 x 2 x 1/3");
+        let mut printed = item(306801, "2 x 2 x 1/3");
+        printed.set_base_mold(Some(id(3068)));
+        let items = vec![
+            tile.clone(),
+            item(3069, "1 x 2 x 1/3"),
+            item(3070, "1 x 1 x 1/3"),
+            item(87079, "2 x 4 x 1/3"),
+            printed,
+            Item::new(id(3024), "Plate 1 x 1".to_string()),
+        ];
+        let found: Vec<(PartId, Relation)> = find(&items, &tile, &[vec![id(3068), id(3024)]])
+            .into_iter()
+            .map(|(other, relation)| (other.get_id(), relation))
+            .collect();
+        // The plate is a substitute, but is not owned.
+        assert_eq!(
+            found,
+            vec![
+                (id(306801), Relation::SameMold),
+                (id(3069), Relation::SimilarSize),
+            ]
+        );
+    }
+}
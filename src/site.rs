@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::PartId;
+
+/// A website with a catalog page for each part.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Site {
+    #[default]
+    BrickLink,
+    Rebrickable,
+    Brickset,
+}
+
+impl Site {
+    /// The name of the site as written in the config and on the command line.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Site::BrickLink => "bricklink",
+            Site::Rebrickable => "rebrickable",
+            Site::Brickset => "brickset",
+        }
+    }
+
+    /// The page of the part on the site. Groups do not exist on any of the sites.
+    pub fn get_part_url(self, id: PartId) -> Option<String> {
+        let number = id.to_bricklink()?;
+        let url = match self {
+            Site::BrickLink => format!(
+                "https://www.bricklink.com/v2/catalog/catalogitem.page?P={}",
+                number
+            ),
+            Site::Rebrickable => format!("https://rebrickable.com/parts/{}/", number),
+            Site::Brickset => format!("https://brickset.com/parts/design-{}", number),
+        };
+        Some(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_url() {
+        let id = PartId::new(3001).unwrap();
+        assert_eq!(
+            Site::Rebrickable.get_part_url(id).unwrap(),
+            "https://rebrickable.com/parts/3001/"
+        );
+        assert_eq!(
+            Site::BrickLink.get_part_url(PartId::group(1).unwrap()),
+            None
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::adjustment;
+use crate::data::{self, RawDatabase};
+use crate::error::{Error, Result};
+use crate::io;
+use crate::locale::tr;
+
+/// The snapshot taken of the current database when rolling back to another one.
+pub const BEFORE_ROLLBACK: &str = "before-rollback";
+
+/// A named copy of the database, kept in a directory next to the database file.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Snapshot {
+    name: String,
+    /// Seconds since the unix epoch.
+    time: u64,
+    parts: usize,
+    lots: usize,
+}
+
+impl Snapshot {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr!(
+                "{}: taken {}, {} parts in {} lots",
+                self.name,
+                adjustment::fmt_time(self.time),
+                self.parts,
+                self.lots
+            )
+        )
+    }
+}
+
+fn get_dir(db_path: &Path) -> PathBuf {
+    db_path.with_extension("snapshots")
+}
+
+fn get_index_path(db_path: &Path) -> PathBuf {
+    get_dir(db_path).join("index.yml")
+}
+
+fn get_path(db_path: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name != "index"
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && !name.starts_with('.');
+    if !valid {
+        return Err(Error::InvalidSnapshotName {
+            name: name.to_string(),
+        });
+    }
+    Ok(get_dir(db_path).join(format!("{}.yml", name)))
+}
+
+/// Lists the snapshots of the database, oldest first.
+pub fn list(db_path: &Path) -> Result<Vec<Snapshot>> {
+    match io::read_contents_from_yaml(get_index_path(db_path)) {
+        Ok(snapshots) => Ok(snapshots),
+        Err(term_lib::Error::IOError(e)) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Takes a snapshot of the database, replacing an earlier one with the same name.
+pub fn create(db_path: &Path, name: &str) -> Result<Snapshot> {
+    let items = data::read_items(db_path)?;
+    let snapshot = Snapshot {
+        name: name.to_string(),
+        time: adjustment::now(),
+        parts: items.len(),
+        lots: items.iter().map(|item| item.get_locations().len()).sum(),
+    };
+    restore(db_path, &snapshot, &items)?;
+    Ok(snapshot)
+}
+
+/// Keeps the items as the snapshot, replacing an earlier one with the same name. Used to take
+/// snapshots and to bring them over from another machine.
+pub fn restore(db_path: &Path, snapshot: &Snapshot, items: &RawDatabase) -> Result<()> {
+    let path = get_path(db_path, &snapshot.name)?;
+    fs::create_dir_all(get_dir(db_path))?;
+    data::write_items(&path, items)?;
+
+    let mut snapshots = list(db_path)?;
+    snapshots.retain(|s| s.name != snapshot.name);
+    snapshots.push(snapshot.clone());
+    io::write_contents_to_yaml(get_index_path(db_path), &snapshots)?;
+    Ok(())
+}
+
+/// Reads the items in the snapshot.
+pub fn read(db_path: &Path, name: &str) -> Result<RawDatabase> {
+    if !list(db_path)?.iter().any(|s| s.name == name) {
+        return Err(Error::SnapshotNotFound {
+            name: name.to_string(),
+        });
+    }
+    data::read_items(&get_path(db_path, name)?)
+}
+
+/// Replaces the database with the snapshot, after taking a snapshot of it so that the rollback
+/// can be undone.
+pub fn rollback(db_path: &Path, name: &str) -> Result<()> {
+    let items = read(db_path, name)?;
+    if name != BEFORE_ROLLBACK {
+        create(db_path, BEFORE_ROLLBACK)?;
+    }
+    data::write_items(db_path, &items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{ColorGroup, Item};
+    use crate::types::PartId;
+
+    #[test]
+    fn test_snapshot_and_rollback() {
+        let db_path = PathBuf::from("test_snapshot.yml");
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        data::write_items(&db_path, &RawDatabase::new(vec![item])).unwrap();
+
+        let result = (|| -> Result<()> {
+            assert!(create(&db_path, "../escape").is_err());
+            let snapshot = create(&db_path, "before-big-import")?;
+            assert_eq!((snapshot.parts, snapshot.lots), (1, 1));
+
+            data::write_items(&db_path, &RawDatabase::default())?;
+            rollback(&db_path, "before-big-import")?;
+            assert_eq!(data::read_items(&db_path)?.len(), 1);
+
+            let names: Vec<String> = list(&db_path)?.into_iter().map(|s| s.name).collect();
+            assert_eq!(names, vec!["before-big-import", BEFORE_ROLLBACK]);
+            assert!(read(&db_path, BEFORE_ROLLBACK)?.is_empty());
+            assert!(read(&db_path, "unknown").is_err());
+            Ok(())
+        })();
+        fs::remove_file(&db_path).unwrap();
+        fs::remove_dir_all(get_dir(&db_path)).unwrap();
+        result.unwrap();
+    }
+}
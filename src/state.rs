@@ -1,16 +1,57 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::marker::PhantomData;
 use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use strum::IntoEnumIterator;
 
-use term_lib::command::Command;
-use term_lib::{display, input, prompt};
+use term_lib::command::{CmdList, Command};
+use term_lib::display;
 
+use crate::adjustment::{self, Reason};
+use crate::age;
+use crate::api::ApiConfig;
+use crate::assembly;
+use crate::availability;
+use crate::budget::{self, Purchase};
+use crate::capacity::Fill;
+use crate::check;
+use crate::clipboard;
 use crate::cmd::{Cmd, MultiCmd};
-use crate::data::{ColorGroup, Database, Item, LocSearch};
+use crate::color;
+use crate::column::{self, Column};
+use crate::config::Config;
+use crate::currency::Price;
+use crate::data::{self, ColorGroup, Condition, Database, DatabaseStats, Item, LocSearch};
+use crate::date::{self, Date, Range};
+use crate::dimensions::Dimensions;
 use crate::error::{Error, Result};
-use crate::mode::Mode;
+use crate::field;
+use crate::filter::Filter;
+use crate::goal;
+use crate::io;
+use crate::key::{self, Key, Keys};
+use crate::link::Link;
+use crate::locale::{tr, tr_n};
+use crate::mode::{Mode, StartMode};
+use crate::placement;
+use crate::plugin;
+use crate::rebalance;
+use crate::rule;
+use crate::sale::{self, Sale};
+use crate::search::{self, MoldCount};
+use crate::section::{self, Section};
+use crate::set::{self, SortBy};
+use crate::shipping::{self, Weight};
+use crate::similar::{self, Relation};
+use crate::site::Site;
+use crate::theme;
+use crate::throughput;
+use crate::timestamp::Timestamp;
+use crate::types::{PartId, Quantity};
+use crate::ui::{self, Answer, Confirm, InputSource, RowAction};
+use crate::usage;
 
 macro_rules! bail {
     ( $self:expr, $c:ident ) => {
@@ -21,53 +62,581 @@ macro_rules! bail {
     };
 }
 
+/// Options that apply to the whole session.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// Disables all commands that can change the database.
+    pub read_only: bool,
+    /// Reports changes instead of writing them to the database.
+    pub dry_run: bool,
+    /// Reloads the database while waiting for a command when it is changed on disk.
+    pub watch: bool,
+}
+
 pub struct State<W: std::io::Write> {
     db: Database,
     mode: Mode,
+    options: Options,
+    input: ui::Macros<W>,
+    config: Config,
+    config_path: PathBuf,
+    /// The macro recorded last in this session.
+    last_macro: Option<Vec<Answer>>,
+    /// The reasons given for changing the quantities of the lots of the item being edited.
+    reasons: BTreeMap<ColorGroup, Reason>,
+    /// The sales recorded for the item being edited, which are logged when it is saved.
+    sales: Vec<Sale>,
+    /// The count typed before the keys of the command being run, like the 5 of `5+`.
+    count: Option<u32>,
+    /// Shown at the bottom of the screen until the next key is pressed, like for keys that give no
+    /// command.
+    status: Option<String>,
+    /// The items viewed in this session, most recent first.
+    recent: Vec<PartId>,
+    /// The sections of the item display that are shown, kept for every item viewed.
+    expanded: BTreeSet<Section>,
+    /// When the screen was last rendered, used to skip renders while keys are repeating.
+    last_render: Option<Instant>,
+    /// When the current mode was entered, for the usage log.
+    mode_since: Instant,
+    /// Checks the colors given to parts against the colors they were produced in.
+    colors: availability::Checker,
     marker: PhantomData<W>,
 }
 
+/// The screen is rendered at most once per interval while more input is waiting.
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// How often the database file is checked for changes while watching it.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How many recently viewed items are remembered.
+const MAX_RECENT: usize = 10;
+
+/// How long loading may take before an indicator is shown.
+const LOADING_INDICATOR_DELAY: Duration = Duration::from_millis(100);
+
+/// Loads the database on another thread, showing an indicator if it takes a while.
+fn load_database<W: std::io::Write>(w: &mut W, db_path: PathBuf) -> Result<Database> {
+    let start = Instant::now();
+    let loader = thread::spawn(move || Database::new(db_path));
+
+    let mut shown = false;
+    while !loader.is_finished() {
+        if !shown && start.elapsed() >= LOADING_INDICATOR_DELAY {
+            display::clear(w)?;
+            display::header(w, tr!("Loading database..."))?;
+            shown = true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    loader
+        .join()
+        .expect("the thread loading the database panicked")
+}
+
+/// Parses a weight in grams, accepting a decimal comma. Returns `Some(None)` for empty input and
+/// `None` if the weight is invalid.
+fn parse_weight(weight: &str) -> Option<Option<f64>> {
+    let weight = weight.trim();
+    if weight.is_empty() {
+        return Some(None);
+    }
+    weight
+        .replace(',', ".")
+        .parse()
+        .ok()
+        .filter(|weight: &f64| weight.is_finite() && *weight > 0.0)
+        .map(Some)
+}
+
+/// Asks for one of the lots of the item, unless it has only one, and returns its index.
+fn select_lot<W: std::io::Write>(
+    input: &mut dyn InputSource<W>,
+    w: &mut W,
+    item: &Item,
+    header: &str,
+) -> Result<usize> {
+    let lots = item.get_locations();
+    if lots.len() == 1 {
+        return Ok(0);
+    }
+    let names: Vec<String> = lots.iter().map(|lot| lot.to_string()).collect();
+    Ok(input.select(w, Some(header), &names)?)
+}
+
+/// Asks for a color group, leaving out the color groups the item already has.
+fn select_color_group<W: std::io::Write>(
+    input: &mut dyn InputSource<W>,
+    w: &mut W,
+    db: &Database,
+    header: &str,
+    item: Option<&Item>,
+) -> Result<ColorGroup> {
+    let color_set = item.map(Item::get_color_set).unwrap_or_default();
+    let options = ColorGroup::iter().filter(|c| !color_set.contains(c));
+
+    let color_group = ui::select_cmd(input, w, header, options)?;
+    if !matches!(color_group, ColorGroup::Other(_)) {
+        return Ok(color_group);
+    }
+
+    display::clear(w)?;
+    let create_new = tr!("Create new color group").to_owned();
+    let item_o_color_set = item.map(Item::get_other_color_set).unwrap_or_default();
+    let options = db
+        .get_other_color_set()
+        .iter()
+        .filter(|s| !item_o_color_set.contains(*s));
+    let color_name = ui::select_from_list(
+        input,
+        w,
+        Some(tr!("Select other color group or create a new one")),
+        std::iter::once(&create_new).chain(options),
+    )?;
+
+    if color_name == &create_new {
+        display::clear(w)?;
+        let color_name = input.input_string(w, tr!("Enter the name of the new color group:"))?;
+        Ok(ColorGroup::Other(color::resolve(&color_name).to_string()))
+    } else {
+        Ok(ColorGroup::Other(color_name.to_string()))
+    }
+}
+
+/// Asks whether to go on with a color the part was never produced in, which is often a mix-up of
+/// similar colors. Color groups other than single colors are not checked.
+/// The answer to a prompt, or None if it was escaped.
+fn unless_escaped<T, E: Into<Error>>(answer: std::result::Result<T, E>) -> Result<Option<T>> {
+    match answer.map_err(Into::into) {
+        Ok(answer) => Ok(Some(answer)),
+        Err(Error::TermError(term_lib::Error::Escape)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn confirm_color<W: std::io::Write>(
+    input: &mut dyn InputSource<W>,
+    w: &mut W,
+    colors: &mut availability::Checker,
+    id: PartId,
+    color_group: &ColorGroup,
+) -> Result<bool> {
+    let ColorGroup::Other(color) = color_group else {
+        return Ok(true);
+    };
+    let Some(colors) = colors.get_other_colors(id, color) else {
+        return Ok(true);
+    };
+    let question = format!(
+        "{}\n\n{}",
+        availability::describe(id, color, colors),
+        tr!("Use the color anyway?")
+    );
+    display::clear(w)?;
+    Ok(input.confirm(w, &question, Confirm::Default(false))?)
+}
+
 impl<W: std::io::Write> State<W> {
-    pub fn new(db_path: PathBuf) -> Result<Self> {
-        let db = Database::new(db_path)?;
+    pub fn new(
+        w: &mut W,
+        config: Config,
+        config_path: PathBuf,
+        options: Options,
+        input: Box<dyn InputSource<W>>,
+    ) -> Result<Self> {
+        let mut db = load_database(w, config.get_db_path())?;
+        if options.dry_run {
+            db.set_dry_run();
+        }
         let mode = Mode::Default { info: None };
+        let colors = availability::Checker::new(match config.is_checking_colors() {
+            true => config.get_api_config(Site::Rebrickable),
+            false => ApiConfig::default(),
+        });
         Ok(Self {
             db,
             mode,
+            options,
+            input: ui::Macros::new(input),
+            config,
+            config_path,
+            last_macro: None,
+            reasons: BTreeMap::new(),
+            sales: Vec::new(),
+            count: None,
+            status: None,
+            recent: Vec::new(),
+            expanded: section::default_expanded(),
+            last_render: None,
+            mode_since: Instant::now(),
+            colors,
             marker: PhantomData,
         })
     }
 
+    /// Opens the place the link points to, as if it had been searched for.
+    pub fn open(&mut self, w: &mut W, link: &Link) -> Result<()> {
+        let mode = match link {
+            Link::Location(location) if !self.db.contains_location(location) => Ok(Mode::Default {
+                info: Some(tr!("Location {} not found in database", location)),
+            }),
+            Link::Location(location) => self.show_location(w, location),
+            Link::Item(id) => Ok(self.find_by_id(id.get_number())),
+            Link::Filter(filter) => self.show_filtered(w, filter),
+        };
+        match mode {
+            Ok(mode) => {
+                if let Mode::DisplayItem { item, .. } = &mode {
+                    self.add_recent(item.get_id());
+                }
+                self.mode = mode;
+            }
+            Err(Error::TermError(term_lib::Error::Escape)) => {}
+            Err(err) => return Err(err),
+        }
+        Ok(())
+    }
+
+    /// Filters out the commands that are not allowed in this session.
+    fn filter_cmds(&self, cmds: CmdList<Cmd>) -> CmdList<Cmd> {
+        let recording = self.input.is_recording();
+        CmdList::new(
+            cmds.iter()
+                .filter(|cmd| !(self.options.read_only && cmd.is_mutating()))
+                // A macro could otherwise end up playing itself.
+                .filter(|cmd| !(recording && **cmd == Cmd::PlayMacro))
+                .filter(|cmd| clipboard::SUPPORTED || !cmd.uses_clipboard())
+                .map(|cmd| match cmd {
+                    Cmd::StartRecording if recording => Cmd::StopRecording,
+                    cmd => *cmd,
+                })
+                .collect(),
+        )
+    }
+
+    /// The commands of the mode, a command for each similar part shown with an item, and the
+    /// plugins of the scopes that fit the mode.
+    fn get_possible_cmds(&self) -> CmdList<Cmd> {
+        let mut cmds: Vec<Cmd> = self.mode.get_possible_cmds().iter().copied().collect();
+        cmds.extend((0..self.get_similar().len()).map(Cmd::ShowSimilar));
+        let fits = |scope| match &self.mode {
+            Mode::Default { .. } => scope != plugin::Scope::Item,
+            Mode::DisplayItem { .. } => scope == plugin::Scope::Item,
+            _ => false,
+        };
+        let plugins = plugin::get_plugins().iter().enumerate();
+        cmds.extend(
+            plugins
+                .filter(|(_, p)| fits(p.get_scope()))
+                .map(|(i, _)| Cmd::Plugin(i)),
+        );
+        self.filter_cmds(CmdList::new(cmds))
+    }
+
+    /// The owned parts similar to the item shown.
+    fn get_similar(&self) -> Vec<(&Item, Relation)> {
+        match &self.mode {
+            Mode::DisplayItem { item, .. } => {
+                similar::find(self.db.get_items(), item, self.config.get_substitutes())
+            }
+            _ => Vec::new(),
+        }
+    }
+
     pub fn wait_for_cmd(&mut self, w: &mut W) -> Result<()> {
+        let possible_cmds = self.get_possible_cmds();
+
+        // When a key is held down, rendering after every key press makes the screen lag behind, so
+        // renders are skipped while more input is waiting, up to one frame interval.
+        let due = self
+            .last_render
+            .is_none_or(|last| last.elapsed() >= FRAME_INTERVAL);
+        if due || !self.input.has_pending() {
+            self.render(w, &possible_cmds)?;
+            self.last_render = Some(Instant::now());
+        }
+
+        if self.options.watch {
+            while !self.input.poll(WATCH_INTERVAL) {
+                if self.db.reload_if_changed()? {
+                    self.refresh_mode();
+                    self.resolve_conflicts(w)?;
+                    return Ok(());
+                }
+            }
+        }
+
+        let Some(cmd) = self.wait_for_keys(w, &possible_cmds)? else {
+            return Ok(());
+        };
+
+        self.run_cmd(w, cmd)
+    }
+
+    /// Starts in the mode as if its command was given in the default menu, where search asks
+    /// what to search by right away.
+    pub fn start_in(&mut self, w: &mut W, mode: StartMode) -> Result<()> {
+        self.run_cmd(w, mode.get_cmd())
+    }
+
+    fn run_cmd(&mut self, w: &mut W, cmd: Cmd) -> Result<()> {
+        let new_mode = self.execute_cmd(w, cmd);
+        let left = match &new_mode {
+            Ok(new_mode) => new_mode.get_name() != self.mode.get_name(),
+            Err(Error::TermError(term_lib::Error::Quit)) => true,
+            Err(_) => false,
+        };
+        if left {
+            usage::record_mode(self.mode.get_name(), self.mode_since.elapsed());
+            self.mode_since = Instant::now();
+        }
+
+        match new_mode {
+            Ok(new_mode) => {
+                if let Mode::DisplayItem { item, .. } = &new_mode {
+                    self.add_recent(item.get_id());
+                }
+                self.mode = new_mode;
+            }
+            Err(Error::TermError(term_lib::Error::Escape)) => {}
+            Err(Error::TermError(term_lib::Error::ParsingError(_))) => {}
+            Err(err) => return Err(err),
+        }
+
+        self.show_dry_run_report(w)?;
+        self.resolve_conflicts(w)
+    }
+
+    /// Reads keys until they give one of the commands. When a longer chord starts with the keys, the
+    /// next key is waited for up to the chord timeout before giving the command of the keys so far.
+    /// Digits typed first are the count of the command, unless they are commands themselves.
+    fn wait_for_keys(&mut self, w: &mut W, possible_cmds: &CmdList<Cmd>) -> Result<Option<Cmd>> {
+        let cmds: Vec<(Cmd, Keys)> = possible_cmds
+            .iter()
+            .map(|cmd| (*cmd, cmd.get_keys()))
+            .collect();
+        let mut typed = Vec::new();
+        self.count = None;
+        loop {
+            let key = match self.input.wait_for_key() {
+                Ok(key) => key,
+                Err(term_lib::Error::Escape) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+            self.status = None;
+            if let Some(digit) = key.get_plain_char().and_then(|c| c.to_digit(10)) {
+                let starts_count = digit != 0 || self.count.is_some();
+                if typed.is_empty() && starts_count && key::find(&cmds, &[key]) == (None, false) {
+                    let count = self.count.unwrap_or(0);
+                    self.count = Some(count.saturating_mul(10).saturating_add(digit));
+                    continue;
+                }
+            }
+            typed.push(key);
+            let (exact, longer) = key::find(&cmds, &typed);
+            if !longer || (exact.is_some() && !self.input.poll(key::get_chord_timeout())) {
+                if exact.is_none() {
+                    let keys: Vec<String> = typed.iter().map(Key::to_string).collect();
+                    self.reject_keys(w, &keys.join(" "))?;
+                }
+                return Ok(exact.copied());
+            }
+        }
+    }
+
+    /// Shows that the keys give no command, in the way set in the config.
+    fn reject_keys(&mut self, w: &mut W, keys: &str) -> Result<()> {
+        let feedback = self.config.get_invalid_key_feedback();
+        if feedback.rings() {
+            write!(w, "\x07")?;
+            w.flush()?;
+        }
+        if feedback.flashes() {
+            self.status = Some(tr!("{} does nothing here", keys));
+        }
+        Ok(())
+    }
+
+    fn render_status(&self, w: &mut W) -> Result<()> {
+        if let Some(status) = &self.status {
+            display::newline(w, 1)?;
+            display::line(w, theme::get().highlight(status))?;
+        }
+        Ok(())
+    }
+
+    /// Updates what is shown after the database was reloaded. Items being edited are kept, since
+    /// the changes to them are merged when they are saved.
+    fn refresh_mode(&mut self) {
+        self.mode = match &self.mode {
+            Mode::DisplayItem { item, msg } => match self.db.get_item_by_id(item.get_id()) {
+                Ok(item) => Mode::DisplayItem {
+                    item: item.clone(),
+                    msg: msg.clone(),
+                },
+                Err(_) => Mode::Default {
+                    info: Some(tr!("Part {} was removed on disk.", item.get_id())),
+                },
+            },
+            Mode::ViewStatistics { stats } => Mode::ViewStatistics {
+                stats: self.get_stats(
+                    stats.includes_listed(),
+                    stats.includes_wanted(),
+                    stats.get_range(),
+                ),
+            },
+            Mode::ViewSetCompletion {
+                sort_by,
+                include_spares,
+                ..
+            } => {
+                let mut sets =
+                    set::get_completions(self.config.get_sets(), &self.db, *include_spares);
+                set::sort(&mut sets, *sort_by);
+                Mode::ViewSetCompletion {
+                    sets,
+                    sort_by: *sort_by,
+                    include_spares: *include_spares,
+                }
+            }
+            mode => mode.clone(),
+        };
+    }
+
+    fn add_recent(&mut self, id: PartId) {
+        self.recent.retain(|recent| *recent != id);
+        self.recent.insert(0, id);
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    fn render(&self, w: &mut W, possible_cmds: &CmdList<Cmd>) -> Result<()> {
         self.mode.emit_mode(w)?;
+        match &self.mode {
+            Mode::Default { .. } => self.render_widgets(w)?,
+            Mode::DisplayItem { item, .. } => self.render_sections(w, item)?,
+            _ => {}
+        }
+        let similar = self.get_similar();
+        if !similar.is_empty() {
+            display::newline(w, 1)?;
+            display::line(w, tr!("Similar parts you own:"))?;
+            for (i, (item, relation)) in similar.iter().enumerate() {
+                display::line(
+                    w,
+                    format!(
+                        "  {}: {} {} ({})",
+                        Cmd::ShowSimilar(i).get_char(),
+                        item.get_id(),
+                        item.get_name(),
+                        relation
+                    ),
+                )?;
+            }
+        }
 
-        let possible_cmds = self.mode.get_possible_cmds();
         display::dash(w)?;
-        display::line(w, "List of possible commands:")?;
+        if self.options.read_only {
+            display::line(w, tr!("READ-ONLY: changes to the database are disabled"))?;
+        }
+        if self.options.dry_run {
+            display::line(w, tr!("DRY RUN: changes are not written to the database"))?;
+        }
+        if self.input.is_recording() {
+            display::line(w, tr!("RECORDING: press Q to stop recording the macro"))?;
+        }
+        display::line(w, tr!("List of possible commands:"))?;
         display::newline(w, 1)?;
         display::possible_cmds(w, possible_cmds.iter())?;
+        self.render_status(w)?;
 
         w.flush()?;
+        Ok(())
+    }
+
+    fn render_widgets(&self, w: &mut W) -> Result<()> {
+        for widget in self.config.get_widgets() {
+            display::newline(w, 1)?;
+            display::line(w, widget.to_string())?;
+            let lines = widget.get_lines(&self.db, &self.config, &self.recent);
+            display::iter(w, lines.iter().map(|line| format!("  {}", line)))?;
+        }
+        Ok(())
+    }
 
-        let cmd_char = match input::wait_for_cmdchar() {
-            Ok(c) => c,
-            Err(term_lib::Error::Escape) => return Ok(()),
-            Err(e) => return Err(e.into()),
+    /// Shows the item in its sections, reading the logs only for the sections that are expanded.
+    fn render_sections(&self, w: &mut W, item: &Item) -> Result<()> {
+        let db_path = self.config.get_db_path();
+        let sales = if self.expanded.iter().any(Section::uses_sales) {
+            sale::read(&sale::get_path(&db_path))?
+        } else {
+            Vec::new()
+        };
+        let adjustments = if self.expanded.iter().any(Section::uses_adjustments) {
+            adjustment::read(&adjustment::get_path(&db_path))?
+        } else {
+            Vec::new()
         };
+        let lines = section::describe(item, &self.expanded, &sales, &adjustments);
+        display::iter(w, lines.iter())?;
+        Ok(())
+    }
 
-        let Some(cmd) = possible_cmds.get(cmd_char) else {
+    fn show_dry_run_report(&mut self, w: &mut W) -> Result<()> {
+        let report = self.db.take_dry_run_report();
+        if report.is_empty() {
             return Ok(());
-        };
+        }
 
-        let new_mode = self.execute_cmd(w, *cmd);
+        display::clear(w)?;
+        display::header(
+            w,
+            tr!("Dry run, the database was not modified. These changes would have been made:"),
+        )?;
+        display::iter(w, report.lines())?;
+        display::newline(w, 1)?;
+        display::line(w, tr!("Press any key to continue"))?;
+        w.flush()?;
 
-        match new_mode {
-            Ok(new_mode) => self.mode = new_mode,
-            Err(Error::TermError(term_lib::Error::Escape)) => {}
-            Err(Error::TermError(term_lib::Error::ParsingError(_))) => {}
-            Err(err) => return Err(err),
+        match self.input.wait_for_cmdchar() {
+            Ok(_) | Err(term_lib::Error::Escape) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Lets the user resolve conflicts from merging with changes made to the database on disk.
+    fn resolve_conflicts(&mut self, w: &mut W) -> Result<()> {
+        let conflicts = self.db.take_conflicts();
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+
+        let mut used_remote = false;
+        for conflict in conflicts.iter() {
+            display::clear(w)?;
+            let question = format!(
+                "{}\n\n{}\n\n{}",
+                tr!("The database was changed on disk during this session."),
+                conflict,
+                tr!("Keep the version from this session? Otherwise the version on disk is used."),
+            );
+            if !self.input.confirm(w, &question, Confirm::Default(true))? {
+                self.db.resolve_with_remote(conflict);
+                used_remote = true;
+            }
+        }
+        if used_remote {
+            self.db.write()?;
         }
 
+        self.mode = Mode::Default {
+            info: Some(tr_n!(
+                "Merged changes made on disk, {} conflict was resolved.",
+                "Merged changes made on disk, {} conflicts were resolved.",
+                conflicts.len(),
+            )),
+        };
         Ok(())
     }
 
@@ -75,15 +644,18 @@ impl<W: std::io::Write> State<W> {
         display::clear(w)?;
         display::line(w, m_cmd.get_header())?;
 
-        let possible_cmds = m_cmd.get_possible_cmds();
+        let possible_cmds = self.filter_cmds(m_cmd.get_possible_cmds());
         display::dash(w)?;
         display::newline(w, 1)?;
         display::possible_cmds(w, possible_cmds.iter())?;
+        self.render_status(w)?;
 
         w.flush()?;
 
-        let cmd_char = input::wait_for_cmdchar()?;
+        let cmd_char = self.input.wait_for_cmdchar()?;
+        self.status = None;
         let Some(cmd) = possible_cmds.get(cmd_char) else {
+            self.reject_keys(w, &cmd_char.to_string())?;
             return self.handle_multi_cmd(w, m_cmd);
         };
 
@@ -91,13 +663,22 @@ impl<W: std::io::Write> State<W> {
     }
 
     fn execute_cmd(&mut self, w: &mut W, cmd: Cmd) -> Result<Mode> {
+        if self.options.read_only && cmd.is_mutating() {
+            return Ok(self.mode.clone());
+        }
+        usage::record_cmd(&cmd.get_name());
+
         use Cmd::*;
         match cmd {
             AddItem => self.add_item(w),
             DeleteItem => self.delete_item(w),
             AddGroup => self.add_group(w),
+            SortBin => self.sort_bin(w),
+            Rebalance => self.rebalance(w),
 
-            Bricklink => self.open_bricklink(),
+            OpenPartPage => self.open_part_page(),
+            CopyPartID => self.copy_part_id(),
+            CopyPartList => self.copy_part_list(w),
 
             Quit => {
                 return Err(Error::TermError(term_lib::Error::Quit));
@@ -108,7 +689,27 @@ impl<W: std::io::Write> State<W> {
             SaveEdit => self.save_edit(),
             EditName => self.edit_name(w),
             EditPartID => self.edit_part_id(w),
+            EditPrice => self.edit_price(w),
             MoveColorGroup => self.move_color_group(w),
+            EditQuantity => self.edit_quantity(w),
+            ListForSale => self.list_for_sale(w),
+            EditPieceWeight => self.edit_piece_weight(w),
+            EditBaseMold => self.edit_base_mold(w),
+            EditComponents => self.edit_components(w),
+            EditDimensions => self.edit_dimensions(w),
+            EditFields => self.edit_fields(w),
+            Assemble => self.convert_assemblies(w, true),
+            Disassemble => self.convert_assemblies(w, false),
+            WeighLot => self.weigh_lot(w),
+            SplitLot => self.split_lot(w),
+            MergeLots => self.merge_lots(w),
+            EditPurchased => self.edit_purchased(w),
+            AddPieces => self.change_pieces(w, true),
+            RemovePieces => self.change_pieces(w, false),
+            ToggleSection(section) => self.toggle_section(section),
+            ShowSimilar(i) => self.show_similar(i),
+            Plugin(i) => self.run_plugin(w, i),
+            RecordSale => self.record_sale(w),
 
             MCmd(m_cmd) => self.handle_multi_cmd(w, m_cmd),
 
@@ -121,22 +722,115 @@ impl<W: std::io::Write> State<W> {
             SearchPartID => self.search_by_id(w),
             SearchName => self.search_by_name(),
             SearchLocation => self.search_by_location(w),
+            SearchPastedPartID => self.search_pasted_id(),
+            SearchFilter => self.search_by_filter(w),
 
             ViewStats => self.view_stats(),
+            ToggleListedLots => self.toggle_listed_lots(),
+            ToggleWantedParts => self.toggle_wanted_parts(),
+            ViewStaleLots => self.view_stale_lots(),
+            ChooseStatsRange => self.choose_stats_range(w),
             QuitStats => self.quit_stats(),
+
+            ViewSets => self.view_sets(),
+            SortSets => self.sort_sets(),
+            ToggleSpareParts => self.toggle_spare_parts(),
+            QuitSets => self.quit_sets(),
+            PartOutValue => self.part_out_value(w),
+            PartOutSet => self.part_out_set(w),
+            ResaleReadiness => self.resale_readiness(w),
+
+            ConsolidateLots => self.consolidate_lots(w),
+            CheckDatabase => self.check_database(w),
+            ViewAdjustments => self.view_adjustments(w),
+            ViewRevenue => self.view_revenue(w),
+            LogPurchase => self.log_purchase(w),
+            ViewUsage => self.view_usage(),
+            ChooseColumns => self.choose_columns(w),
+
+            StartRecording => self.start_recording(),
+            StopRecording => self.stop_recording(w),
+            PlayMacro => self.play_macro(w),
+        }
+    }
+
+    fn start_recording(&mut self) -> Result<Mode> {
+        self.input.start_recording();
+        Ok(self.mode.clone())
+    }
+
+    fn stop_recording(&mut self, w: &mut W) -> Result<Mode> {
+        let answers = self.input.stop_recording();
+        let steps = answers.len();
+        self.last_macro = Some(answers.clone());
+
+        display::clear(w)?;
+        let name = self.input.input_string(
+            w,
+            tr!("Enter a name to save the macro, or leave it empty to keep it for this session only:"),
+        )?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Ok(self.mode.with_msg(tr_n!(
+                "Recorded a macro with {} step.",
+                "Recorded a macro with {} steps.",
+                steps
+            )));
+        }
+
+        self.config.set_macro(name.to_string(), answers);
+        if !self.options.dry_run {
+            io::write_contents_to_yaml(&self.config_path, &self.config)?;
+        }
+        Ok(self.mode.with_msg(tr!("Saved macro {}.", name)))
+    }
+
+    fn play_macro(&mut self, w: &mut W) -> Result<Mode> {
+        let mut macros: Vec<(String, Vec<Answer>)> = Vec::new();
+        if let Some(answers) = &self.last_macro {
+            macros.push((tr!("Last recorded macro").to_string(), answers.clone()));
+        }
+        for (name, answers) in self.config.get_macros() {
+            macros.push((name.clone(), answers.clone()));
         }
+        if macros.is_empty() {
+            return Ok(self
+                .mode
+                .with_msg(tr!("No macros have been recorded yet.").to_string()));
+        }
+
+        display::clear(w)?;
+        let names: Vec<String> = macros.iter().map(|(name, _)| name.clone()).collect();
+        let i = self
+            .input
+            .select(w, Some(tr!("Select a macro to play:")), &names)?;
+        display::clear(w)?;
+        let times = self
+            .input
+            .input_u32(w, tr!("How many times should the macro be played?"))?;
+
+        self.input.play(&macros[i].1, times);
+        Ok(self.mode.clone())
     }
 
     fn add_item(&mut self, w: &mut W) -> Result<Mode> {
         display::clear(w)?;
-        display::line(w, "Adding a new item to the database")?;
-        let part_id = prompt::input_u32(w, "Enter the part ID of the new item")?;
+        display::line(w, tr!("Adding a new item to the database"))?;
+        let part_id = self
+            .input
+            .input_u32(w, tr!("Enter the part ID of the new item"))?;
+        let Some(part_id) = PartId::new(part_id) else {
+            return Ok(Mode::Default {
+                info: Some(tr!("{} is not a valid part ID", part_id)),
+            });
+        };
 
         if let Some(main_id) = self.db.contains_id(part_id) {
             let item = self.db.get_item_by_id(part_id)?;
-            let msg = Some(format!(
+            let msg = Some(tr!(
                 "Item with part ID {} already exists in database under item with part ID {}",
-                part_id, main_id
+                part_id,
+                main_id
             ));
             return Ok(Mode::DisplayItem {
                 item: item.clone(),
@@ -145,8 +839,10 @@ impl<W: std::io::Write> State<W> {
         }
 
         display::clear(w)?;
-        display::line(w, "Adding a new item to the database")?;
-        let name = prompt::input_string(w, &format!("Enter name of new part: {}", part_id))?;
+        display::line(w, tr!("Adding a new item to the database"))?;
+        let name = self
+            .input
+            .input_string(w, &tr!("Enter name of new part: {}", part_id))?;
 
         let new_item = Item::new(part_id, name);
         self.db.add_item(new_item.clone())?;
@@ -157,75 +853,723 @@ impl<W: std::io::Write> State<W> {
         })
     }
 
-    fn add_group(&mut self, w: &mut W) -> Result<Mode> {
-        display::clear(w)?;
-        display::line(w, "Adding a new group to the database")?;
+    /// Walks through the parts of an intake bin, asking for the part, color group and count of
+    /// each and where to store it, with the location suggested from where similar parts are.
+    fn sort_bin(&mut self, w: &mut W) -> Result<Mode> {
+        let mut session = throughput::Session::start();
+        let mut msg = None;
+        loop {
+            display::clear(w)?;
+            display::header(
+                w,
+                &tr_n!(
+                    "Sorting a bin, {} part placed so far",
+                    "Sorting a bin, {} parts placed so far",
+                    session.get_lots()
+                ),
+            )?;
+            let rate = session.describe_rate();
+            if !rate.is_empty() {
+                display::line(w, rate)?;
+            }
+            if let Some(msg) = msg.take() {
+                display::line(w, msg)?;
+            }
+            let id = match self.input.input_u32(
+                w,
+                tr!("Enter the part ID of the next part, or press Escape when the bin is empty:"),
+            ) {
+                Ok(id) => id,
+                Err(term_lib::Error::Escape) => break,
+                Err(e) => return Err(e.into()),
+            };
+            let Some(id) = PartId::new(id) else {
+                msg = Some(tr!("{} is not a valid part ID", id));
+                continue;
+            };
+            let old_item = self.db.get_item_by_id(id).ok().cloned();
+            let mut item = old_item
+                .clone()
+                .unwrap_or_else(|| Item::new(id, String::new()));
 
-        let part_id = self.db.get_next_group_id();
+            // Escaping any of the prompts for a part skips it.
+            let not_placed = tr!("Part {} was not placed.", item.get_id());
+            display::clear(w)?;
+            let Some(color_group) = unless_escaped(select_color_group(
+                &mut self.input,
+                w,
+                &self.db,
+                &tr!("Select the color group of part {}:", item.get_id()),
+                None,
+            ))?
+            else {
+                msg = Some(not_placed);
+                continue;
+            };
+            if unless_escaped(confirm_color(
+                &mut self.input,
+                w,
+                &mut self.colors,
+                item.get_id(),
+                &color_group,
+            ))? != Some(true)
+            {
+                msg = Some(not_placed);
+                continue;
+            }
+            display::clear(w)?;
+            let Some(quantity) =
+                unless_escaped(self.input.input_u32(w, tr!("How many pieces are there?")))?
+            else {
+                msg = Some(not_placed);
+                continue;
+            };
 
-        display::clear(w)?;
-        display::line(w, "Adding a new group to the database")?;
-        let name = prompt::input_string(w, "Enter name of new group:")?;
+            display::clear(w)?;
+            let Some(location) = unless_escaped(self.ask_location(w, &item, &color_group))? else {
+                msg = Some(not_placed);
+                continue;
+            };
+            if location.is_empty() {
+                msg = Some(tr!("The part was not placed, as no location was given.").to_string());
+                continue;
+            }
 
-        let new_item = Item::new(part_id, name);
-        self.db.add_item(new_item.clone())?;
-        Ok(Mode::EditItem {
-            old_item: new_item.clone(),
-            new_item,
-            msg: None,
+            if old_item.is_none() {
+                display::clear(w)?;
+                let Some(name) = unless_escaped(
+                    self.input
+                        .input_string(w, &tr!("Enter name of new part: {}", id)),
+                )?
+                else {
+                    msg = Some(not_placed);
+                    continue;
+                };
+                item.set_name(name.trim());
+            }
+
+            let is_new_lot = !item.get_locations().iter().any(|lot| {
+                *lot.get_color_group() == color_group && *lot.get_location() == location
+            });
+            item.add_pieces(&color_group, &location, quantity);
+            let warning = self.get_capacity_warning(&item);
+            match &old_item {
+                Some(old_item) => {
+                    self.db.update_item(old_item, &item)?;
+                    let adjustments =
+                        adjustment::get_adjustments(old_item, &item, &BTreeMap::new());
+                    if !self.options.dry_run && !adjustments.is_empty() {
+                        let path = adjustment::get_path(&self.config.get_db_path());
+                        adjustment::append(&path, &adjustments, self.config.get_user())?;
+                    }
+                }
+                None => self.db.add_item(item.clone())?,
+            }
+            session.add(quantity, is_new_lot);
+            let placed_msg = tr!("Placed part {} in {}.", item.get_id(), location);
+            msg = Some(match warning {
+                Some(warning) => format!("{}\n{}", placed_msg, warning),
+                None => placed_msg,
+            });
+        }
+        let placed = tr_n!(
+            "Placed {} part from the bin.",
+            "Placed {} parts from the bin.",
+            session.get_lots()
+        );
+        Ok(Mode::Default {
+            info: Some(format!("{}\n{}", placed, session.describe())),
         })
     }
 
-    fn search_by_id(&self, w: &mut W) -> Result<Mode> {
-        display::clear(w)?;
-        let searched_id = prompt::input_u32(w, "Enter the part ID of the new to search for.")?;
-
-        if let Ok(item) = self.db.get_item_by_id(searched_id) {
-            return Ok(Mode::DisplayItem {
-                item: item.clone(),
-                msg: None,
+    /// Saves the items, adding those that are new, as one transaction. If saving any of them
+    /// fails, none of them are changed.
+    fn save_in_transaction(&mut self, name: &str, items: &[Item]) -> Result<()> {
+        self.db.begin(name);
+        let saved = items
+            .iter()
+            .try_for_each(|item| match self.db.find(item.get_id()).cloned() {
+                Some(old_item) => self.db.update_item(&old_item, item),
+                None => self.db.add_item(item.clone()),
             });
+        match saved {
+            Ok(()) => self.db.commit(self.config.get_user()),
+            Err(e) => {
+                self.db.abort();
+                Err(e)
+            }
         }
+    }
 
-        Ok(Mode::Default {
-            info: Some(format!("Part {} not found in database", searched_id)),
-        })
+    /// Asks where to store pieces of the color group of the item, suggesting the locations of
+    /// similar parts. The location is empty if none was given.
+    fn ask_location(&mut self, w: &mut W, item: &Item, color_group: &ColorGroup) -> Result<String> {
+        let suggestions = placement::suggest(
+            self.db.get_items(),
+            item,
+            color_group,
+            self.config.get_substitutes(),
+        );
+        if suggestions.is_empty() {
+            display::line(w, tr!("No location can be suggested for this part."))?;
+        } else {
+            display::line(w, tr!("Suggested locations:"))?;
+            display::iter(w, suggestions.iter().map(|s| format!("  {}", s)))?;
+        }
+        display::newline(w, 1)?;
+        let location = self.input.edit_string(
+            w,
+            tr!("Enter the location to store the pieces in:"),
+            suggestions.first().map_or("", |s| s.location.as_str()),
+        )?;
+        Ok(location.trim().to_uppercase())
     }
 
-    fn search_by_name(&self) -> Result<Mode> {
-        let opts = self.db.get_all_names_string();
+    /// Goes through the inventory of a set part by part while the pieces are bagged, and saves
+    /// the whole part-out at once when it is done.
+    fn part_out_set(&mut self, w: &mut W) -> Result<Mode> {
+        display::clear(w)?;
+        let number = self
+            .input
+            .input_string(w, tr!("Enter the number of the set:"))?;
+        let Some(set) = self
+            .config
+            .get_sets()
+            .iter()
+            .find(|set| set.matches_number(&number))
+            .cloned()
+        else {
+            return Ok(self.mode.with_msg(tr!(
+                "Set {} has not been added to the config",
+                number.trim()
+            )));
+        };
+        if set.get_parts().is_empty() {
+            return Ok(self.mode.with_msg(tr!(
+                "The inventory of set {} is not known, import it with `tuibricks set import`",
+                set.get_number()
+            )));
+        }
+        let include_spares = !set.get_spare_parts().is_empty() && {
+            display::clear(w)?;
+            self.input.confirm(
+                w,
+                tr!("Are the spare parts parted out as well?"),
+                Confirm::Default(true),
+            )?
+        };
+        let parts = set.get_needed_parts(include_spares);
 
-        let searched_name = prompt::fzf_search(&opts)?;
-        if searched_name.is_empty() {
-            return Ok(self.mode.clone());
+        // The changed items are kept aside until the end, so that nothing is saved if the
+        // part-out is abandoned.
+        let mut old_items: Vec<Item> = Vec::new();
+        let mut new_items: Vec<Item> = Vec::new();
+        let mut pieces = 0;
+        let mut msg = None;
+        for (n, (&id, &expected)) in parts.iter().enumerate() {
+            let item = match new_items.iter().find(|item| item.get_id() == id) {
+                Some(item) => item.clone(),
+                None => self
+                    .db
+                    .get_item_by_id(id)
+                    .ok()
+                    .cloned()
+                    .unwrap_or_else(|| Item::new(id, String::new())),
+            };
+            display::clear(w)?;
+            display::header(
+                w,
+                &tr!(
+                    "Parting out set {}: {}, part {} of {}",
+                    set.get_number(),
+                    set.get_name(),
+                    n + 1,
+                    parts.len()
+                ),
+            )?;
+            if let Some(msg) = msg.take() {
+                display::line(w, msg)?;
+            }
+            display::line(w, tr!("Part {}: {}", id, item.get_name()))?;
+            let count = match self.input.edit_u32(
+                w,
+                &tr!(
+                    "How many pieces are bagged? The set has {}. Enter 0 to skip the part, or press Escape to stop:",
+                    expected
+                ),
+                expected,
+            ) {
+                Ok(count) => count,
+                Err(term_lib::Error::Escape) => break,
+                Err(e) => return Err(e.into()),
+            };
+            if count == 0 {
+                continue;
+            }
+            let bagged = match self.bag_pieces(w, item, count) {
+                Ok(bagged) => bagged,
+                Err(Error::TermError(term_lib::Error::Escape)) => None,
+                Err(e) => return Err(e),
+            };
+            let Some(item) = bagged else {
+                msg = Some(tr!("Part {} was skipped.", id));
+                continue;
+            };
+            pieces += count;
+            match new_items.iter_mut().find(|other| other.get_id() == id) {
+                Some(other) => *other = item,
+                None => {
+                    if let Ok(old_item) = self.db.get_item_by_id(id) {
+                        old_items.push(old_item.clone());
+                    }
+                    new_items.push(item);
+                }
+            }
         }
 
-        if let Ok(item) = self.db.get_item_by_name(&searched_name) {
-            return Ok(Mode::DisplayItem {
-                item: item.clone(),
-                msg: None,
+        if new_items.is_empty() {
+            return Ok(Mode::Default {
+                info: Some(tr!("Nothing was parted out of set {}.", set.get_number())),
+            });
+        }
+        display::clear(w)?;
+        let question = format!(
+            "{}
+{}",
+            data::describe_changes(&old_items, &new_items),
+            tr!("Save the part-out of set {}?", set.get_number())
+        );
+        if !self.input.confirm(w, &question, Confirm::Default(true))? {
+            return Ok(Mode::Default {
+                info: Some(tr!(
+                    "The part-out of set {} was discarded.",
+                    set.get_number()
+                )),
             });
         }
 
+        self.save_in_transaction(&tr!("Part-out of set {}", set.get_number()), &new_items)?;
+        let mut adjustments = Vec::new();
+        for new_item in new_items.iter() {
+            let old_item = old_items
+                .iter()
+                .find(|item| item.get_id() == new_item.get_id())
+                .cloned()
+                .unwrap_or_else(|| Item::new(new_item.get_id(), String::new()));
+            let reasons = new_item
+                .get_color_set()
+                .into_iter()
+                .map(|color_group| (color_group.clone(), Reason::PartedOut))
+                .collect();
+            adjustments.append(&mut adjustment::get_adjustments(
+                &old_item, new_item, &reasons,
+            ));
+        }
+        if !self.options.dry_run && !adjustments.is_empty() {
+            let path = adjustment::get_path(&self.config.get_db_path());
+            adjustment::append(&path, &adjustments, self.config.get_user())?;
+        }
         Ok(Mode::Default {
-            info: Some(format!("Part {} not found in database", searched_name)),
+            info: Some(tr_n!(
+                "Parted out {} piece of set {} into {} parts.",
+                "Parted out {} pieces of set {} into {} parts.",
+                pieces as usize,
+                set.get_number(),
+                new_items.len()
+            )),
         })
     }
 
-    fn search_by_location(&self, w: &mut W) -> Result<Mode> {
-        let opts = self.db.get_all_locations_string();
-        let searched_loc = prompt::fzf_search(&opts)?;
-        if !self.db.contains_location(&searched_loc) {
-            return Ok(self.mode.clone());
-        }
-
-        let locations = self.db.get_items_at_location(&searched_loc);
-
+    /// Asks for the color group and location of pieces of a part that are bagged in a part-out,
+    /// and the name of the part if it is new. Returns the item with the pieces added, or none if
+    /// they were not placed.
+    fn bag_pieces(&mut self, w: &mut W, mut item: Item, count: u32) -> Result<Option<Item>> {
         display::clear(w)?;
-
-        let info = format!("List of items located at location:\n{}", searched_loc);
+        let color_group = select_color_group(
+            &mut self.input,
+            w,
+            &self.db,
+            &tr!("Select the color group of part {}:", item.get_id()),
+            None,
+        )?;
+        if !confirm_color(
+            &mut self.input,
+            w,
+            &mut self.colors,
+            item.get_id(),
+            &color_group,
+        )? {
+            return Ok(None);
+        }
+        display::clear(w)?;
+        let location = self.ask_location(w, &item, &color_group)?;
+        if location.is_empty() {
+            return Ok(None);
+        }
+        item.add_pieces(&color_group, &location, count);
+        if item.get_name().is_empty() {
+            display::clear(w)?;
+            let name = self
+                .input
+                .input_string(w, &tr!("Enter name of new part: {}", item.get_id()))?;
+            item.set_name(name.trim());
+        }
+        Ok(Some(item))
+    }
+
+    /// Proposes moves of lots out of overfull locations, applying those that are accepted.
+    fn rebalance(&mut self, w: &mut W) -> Result<Mode> {
+        let moves = rebalance::propose(self.db.get_items(), |location| {
+            self.config.get_capacity(location)
+        });
+        if moves.is_empty() {
+            return Ok(Mode::Default {
+                info: Some(
+                    tr!("No lots can be moved to make room in overfull locations.").to_string(),
+                ),
+            });
+        }
+
+        let mut moved = 0;
+        for (i, m) in moves.iter().enumerate() {
+            display::clear(w)?;
+            display::header(w, &tr!("Proposed move {} of {}", i + 1, moves.len()))?;
+            let question = format!("{}\n\n{}", m, tr!("Move this lot?"));
+            if !self.input.confirm(w, &question, Confirm::Default(true))? {
+                continue;
+            }
+            let old_item = self.db.get_item_by_id(m.part_id)?.clone();
+            let mut item = old_item.clone();
+            item.move_lot(m.lot, m.to.clone());
+            self.db.update_item(&old_item, &item)?;
+            moved += 1;
+        }
+        Ok(Mode::Default {
+            info: Some(tr_n!(
+                "{} lot was proposed to be moved, {} were moved.",
+                "{} lots were proposed to be moved, {} were moved.",
+                moves.len(),
+                moved
+            )),
+        })
+    }
+
+    fn add_group(&mut self, w: &mut W) -> Result<Mode> {
+        display::clear(w)?;
+        display::line(w, tr!("Adding a new group to the database"))?;
+
+        let part_id = self.db.get_next_group_id();
+
+        display::clear(w)?;
+        display::line(w, tr!("Adding a new group to the database"))?;
+        let name = self
+            .input
+            .input_string(w, tr!("Enter name of new group:"))?;
+
+        let new_item = Item::new(part_id, name);
+        self.db.add_item(new_item.clone())?;
+        Ok(Mode::EditItem {
+            old_item: new_item.clone(),
+            new_item,
+            msg: None,
+        })
+    }
+
+    fn search_by_id(&mut self, w: &mut W) -> Result<Mode> {
+        display::clear(w)?;
+        let searched_id = self
+            .input
+            .input_u32(w, tr!("Enter the part ID to search for."))?;
+        Ok(self.find_by_id(searched_id))
+    }
+
+    fn find_by_id(&self, searched_id: u32) -> Mode {
+        let Some(searched_id) = PartId::new(searched_id) else {
+            return Mode::Default {
+                info: Some(tr!("{} is not a valid part ID", searched_id)),
+            };
+        };
+
+        if let Ok(item) = self.db.get_item_by_id(searched_id) {
+            return Mode::DisplayItem {
+                item: item.clone(),
+                msg: None,
+            };
+        }
+
+        Mode::Default {
+            info: Some(tr!("Part {} not found in database", searched_id)),
+        }
+    }
+
+    fn search_pasted_id(&self) -> Result<Mode> {
+        let pasted = match clipboard::paste() {
+            Ok(pasted) => pasted,
+            Err(e) => return Ok(self.mode.with_msg(e.to_string())),
+        };
+        match pasted.trim().parse() {
+            Ok(searched_id) => Ok(self.find_by_id(searched_id)),
+            Err(_) => Ok(self.mode.with_msg(tr!(
+                "The clipboard does not contain a part ID: {}",
+                pasted.trim()
+            ))),
+        }
+    }
+
+    fn search_by_filter(&mut self, w: &mut W) -> Result<Mode> {
+        display::clear(w)?;
+        let filter = self.input.input_string(
+            w,
+            tr!("Enter a filter like \"color=red, brick\", or the name of a saved filter:"),
+        )?;
+        if filter.trim().is_empty() {
+            return Ok(self.mode.clone());
+        }
+        self.show_filtered(w, filter.trim())
+    }
+
+    fn search_by_name(&mut self) -> Result<Mode> {
+        let opts = self.db.get_all_names_string();
+
+        let searched_name = self.input.fzf_search(&opts)?;
+        if searched_name.is_empty() {
+            return Ok(self.mode.clone());
+        }
+
+        if let Ok(item) = self.db.get_item_by_name(&searched_name) {
+            return Ok(Mode::DisplayItem {
+                item: item.clone(),
+                msg: None,
+            });
+        }
+
+        Ok(Mode::Default {
+            info: Some(tr!("Part {} not found in database", searched_name)),
+        })
+    }
+
+    fn search_by_location(&mut self, w: &mut W) -> Result<Mode> {
+        let opts = self.db.get_all_locations_string();
+        let searched_loc = self.input.fzf_search(&opts)?;
+        if !self.db.contains_location(&searched_loc) {
+            return Ok(self.mode.clone());
+        }
+
+        self.show_location(w, &searched_loc)
+    }
+
+    /// Lists the items matching the filter, which may be the name of a saved filter, in the columns
+    /// chosen for it and displays the one selected.
+    fn show_filtered(&mut self, w: &mut W, filter: &str) -> Result<Mode> {
+        let saved_filter = self
+            .config
+            .get_saved_filters()
+            .get_key_value(filter)
+            .map(|(name, saved)| (name.clone(), saved.clone()));
+        let (saved_name, filter) = match &saved_filter {
+            Some((name, saved)) => (Some(name.as_str()), saved.as_str()),
+            None => (None, filter),
+        };
+        let parsed = match Filter::parse(filter) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                return Ok(Mode::Default {
+                    info: Some(e.to_string()),
+                })
+            }
+        };
+        let items: Vec<&Item> = self
+            .db
+            .get_items()
+            .iter()
+            .filter(|item| parsed.matches(item))
+            .collect();
+        if parsed.rolls_up() && !items.is_empty() {
+            let molds = search::roll_up(&items);
+            return self.show_molds(w, filter, molds);
+        }
+        match items.as_slice() {
+            [] => {
+                return Ok(Mode::Default {
+                    info: Some(tr!("No parts match {}", filter)),
+                })
+            }
+            [item] => {
+                return Ok(Mode::DisplayItem {
+                    item: (*item).clone(),
+                    msg: None,
+                })
+            }
+            _ => {}
+        }
+
+        // The same parts are listed again after editing a row in place, even if they no longer
+        // match the filter.
+        let ids: Vec<PartId> = items.iter().map(|item| item.get_id()).collect();
+        let (mut selected, mut msg) = (0, None);
+        loop {
+            let items: Vec<&Item> = ids
+                .iter()
+                .filter_map(|id| self.db.get_item_by_id(*id).ok())
+                .collect();
+            let (columns, rows, column_starts) =
+                column::to_rows(&items, self.config.get_columns(saved_name));
+            display::clear(w)?;
+            let mut header = tr_n!(
+                "{} part matches {}:",
+                "{} parts match {}:",
+                items.len(),
+                filter
+            );
+            if let Some(msg) = msg.take() {
+                header = format!("{}\n{}", msg, header);
+            }
+            let table = ui::Table {
+                header: &header,
+                columns: &columns,
+                rows: &rows,
+                column_starts: &column_starts,
+                footer: &column::get_totals(&items),
+                selected,
+                editable: !self.options.read_only,
+            };
+            let (i, action) = self.input.select_row(w, &table)?;
+            let item = items[i].clone();
+            if action == RowAction::Open {
+                return Ok(Mode::DisplayItem { item, msg: None });
+            }
+            selected = i;
+            msg = match self.quick_edit(w, &item, action) {
+                Ok(edited) => Some(edited),
+                Err(Error::TermError(term_lib::Error::Escape)) => None,
+                Err(e) => return Err(e),
+            };
+        }
+    }
+
+    /// Changes the number of pieces or the location of a lot of the item and saves it right away,
+    /// asking for the new value where the keys of the table are shown. Counts can also be given
+    /// relative to the old one, like `+1` or `-3`. Returns a message telling what was changed.
+    fn quick_edit(&mut self, w: &mut W, item: &Item, action: RowAction) -> Result<String> {
+        let i = match item.get_locations() {
+            [] => return Ok(tr!("{} has no lots", item.get_id())),
+            [_] => 0,
+            _ => {
+                display::clear(w)?;
+                let header = tr!("Select a color group of {}:", item.get_id());
+                select_lot(&mut self.input, w, item, &header)?
+            }
+        };
+        let lot = &item.get_locations()[i];
+        let color_group = lot.get_color_group();
+
+        let mut new_item = item.clone();
+        let msg = match action {
+            RowAction::EditQuantity => {
+                let old = lot.get_quantity();
+                let old_text = old.map(|q| q.get().to_string()).unwrap_or_default();
+                let prompt = tr!("Number of pieces of {} {}:", item.get_id(), color_group);
+                let text = self.input.edit_string(w, &prompt, &old_text)?;
+                let text = text.trim();
+                let base = old.map_or(0, |q| q.get() as i64);
+                let parsed = if text.starts_with(['+', '-']) {
+                    text.parse::<i64>().ok().map(|change| base + change)
+                } else {
+                    text.parse::<i64>().ok()
+                };
+                let quantity = if text.is_empty() {
+                    None
+                } else if let Some(quantity) = parsed.and_then(|q| u32::try_from(q).ok()) {
+                    Some(Quantity::new(quantity))
+                } else {
+                    return Ok(tr!("{} is not a valid number of pieces", text));
+                };
+                new_item.set_lot_quantity(i, quantity);
+                self.get_capacity_warning(&new_item)
+                    .unwrap_or_else(|| tr!("Count of {} {} updated.", item.get_id(), color_group))
+            }
+            RowAction::EditLocation => {
+                let prompt = tr!("New location of {} {}:", item.get_id(), color_group);
+                let location = self.input.edit_string(w, &prompt, lot.get_location())?;
+                let location = location.trim().to_uppercase();
+                if location.is_empty() {
+                    return Err(term_lib::Error::Escape.into());
+                }
+                new_item.move_lot(i, location.clone());
+                tr!("Moved {} {} to {}.", item.get_id(), color_group, location)
+            }
+            RowAction::Open => return Ok(String::new()),
+        };
+        if new_item == *item {
+            return Ok(msg);
+        }
+
+        self.db.update_item(item, &new_item)?;
+        let adjustments = adjustment::get_adjustments(item, &new_item, &BTreeMap::new());
+        if !self.options.dry_run && !adjustments.is_empty() {
+            let path = adjustment::get_path(&self.config.get_db_path());
+            adjustment::append(&path, &adjustments, self.config.get_user())?;
+        }
+        Ok(msg)
+    }
+
+    /// Lists the base molds of the items with the pieces of each, and displays the mold selected,
+    /// or its first decorated part if the mold itself is not in the database.
+    fn show_molds(&mut self, w: &mut W, filter: &str, molds: Vec<MoldCount>) -> Result<Mode> {
+        let names: Vec<String> = molds
+            .iter()
+            .map(|mold| {
+                let name = self
+                    .db
+                    .get_item_by_id(mold.mold)
+                    .map_or(String::new(), |item| item.get_name().clone());
+                let quantity = tr_n!("{} piece", "{} pieces", mold.quantity as usize);
+                let variants = tr_n!(
+                    "{} decorated variant",
+                    "{} decorated variants",
+                    mold.variants.len()
+                );
+                format!("{} {} ({}, {})", mold.mold, name, quantity, variants)
+            })
+            .collect();
+        display::clear(w)?;
+        let header = tr_n!(
+            "{} base mold matches {}:",
+            "{} base molds match {}:",
+            molds.len(),
+            filter
+        );
+        let mold = &molds[self.input.select(w, Some(&header), &names)?];
+        let item = match self.db.get_item_by_id(mold.mold) {
+            Ok(item) => item,
+            Err(_) => self.db.get_item_by_id(mold.variants[0])?,
+        };
+        Ok(Mode::DisplayItem {
+            item: item.clone(),
+            msg: None,
+        })
+    }
+
+    /// Lists the items at the location and displays the one selected.
+    fn show_location(&mut self, w: &mut W, searched_loc: &str) -> Result<Mode> {
+        let locations = self.db.get_items_at_location(searched_loc);
+
+        display::clear(w)?;
+
+        let info = tr_n!(
+            "{} item located at location:\n{}",
+            "{} items located at location:\n{}",
+            locations.clone().count(),
+            searched_loc,
+        );
         display::header(w, &info)?;
-        let selected: LocSearch = prompt::select_from_list(w, None, locations)?;
+        let fill = Fill::new(
+            self.db.get_items().iter(),
+            searched_loc,
+            self.config.get_capacity(searched_loc),
+        );
+        display::line(w, tr!("Filled: {}", fill))?;
+        let selected: LocSearch = ui::select_from_list(&mut self.input, w, None, locations)?;
 
         let item = self.db.get_item_by_id(selected.id)?;
 
@@ -235,6 +1579,32 @@ impl<W: std::io::Write> State<W> {
         })
     }
 
+    /// Warns about the locations of the item that would hold more pieces than fit in them once
+    /// the item is changed.
+    fn get_capacity_warning(&self, item: &Item) -> Option<String> {
+        let locations: BTreeSet<&String> = item
+            .get_locations()
+            .iter()
+            .map(|lot| lot.get_location())
+            .collect();
+        let warnings: Vec<String> = locations
+            .into_iter()
+            .filter_map(|location| {
+                let capacity = self.config.get_capacity(location);
+                let others = self
+                    .db
+                    .get_items()
+                    .iter()
+                    .filter(|other| other.get_id() != item.get_id());
+                let old = Fill::new(self.db.get_items().iter(), location, capacity);
+                let new = Fill::new(others.chain(std::iter::once(item)), location, capacity);
+                (new.is_overfull() && new.pieces > old.pieces)
+                    .then(|| tr!("Location {} would be overfull: {}", location, new))
+            })
+            .collect();
+        (!warnings.is_empty()).then(|| warnings.join("\n"))
+    }
+
     fn edit_item(&self) -> Result<Mode> {
         let Mode::DisplayItem { item, msg: _ } = &self.mode else {
             bail!(self, Edit);
@@ -255,14 +1625,41 @@ impl<W: std::io::Write> State<W> {
         else {
             bail!(self, SaveEdit);
         };
+        let broken = rule::get_broken(self.config.get_rules(), new_item);
+        if !broken.is_empty() {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr_n!(
+                    "Not saved, the item breaks {} rule: {}",
+                    "Not saved, the item breaks {} rules: {}",
+                    broken.len(),
+                    broken.join(", ")
+                )),
+            });
+        }
         self.db.update_item(old_item, new_item)?;
+
+        let adjustments = adjustment::get_adjustments(old_item, new_item, &self.reasons);
+        self.reasons.clear();
+        let sales = std::mem::take(&mut self.sales);
+        if !self.options.dry_run {
+            let db_path = self.config.get_db_path();
+            if !adjustments.is_empty() {
+                let path = adjustment::get_path(&db_path);
+                adjustment::append(&path, &adjustments, self.config.get_user())?;
+            }
+            if !sales.is_empty() {
+                sale::append(&sale::get_path(&db_path), &sales)?;
+            }
+        }
         Ok(Mode::DisplayItem {
             item: new_item.clone(),
             msg: None,
         })
     }
 
-    fn quit_edit(&self, w: &mut W) -> Result<Mode> {
+    fn quit_edit(&mut self, w: &mut W) -> Result<Mode> {
         let Mode::EditItem {
             old_item,
             new_item,
@@ -280,12 +1677,14 @@ impl<W: std::io::Write> State<W> {
         }
 
         display::clear(w)?;
-        let changes = format!(
+        let changes = tr!(
             "Are you sure you want to quit editing and cancel these changes?\n\n{}",
             old_item.diff(new_item)
         );
 
-        if prompt::confirmation(w, &changes)? {
+        if self.input.confirm(w, &changes, Confirm::Destructive)? {
+            self.reasons.clear();
+            self.sales.clear();
             Ok(Mode::DisplayItem {
                 item: new_item.clone(),
                 msg: None,
@@ -299,7 +1698,7 @@ impl<W: std::io::Write> State<W> {
         }
     }
 
-    fn edit_name(&self, w: &mut W) -> Result<Mode> {
+    fn edit_name(&mut self, w: &mut W) -> Result<Mode> {
         let Mode::EditItem {
             old_item,
             new_item,
@@ -310,16 +1709,19 @@ impl<W: std::io::Write> State<W> {
         };
 
         display::clear(w)?;
-        display::line(w, format!("Editing name of part: {}", new_item.get_id()))?;
-        let new_name = prompt::edit_string(w, "Enter new name:", new_item.get_name())?;
+        display::line(w, tr!("Editing name of part: {}", new_item.get_id()))?;
+        let new_name = self
+            .input
+            .edit_string(w, tr!("Enter new name:"), new_item.get_name())?;
 
         if let Some(existing_id) = self.db.contains_name(&new_name) {
             return Ok(Mode::EditItem {
                 old_item: old_item.clone(),
                 new_item: new_item.clone(),
-                msg: Some(format!(
+                msg: Some(tr!(
                     "The item with part ID {} already has the name {}",
-                    existing_id, new_name,
+                    existing_id,
+                    new_name,
                 )),
             });
         }
@@ -329,11 +1731,11 @@ impl<W: std::io::Write> State<W> {
         Ok(Mode::EditItem {
             old_item: old_item.clone(),
             new_item: updated_item,
-            msg: Some("Item name succesfully updated.".to_string()),
+            msg: Some(tr!("Item name succesfully updated.").to_string()),
         })
     }
 
-    fn edit_part_id(&self, w: &mut W) -> Result<Mode> {
+    fn edit_part_id(&mut self, w: &mut W) -> Result<Mode> {
         let Mode::EditItem {
             old_item,
             new_item,
@@ -344,16 +1746,31 @@ impl<W: std::io::Write> State<W> {
         };
 
         display::clear(w)?;
-        display::line(w, format!("Editing ID of part: {}", new_item.get_id()))?;
-        let new_id = prompt::edit_u32(w, "Enter new ID:", new_item.get_id())?;
+        display::line(w, tr!("Editing ID of part: {}", new_item.get_id()))?;
+        let new_number =
+            self.input
+                .edit_u32(w, tr!("Enter new ID:"), new_item.get_id().get_number())?;
+        let new_id = if new_item.is_group() {
+            PartId::group(new_number)
+        } else {
+            PartId::new(new_number)
+        };
+        let Some(new_id) = new_id else {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("{} is not a valid part ID", new_number)),
+            });
+        };
 
         if let Some(main_id) = self.db.contains_id(new_id) {
             return Ok(Mode::EditItem {
                 old_item: old_item.clone(),
                 new_item: new_item.clone(),
-                msg: Some(format!(
+                msg: Some(tr!(
                     "Item with part ID {} already exists in database under item with part ID {}",
-                    new_id, main_id
+                    new_id,
+                    main_id
                 )),
             });
         }
@@ -363,161 +1780,178 @@ impl<W: std::io::Write> State<W> {
         Ok(Mode::EditItem {
             old_item: old_item.clone(),
             new_item: updated_item,
-            msg: Some("Part ID succesfully updated.".to_string()),
+            msg: Some(tr!("Part ID succesfully updated.").to_string()),
         })
     }
 
-    fn move_color_group(&self, w: &mut W) -> Result<Mode> {
+    fn edit_price(&mut self, w: &mut W) -> Result<Mode> {
         let Mode::EditItem {
             old_item,
             new_item,
-            msg: None,
+            msg: _,
         } = &self.mode
         else {
-            bail!(self, EditName);
+            bail!(self, EditPrice);
         };
 
         display::clear(w)?;
+        display::line(w, tr!("Editing price of part: {}", new_item.get_id()))?;
+        let old_price = new_item
+            .get_price()
+            .map(|price| format!("{} {}", price.get_amount(), price.get_currency()))
+            .unwrap_or_default();
+        let new_price = self.input.edit_string(
+            w,
+            tr!("Enter new price, e.g. \"0.25 EUR\". Leave empty to remove the price:"),
+            &old_price,
+        )?;
 
-        let color_group: &ColorGroup = if new_item.get_locations().len() == 1 {
-            &new_item.get_locations()[0].0
+        let price = if new_price.trim().is_empty() {
+            None
+        } else if let Some(price) = Price::parse(&new_price) {
+            Some(price)
         } else {
-            prompt::select_from_list(
-                w,
-                Some("Select color group to move:"),
-                new_item.get_color_set().iter(),
-            )?
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("{} is not a valid price", new_price)),
+            });
         };
 
-        display::clear(w)?;
-        display::line(
-            w,
-            format!(
-                "Moving color group {} from item with ID: {}",
-                color_group,
-                new_item.get_id()
-            ),
-        )?;
-        let part_loc =
-            prompt::input_string(w, &format!("Enter new location of group {}:", color_group))?;
-        let part_loc = part_loc.to_uppercase();
-
         let mut updated_item = new_item.clone();
-        updated_item.remove_color_group(color_group);
-        updated_item.add_color_group(color_group.clone(), part_loc);
-
+        updated_item.set_price(price);
         Ok(Mode::EditItem {
             old_item: old_item.clone(),
             new_item: updated_item,
-            msg: None,
+            msg: Some(tr!("Price succesfully updated.").to_string()),
         })
     }
 
-    fn add_color_group(&self, w: &mut W) -> Result<Mode> {
+    fn edit_quantity(&mut self, w: &mut W) -> Result<Mode> {
         let Mode::EditItem {
             old_item,
             new_item,
             msg: _,
         } = &self.mode
         else {
-            bail!(self, AddColorGroup)
+            bail!(self, EditQuantity);
         };
 
         display::clear(w)?;
-        display::line(
-            w,
-            format!(
-                "Adding a new color group to item with ID: {}",
-                new_item.get_id()
-            ),
-        )?;
 
-        let color_set = new_item.get_color_set();
-        let options = ColorGroup::iter().filter(|c| !color_set.contains(c));
-
-        let mut color_group = prompt::select_cmd(
+        let i = select_lot(
+            &mut self.input,
             w,
-            "Select a color group for which to add a location",
-            options,
+            new_item,
+            tr!("Select color group to count:"),
         )?;
-
-        if let ColorGroup::Other(_) = color_group {
-            display::clear(w)?;
-
-            let create_new = "Create new color group".to_owned();
-
-            let item_o_color_set = new_item.get_other_color_set();
-            let options = self
-                .db
-                .get_other_color_set()
-                .iter()
-                .filter(|s| !item_o_color_set.contains(*s));
-
-            let color_name = prompt::select_from_list(
-                w,
-                Some("Select other color group or create a new one"),
-                std::iter::once(&create_new).chain(options),
-            )?;
-
-            if color_name == &create_new {
-                display::clear(w)?;
-                let color_name =
-                    prompt::input_string(w, &format!("Enter the name of the new color group:"))?;
-                color_group = ColorGroup::Other(color_name);
-            } else {
-                color_group = ColorGroup::Other(color_name.to_string());
-            }
-        }
+        let lot = &new_item.get_locations()[i];
+        let color_group = lot.get_color_group();
 
         display::clear(w)?;
         display::line(
             w,
-            format!(
-                "Adding a new color group to item with ID: {}",
+            tr!(
+                "Editing count of color group {} of part: {}",
+                color_group,
                 new_item.get_id()
             ),
         )?;
+        let old_quantity = lot
+            .get_quantity()
+            .map(|quantity| quantity.get().to_string())
+            .unwrap_or_default();
+        let new_quantity = self.input.edit_string(
+            w,
+            tr!("Enter number of pieces. Leave empty if unknown:"),
+            &old_quantity,
+        )?;
 
-        let part_loc =
-            prompt::input_string(w, &format!("Enter location of group {}:", color_group))?;
-        let part_loc = part_loc.to_uppercase();
+        let quantity = if new_quantity.trim().is_empty() {
+            None
+        } else if let Some(quantity) = Quantity::parse(&new_quantity) {
+            Some(quantity)
+        } else {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("{} is not a valid number of pieces", new_quantity)),
+            });
+        };
+
+        if quantity != lot.get_quantity() {
+            display::clear(w)?;
+            let reason = match ui::select_cmd(
+                &mut self.input,
+                w,
+                tr!("Why did the count change?"),
+                Reason::iter(),
+            ) {
+                Ok(reason) => reason,
+                Err(term_lib::Error::Escape) => Reason::Unspecified,
+                Err(e) => return Err(e.into()),
+            };
+            self.reasons.insert(color_group.clone(), reason);
+        }
 
         let mut updated_item = new_item.clone();
-        updated_item.add_color_group(color_group, part_loc);
+        updated_item.set_lot_quantity(i, quantity);
+        let msg = self
+            .get_capacity_warning(&updated_item)
+            .unwrap_or_else(|| tr!("Count succesfully updated.").to_string());
         Ok(Mode::EditItem {
             old_item: old_item.clone(),
             new_item: updated_item,
-            msg: None,
+            msg: Some(msg),
         })
     }
 
-    fn remove_color_group(&self, w: &mut W) -> Result<Mode> {
+    fn list_for_sale(&mut self, w: &mut W) -> Result<Mode> {
         let Mode::EditItem {
             old_item,
             new_item,
-            msg: None,
+            msg: _,
         } = &self.mode
         else {
-            bail!(self, RemoveColorGroup);
+            bail!(self, ListForSale);
         };
 
         display::clear(w)?;
-        display::line(
+        let i = select_lot(
+            &mut self.input,
             w,
-            format!(
-                "Removing a color group from item with ID: {}",
-                new_item.get_id()
-            ),
+            new_item,
+            tr!("Select color group to list for sale:"),
         )?;
+        let lot = &new_item.get_locations()[i];
 
-        let color_group: &ColorGroup = prompt::select_from_list(
+        display::clear(w)?;
+        let old_price = lot
+            .get_asking_price()
+            .map(|price| format!("{} {}", price.get_amount(), price.get_currency()))
+            .unwrap_or_default();
+        let new_price = self.input.edit_string(
             w,
-            Some("Select color group to remove:"),
-            new_item.get_color_set().iter(),
+            tr!(
+                "Enter the asking price per piece, e.g. \"0.05 EUR\". Leave empty to stop listing:"
+            ),
+            &old_price,
         )?;
 
+        let price = if new_price.trim().is_empty() {
+            None
+        } else if let Some(price) = Price::parse(&new_price) {
+            Some(price)
+        } else {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("{} is not a valid price", new_price)),
+            });
+        };
+
         let mut updated_item = new_item.clone();
-        updated_item.remove_color_group(color_group);
+        updated_item.set_lot_asking_price(i, price);
         Ok(Mode::EditItem {
             old_item: old_item.clone(),
             new_item: updated_item,
@@ -525,33 +1959,42 @@ impl<W: std::io::Write> State<W> {
         })
     }
 
-    fn add_alt_id(&self, w: &mut W) -> Result<Mode> {
+    fn edit_base_mold(&mut self, w: &mut W) -> Result<Mode> {
         let Mode::EditItem {
             old_item,
             new_item,
             msg: _,
         } = &self.mode
         else {
-            bail!(self, AddAltId);
+            bail!(self, EditBaseMold);
         };
 
         display::clear(w)?;
-        let new_id = prompt::input_u32(w, "Enter the new alternative part ID to add to this item")?;
-
-        if let Some(main_id) = self.db.contains_id(new_id) {
-            let msg = Some(format!(
-                "Item with part ID {} already exists in database under item with part ID {}",
-                new_id, main_id
-            ));
-            return Ok(Mode::EditItem {
-                old_item: old_item.clone(),
-                new_item: new_item.clone(),
-                msg,
-            });
-        }
+        let old_mold = new_item
+            .get_base_mold()
+            .map(|mold| mold.to_string())
+            .unwrap_or_default();
+        let new_mold = self.input.edit_string(
+            w,
+            tr!("Enter the part ID of the plain part this part is a print or pattern of. Leave empty if it is not decorated:"),
+            &old_mold,
+        )?;
+        let mold = match new_mold.trim() {
+            "" => None,
+            mold => match mold.parse().ok().and_then(PartId::new) {
+                Some(mold) if mold != new_item.get_id() => Some(mold),
+                _ => {
+                    return Ok(Mode::EditItem {
+                        old_item: old_item.clone(),
+                        new_item: new_item.clone(),
+                        msg: Some(tr!("{} is not a valid base mold", new_mold)),
+                    })
+                }
+            },
+        };
 
         let mut updated_item = new_item.clone();
-        updated_item.add_alt_id(new_id);
+        updated_item.set_base_mold(mold);
         Ok(Mode::EditItem {
             old_item: old_item.clone(),
             new_item: updated_item,
@@ -559,27 +2002,42 @@ impl<W: std::io::Write> State<W> {
         })
     }
 
-    fn remove_alt_id(&self, w: &mut W) -> Result<Mode> {
+    fn edit_dimensions(&mut self, w: &mut W) -> Result<Mode> {
         let Mode::EditItem {
             old_item,
             new_item,
             msg: _,
         } = &self.mode
         else {
-            bail!(self, RemoveAltId);
+            bail!(self, EditDimensions);
         };
 
         display::clear(w)?;
-
-        let options: BTreeSet<u32> = new_item.get_alternative_ids().iter().map(|i| *i).collect();
-        let alt_id = prompt::select_from_list(
+        let old_dimensions = new_item
+            .get_dimensions()
+            .map(|dimensions| dimensions.to_string())
+            .unwrap_or_default();
+        let new_dimensions = self.input.edit_string(
             w,
-            Some("Which alternative ID do you want to remove?"),
-            options.iter(),
+            tr!("Enter the size in studs and the height in bricks, e.g. \"1 x 4 x 1/3\" for a plate. Leave empty if unknown:"),
+            &old_dimensions,
         )?;
+        let dimensions = match new_dimensions.trim() {
+            "" => None,
+            dimensions => match Dimensions::parse(dimensions) {
+                Some(dimensions) => Some(dimensions),
+                None => {
+                    return Ok(Mode::EditItem {
+                        old_item: old_item.clone(),
+                        new_item: new_item.clone(),
+                        msg: Some(tr!("{} is not a valid size", new_dimensions)),
+                    })
+                }
+            },
+        };
 
         let mut updated_item = new_item.clone();
-        updated_item.remove_alt_id(*alt_id);
+        updated_item.set_dimensions(dimensions);
         Ok(Mode::EditItem {
             old_item: old_item.clone(),
             new_item: updated_item,
@@ -587,28 +2045,1011 @@ impl<W: std::io::Write> State<W> {
         })
     }
 
-    fn delete_item(&mut self, w: &mut W) -> Result<Mode> {
+    fn edit_fields(&mut self, w: &mut W) -> Result<Mode> {
         let Mode::EditItem {
             old_item,
             new_item,
             msg: _,
         } = &self.mode
         else {
-            bail!(self, DeleteItem);
+            bail!(self, EditFields);
+        };
+
+        display::clear(w)?;
+        let fields = field::get_fields();
+        let options: Vec<String> = fields
+            .iter()
+            .map(|field| match new_item.get_field(field.get_name()) {
+                Some(value) => format!("{}: {}", field.get_name(), field.fmt_value(value)),
+                None => field.get_name().to_string(),
+            })
+            .collect();
+        let i = self
+            .input
+            .select(w, Some(tr!("Select the field to edit")), &options)?;
+        let Some(field) = fields.get(i) else {
+            bail!(self, EditFields);
+        };
+
+        display::clear(w)?;
+        let old_value = new_item.get_field(field.get_name()).unwrap_or_default();
+        let header = tr!("Enter the {}. Leave empty if unknown:", field.get_name());
+        let new_value = match field.get_kind() {
+            field::Kind::Text | field::Kind::Number => {
+                self.input.edit_string(w, &header, old_value)?
+            }
+            field::Kind::Bool | field::Kind::Enum { .. } => {
+                let values = match field.get_kind() {
+                    field::Kind::Enum { values } => values.clone(),
+                    _ => vec!["true".to_string(), "false".to_string()],
+                };
+                let mut options: Vec<String> =
+                    values.iter().map(|value| field.fmt_value(value)).collect();
+                options.push(tr!("Leave empty").to_string());
+                let i = self.input.select(w, Some(&header), &options)?;
+                values.get(i).cloned().unwrap_or_default()
+            }
+        };
+        let value = match new_value.trim() {
+            "" => None,
+            value => match field.parse(value) {
+                Some(value) => Some(value),
+                None => {
+                    return Ok(Mode::EditItem {
+                        old_item: old_item.clone(),
+                        new_item: new_item.clone(),
+                        msg: Some(tr!("{} is not a valid {}", new_value, field.get_name())),
+                    })
+                }
+            },
+        };
+
+        let mut updated_item = new_item.clone();
+        updated_item.set_field(field.get_name(), value);
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            new_item: updated_item,
+            msg: None,
+        })
+    }
+
+    fn edit_components(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, EditComponents);
+        };
+
+        display::clear(w)?;
+        let new_components = self.input.edit_string(
+            w,
+            tr!("Enter the parts this assembly is made of, e.g. \"3482 x 1, 3483 x 1\". Leave empty if it is not an assembly:"),
+            &assembly::fmt(new_item.get_components()),
+        )?;
+        let components = match assembly::parse(&new_components) {
+            Some(components) if !components.contains_key(&new_item.get_id()) => components,
+            _ => {
+                return Ok(Mode::EditItem {
+                    old_item: old_item.clone(),
+                    new_item: new_item.clone(),
+                    msg: Some(tr!("{} are not valid components", new_components)),
+                })
+            }
+        };
+
+        let mut updated_item = new_item.clone();
+        updated_item.set_components(components);
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            new_item: updated_item,
+            msg: None,
+        })
+    }
+
+    /// Puts together assemblies from their components, or takes them apart if not `assemble`,
+    /// changing the counts of the assembly and its components right away.
+    fn convert_assemblies(&mut self, w: &mut W, assemble: bool) -> Result<Mode> {
+        let Mode::DisplayItem { item, .. } = &self.mode else {
+            if assemble {
+                bail!(self, Assemble);
+            } else {
+                bail!(self, Disassemble);
+            }
+        };
+        let item = item.clone();
+
+        display::clear(w)?;
+        let lot = if item.get_locations().len() == 1 {
+            &item.get_locations()[0]
+        } else {
+            ui::select_from_list(
+                &mut self.input,
+                w,
+                Some(tr!("Select color group of the assemblies:")),
+                item.get_locations().iter(),
+            )?
+        };
+        let color_group = lot.get_color_group().clone();
+
+        display::clear(w)?;
+        let question = if assemble {
+            tr!("How many assemblies were put together?")
+        } else {
+            tr!("How many assemblies were taken apart?")
+        };
+        let quantity = self.input.input_u32(w, question)?;
+        let changes = match assembly::get_changes(&item, &color_group, quantity, assemble, |id| {
+            self.db.get_item_by_id(id)
+        }) {
+            Ok(changes) => changes,
+            Err(e) => {
+                return Ok(Mode::DisplayItem {
+                    item,
+                    msg: Some(e.to_string()),
+                })
+            }
+        };
+
+        let mut adjustments = Vec::new();
+        for new_item in changes.iter() {
+            let old_item = self.db.get_item_by_id(new_item.get_id())?.clone();
+            let reasons = new_item
+                .get_color_set()
+                .into_iter()
+                .map(|color_group| (color_group.clone(), Reason::Assembly))
+                .collect();
+            adjustments.extend(adjustment::get_adjustments(&old_item, new_item, &reasons));
+        }
+        let name = match assemble {
+            true => tr!("Assembly of part {}", item.get_id()),
+            false => tr!("Disassembly of part {}", item.get_id()),
+        };
+        self.save_in_transaction(&name, &changes)?;
+        if !self.options.dry_run && !adjustments.is_empty() {
+            let path = adjustment::get_path(&self.config.get_db_path());
+            adjustment::append(&path, &adjustments, self.config.get_user())?;
+        }
+
+        let msg = if assemble {
+            tr_n!(
+                "Put together {} assembly from its components.",
+                "Put together {} assemblies from their components.",
+                quantity as usize
+            )
+        } else {
+            tr_n!(
+                "Took apart {} assembly into its components.",
+                "Took apart {} assemblies into their components.",
+                quantity as usize
+            )
+        };
+        Ok(Mode::DisplayItem {
+            item: changes[0].clone(),
+            msg: Some(msg),
+        })
+    }
+
+    fn toggle_section(&mut self, section: Section) -> Result<Mode> {
+        if !self.expanded.remove(&section) {
+            self.expanded.insert(section);
+        }
+        Ok(self.mode.clone())
+    }
+
+    fn show_similar(&self, i: usize) -> Result<Mode> {
+        let Some((item, _)) = self.get_similar().get(i).copied() else {
+            return Err(Error::CmdModeMismatch {
+                cmd: Cmd::ShowSimilar(i).display_as_cmd(),
+                mode: self.mode.to_string(),
+            });
+        };
+        Ok(Mode::DisplayItem {
+            item: item.clone(),
+            msg: None,
+        })
+    }
+
+    /// Runs the plugin on the items of its scope, and adds or replaces the items it answers with
+    /// if it changes items.
+    fn run_plugin(&mut self, w: &mut W, i: usize) -> Result<Mode> {
+        let mismatch = || Error::CmdModeMismatch {
+            cmd: Cmd::Plugin(i).display_as_cmd(),
+            mode: self.mode.to_string(),
+        };
+        let plugin = plugin::get(i).ok_or_else(mismatch)?;
+
+        let filter = match plugin.get_scope() {
+            plugin::Scope::Filter => {
+                display::clear(w)?;
+                let filter = self.input.input_string(
+                    w,
+                    tr!("Enter a filter like \"color=red, brick\", or the name of a saved filter:"),
+                )?;
+                let filter = filter.trim().to_string();
+                Some(match self.config.get_saved_filters().get(&filter) {
+                    Some(saved) => saved.clone(),
+                    None => filter,
+                })
+            }
+            _ => None,
+        };
+        let output = {
+            let items: Vec<&Item> = match (plugin.get_scope(), &self.mode, &filter) {
+                (plugin::Scope::Item, Mode::DisplayItem { item, .. }, _) => vec![item],
+                (plugin::Scope::Filter, _, Some(filter)) => {
+                    let filter = match Filter::parse(filter) {
+                        Ok(filter) => filter,
+                        Err(e) => return Ok(self.mode.with_msg(e.to_string())),
+                    };
+                    let items = self.db.get_items().iter();
+                    items.filter(|item| filter.matches(item)).collect()
+                }
+                (plugin::Scope::Database, _, _) => self.db.get_items().iter().collect(),
+                _ => return Err(mismatch()),
+            };
+            display::clear(w)?;
+            display::line(w, tr!("Running {}...", plugin.get_label()))?;
+            plugin::run(plugin, filter.as_deref(), &items)?
+        };
+
+        let mut changed = 0;
+        if plugin.changes_items() {
+            self.save_in_transaction(plugin.get_label(), &output.items)?;
+            changed = output.items.len();
+        }
+        let msg = output.message.unwrap_or_else(|| {
+            tr_n!(
+                "Done, {} item was changed.",
+                "Done, {} items were changed.",
+                changed
+            )
+        });
+        Ok(match &self.mode {
+            Mode::DisplayItem { item, .. } => Mode::DisplayItem {
+                item: self.db.find(item.get_id()).unwrap_or(item).clone(),
+                msg: Some(msg),
+            },
+            mode => mode.with_msg(msg),
+        })
+    }
+
+    fn edit_piece_weight(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, EditPieceWeight);
+        };
+
+        display::clear(w)?;
+        let old_weight = new_item
+            .get_piece_weight()
+            .map(|weight| weight.to_string())
+            .unwrap_or_default();
+        let new_weight = self.input.edit_string(
+            w,
+            tr!("Enter the weight of one piece in grams. Leave empty if unknown:"),
+            &old_weight,
+        )?;
+        let Some(weight) = parse_weight(&new_weight) else {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("{} is not a valid weight", new_weight)),
+            });
+        };
+
+        let mut updated_item = new_item.clone();
+        updated_item.set_piece_weight(weight);
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            new_item: updated_item,
+            msg: None,
+        })
+    }
+
+    fn weigh_lot(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, WeighLot);
+        };
+
+        display::clear(w)?;
+        let i = select_lot(
+            &mut self.input,
+            w,
+            new_item,
+            tr!("Select color group to weigh:"),
+        )?;
+        let lot = &new_item.get_locations()[i];
+
+        display::clear(w)?;
+        let old_weight = lot
+            .get_weight()
+            .map(|weight| weight.to_string())
+            .unwrap_or_default();
+        let new_weight = self.input.edit_string(
+            w,
+            tr!("Enter the weight of the pieces in grams. Leave empty if they are not weighed:"),
+            &old_weight,
+        )?;
+        let Some(weight) = parse_weight(&new_weight) else {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("{} is not a valid weight", new_weight)),
+            });
+        };
+
+        let mut updated_item = new_item.clone();
+        updated_item.set_lot_weight(i, weight);
+        let msg = if new_item.get_piece_weight().is_none() && weight.is_some() {
+            Some(tr!("Enter the weight of one piece to estimate the number of pieces.").to_string())
+        } else {
+            None
+        };
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            new_item: updated_item,
+            msg,
+        })
+    }
+
+    fn split_lot(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, SplitLot);
+        };
+
+        display::clear(w)?;
+        let i = select_lot(
+            &mut self.input,
+            w,
+            new_item,
+            tr!("Select the lot to divide:"),
+        )?;
+        let lot = &new_item.get_locations()[i];
+
+        display::clear(w)?;
+        display::line(w, lot.to_string())?;
+        let pieces = self
+            .input
+            .input_u32(w, tr!("Enter the number of pieces to move:"))?;
+        let location = self
+            .input
+            .input_string(w, tr!("Enter the location of the new lot:"))?
+            .to_uppercase();
+
+        let mut updated_item = new_item.clone();
+        let Some(j) = updated_item.split_lot(i, pieces, location.clone()) else {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!(
+                    "The lot cannot be divided: it needs more than {} counted pieces, and {} must not have a lot of {} already.",
+                    pieces,
+                    location,
+                    lot.get_color_group()
+                )),
+            });
+        };
+
+        display::clear(w)?;
+        let same = tr!("the same condition").to_string();
+        let conditions = [None, Some(Condition::New), Some(Condition::Used)];
+        let options: Vec<String> = conditions
+            .iter()
+            .map(|condition| condition.map_or(same.clone(), |c| c.to_string()))
+            .collect();
+        let k = self.input.select(
+            w,
+            Some(tr!("Select the condition of the new lot:")),
+            &options,
+        )?;
+        let mut new_lots = updated_item.get_locations().to_vec();
+        if let Some(Some(condition)) = conditions.get(k) {
+            new_lots[j].set_condition(Some(*condition));
+        }
+
+        display::clear(w)?;
+        let old_price = new_lots[j]
+            .get_asking_price()
+            .map(|price| format!("{} {}", price.get_amount(), price.get_currency()))
+            .unwrap_or_default();
+        let new_price = self.input.edit_string(
+            w,
+            tr!(
+                "Enter the asking price per piece of the new lot. Leave empty if it is not listed:"
+            ),
+            &old_price,
+        )?;
+        match new_price.trim() {
+            "" => new_lots[j].set_asking_price(None),
+            price => match Price::parse(price) {
+                Some(price) => new_lots[j].set_asking_price(Some(price)),
+                None => {
+                    return Ok(Mode::EditItem {
+                        old_item: old_item.clone(),
+                        new_item: new_item.clone(),
+                        msg: Some(tr!("{} is not a valid price", new_price)),
+                    })
+                }
+            },
+        }
+        updated_item.set_locations(new_lots);
+
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            msg: self.get_capacity_warning(&updated_item),
+            new_item: updated_item,
+        })
+    }
+
+    fn edit_purchased(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, EditPurchased);
+        };
+
+        display::clear(w)?;
+        let i = select_lot(
+            &mut self.input,
+            w,
+            new_item,
+            tr!("Select the lot that was bought:"),
+        )?;
+        let lot = &new_item.get_locations()[i];
+        let start = lot.get_purchased().map_or_else(Date::today, Date::of);
+        let day = date::input_date(
+            &mut self.input,
+            w,
+            tr!("When were the pieces bought?"),
+            start,
+        )?;
+
+        let mut updated_item = new_item.clone();
+        updated_item.set_lot_purchased(i, Some(day.to_timestamp()));
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            msg: self.get_capacity_warning(&updated_item),
+            new_item: updated_item,
+        })
+    }
+
+    /// Merges two lots of a color group, asking which location, notes and condition to keep
+    /// where they differ.
+    fn merge_lots(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, MergeLots);
+        };
+
+        display::clear(w)?;
+        let lots = new_item.get_locations();
+        let lot = ui::select_from_list(
+            &mut self.input,
+            w,
+            Some(tr!("Select the first lot to merge:")),
+            lots.iter(),
+        )?;
+        let i = lots.iter().position(|other| other == lot).unwrap_or(0);
+        let others: Vec<_> = lots
+            .iter()
+            .enumerate()
+            .filter(|(j, other)| *j != i && other.get_color_group() == lot.get_color_group())
+            .map(|(_, other)| other)
+            .collect();
+        let other = match others.as_slice() {
+            [] => {
+                return Ok(Mode::EditItem {
+                    old_item: old_item.clone(),
+                    new_item: new_item.clone(),
+                    msg: Some(tr!(
+                        "There is no other lot of {} to merge with",
+                        lot.get_color_group()
+                    )),
+                })
+            }
+            [other] => *other,
+            others => {
+                display::clear(w)?;
+                ui::select_from_list(
+                    &mut self.input,
+                    w,
+                    Some(tr!("Select the lot to merge it with:")),
+                    others.iter().copied(),
+                )?
+            }
+        };
+        let j = lots.iter().position(|lot| lot == other).unwrap_or(0);
+
+        let (mut keep, mut merged) = (i, j);
+        if lot.get_location() != other.get_location() {
+            display::clear(w)?;
+            let locations = [lot.get_location().clone(), other.get_location().clone()];
+            if self.input.select(
+                w,
+                Some(tr!("Select the location of the merged lot:")),
+                &locations,
+            )? == 1
+            {
+                (keep, merged) = (j, i);
+            }
+        }
+
+        let mut new_lots = lots.to_vec();
+        if let (Some(a), Some(b)) = (lots[keep].get_notes(), lots[merged].get_notes()) {
+            if a != b {
+                display::clear(w)?;
+                let options = [a.to_string(), b.to_string(), format!("{}; {}", a, b)];
+                let k = self.input.select(
+                    w,
+                    Some(tr!(
+                        "The lots have different notes. Select the notes to keep:"
+                    )),
+                    &options,
+                )?;
+                new_lots[keep].set_notes(Some(options[k].clone()));
+            }
+        }
+        if let (Some(a), Some(b)) = (lots[keep].get_condition(), lots[merged].get_condition()) {
+            if a != b {
+                display::clear(w)?;
+                let options = [a.to_string(), b.to_string()];
+                let k = self.input.select(
+                    w,
+                    Some(tr!("The lots are in different conditions. Select the condition of the merged lot:")),
+                    &options,
+                )?;
+                new_lots[keep].set_condition(Some([a, b][k]));
+                new_lots[merged].set_condition(Some([a, b][k]));
+            }
+        }
+
+        let mut updated_item = new_item.clone();
+        updated_item.set_locations(new_lots);
+        if updated_item.merge_lots(keep, merged).is_none() {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("The lots could not be merged").to_string()),
+            });
+        }
+
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            msg: self.get_capacity_warning(&updated_item),
+            new_item: updated_item,
+        })
+    }
+
+    /// Adds or removes the count typed before the command, or one, to or from the pieces of a lot.
+    fn change_pieces(&mut self, w: &mut W, add: bool) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            let cmd = if add {
+                Cmd::AddPieces
+            } else {
+                Cmd::RemovePieces
+            };
+            return Err(Error::CmdModeMismatch {
+                cmd: cmd.display_as_cmd(),
+                mode: self.mode.to_string(),
+            });
+        };
+        let count = self.count.take().unwrap_or(1);
+
+        let i = if new_item.get_locations().len() == 1 {
+            0
+        } else {
+            display::clear(w)?;
+            let header = if add {
+                tr_n!(
+                    "Select color group to add {} piece to:",
+                    "Select color group to add {} pieces to:",
+                    count as usize
+                )
+            } else {
+                tr_n!(
+                    "Select color group to remove {} piece from:",
+                    "Select color group to remove {} pieces from:",
+                    count as usize
+                )
+            };
+            select_lot(&mut self.input, w, new_item, &header)?
+        };
+        let lot = &new_item.get_locations()[i];
+        let color_group = lot.get_color_group();
+        let old = lot.get_quantity().map_or(0, |quantity| quantity.get());
+
+        let new = if add {
+            old.saturating_add(count)
+        } else if let Some(new) = old.checked_sub(count) {
+            new
+        } else {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr_n!(
+                    "There is only {} piece in color group {}",
+                    "There are only {} pieces in color group {}",
+                    old as usize,
+                    color_group
+                )),
+            });
+        };
+
+        let mut updated_item = new_item.clone();
+        updated_item.set_lot_quantity(i, Some(Quantity::new(new)));
+        let msg = self.get_capacity_warning(&updated_item).unwrap_or_else(|| {
+            tr!(
+                "Count of color group {} changed from {} to {}.",
+                color_group,
+                old,
+                new
+            )
+        });
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            new_item: updated_item,
+            msg: Some(msg),
+        })
+    }
+
+    fn record_sale(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, RecordSale);
+        };
+
+        display::clear(w)?;
+        let i = select_lot(
+            &mut self.input,
+            w,
+            new_item,
+            tr!("Select color group that was sold from:"),
+        )?;
+        let lot = &new_item.get_locations()[i];
+        let color_group = lot.get_color_group().clone();
+
+        display::clear(w)?;
+        let quantity = self.input.input_u32(w, tr!("How many pieces were sold?"))?;
+        display::clear(w)?;
+        let buyer = self.input.input_string(w, tr!("Who was the buyer?"))?;
+        display::clear(w)?;
+        let default_price = lot
+            .get_asking_price()
+            .map(|price| {
+                let total = price.get_amount().times(quantity);
+                format!("{} {}", total, price.get_currency())
+            })
+            .unwrap_or_default();
+        let price = self.input.edit_string(
+            w,
+            tr!("Enter the total price paid, e.g. \"1.50 EUR\":"),
+            &default_price,
+        )?;
+        let Some(price) = Price::parse(&price) else {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("{} is not a valid price", price)),
+            });
+        };
+        let today = Date::today();
+        let day = date::input_date(&mut self.input, w, tr!("When was the sale?"), today)?;
+        // Sales of today keep the time they were recorded at.
+        let time = if day == today {
+            Timestamp::now()
+        } else {
+            day.to_timestamp()
+        };
+
+        // Pieces in lots without a known count can not be taken away.
+        let mut updated_item = new_item.clone();
+        let remaining = lot
+            .get_quantity()
+            .map(|q| Quantity::new(q.get().saturating_sub(quantity)));
+        updated_item.set_lot_quantity(i, remaining);
+        self.reasons.insert(color_group.clone(), Reason::Sold);
+        self.sales.push(Sale::new(
+            new_item.get_id(),
+            color_group,
+            quantity,
+            buyer.trim().to_string(),
+            price,
+            time,
+        ));
+
+        let mut msg = tr!("The sale is recorded when the changes are saved.").to_string();
+        if let Some(grams) = new_item.get_weight(quantity) {
+            let mut weight = Weight::default();
+            weight.add(Some(grams));
+            msg.push('\n');
+            msg.push_str(&shipping::describe(
+                &weight,
+                self.config.get_shipping_rates(),
+            ));
+        }
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            new_item: updated_item,
+            msg: Some(msg),
+        })
+    }
+
+    fn move_color_group(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: None,
+        } = &self.mode
+        else {
+            bail!(self, EditName);
+        };
+
+        display::clear(w)?;
+
+        let i = select_lot(
+            &mut self.input,
+            w,
+            new_item,
+            tr!("Select color group to move:"),
+        )?;
+        let color_group = new_item.get_locations()[i].get_color_group();
+
+        display::clear(w)?;
+        display::line(
+            w,
+            tr!(
+                "Moving color group {} from item with ID: {}",
+                color_group,
+                new_item.get_id()
+            ),
+        )?;
+        let part_loc = self
+            .input
+            .input_string(w, &tr!("Enter new location of group {}:", color_group))?;
+        let part_loc = part_loc.to_uppercase();
+
+        let mut updated_item = new_item.clone();
+        updated_item.move_lot(i, part_loc);
+
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            msg: self.get_capacity_warning(&updated_item),
+            new_item: updated_item,
+        })
+    }
+
+    fn add_color_group(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, AddColorGroup)
+        };
+
+        display::clear(w)?;
+        display::line(
+            w,
+            tr!(
+                "Adding a new color group to item with ID: {}",
+                new_item.get_id()
+            ),
+        )?;
+
+        let color_group = select_color_group(
+            &mut self.input,
+            w,
+            &self.db,
+            tr!("Select a color group for which to add a location"),
+            Some(new_item),
+        )?;
+        if !confirm_color(
+            &mut self.input,
+            w,
+            &mut self.colors,
+            new_item.get_id(),
+            &color_group,
+        )? {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("The color group was not added.").to_string()),
+            });
+        }
+
+        display::clear(w)?;
+        display::line(
+            w,
+            tr!(
+                "Adding a new color group to item with ID: {}",
+                new_item.get_id()
+            ),
+        )?;
+
+        let part_loc = self
+            .input
+            .input_string(w, &tr!("Enter location of group {}:", color_group))?;
+        let part_loc = part_loc.to_uppercase();
+
+        let mut updated_item = new_item.clone();
+        updated_item.add_color_group(color_group, part_loc);
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            msg: self.get_capacity_warning(&updated_item),
+            new_item: updated_item,
+        })
+    }
+
+    fn remove_color_group(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: None,
+        } = &self.mode
+        else {
+            bail!(self, RemoveColorGroup);
+        };
+
+        display::clear(w)?;
+        display::line(
+            w,
+            tr!(
+                "Removing a color group from item with ID: {}",
+                new_item.get_id()
+            ),
+        )?;
+
+        let color_group: &ColorGroup = ui::select_from_list(
+            &mut self.input,
+            w,
+            Some(tr!("Select color group to remove:")),
+            new_item.get_color_set().iter(),
+        )?;
+
+        let mut updated_item = new_item.clone();
+        updated_item.remove_color_group(color_group);
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            new_item: updated_item,
+            msg: None,
+        })
+    }
+
+    fn add_alt_id(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, AddAltId);
+        };
+
+        display::clear(w)?;
+        let new_id = self.input.input_u32(
+            w,
+            tr!("Enter the new alternative part ID to add to this item"),
+        )?;
+        let Some(new_id) = PartId::new(new_id) else {
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg: Some(tr!("{} is not a valid part ID", new_id)),
+            });
+        };
+
+        if let Some(main_id) = self.db.contains_id(new_id) {
+            let msg = Some(tr!(
+                "Item with part ID {} already exists in database under item with part ID {}",
+                new_id,
+                main_id
+            ));
+            return Ok(Mode::EditItem {
+                old_item: old_item.clone(),
+                new_item: new_item.clone(),
+                msg,
+            });
+        }
+
+        let mut updated_item = new_item.clone();
+        updated_item.add_alt_id(new_id);
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            new_item: updated_item,
+            msg: None,
+        })
+    }
+
+    fn remove_alt_id(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, RemoveAltId);
+        };
+
+        display::clear(w)?;
+
+        let options: BTreeSet<PartId> = new_item.get_alternative_ids().iter().copied().collect();
+        let alt_id = ui::select_from_list(
+            &mut self.input,
+            w,
+            Some(tr!("Which alternative ID do you want to remove?")),
+            options.iter(),
+        )?;
+
+        let mut updated_item = new_item.clone();
+        updated_item.remove_alt_id(*alt_id);
+        Ok(Mode::EditItem {
+            old_item: old_item.clone(),
+            new_item: updated_item,
+            msg: None,
+        })
+    }
+
+    fn delete_item(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::EditItem {
+            old_item,
+            new_item,
+            msg: _,
+        } = &self.mode
+        else {
+            bail!(self, DeleteItem);
         };
 
         display::clear(w)?;
         display::iter(w, old_item.to_string().split("\n"))?;
 
-        let changes = format!(
+        let changes = tr!(
             "Are you absolutely sure that you want to delete the item with ID: {}?\n",
             old_item.get_id(),
         );
 
-        if prompt::confirmation(w, &changes)? {
+        if self
+            .input
+            .confirm(w, &changes, Confirm::Typed(old_item.get_name()))?
+        {
             self.db.remove_item(old_item.get_id())?;
+            self.reasons.clear();
+            self.sales.clear();
             Ok(Mode::Default {
-                info: Some(format!("Item with ID: {} was deleted.", old_item.get_id())),
+                info: Some(tr!("Item with ID: {} was deleted.", old_item.get_id())),
             })
         } else {
             Ok(Mode::EditItem {
@@ -619,27 +3060,1113 @@ impl<W: std::io::Write> State<W> {
         }
     }
 
+    /// The statistics of the database, counting the pieces still wanted for the goals if asked to.
+    fn get_stats(
+        &self,
+        include_listed: bool,
+        include_wanted: bool,
+        range: Option<Range>,
+    ) -> DatabaseStats {
+        let mut stats = self.db.get_stats(include_listed, range);
+        if include_wanted {
+            let wanted = goal::get_wanted(&self.db, self.config.get_goals());
+            stats.count_wanted(&self.db, &wanted);
+        }
+        stats
+    }
+
     fn view_stats(&self) -> Result<Mode> {
-        let stats = self.db.get_stats();
+        let stats = self.get_stats(true, false, None);
         Ok(Mode::ViewStatistics { stats })
     }
 
+    fn toggle_listed_lots(&self) -> Result<Mode> {
+        let Mode::ViewStatistics { stats } = &self.mode else {
+            bail!(self, ToggleListedLots);
+        };
+        let stats = self.get_stats(
+            !stats.includes_listed(),
+            stats.includes_wanted(),
+            stats.get_range(),
+        );
+        Ok(Mode::ViewStatistics { stats })
+    }
+
+    fn toggle_wanted_parts(&self) -> Result<Mode> {
+        let Mode::ViewStatistics { stats } = &self.mode else {
+            bail!(self, ToggleWantedParts);
+        };
+        let stats = self.get_stats(
+            stats.includes_listed(),
+            !stats.includes_wanted(),
+            stats.get_range(),
+        );
+        Ok(Mode::ViewStatistics { stats })
+    }
+
+    fn choose_stats_range(&mut self, w: &mut W) -> Result<Mode> {
+        let Mode::ViewStatistics { stats } = &self.mode else {
+            bail!(self, ChooseStatsRange);
+        };
+        let (include_listed, include_wanted) = (stats.includes_listed(), stats.includes_wanted());
+        let range = date::input_range(&mut self.input, w, tr!("Count the lots added in:"))?;
+        let stats = self.get_stats(include_listed, include_wanted, range);
+        Ok(Mode::ViewStatistics { stats })
+    }
+
+    fn view_stale_lots(&self) -> Result<Mode> {
+        let stale = age::get_stale_lots(self.db.get_items(), Timestamp::now(), age::STALE_AGE);
+        if stale.is_empty() {
+            return Ok(Mode::Default {
+                info: Some(tr!("No lots have gone unchanged for over two years.").to_string()),
+            });
+        }
+        let mut info = tr!(
+            "These lots have not changed for over two years and may be worth selling or sorting anew:"
+        )
+        .to_string();
+        for (item, lot) in stale {
+            let touched = age::get_last_touched(lot).expect("stale lots have a time");
+            info.push_str(&format!(
+                "\n{} {} {}: {}",
+                item.get_id(),
+                item.get_name(),
+                lot,
+                tr!("last changed {}", touched)
+            ));
+        }
+        Ok(Mode::Default { info: Some(info) })
+    }
+
     fn quit_stats(&self) -> Result<Mode> {
         Ok(Mode::Default { info: None })
     }
 
-    fn open_bricklink(&self) -> Result<Mode> {
+    fn view_sets(&self) -> Result<Mode> {
+        let sort_by = SortBy::Completion;
+        let mut sets = set::get_completions(self.config.get_sets(), &self.db, false);
+        set::sort(&mut sets, sort_by);
+        Ok(Mode::ViewSetCompletion {
+            sets,
+            sort_by,
+            include_spares: false,
+        })
+    }
+
+    fn sort_sets(&self) -> Result<Mode> {
+        let Mode::ViewSetCompletion {
+            sets,
+            sort_by,
+            include_spares,
+        } = &self.mode
+        else {
+            bail!(self, SortSets);
+        };
+        let sort_by = sort_by.toggle();
+        let mut sets = sets.clone();
+        set::sort(&mut sets, sort_by);
+        Ok(Mode::ViewSetCompletion {
+            sets,
+            sort_by,
+            include_spares: *include_spares,
+        })
+    }
+
+    fn toggle_spare_parts(&self) -> Result<Mode> {
+        let Mode::ViewSetCompletion {
+            sort_by,
+            include_spares,
+            ..
+        } = &self.mode
+        else {
+            bail!(self, ToggleSpareParts);
+        };
+        let include_spares = !include_spares;
+        let mut sets = set::get_completions(self.config.get_sets(), &self.db, include_spares);
+        set::sort(&mut sets, *sort_by);
+        Ok(Mode::ViewSetCompletion {
+            sets,
+            sort_by: *sort_by,
+            include_spares,
+        })
+    }
+
+    fn quit_sets(&self) -> Result<Mode> {
+        Ok(Mode::Default { info: None })
+    }
+
+    fn part_out_value(&mut self, w: &mut W) -> Result<Mode> {
+        display::clear(w)?;
+        let number = self
+            .input
+            .input_string(w, tr!("Enter the number of the set:"))?;
+        let Some(set) = self
+            .config
+            .get_sets()
+            .iter()
+            .find(|set| set.matches_number(&number))
+        else {
+            return Ok(self.mode.with_msg(tr!(
+                "Set {} has not been added to the config",
+                number.trim()
+            )));
+        };
+        let part_out = set::get_part_out(set, &self.db);
+        let weight = set::get_weight(set, &self.db);
+        Ok(Mode::Default {
+            info: Some(format!(
+                "{}\n\n{}",
+                part_out,
+                shipping::describe(&weight, self.config.get_shipping_rates())
+            )),
+        })
+    }
+
+    fn resale_readiness(&mut self, w: &mut W) -> Result<Mode> {
+        display::clear(w)?;
+        let number = self
+            .input
+            .input_string(w, tr!("Enter the number of the set:"))?;
+        let Some(set) = self
+            .config
+            .get_sets()
+            .iter()
+            .find(|set| set.matches_number(&number))
+        else {
+            return Ok(self.mode.with_msg(tr!(
+                "Set {} has not been added to the config",
+                number.trim()
+            )));
+        };
+        let readiness = set::get_readiness(set, &self.db);
+        Ok(Mode::Default {
+            info: Some(readiness.to_string()),
+        })
+    }
+
+    fn consolidate_lots(&mut self, w: &mut W) -> Result<Mode> {
+        let report = self.db.describe_duplicate_lots();
+        if report.is_empty() {
+            return Ok(Mode::Default {
+                info: Some(tr!("No duplicate lots were found.").to_string()),
+            });
+        }
+
+        display::clear(w)?;
+        display::header(
+            w,
+            tr!("These lots have the same color group, condition and location as another lot:"),
+        )?;
+        display::iter(w, report.iter())?;
+        display::newline(w, 1)?;
+        if !self.input.confirm(
+            w,
+            tr!("Merge the duplicate lots? Their quantities are added up."),
+            Confirm::Default(true),
+        )? {
+            return Ok(self.mode.clone());
+        }
+
+        let items: Vec<Item> = self
+            .db
+            .get_items()
+            .iter()
+            .filter(|item| !item.get_duplicate_lots().is_empty())
+            .map(|item| {
+                let mut item = item.clone();
+                item.consolidate_lots();
+                item
+            })
+            .collect();
+        self.save_in_transaction(tr!("Merge duplicate lots"), &items)?;
+        Ok(Mode::Default {
+            info: Some(tr_n!(
+                "Merged the duplicate lots of {} item.",
+                "Merged the duplicate lots of {} items.",
+                items.len()
+            )),
+        })
+    }
+
+    /// Goes through the problems in the database, offering to repair each unless the session is
+    /// read-only.
+    fn check_database(&mut self, w: &mut W) -> Result<Mode> {
+        let problems = check::check(self.db.get_items(), &self.config);
+        if problems.is_empty() {
+            return Ok(Mode::Default {
+                info: Some(tr!("No problems were found in the database.").to_string()),
+            });
+        }
+
+        let mut repaired = 0;
+        let mut remaining = Vec::new();
+        for problem in problems.iter() {
+            let item = problem
+                .get_item_to_repair()
+                .filter(|_| !self.options.read_only)
+                .and_then(|id| self.db.find(id));
+            let (Some(old_item), Some(repair)) = (item.cloned(), problem.get_repair_info()) else {
+                remaining.push(problem.to_string());
+                continue;
+            };
+
+            display::clear(w)?;
+            display::iter(w, old_item.to_string().split("\n"))?;
+            display::newline(w, 1)?;
+            if !self.input.confirm(
+                w,
+                &format!("{}\n\n{}", problem, repair),
+                Confirm::Default(true),
+            )? {
+                remaining.push(problem.to_string());
+                continue;
+            }
+
+            let mut new_item = old_item.clone();
+            if !problem.repair(&mut new_item) {
+                display::clear(w)?;
+                let name = self.input.input_string(w, tr!("Enter the name:"))?;
+                new_item.set_name(name.trim());
+            }
+            self.db.update_item(&old_item, &new_item)?;
+            repaired += 1;
+        }
+
+        let mut info = tr_n!(
+            "{} problem was found and {} repaired.",
+            "{} problems were found and {} repaired.",
+            problems.len(),
+            repaired
+        );
+        for problem in remaining {
+            info.push('\n');
+            info.push_str(&problem);
+        }
+        Ok(Mode::Default { info: Some(info) })
+    }
+
+    fn view_adjustments(&mut self, w: &mut W) -> Result<Mode> {
+        let path = adjustment::get_path(&self.config.get_db_path());
+        let mut adjustments = adjustment::read(&path)?;
+
+        // The history can be narrowed to one user once changes are recorded with users.
+        let everyone = tr!("everyone").to_string();
+        let users: Vec<String> = adjustment::get_users(&adjustments)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let mut header = tr!("Changes to quantities by month and reason:").to_string();
+        if !users.is_empty() {
+            display::clear(w)?;
+            let user = ui::select_from_list(
+                &mut self.input,
+                w,
+                Some(tr!("Show the changes made by:")),
+                std::iter::once(everyone.clone()).chain(users),
+            )?;
+            if user != everyone {
+                adjustments.retain(|adjustment| adjustment.get_user() == Some(user.as_str()));
+                header = tr!(
+                    "Changes to quantities made by {} by month and reason:",
+                    user
+                );
+            }
+        }
+
+        if adjustments.is_empty() {
+            let info = tr!("No changes to quantities have been recorded yet.").to_string();
+            return Ok(Mode::Default { info: Some(info) });
+        }
+        let range = date::input_range(&mut self.input, w, tr!("Show the changes made in:"))?;
+        if let Some(range) = range {
+            adjustments.retain(|adjustment| range.contains(adjustment.get_time()));
+            header = format!("{}\n{}", tr!("Days: {}", range), header);
+        }
+
+        let report = adjustment::report(&adjustments);
+        let info = if report.is_empty() {
+            tr!("No changes to quantities were recorded on these days.").to_string()
+        } else {
+            format!(
+                "{}\n{}\n{}",
+                header,
+                report.join("\n"),
+                adjustment::total(&adjustments)
+            )
+        };
+        Ok(Mode::Default { info: Some(info) })
+    }
+
+    /// Toggles the columns of the list of parts matching a saved filter, or of the list for other
+    /// filters, and saves them in the config.
+    fn choose_columns(&mut self, w: &mut W) -> Result<Mode> {
+        let saved: Vec<String> = self.config.get_saved_filters().keys().cloned().collect();
+        let saved_filter = if saved.is_empty() {
+            None
+        } else {
+            let mut options = vec![tr!("Filters that are not saved").to_string()];
+            options.extend(saved.iter().map(|name| tr!("Saved filter {}", name)));
+            display::clear(w)?;
+            let header = tr!("Which list should the columns be chosen for?");
+            let i = self.input.select(w, Some(header), &options)?;
+            i.checked_sub(1).map(|i| saved[i].clone())
+        };
+
+        let mut chosen = self.config.get_columns(saved_filter.as_deref()).to_vec();
+        loop {
+            let mut options: Vec<(char, String)> = Column::iter()
+                .map(|column| {
+                    let mark = if chosen.contains(&column) {
+                        "[x]"
+                    } else {
+                        "[ ]"
+                    };
+                    (
+                        column.get_char(),
+                        format!("{} {} ({})", mark, column, column.get_char()),
+                    )
+                })
+                .collect();
+            options.push(('d', tr!("(d)one").to_string()));
+            display::clear(w)?;
+            let i = self
+                .input
+                .select_cmd(w, tr!("Toggle the columns to show:"), &options)?;
+            let Some(column) = Column::iter().nth(i) else {
+                break;
+            };
+            match chosen.iter().position(|c| *c == column) {
+                Some(j) => {
+                    chosen.remove(j);
+                }
+                None => chosen.push(column),
+            }
+        }
+        if chosen.is_empty() {
+            return Ok(self
+                .mode
+                .with_msg(tr!("At least one column must be shown.").to_string()));
+        }
+
+        let columns = Column::iter().filter(|c| chosen.contains(c)).collect();
+        self.config.set_columns(saved_filter.as_deref(), columns);
+        // In read-only mode nothing is written, so the columns are only kept for the session.
+        if self.options.read_only {
+            return Ok(self.mode.with_msg(
+                tr!("The columns are shown for this session only, as nothing is saved in read-only mode.")
+                    .to_string(),
+            ));
+        }
+        if !self.options.dry_run {
+            io::write_contents_to_yaml(&self.config_path, &self.config)?;
+        }
+        Ok(self
+            .mode
+            .with_msg(tr!("The columns were saved.").to_string()))
+    }
+
+    fn view_revenue(&mut self, w: &mut W) -> Result<Mode> {
+        let path = sale::get_path(&self.config.get_db_path());
+        let mut sales = sale::read(&path)?;
+        if sales.is_empty() {
+            let info = tr!("No sales have been recorded yet.").to_string();
+            return Ok(Mode::Default { info: Some(info) });
+        }
+        let mut header = tr!("Revenue from sales by month:").to_string();
+        if let Some(range) = date::input_range(&mut self.input, w, tr!("Show the sales made in:"))?
+        {
+            sales.retain(|sale| range.contains(sale.get_time()));
+            header = format!("{}\n{}", tr!("Days: {}", range), header);
+        }
+
+        let report = sale::report(&sales);
+        let info = if report.is_empty() {
+            tr!("No sales were made on these days.").to_string()
+        } else {
+            format!("{}\n{}\n{}", header, report.join("\n"), sale::total(&sales))
+        };
+        Ok(Mode::Default { info: Some(info) })
+    }
+
+    fn log_purchase(&mut self, w: &mut W) -> Result<Mode> {
+        display::clear(w)?;
+        let description = self
+            .input
+            .input_string(w, tr!("What was bought, and from whom?"))?;
+        display::clear(w)?;
+        let price =
+            self.input
+                .edit_string(w, tr!("Enter the total price paid, e.g. \"25 EUR\":"), "")?;
+        let Some(price) = Price::parse(&price) else {
+            return Ok(Mode::Default {
+                info: Some(tr!("{} is not a valid price", price)),
+            });
+        };
+        let today = Date::today();
+        let day = date::input_date(&mut self.input, w, tr!("When was it bought?"), today)?;
+        let time = if day == today {
+            Timestamp::now()
+        } else {
+            day.to_timestamp()
+        };
+
+        let purchase = Purchase::new(description.trim().to_string(), price, time);
+        let path = budget::get_path(&self.config.get_db_path());
+        let mut purchases = budget::read(&path)?;
+        if !self.options.dry_run {
+            budget::append(&path, std::slice::from_ref(&purchase))?;
+        }
+        let mut info = tr!("Logged the purchase {}.", purchase);
+        purchases.push(purchase);
+        if let Some(monthly_budget) = self.config.get_monthly_budget() {
+            let (line, exceeded) = budget::describe(monthly_budget, &purchases, today);
+            info.push('\n');
+            info.push_str(&if exceeded {
+                theme::get().warning(&line)
+            } else {
+                line
+            });
+        }
+        Ok(Mode::Default { info: Some(info) })
+    }
+
+    fn view_usage(&self) -> Result<Mode> {
+        let info = match usage::get_path() {
+            None => tr!("Usage is not logged, set usage_log to true in the config to log it.")
+                .to_string(),
+            Some(path) => usage::read(path)?.describe().join("\n"),
+        };
+        Ok(Mode::Default { info: Some(info) })
+    }
+
+    fn copy_part_id(&self) -> Result<Mode> {
+        let Mode::DisplayItem { item, .. } = &self.mode else {
+            bail!(self, CopyPartID);
+        };
+        let msg = match clipboard::copy(&item.get_id().to_string()) {
+            Ok(()) => tr!("Copied part ID {} to the clipboard.", item.get_id()),
+            Err(e) => e.to_string(),
+        };
+        Ok(self.mode.with_msg(msg))
+    }
+
+    /// Copies the parts whose name contains the filter, which may also be the name of a saved
+    /// filter.
+    fn copy_part_list(&mut self, w: &mut W) -> Result<Mode> {
+        if !matches!(self.mode, Mode::Default { .. }) {
+            bail!(self, CopyPartList);
+        }
+        display::clear(w)?;
+        let filter = self.input.input_string(
+            w,
+            tr!("Enter a filter like \"color=red, brick\", or the name of a saved filter:"),
+        )?;
+        let filter = match self.config.get_saved_filters().get(filter.trim()) {
+            Some(saved) => Filter::parse(saved),
+            None => Filter::parse(&filter),
+        };
+        let filter = match filter {
+            Ok(filter) => filter,
+            Err(e) => return Ok(self.mode.with_msg(e.to_string())),
+        };
+        let items: Vec<&Item> = self
+            .db
+            .get_items()
+            .iter()
+            .filter(|item| filter.matches(item))
+            .collect();
+
+        let msg = match clipboard::copy(&clipboard::to_csv(&items)) {
+            Ok(()) => tr_n!(
+                "Copied {} part to the clipboard.",
+                "Copied {} parts to the clipboard.",
+                items.len()
+            ),
+            Err(e) => e.to_string(),
+        };
+        Ok(self.mode.with_msg(msg))
+    }
+
+    fn open_part_page(&self) -> Result<Mode> {
         match &self.mode {
             Mode::EditItem { new_item: item, .. } | Mode::DisplayItem { item, .. } => {
-                webbrowser::open(&format!(
-                    "https://www.bricklink.com/v2/catalog/catalogitem.page?P={}",
-                    item.get_id()
-                ))?;
+                let site = self.config.get_part_page_site();
+                if let Some(url) = site.get_part_url(item.get_id()) {
+                    webbrowser::open(&url)?;
+                }
             }
             _ => {
-                bail!(self, Bricklink);
+                bail!(self, OpenPartPage);
             }
         }
         Ok(self.mode.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::journal;
+    use crate::transaction;
+
+    #[derive(Debug)]
+    enum Step {
+        Key(char),
+        Text(&'static str),
+        Number(u32),
+        Yes,
+        Row(usize, RowAction),
+        Escape,
+    }
+
+    /// Answers prompts from a script, and quits once it runs out.
+    struct Script(VecDeque<Step>);
+
+    impl Script {
+        fn next(&mut self, msg: &str) -> term_lib::Result<Step> {
+            log::debug!("scripted answer for: {}", msg);
+            match self.0.pop_front() {
+                Some(Step::Escape) => Err(term_lib::Error::Escape),
+                Some(step) => Ok(step),
+                None => Err(term_lib::Error::Quit),
+            }
+        }
+    }
+
+    fn unexpected<T>(answer: Step, msg: &str) -> term_lib::Result<T> {
+        panic!("{:?} is not an answer to: {}", answer, msg)
+    }
+
+    impl InputSource<Vec<u8>> for Script {
+        fn wait_for_cmdchar(&mut self) -> term_lib::Result<char> {
+            match self.next("command")? {
+                Step::Key(c) => Ok(c),
+                a => unexpected(a, "command"),
+            }
+        }
+
+        fn input_string(&mut self, _: &mut Vec<u8>, msg: &str) -> term_lib::Result<String> {
+            match self.next(msg)? {
+                Step::Text(s) => Ok(s.to_string()),
+                a => unexpected(a, msg),
+            }
+        }
+
+        fn input_u32(&mut self, _: &mut Vec<u8>, msg: &str) -> term_lib::Result<u32> {
+            match self.next(msg)? {
+                Step::Number(n) => Ok(n),
+                a => unexpected(a, msg),
+            }
+        }
+
+        fn edit_string(&mut self, w: &mut Vec<u8>, msg: &str, _: &str) -> term_lib::Result<String> {
+            self.input_string(w, msg)
+        }
+
+        fn edit_u32(&mut self, w: &mut Vec<u8>, msg: &str, _: u32) -> term_lib::Result<u32> {
+            self.input_u32(w, msg)
+        }
+
+        fn confirmation(&mut self, _: &mut Vec<u8>, msg: &str) -> term_lib::Result<bool> {
+            match self.next(msg)? {
+                Step::Yes => Ok(true),
+                a => unexpected(a, msg),
+            }
+        }
+
+        fn fzf_search(&mut self, opts: &str) -> term_lib::Result<String> {
+            self.input_string(&mut Vec::new(), opts)
+        }
+
+        fn select(
+            &mut self,
+            _: &mut Vec<u8>,
+            header: Option<&str>,
+            options: &[String],
+        ) -> term_lib::Result<usize> {
+            let msg = header.unwrap_or("select");
+            match self.next(msg)? {
+                Step::Text(s) => Ok(options.iter().position(|o| o == s).unwrap()),
+                a => unexpected(a, msg),
+            }
+        }
+
+        fn select_cmd(
+            &mut self,
+            _: &mut Vec<u8>,
+            header: &str,
+            cmds: &[(char, String)],
+        ) -> term_lib::Result<usize> {
+            match self.next(header)? {
+                Step::Key(c) => Ok(cmds.iter().position(|(o, _)| *o == c).unwrap()),
+                a => unexpected(a, header),
+            }
+        }
+
+        fn select_row(
+            &mut self,
+            _: &mut Vec<u8>,
+            table: &ui::Table,
+        ) -> term_lib::Result<(usize, RowAction)> {
+            match self.next(table.header)? {
+                Step::Row(i, action) => Ok((i, action)),
+                a => unexpected(a, table.header),
+            }
+        }
+    }
+
+    /// Runs the program headless on a fresh database, driven by a script. Everything is rendered
+    /// to a buffer instead of the terminal.
+    struct Harness {
+        state: State<Vec<u8>>,
+        db_path: PathBuf,
+    }
+
+    impl Harness {
+        fn run(name: &str, script: Vec<Step>) -> Self {
+            Self::run_with_config(name, Config::default(), script)
+        }
+
+        fn run_with_config(name: &str, mut config: Config, script: Vec<Step>) -> Self {
+            let db_path = PathBuf::from(format!("test_state_{}.yml", name));
+            let config_path = PathBuf::from(format!("test_state_{}_config.yml", name));
+            config.set_db_path(&db_path);
+            let mut w = Vec::new();
+            let input = Box::new(Script(script.into()));
+            let mut state =
+                State::new(&mut w, config, config_path, Options::default(), input).unwrap();
+            loop {
+                match state.wait_for_cmd(&mut w) {
+                    Ok(()) => {}
+                    Err(Error::TermError(term_lib::Error::Quit)) => break,
+                    Err(e) => panic!("{}", e),
+                }
+            }
+            Self { state, db_path }
+        }
+    }
+
+    impl Drop for Harness {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.db_path);
+            let _ = std::fs::remove_file(journal::get_path(&self.db_path));
+            let _ = std::fs::remove_file(adjustment::get_path(&self.db_path));
+            let _ = std::fs::remove_file(sale::get_path(&self.db_path));
+            let _ = std::fs::remove_file(budget::get_path(&self.db_path));
+            let _ = std::fs::remove_file(transaction::get_path(&self.db_path));
+            let _ = std::fs::remove_file(&self.state.config_path);
+        }
+    }
+
+    #[test]
+    fn test_add_item_and_edit_quantity() {
+        use Step::*;
+        let harness = Harness::run(
+            "add_item",
+            vec![
+                Key('a'),
+                Number(3001),
+                Text("Brick 2 x 4"),
+                Key('a'),
+                Key('c'),
+                Key('b'),
+                Text("a1"),
+                Key('c'),
+                Text("12"),
+                Key('f'),
+                Key('s'),
+            ],
+        );
+
+        assert!(matches!(harness.state.mode, Mode::DisplayItem { .. }));
+        let item = harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3001).unwrap())
+            .unwrap();
+        assert_eq!(item.get_name(), "Brick 2 x 4");
+        assert_eq!(item.get_locations()[0].get_location(), "A1");
+        assert_eq!(item.get_quantity(), Quantity::new(12));
+        let adjustments = adjustment::read(&adjustment::get_path(&harness.db_path)).unwrap();
+        assert_eq!(adjustment::report(&adjustments).len(), 1);
+    }
+
+    #[test]
+    fn test_count_prefix() {
+        use Step::*;
+        let script = vec![
+            Key('a'),
+            Number(3001),
+            Text("Brick 2 x 4"),
+            Key('a'),
+            Key('c'),
+            Key('b'),
+            Text("a1"),
+            Key('1'),
+            Key('2'),
+            Key('+'),
+            Key('+'),
+            Key('5'),
+            Key('-'),
+            Key('2'),
+            Key('0'),
+            Key('-'),
+            Key('s'),
+        ];
+        let harness = Harness::run("count_prefix", script);
+
+        let item = harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3001).unwrap())
+            .unwrap();
+        assert_eq!(item.get_quantity(), Quantity::new(8));
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        let harness = Harness::run("invalid_key", vec![Step::Key('Z')]);
+        assert_eq!(harness.state.status.as_deref(), Some("Z does nothing here"));
+
+        let harness = Harness::run("valid_key", vec![Step::Key('Z'), Step::Key('v')]);
+        assert!(harness.state.status.is_none());
+    }
+
+    #[test]
+    fn test_quick_edit() {
+        use Step::*;
+        let mut script = Vec::new();
+        for (id, location) in [(3001, "a1"), (3002, "a2")] {
+            script.extend([Key('a'), Number(id), Text("Brick")]);
+            script.extend([Key('a'), Key('c'), Key('b'), Text(location), Key('s')]);
+        }
+        script.extend([
+            Key('s'),
+            Key('f'),
+            Text("brick"),
+            Row(1, RowAction::EditQuantity),
+            Text("+3"),
+            Row(1, RowAction::EditQuantity),
+            Text("-1"),
+            Row(0, RowAction::EditLocation),
+            Text("b7"),
+            Row(1, RowAction::Open),
+        ]);
+        let harness = Harness::run("quick_edit", script);
+
+        let Mode::DisplayItem { item, .. } = &harness.state.mode else {
+            panic!("the part is not shown");
+        };
+        assert_eq!(item.get_id(), PartId::new(3002).unwrap());
+        assert_eq!(item.get_quantity(), Quantity::new(2));
+        let moved = harness.state.db.get_item_by_id(PartId::new(3001).unwrap());
+        assert_eq!(moved.unwrap().get_locations()[0].get_location(), "B7");
+        let adjustments = adjustment::read(&adjustment::get_path(&harness.db_path)).unwrap();
+        assert_eq!(adjustments.len(), 2);
+    }
+
+    #[test]
+    fn test_record_and_play_macro() {
+        use Step::*;
+        let harness = Harness::run(
+            "macro",
+            vec![
+                Key('a'),
+                Number(3001),
+                Text("Brick 2 x 4"),
+                Key('Q'),
+                Key('e'),
+                Key('n'),
+                Text("Brick 2x4"),
+                Key('s'),
+                Key('Q'),
+                Text(""),
+                Key('e'),
+                Key('n'),
+                Text("Brick"),
+                Key('s'),
+                Key('@'),
+                Text("Last recorded macro"),
+                Number(2),
+            ],
+        );
+
+        let item = harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3001).unwrap())
+            .unwrap();
+        assert_eq!(item.get_name(), "Brick 2x4");
+        assert_eq!(harness.state.last_macro.as_ref().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_sort_bin() {
+        use Step::*;
+        let harness = Harness::run(
+            "sort_bin",
+            vec![
+                Key('b'),
+                Number(3001),
+                Key('b'),
+                Number(5),
+                Text("a1"),
+                Text("Brick 2 x 4"),
+                Number(3001),
+                Key('b'),
+                Number(3),
+                Text("a1"),
+            ],
+        );
+
+        let item = harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3001).unwrap())
+            .unwrap();
+        assert_eq!(item.get_name(), "Brick 2 x 4");
+        assert_eq!(item.get_locations().len(), 1);
+        assert_eq!(item.get_quantity(), Quantity::new(8));
+    }
+
+    #[test]
+    fn test_sort_bin_escape() {
+        use Step::*;
+        let harness = Harness::run(
+            "sort_bin_escape",
+            vec![
+                Key('b'),
+                Number(3001),
+                Key('b'),
+                Number(5),
+                Text("a1"),
+                Escape,
+                Number(3003),
+                Escape,
+                Number(3001),
+                Key('b'),
+                Number(3),
+                Text("a1"),
+                Text("Brick 2 x 4"),
+                Escape,
+            ],
+        );
+
+        let Mode::Default { info: Some(info) } = &harness.state.mode else {
+            panic!("the bin was left without a summary");
+        };
+        assert!(info.starts_with("Placed 1 part from the bin."));
+        let item = harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3001).unwrap())
+            .unwrap();
+        assert_eq!(item.get_quantity(), Quantity::new(3));
+        assert!(harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3003).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_part_out_set() {
+        use Step::*;
+        let mut config = Config::default();
+        config.add_set(set::parse_inventory("part,quantity\n3001,4\n3003,2\n", "6020-1").unwrap());
+        let harness = Harness::run_with_config(
+            "part_out_set",
+            config,
+            vec![
+                Key('w'),
+                Text("6020"),
+                Number(4),
+                Key('b'),
+                Text("a1"),
+                Text("Brick 2 x 4"),
+                Number(0),
+                Yes,
+            ],
+        );
+
+        let item = harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3001).unwrap())
+            .unwrap();
+        assert_eq!(item.get_quantity(), Quantity::new(4));
+        assert!(harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3003).unwrap())
+            .is_err());
+        let adjustments = adjustment::read(&adjustment::get_path(&harness.db_path)).unwrap();
+        assert_eq!(adjustments.len(), 1);
+        assert_eq!(adjustments[0].get_reason(), Reason::PartedOut);
+        let transactions = transaction::read(&transaction::get_path(&harness.db_path)).unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].get_name(), "Part-out of set 6020-1");
+    }
+
+    #[test]
+    fn test_record_sale() {
+        use Step::*;
+        let harness = Harness::run(
+            "record_sale",
+            vec![
+                Key('a'),
+                Number(3001),
+                Text("Brick 2 x 4"),
+                Key('a'),
+                Key('c'),
+                Key('b'),
+                Text("a1"),
+                Key('c'),
+                Text("10"),
+                Key('f'),
+                Key('o'),
+                Number(4),
+                Text("A buyer"),
+                Text("2 EUR"),
+                Key('c'),
+                // Listing and selling from one half of a divided lot leaves the other half alone.
+                Key('v'),
+                Number(2),
+                Text("b1"),
+                Text("the same condition"),
+                Text(""),
+                Key('l'),
+                Text("Basic: B1 (2 pieces)"),
+                Text("0.10 EUR"),
+                Key('o'),
+                Text("Basic: B1 (2 pieces) listed for 0.10 EUR each"),
+                Number(1),
+                Text("Another buyer"),
+                Text("0.10 EUR"),
+                Key('c'),
+                Key('s'),
+            ],
+        );
+
+        let item = harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3001).unwrap())
+            .unwrap();
+        assert_eq!(item.get_quantity(), Quantity::new(5));
+        let lots = item.get_locations();
+        assert_eq!(lots[0].get_quantity(), Some(Quantity::new(4)));
+        assert_eq!(lots[0].get_asking_price(), None);
+        assert_eq!(lots[1].get_quantity(), Some(Quantity::new(1)));
+        assert!(lots[1].get_asking_price().is_some());
+        let sales = sale::read(&sale::get_path(&harness.db_path)).unwrap();
+        assert_eq!(sales.len(), 2);
+    }
+
+    #[test]
+    fn test_edit_purchased() {
+        use Step::*;
+        let harness = Harness::run(
+            "edit_purchased",
+            vec![
+                Key('a'),
+                Number(3001),
+                Text("Brick 2 x 4"),
+                Key('a'),
+                Key('c'),
+                Key('b'),
+                Text("a1"),
+                Key('c'),
+                Text("10"),
+                Key('f'),
+                Key('k'),
+                Key('e'),
+                Text("2024-05-01"),
+                Key('p'),
+                Key('c'),
+                Key('s'),
+            ],
+        );
+
+        let item = harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3001).unwrap())
+            .unwrap();
+        assert_eq!(
+            item.get_locations()[0].get_purchased(),
+            Timestamp::parse("2024-04-01")
+        );
+    }
+
+    #[test]
+    fn test_log_purchase() {
+        use Step::*;
+        let harness = Harness::run(
+            "log_purchase",
+            vec![
+                Key('m'),
+                Text("Bricks from a flea market"),
+                Text("25 EUR"),
+                Key('h'),
+                Key('c'),
+            ],
+        );
+
+        let purchases = budget::read(&budget::get_path(&harness.db_path)).unwrap();
+        assert_eq!(purchases.len(), 1);
+        assert_eq!(
+            Date::of(purchases[0].get_time()),
+            Date::today().add_days(-1)
+        );
+    }
+
+    #[test]
+    fn test_quit_edit() {
+        use Step::*;
+        let harness = Harness::run(
+            "quit_edit",
+            vec![
+                Key('a'),
+                Number(3003),
+                Text("Brick 2 x 2"),
+                Key('n'),
+                Text("Brick 2x2"),
+                Key('q'),
+                Yes,
+            ],
+        );
+
+        assert!(matches!(harness.state.mode, Mode::DisplayItem { .. }));
+        let item = harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3003).unwrap())
+            .unwrap();
+        assert_eq!(item.get_name(), "Brick 2 x 2");
+    }
+
+    #[test]
+    fn test_delete_item() {
+        use Step::*;
+        let harness = Harness::run(
+            "delete_item",
+            vec![
+                Key('a'),
+                Number(3003),
+                Text("Brick 2 x 2"),
+                Key('d'),
+                Text("Brick 2 x 2"),
+            ],
+        );
+
+        assert!(matches!(harness.state.mode, Mode::Default { .. }));
+        assert!(harness
+            .state
+            .db
+            .get_item_by_id(PartId::new(3003).unwrap())
+            .is_err());
+    }
+}
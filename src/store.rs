@@ -0,0 +1,387 @@
+use std::fmt;
+
+use serde::Deserialize;
+use ulid::Ulid;
+
+use crate::api;
+use crate::brickstore;
+use crate::currency::{self, Amount, Price};
+use crate::data::{Condition, Item};
+use crate::error::{Error, Result};
+use crate::locale::tr;
+use crate::oauth::{self, OAuthKeys};
+use crate::timestamp::Timestamp;
+use crate::types::{ColorId, PartId};
+
+const STORE_URL: &str = "https://api.bricklink.com/api/store/v1";
+
+/// A lot in the store on BrickLink, of which only parts are read. The store knows its lots by
+/// part, color and condition.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct StoreLot {
+    inventory_id: u64,
+    item: StoreItem,
+    color_id: ColorId,
+    quantity: u32,
+    new_or_used: String,
+    /// The price of a piece in the currency of the store, as a decimal number.
+    unit_price: String,
+    #[serde(default)]
+    remarks: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+struct StoreItem {
+    no: String,
+    #[serde(rename = "type")]
+    item_type: String,
+}
+
+/// The lots listed for sale here of one part, color and condition, which are pushed as one lot
+/// of the store.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Listing {
+    id: PartId,
+    name: String,
+    color_id: ColorId,
+    color_name: String,
+    condition: Condition,
+    quantity: u32,
+    /// The asking price of a piece in the home currency, which the store is taken to sell in.
+    price: Amount,
+    remarks: String,
+}
+
+fn get_code(condition: Condition) -> &'static str {
+    match condition {
+        Condition::New => "N",
+        Condition::Used => "U",
+    }
+}
+
+impl Listing {
+    fn is_listed_as(&self, lot: &StoreLot) -> bool {
+        lot.item.item_type == "PART"
+            && self
+                .id
+                .to_bricklink()
+                .is_some_and(|number| number == lot.item.no)
+            && lot.color_id == self.color_id
+            && lot.new_or_used == get_code(self.condition)
+    }
+
+    /// Adds the lot, averaging the asking prices by the pieces.
+    fn add(&mut self, quantity: u32, price: Amount, remarks: Option<&str>) {
+        self.price = Amount::weighted_average(&[(self.price, self.quantity), (price, quantity)])
+            .unwrap_or(self.price);
+        self.quantity += quantity;
+        if let Some(remarks) = remarks.filter(|remarks| !self.remarks.contains(remarks)) {
+            if !self.remarks.is_empty() {
+                self.remarks.push_str("; ");
+            }
+            self.remarks.push_str(remarks);
+        }
+    }
+}
+
+/// The lots to push to the store, which are the lots listed for sale here, with their asking
+/// price. Returns the listings and why each listed lot that cannot be pushed is left out.
+pub fn get_listings(items: &[Item]) -> (Vec<Listing>, Vec<String>) {
+    let mut listings: Vec<Listing> = Vec::new();
+    let mut skipped = Vec::new();
+    for item in items {
+        for lot in item.get_locations() {
+            let Some(price) = lot.get_asking_price() else {
+                continue;
+            };
+            let (color_id, color_name) = brickstore::from_color_group(lot.get_color_group());
+            let reason = if item.is_group() {
+                Some(tr!("groups are not parts on BrickLink").to_string())
+            } else if color_id.is_none() {
+                Some(tr!("{} is not a color on BrickLink", lot.get_color_group()))
+            } else if lot.get_condition().is_none() {
+                Some(tr!("it has no condition").to_string())
+            } else if lot.get_quantity().is_none() {
+                Some(tr!("its pieces are not counted").to_string())
+            } else if price.to_home_currency().is_none() {
+                Some(tr!("its price has no exchange rate to the home currency").to_string())
+            } else {
+                None
+            };
+            let (Some(color_id), Some(condition), Some(quantity), Some(price), None) = (
+                color_id,
+                lot.get_condition(),
+                lot.get_quantity(),
+                price.to_home_currency(),
+                reason.as_ref(),
+            ) else {
+                skipped.push(tr!(
+                    "The lot of part {} {} at {} is left out, as {}",
+                    item.get_id(),
+                    item.get_name(),
+                    lot.get_location(),
+                    reason.unwrap_or_default()
+                ));
+                continue;
+            };
+
+            let (quantity, price) = (quantity.get(), price.get_amount());
+            let same = listings.iter_mut().find(|listing| {
+                listing.id == item.get_id()
+                    && listing.color_id == color_id
+                    && listing.condition == condition
+            });
+            match same {
+                Some(listing) => listing.add(quantity, price, lot.get_notes()),
+                None => listings.push(Listing {
+                    id: item.get_id(),
+                    name: item.get_name().clone(),
+                    color_id,
+                    color_name,
+                    condition,
+                    quantity,
+                    price,
+                    remarks: lot.get_notes().unwrap_or_default().to_string(),
+                }),
+            }
+        }
+    }
+    (listings, skipped)
+}
+
+/// A change to the store that makes it match a listing. Lots of the store that are not listed here
+/// are left as they are.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Change {
+    Add(Listing),
+    /// The lot of the store and the listing it is changed to.
+    Update(StoreLot, Listing),
+}
+
+/// The changes that make the store match the listings.
+pub fn get_changes(listings: &[Listing], store: &[StoreLot]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for listing in listings {
+        match store.iter().find(|lot| listing.is_listed_as(lot)) {
+            None => changes.push(Change::Add(listing.clone())),
+            Some(lot) => {
+                if lot.quantity != listing.quantity
+                    || Amount::parse(&lot.unit_price) != Some(listing.price)
+                    || lot.remarks != listing.remarks
+                {
+                    changes.push(Change::Update(lot.clone(), listing.clone()));
+                }
+            }
+        }
+    }
+    changes
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let home = currency::get_home_currency();
+        let listing = match self {
+            Change::Add(listing) | Change::Update(_, listing) => listing,
+        };
+        let lot = format!(
+            "{} {} ({}, {})",
+            listing.id, listing.name, listing.color_name, listing.condition
+        );
+        let Change::Update(old, _) = self else {
+            return write!(
+                f,
+                "{}",
+                tr!(
+                    "Add {}: {} pieces at {}",
+                    lot,
+                    listing.quantity,
+                    Price::new(listing.price, home)
+                )
+            );
+        };
+
+        let mut changed = Vec::new();
+        if old.quantity != listing.quantity {
+            changed.push(tr!("{} to {} pieces", old.quantity, listing.quantity));
+        }
+        if Amount::parse(&old.unit_price) != Some(listing.price) {
+            changed.push(tr!(
+                "price {} to {}",
+                old.unit_price,
+                Price::new(listing.price, home)
+            ));
+        }
+        if old.remarks != listing.remarks {
+            changed.push(tr!(
+                "remarks \"{}\" to \"{}\"",
+                old.remarks,
+                listing.remarks
+            ));
+        }
+        write!(f, "{}", tr!("Update {}: {}", lot, changed.join(", ")))
+    }
+}
+
+/// Every answer of BrickLink says whether the request succeeded, and why not if it did not.
+#[derive(Deserialize)]
+struct Meta {
+    code: u16,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    meta: Meta,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+/// A client for the API of a store on BrickLink, which signs its requests with the keys of the
+/// store.
+pub struct Store {
+    keys: OAuthKeys,
+}
+
+impl Store {
+    pub fn new(keys: OAuthKeys) -> Self {
+        Self { keys }
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let url = format!("{}{}", STORE_URL, path);
+        let authorization = oauth::get_authorization(
+            &self.keys,
+            method,
+            &url,
+            query,
+            &Ulid::generate().to_string(),
+            Timestamp::now().get_secs(),
+        );
+        let mut request = ureq::request(method, &url).set("Authorization", &authorization);
+        for (name, value) in query {
+            request = request.query(name, value);
+        }
+        let result = match body {
+            Some(body) => request
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string()),
+            None => request.call(),
+        };
+        let response = result.map_err(|e| Error::ApiError { msg: e.to_string() })?;
+        let response: Response = api::parse(&response.into_string()?)?;
+        if !(200..300).contains(&response.meta.code) {
+            return Err(Error::ApiError {
+                msg: format!(
+                    "BrickLink answered {}: {}",
+                    response.meta.code, response.meta.description
+                ),
+            });
+        }
+        Ok(response.data)
+    }
+
+    /// Sends a small request to find out whether the keys are accepted.
+    pub fn check_keys(&self) -> Result<()> {
+        self.send("GET", "/colors", &[], None)?;
+        Ok(())
+    }
+
+    /// The lots of parts in the store.
+    pub fn get_lots(&self) -> Result<Vec<StoreLot>> {
+        let data = self.send("GET", "/inventories", &[("item_type", "PART")], None)?;
+        serde_json::from_value(data).map_err(|e| Error::ApiError { msg: e.to_string() })
+    }
+
+    pub fn apply(&self, change: &Change) -> Result<()> {
+        match change {
+            Change::Add(listing) => {
+                let Some(number) = listing.id.to_bricklink() else {
+                    return Ok(());
+                };
+                let lot = serde_json::json!({
+                    "item": {"no": number, "type": "PART"},
+                    "color_id": listing.color_id,
+                    "quantity": listing.quantity,
+                    "unit_price": String::from(listing.price),
+                    "new_or_used": get_code(listing.condition),
+                    "remarks": listing.remarks,
+                });
+                self.send("POST", "/inventories", &[], Some(serde_json::json!([lot])))?;
+            }
+            Change::Update(old, listing) => {
+                let mut lot = serde_json::json!({
+                    "unit_price": String::from(listing.price),
+                    "remarks": listing.remarks,
+                });
+                // The quantity of an update is the change to it, written with its sign.
+                let added = listing.quantity as i64 - old.quantity as i64;
+                if added != 0 {
+                    lot["quantity"] = format!("{:+}", added).into();
+                }
+                let path = format!("/inventories/{}", old.inventory_id);
+                self.send("PUT", &path, &[], Some(lot))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::Quantity;
+
+    #[test]
+    fn test_changes() {
+        let home = currency::get_home_currency();
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Other("Red".to_string()), "A1".to_string());
+        item.add_color_group(ColorGroup::Other("Red".to_string()), "B1".to_string());
+        item.add_color_group(ColorGroup::Basic, "A2".to_string());
+        item.add_color_group(ColorGroup::Other("Blue".to_string()), "A3".to_string());
+        let mut lots = item.get_locations().to_vec();
+        for (lot, (quantity, cents)) in lots.iter_mut().zip([(10, 10), (30, 30), (5, 5), (2, 50)]) {
+            lot.set_quantity(Some(Quantity::new(quantity)));
+            lot.set_asking_price(Some(Price::new(Amount::from_cents(cents), home)));
+            lot.set_condition(Some(Condition::Used));
+        }
+        lots[3].set_quantity(None);
+        item.set_locations(lots);
+
+        let (listings, skipped) = get_listings(&[item]);
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(listings.len(), 1);
+        assert_eq!(listings[0].quantity, 40);
+        assert_eq!(listings[0].price, Amount::from_cents(25));
+
+        let store: Vec<StoreLot> = serde_json::from_str(
+            r#"[{"inventory_id": 7, "item": {"no": "3001", "type": "PART"}, "color_id": 5,
+                "quantity": 40, "new_or_used": "U", "unit_price": "0.2000", "remarks": ""}]"#,
+        )
+        .unwrap();
+        let changes = get_changes(&listings, &store);
+        assert_eq!(
+            changes,
+            vec![Change::Update(store[0].clone(), listings[0].clone())]
+        );
+        assert!(changes[0]
+            .to_string()
+            .ends_with(&format!("price 0.2000 to 0.25 {}", home)));
+        assert_eq!(
+            get_changes(&listings, &[]),
+            vec![Change::Add(listings[0].clone())]
+        );
+
+        let mut store = store;
+        store[0].unit_price = "0.2500".to_string();
+        assert!(get_changes(&listings, &store).is_empty());
+    }
+}
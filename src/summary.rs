@@ -0,0 +1,225 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::adjustment::Adjustment;
+use crate::budget::{self, Purchase};
+use crate::currency::{self, Amount, Price};
+use crate::data::Item;
+use crate::date::{Date, Range};
+use crate::error::{Error, Result};
+use crate::locale::{tr, tr_n};
+use crate::sale::{self, Sale};
+
+/// A command the summary is piped to, e.g. `{command: sendmail, args: [me@example.com]}`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SummaryCommand {
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+/// What happened to the collection in a month. The change in value is estimated from the changes
+/// to quantities, valued at the prices the parts have now.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Summary {
+    month: Date,
+    lots_added: usize,
+    pieces_added: usize,
+    spent: Amount,
+    /// Purchases without an exchange rate to the home currency.
+    num_unconverted: usize,
+    sales: String,
+    value_change: Amount,
+}
+
+/// The value of the pieces at the price of the part, which is none if it has no price in the
+/// home currency.
+fn get_value(items: &[Item], adjustment: &Adjustment) -> Option<Amount> {
+    let item = items
+        .iter()
+        .find(|item| item.get_id() == adjustment.get_id())?;
+    let price = item.get_price()?.to_home_currency()?.get_amount();
+    let value = price.times(adjustment.get_change().unsigned_abs() as u32);
+    Some(if adjustment.get_change() < 0 {
+        Amount::ZERO - value
+    } else {
+        value
+    })
+}
+
+impl Summary {
+    /// Sums up the month of the date.
+    pub fn new(
+        month: Date,
+        items: &[Item],
+        adjustments: &[Adjustment],
+        sales: &[Sale],
+        purchases: &[Purchase],
+    ) -> Self {
+        let range = Range::this_month(month);
+        let (spent, num_unconverted) = budget::get_spent(purchases, range);
+        let mut summary = Self {
+            month,
+            lots_added: 0,
+            pieces_added: 0,
+            spent,
+            num_unconverted,
+            sales: String::new(),
+            value_change: Amount::ZERO,
+        };
+        for lot in items.iter().flat_map(|item| item.get_locations()) {
+            if lot.get_added().is_some_and(|added| range.contains(added)) {
+                summary.lots_added += 1;
+                summary.pieces_added += lot.get_quantity().map_or(0, |q| q.get() as usize);
+            }
+        }
+        for adjustment in adjustments
+            .iter()
+            .filter(|adjustment| range.contains(adjustment.get_time()))
+        {
+            summary.value_change += get_value(items, adjustment).unwrap_or(Amount::ZERO);
+        }
+        let sales: Vec<Sale> = sales
+            .iter()
+            .filter(|sale| range.contains(sale.get_time()))
+            .cloned()
+            .collect();
+        summary.sales = sale::summarize(&sales);
+        summary
+    }
+
+    fn get_title(&self) -> String {
+        let (year, month) = self.month.get_month();
+        tr!("Summary of {}", format!("{}-{:02}", year, month))
+    }
+
+    /// The lines of the summary as names and values.
+    fn get_lines(&self) -> Vec<(String, String)> {
+        let home = currency::get_home_currency();
+        let mut spent = Price::new(self.spent, home).to_string();
+        if self.num_unconverted != 0 {
+            spent.push(' ');
+            spent.push_str(&tr_n!(
+                "({} purchase without an exchange rate is not included)",
+                "({} purchases without an exchange rate are not included)",
+                self.num_unconverted
+            ));
+        }
+        vec![
+            (
+                tr!("Lots added").to_string(),
+                tr_n!(
+                    "{} lot with {} counted pieces",
+                    "{} lots with {} counted pieces",
+                    self.lots_added,
+                    self.pieces_added
+                ),
+            ),
+            (tr!("Money spent").to_string(), spent),
+            (tr!("Sales").to_string(), self.sales.clone()),
+            (
+                tr!("Change in value").to_string(),
+                Price::new(self.value_change, home).to_string(),
+            ),
+        ]
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n\n", self.get_title());
+        for (name, value) in self.get_lines() {
+            text.push_str(&format!("{}: {}\n", name, value));
+        }
+        text
+    }
+
+    pub fn to_html(&self) -> String {
+        let title = escape(&self.get_title());
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+             </head>\n<body>\n<h1>{}</h1>\n<table>\n",
+            title, title
+        );
+        for (name, value) in self.get_lines() {
+            html.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                escape(&name),
+                escape(&value)
+            ));
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Pipes the summary to the command and waits for it to finish.
+pub fn send(command: &SummaryCommand, contents: &str) -> Result<()> {
+    let failed = |msg: String| Error::SummaryNotSent { msg };
+    let mut child = Command::new(&command.command)
+        .args(&command.args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| failed(e.to_string()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(contents.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(failed(status.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjustment::{self, Reason};
+    use crate::data::ColorGroup;
+    use crate::types::{PartId, Quantity};
+
+    #[test]
+    fn test_summary() {
+        let mut brick = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        brick.add_color_group(ColorGroup::Basic, "A1".to_string());
+        let mut lots = brick.get_locations().to_vec();
+        lots[0].set_quantity(Some(Quantity::new(10)));
+        let mut yaml = serde_yaml::to_string(&lots[0]).unwrap();
+        yaml.push_str("added: 2024-05-03T00:00:00Z\n");
+        lots[0] = serde_yaml::from_str(&yaml).unwrap();
+        brick.set_locations(lots);
+        brick.set_price(Price::parse("0.10 EUR"));
+        let items = vec![brick.clone()];
+
+        let mut old_brick = brick.clone();
+        old_brick.set_quantity(&ColorGroup::Basic, Some(Quantity::new(0)));
+        let bought = adjustment::get_adjustments(
+            &old_brick,
+            &brick,
+            &[(ColorGroup::Basic, Reason::Bought)].into(),
+        );
+        let month = Date::new(2024, 5, 1).unwrap();
+        let purchase = Purchase::new(
+            "Bricks".to_string(),
+            Price::parse("12 EUR").unwrap(),
+            month.add_days(3).to_timestamp(),
+        );
+        let summary = Summary::new(month, &items, &[], &[], &[purchase]);
+        assert_eq!(summary.spent, Amount::parse("12").unwrap());
+        assert_eq!(summary.lots_added, 1);
+        assert_eq!(summary.pieces_added, 10);
+        assert!(summary.to_html().contains("<h1>Summary of 2024-05</h1>"));
+
+        // The adjustments are made now, so they are only in the summary of this month.
+        let summary = Summary::new(Date::today(), &items, &bought, &[], &[]);
+        assert_eq!(summary.value_change, Amount::parse("1").unwrap());
+        assert!(summary.to_text().starts_with(&summary.get_title()));
+    }
+}
@@ -0,0 +1,385 @@
+use std::fmt;
+
+use crate::data::{Item, Lot};
+use crate::locale::tr;
+use crate::types::PartId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Item,
+    Name,
+    AlternativeIds,
+    Locations,
+    Price,
+    /// The fields that are not merged one by one, like the category, dimensions and custom fields,
+    /// which are merged as a whole.
+    Details,
+}
+
+/// A change made both in this session and in the file on disk to the same field of the same item.
+/// The merged database keeps the local version until the conflict is resolved otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub id: PartId,
+    pub field: Field,
+    pub local: Option<Item>,
+    pub remote: Option<Item>,
+}
+
+impl Conflict {
+    fn fmt_field(&self, item: &Item) -> String {
+        use Field::*;
+        match self.field {
+            Item | Details => item.to_string(),
+            Name => item.get_name().to_string(),
+            AlternativeIds => item
+                .get_alternative_ids()
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Locations => item
+                .get_locations()
+                .iter()
+                .map(|lot| lot.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Price => match item.get_price() {
+                Some(price) => price.to_string(),
+                None => tr!("None").to_string(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = match self.field {
+            Field::Item => tr!("Item"),
+            Field::Name => tr!("Name"),
+            Field::AlternativeIds => tr!("Alternative IDs"),
+            Field::Locations => tr!("Locations"),
+            Field::Price => tr!("Price"),
+            Field::Details => tr!("Details"),
+        };
+        let fmt_side = |item: &Option<Item>| match item {
+            Some(item) => self.fmt_field(item),
+            None => tr!("Deleted").to_string(),
+        };
+        write!(
+            f,
+            "{}",
+            tr!(
+                "{} of part {} was changed both here and on disk.\n\nHere:\n{}\n\nOn disk:\n{}",
+                field,
+                self.id,
+                fmt_side(&self.local),
+                fmt_side(&self.remote),
+            )
+        )
+    }
+}
+
+fn find(items: &[Item], id: PartId) -> Option<&Item> {
+    items.iter().find(|item| item.get_id() == id)
+}
+
+/// Picks the side that changed compared to base. Returns true if both sides changed differently,
+/// in which case the local side is picked.
+fn pick<'a, T: PartialEq + ?Sized>(base: Option<&T>, local: &'a T, remote: &'a T) -> (&'a T, bool) {
+    if local == remote || base == Some(remote) {
+        (local, false)
+    } else if base == Some(local) {
+        (remote, false)
+    } else {
+        (local, true)
+    }
+}
+
+fn find_lot<'a>(lots: &'a [Lot], lot: &Lot) -> Option<&'a Lot> {
+    lots.iter().find(|other| other.is_same_lot(lot))
+}
+
+/// Merges lots that were changed on only one side, matched by their IDs. Returns none if a lot was
+/// changed on both sides, or if lots without an ID cannot be matched.
+fn merge_lots(base: &[Lot], local: &[Lot], remote: &[Lot]) -> Option<Vec<Lot>> {
+    let lots = || base.iter().chain(local.iter()).chain(remote.iter());
+    if lots().any(|lot| lot.get_id().is_none()) {
+        return None;
+    }
+
+    let mut merged: Vec<Lot> = Vec::new();
+    let mut seen: Vec<&Lot> = Vec::new();
+    for lot in local.iter().chain(remote.iter()) {
+        if seen.iter().any(|other| other.is_same_lot(lot)) {
+            continue;
+        }
+        seen.push(lot);
+        let sides = [
+            find_lot(base, lot),
+            find_lot(local, lot),
+            find_lot(remote, lot),
+        ];
+        let (picked, conflict) = pick(Some(&sides[0]), &sides[1], &sides[2]);
+        if conflict {
+            return None;
+        }
+        merged.extend(picked.cloned());
+    }
+    Some(merged)
+}
+
+/// The item without the fields that are merged one by one, so that a change to any other field
+/// is seen when comparing them.
+fn get_details(item: &Item) -> Item {
+    let mut details = item.clone();
+    details.set_name("");
+    details.set_alternative_ids(Vec::new());
+    details.set_locations(Vec::new());
+    details.set_price(None);
+    details
+}
+
+/// The item with the details of the other item in place of its own.
+fn with_details(item: &Item, other: &Item) -> Item {
+    let mut merged = other.clone();
+    merged.set_name(item.get_name());
+    merged.set_alternative_ids(item.get_alternative_ids().to_vec());
+    merged.set_locations(item.get_locations().to_vec());
+    merged.set_price(item.get_price().cloned());
+    merged
+}
+
+fn merge_item(base: Option<&Item>, local: &Item, remote: &Item) -> (Item, Vec<Field>) {
+    let mut conflicts = Vec::new();
+
+    let base_details = base.map(get_details);
+    let (local_details, remote_details) = (get_details(local), get_details(remote));
+    let (details, conflict) = pick(base_details.as_ref(), &local_details, &remote_details);
+    let mut merged = details.clone();
+    if conflict {
+        conflicts.push(Field::Details);
+    }
+
+    let (name, conflict) = pick(
+        base.map(|b| b.get_name()),
+        local.get_name(),
+        remote.get_name(),
+    );
+    merged.set_name(name);
+    if conflict {
+        conflicts.push(Field::Name);
+    }
+
+    let (alt_ids, conflict) = pick(
+        base.map(|b| b.get_alternative_ids()),
+        local.get_alternative_ids(),
+        remote.get_alternative_ids(),
+    );
+    merged.set_alternative_ids(alt_ids.to_vec());
+    if conflict {
+        conflicts.push(Field::AlternativeIds);
+    }
+
+    let (locations, conflict) = pick(
+        base.map(|b| b.get_locations()),
+        local.get_locations(),
+        remote.get_locations(),
+    );
+    let merged_lots = match (conflict, base) {
+        (true, Some(base)) => merge_lots(
+            base.get_locations(),
+            local.get_locations(),
+            remote.get_locations(),
+        ),
+        _ => None,
+    };
+    match merged_lots {
+        Some(lots) => merged.set_locations(lots),
+        None => {
+            merged.set_locations(locations.to_vec());
+            if conflict {
+                conflicts.push(Field::Locations);
+            }
+        }
+    }
+
+    let base_price = base.map(|b| b.get_price());
+    let (local_price, remote_price) = (local.get_price(), remote.get_price());
+    let (price, conflict) = pick(base_price.as_ref(), &local_price, &remote_price);
+    merged.set_price(price.cloned());
+    if conflict {
+        conflicts.push(Field::Price);
+    }
+
+    (merged, conflicts)
+}
+
+/// Three-way merges the items changed in this session with the items in the file on disk, where
+/// base is the content of the file when it was last read or written by this session.
+pub fn merge(base: &[Item], local: &[Item], remote: &[Item]) -> (Vec<Item>, Vec<Conflict>) {
+    let mut merged = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for item in local.iter() {
+        let id = item.get_id();
+        match (find(base, id), find(remote, id)) {
+            (base_item, Some(remote_item)) => {
+                let (merged_item, fields) = merge_item(base_item, item, remote_item);
+                for field in fields {
+                    conflicts.push(Conflict {
+                        id,
+                        field,
+                        local: Some(item.clone()),
+                        remote: Some(remote_item.clone()),
+                    });
+                }
+                merged.push(merged_item);
+            }
+            (Some(base_item), None) => {
+                // Deleted on disk, keep it only if it was also changed here.
+                if item != base_item {
+                    conflicts.push(Conflict {
+                        id,
+                        field: Field::Item,
+                        local: Some(item.clone()),
+                        remote: None,
+                    });
+                    merged.push(item.clone());
+                }
+            }
+            (None, None) => merged.push(item.clone()),
+        }
+    }
+
+    for remote_item in remote.iter() {
+        let id = remote_item.get_id();
+        if find(local, id).is_some() {
+            continue;
+        }
+        match find(base, id) {
+            None => merged.push(remote_item.clone()),
+            Some(base_item) => {
+                // Deleted here, keep it deleted but report it if it was changed on disk.
+                if remote_item != base_item {
+                    conflicts.push(Conflict {
+                        id,
+                        field: Field::Item,
+                        local: None,
+                        remote: Some(remote_item.clone()),
+                    });
+                }
+            }
+        }
+    }
+
+    (merged, conflicts)
+}
+
+/// Resolves the conflict by using the version from disk.
+pub fn apply_remote(items: &mut Vec<Item>, conflict: &Conflict) {
+    let position = items.iter().position(|item| item.get_id() == conflict.id);
+
+    let Some(remote) = &conflict.remote else {
+        items.retain(|item| item.get_id() != conflict.id);
+        return;
+    };
+
+    let Some(i) = position else {
+        items.push(remote.clone());
+        return;
+    };
+
+    let item = &mut items[i];
+    match conflict.field {
+        Field::Item => *item = remote.clone(),
+        Field::Name => item.set_name(remote.get_name()),
+        Field::AlternativeIds => item.set_alternative_ids(remote.get_alternative_ids().to_vec()),
+        Field::Locations => item.set_locations(remote.get_locations().to_vec()),
+        Field::Price => item.set_price(remote.get_price().cloned()),
+        Field::Details => *item = with_details(item, remote),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::Quantity;
+
+    #[test]
+    fn test_merge() {
+        let base = vec![Item::new(
+            PartId::new(3001).unwrap(),
+            "Brick 2 x 4".to_string(),
+        )];
+
+        let mut local = base.clone();
+        local[0].set_name("Brick 2x4");
+        local.push(Item::new(
+            PartId::new(3003).unwrap(),
+            "Brick 2 x 2".to_string(),
+        ));
+
+        let mut remote = base.clone();
+        remote[0].add_color_group(ColorGroup::Basic, "A1".to_string());
+
+        let (merged, conflicts) = merge(&base, &local, &remote);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].get_name(), "Brick 2x4");
+        assert_eq!(merged[0].get_locations().len(), 1);
+
+        remote[0].set_name("Brick 4 x 2");
+        let (mut merged, conflicts) = merge(&base, &local, &remote);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, Field::Name);
+        assert_eq!(merged[0].get_name(), "Brick 2x4");
+
+        apply_remote(&mut merged, &conflicts[0]);
+        assert_eq!(merged[0].get_name(), "Brick 4 x 2");
+
+        let mut base = vec![Item::new(
+            PartId::new(3001).unwrap(),
+            "Brick 2 x 4".to_string(),
+        )];
+        base[0].add_color_group(ColorGroup::Basic, "A1".to_string());
+        base[0].add_color_group(ColorGroup::Earth, "A2".to_string());
+        base[0].assign_lot_ids();
+        let (mut local, mut remote) = (base.clone(), base.clone());
+        local[0].set_quantity(&ColorGroup::Basic, Some(Quantity::new(3)));
+        remote[0].set_quantity(&ColorGroup::Earth, Some(Quantity::new(5)));
+        let (merged, conflicts) = merge(&base, &local, &remote);
+        assert!(conflicts.is_empty());
+        let quantities: Vec<_> = merged[0]
+            .get_locations()
+            .iter()
+            .map(|lot| lot.get_quantity())
+            .collect();
+        assert_eq!(
+            quantities,
+            vec![Some(Quantity::new(3)), Some(Quantity::new(5))]
+        );
+
+        remote[0].set_quantity(&ColorGroup::Basic, Some(Quantity::new(4)));
+        let (_, conflicts) = merge(&base, &local, &remote);
+        assert_eq!(conflicts[0].field, Field::Locations);
+
+        // Fields that are not merged one by one are still taken from the side that changed them.
+        let (mut local, mut remote) = (base.clone(), base.clone());
+        local[0].set_name("Brick 2x4");
+        remote[0].set_category(Some("Bricks".to_string()));
+        let (merged, conflicts) = merge(&base, &local, &remote);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged[0].get_name(), "Brick 2x4");
+        assert_eq!(merged[0].get_category(), Some("Bricks"));
+
+        local[0].set_category(Some("Plates".to_string()));
+        let (mut merged, conflicts) = merge(&base, &local, &remote);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, Field::Details);
+        apply_remote(&mut merged, &conflicts[0]);
+        assert_eq!(merged[0].get_name(), "Brick 2x4");
+        assert_eq!(merged[0].get_category(), Some("Bricks"));
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::OnceLock;
+
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+
+use crate::platform;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// How selection and emphasis are shown.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    #[default]
+    Color,
+    /// Bold and reverse video without colors, where warnings are shown in reverse video instead of
+    /// red. Used in place of `Color` when `NO_COLOR` is set.
+    HighContrast,
+    /// No styling at all, where the selected row is marked with `>`, emphasis with brackets and
+    /// warnings with `!`, for terminals that show no styles.
+    Monochrome,
+}
+
+impl Theme {
+    /// Marks the row as selected or not. Rows are shifted by the same width either way.
+    pub fn row(&self, text: &str, selected: bool) -> String {
+        match self {
+            Theme::Monochrome if selected => format!("> {}", text),
+            Theme::Monochrome => format!("  {}", text),
+            _ if selected => text.reverse().to_string(),
+            _ => text.to_string(),
+        }
+    }
+
+    /// How much wider rows are made by marking them.
+    pub fn get_row_marker_width(&self) -> usize {
+        match self {
+            Theme::Monochrome => 2,
+            _ => 0,
+        }
+    }
+
+    /// Makes a heading like the names of columns stand out, where the theme can.
+    pub fn heading(&self, text: &str) -> String {
+        match self {
+            Theme::Monochrome => text.to_string(),
+            _ => text.bold().to_string(),
+        }
+    }
+
+    pub fn emphasis(&self, text: &str) -> String {
+        match self {
+            Theme::Monochrome => format!("[{}]", text),
+            _ => text.bold().to_string(),
+        }
+    }
+
+    /// Shows a message that has to be noticed, like a key press that did nothing.
+    pub fn highlight(&self, text: &str) -> String {
+        match self {
+            Theme::Monochrome => format!("[{}]", text),
+            _ => text.reverse().to_string(),
+        }
+    }
+
+    /// Marks what destroys data, like going ahead with deleting an item.
+    pub fn warning(&self, text: &str) -> String {
+        match self {
+            Theme::Color => text.red().bold().to_string(),
+            Theme::HighContrast => text.reverse().bold().to_string(),
+            Theme::Monochrome => format!("!{}!", text),
+        }
+    }
+}
+
+/// Sets the theme used for the rest of the session, where colors are left out if the `NO_COLOR`
+/// environment variable is set to anything, and all styles if the terminal does not show them.
+/// Can only be set once.
+pub fn init(theme: Theme) {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let theme = match theme {
+        _ if !platform::supports_styles() => Theme::Monochrome,
+        Theme::Color if no_color => Theme::HighContrast,
+        theme => theme,
+    };
+    let _ = THEME.set(theme);
+}
+
+pub fn get() -> Theme {
+    THEME.get().copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme() {
+        let rows = [
+            Theme::Monochrome.row("a", true),
+            Theme::Monochrome.row("b", false),
+        ];
+        assert_eq!(rows, ["> a", "  b"]);
+        assert_eq!(Theme::Monochrome.emphasis("y: yes"), "[y: yes]");
+        assert_eq!(Theme::Monochrome.warning("y: yes"), "!y: yes!");
+        assert_eq!(Theme::Color.row("a", false), "a");
+        assert_eq!(Theme::Color.row("a", true), "\x1b[7ma\x1b[0m");
+        assert!(Theme::Color.warning("y").contains("\x1b[38;5;"));
+        assert!(!Theme::HighContrast.warning("y").contains("\x1b[38;5;"));
+    }
+}
@@ -0,0 +1,110 @@
+use std::time::{Duration, Instant};
+
+use crate::locale::{self, tr, tr_n};
+
+/// What was catalogued in one sitting of sorting a bin, to show how fast it is going.
+pub struct Session {
+    started: Instant,
+    lots: usize,
+    /// The lots among them that did not exist before.
+    new_lots: usize,
+    pieces: u32,
+}
+
+impl Session {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            lots: 0,
+            new_lots: 0,
+            pieces: 0,
+        }
+    }
+
+    /// Counts pieces put into a lot, which is new if the part had no lot with the color group in
+    /// the location before.
+    pub fn add(&mut self, pieces: u32, is_new_lot: bool) {
+        self.lots += 1;
+        self.new_lots += is_new_lot as usize;
+        self.pieces += pieces;
+    }
+
+    pub fn get_lots(&self) -> usize {
+        self.lots
+    }
+
+    /// The pieces catalogued per hour so far, shown while sorting.
+    pub fn describe_rate(&self) -> String {
+        describe_rate(self.pieces, self.started.elapsed())
+    }
+
+    /// Sums up the session when it ends.
+    pub fn describe(&self) -> String {
+        describe(
+            self.lots,
+            self.new_lots,
+            self.pieces,
+            self.started.elapsed(),
+        )
+    }
+}
+
+/// The rate is only given after a minute, as a few seconds in it says little.
+fn get_per_hour(count: f64, time: Duration) -> Option<f64> {
+    let hours = time.as_secs_f64() / 3600.0;
+    (time >= Duration::from_secs(60)).then(|| count / hours)
+}
+
+fn describe_rate(pieces: u32, time: Duration) -> String {
+    match get_per_hour(pieces as f64, time) {
+        Some(rate) => tr!("{} pieces an hour", locale::fmt_decimal(rate, 0)),
+        None => String::new(),
+    }
+}
+
+fn describe(lots: usize, new_lots: usize, pieces: u32, time: Duration) -> String {
+    let mut lines = vec![
+        tr!("Sorted for {}.", locale::fmt_duration(time)),
+        tr_n!(
+            "Put pieces into {} lot, {} of them new.",
+            "Put pieces into {} lots, {} of them new.",
+            lots,
+            new_lots
+        ),
+        tr_n!(
+            "Catalogued {} piece.",
+            "Catalogued {} pieces.",
+            pieces as usize
+        ),
+    ];
+    let lots_rate = get_per_hour(lots as f64, time);
+    let pieces_rate = get_per_hour(pieces as f64, time);
+    if let (Some(lots_rate), Some(pieces_rate)) = (lots_rate, pieces_rate) {
+        lines.push(tr!(
+            "That is {} lots and {} pieces an hour.",
+            locale::fmt_decimal(lots_rate, 1),
+            locale::fmt_decimal(pieces_rate, 0)
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let time = Duration::from_secs(30 * 60);
+        assert_eq!(
+            describe(12, 5, 640, time),
+            "Sorted for 0h 30m.\n\
+            Put pieces into 12 lots, 5 of them new.\n\
+            Catalogued 640 pieces.\n\
+            That is 24.0 lots and 1,280 pieces an hour."
+        );
+        assert_eq!(describe_rate(640, time), "1,280 pieces an hour");
+        assert_eq!(describe_rate(10, Duration::from_secs(20)), "");
+        assert!(!describe(1, 1, 1, Duration::from_secs(5)).contains("an hour"));
+    }
+}
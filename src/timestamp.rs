@@ -0,0 +1,241 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::adjustment;
+
+static TIME_CONFIG: OnceLock<TimeConfig> = OnceLock::new();
+
+/// How times are shown. They are always stored in UTC.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TimeConfig {
+    /// The offset of local time from UTC, like `+02:00` or `-05:30`.
+    #[serde(default = "default_utc_offset")]
+    utc_offset: String,
+    /// The format of times, where `%Y`, `%m`, `%d`, `%H`, `%M` and `%S` are the parts of the time
+    /// and `%Z` is the offset from UTC.
+    #[serde(default = "default_format")]
+    format: String,
+}
+
+fn default_utc_offset() -> String {
+    "+00:00".to_string()
+}
+
+fn default_format() -> String {
+    "%Y-%m-%d %H:%M %Z".to_string()
+}
+
+impl Default for TimeConfig {
+    fn default() -> Self {
+        Self {
+            utc_offset: default_utc_offset(),
+            format: default_format(),
+        }
+    }
+}
+
+impl TimeConfig {
+    /// Returns the offset in seconds, where an offset that cannot be read is taken as UTC.
+    fn get_offset(&self) -> i64 {
+        parse_offset(&self.utc_offset).unwrap_or_else(|| {
+            log::warn!("invalid UTC offset: {}", self.utc_offset);
+            0
+        })
+    }
+}
+
+fn parse_offset(offset: &str) -> Option<i64> {
+    if offset == "UTC" || offset == "Z" {
+        return Some(0);
+    }
+    let (sign, offset) = match offset.split_at_checked(1)? {
+        ("+", offset) => (1, offset),
+        ("-", offset) => (-1, offset),
+        _ => return None,
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    let (hours, minutes): (i64, i64) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours <= 14 && minutes < 60).then_some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Sets how times are shown for the rest of the session. Can only be set once.
+pub fn init(config: TimeConfig) {
+    let _ = TIME_CONFIG.set(config);
+}
+
+fn get_config() -> &'static TimeConfig {
+    TIME_CONFIG.get_or_init(TimeConfig::default)
+}
+
+/// A point in time in seconds since the unix epoch, stored as UTC like `2024-02-29T12:00:00Z` and
+/// shown in local time.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Self(adjustment::now())
+    }
+
+    pub fn from_secs(secs: u64) -> Self {
+        Self(secs)
+    }
+
+    pub fn get_secs(self) -> u64 {
+        self.0
+    }
+
+    /// Parses times like `2024-02-29T12:00:00Z`, or dates like `2024-02-29` at midnight UTC.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (date, time) = s.split_once('T').unwrap_or((s, "00:00:00Z"));
+        let mut date = date.splitn(3, '-');
+        let year: i64 = date.next()?.parse().ok()?;
+        let month: u32 = date.next()?.parse().ok()?;
+        let day: u32 = date.next()?.parse().ok()?;
+        let mut time = time.strip_suffix('Z')?.splitn(3, ':');
+        let hours: u64 = time.next()?.parse().ok()?;
+        let minutes: u64 = time.next()?.parse().ok()?;
+        let seconds: u64 = time.next().map_or(Some(0), |s| s.parse().ok())?;
+        if hours >= 24 || minutes >= 60 || seconds >= 60 {
+            return None;
+        }
+
+        let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+        let timestamp = Self(days * 86400 + hours * 3600 + minutes * 60 + seconds);
+        // Dates like 2023-02-30 fall on another day.
+        (adjustment::get_date(timestamp.0) == (year, month, day)).then_some(timestamp)
+    }
+
+    /// The year, month and day of the time in local time.
+    pub fn get_local_date(self) -> (i64, u32, u32) {
+        adjustment::get_date(self.0.saturating_add_signed(get_config().get_offset()))
+    }
+
+    /// Formats the time in UTC as it is stored.
+    pub fn to_utc_string(self) -> String {
+        let (year, month, day) = adjustment::get_date(self.0);
+        let seconds = self.0 % 86400;
+        format!(
+            "{}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+
+    fn format(self, format: &str, offset: i64) -> String {
+        let local = self.0.saturating_add_signed(offset);
+        let (year, month, day) = adjustment::get_date(local);
+        let seconds = local % 86400;
+        let zone = match offset {
+            0 => "UTC".to_string(),
+            offset => format!(
+                "{}{:02}:{:02}",
+                if offset < 0 { '-' } else { '+' },
+                offset.abs() / 3600,
+                offset.abs() / 60 % 60
+            ),
+        };
+
+        let mut res = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                res.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => res.push_str(&year.to_string()),
+                Some('m') => res.push_str(&format!("{:02}", month)),
+                Some('d') => res.push_str(&format!("{:02}", day)),
+                Some('H') => res.push_str(&format!("{:02}", seconds / 3600)),
+                Some('M') => res.push_str(&format!("{:02}", seconds / 60 % 60)),
+                Some('S') => res.push_str(&format!("{:02}", seconds % 60)),
+                Some('Z') => res.push_str(&zone),
+                Some(c) => {
+                    res.push('%');
+                    if c != '%' {
+                        res.push(c);
+                    }
+                }
+                None => res.push('%'),
+            }
+        }
+        res
+    }
+}
+
+/// Returns the days since the epoch of a civil date, see
+/// https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Shows the time in local time in the configured format.
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let config = get_config();
+        write!(f, "{}", self.format(&config.format, config.get_offset()))
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_utc_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Timestamp::parse(&s).ok_or_else(|| {
+            de::Error::custom(format!(
+                "invalid time, expected UTC like 2024-02-29T12:00:00Z: {}",
+                s
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp() {
+        let time = Timestamp::parse("2024-02-29T12:00:00Z").unwrap();
+        assert_eq!(time.get_secs(), 1709208000);
+        assert_eq!(time.to_utc_string(), "2024-02-29T12:00:00Z");
+        assert_eq!(
+            Timestamp::parse("2024-02-29").unwrap().get_secs(),
+            1709164800
+        );
+        assert_eq!(Timestamp::parse("2023-02-29"), None);
+        assert_eq!(Timestamp::parse("2024-02-29T24:00:00Z"), None);
+
+        let offset = parse_offset("-05:30").unwrap();
+        assert_eq!(
+            time.format("%d.%m.%Y %H:%M %Z", offset),
+            "29.02.2024 06:30 -05:30"
+        );
+        assert_eq!(
+            time.format("%Y-%m-%d %H:%M %Z 100%%", 0),
+            "2024-02-29 12:00 UTC 100%"
+        );
+        assert_eq!(parse_offset("+2"), Some(7200));
+        assert_eq!(parse_offset("02:00"), None);
+
+        let yaml = serde_yaml::to_string(&time).unwrap();
+        assert_eq!(serde_yaml::from_str::<Timestamp>(&yaml).unwrap(), time);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::adjustment;
+use crate::data::Item;
+use crate::error::{Error, Result};
+use crate::io;
+use crate::locale::{tr, tr_n};
+use crate::types::PartId;
+
+/// A part as it was before and after a transaction, where none means it did not exist.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Change {
+    id: PartId,
+    before: Option<Item>,
+    after: Option<Item>,
+}
+
+/// Changes to several parts made by one operation, e.g. a part-out or an import, which are saved
+/// together and undone together. Transactions are kept in a log next to the database file.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Transaction {
+    name: String,
+    /// Seconds since the unix epoch.
+    time: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+    changes: Vec<Change>,
+}
+
+impl Transaction {
+    /// The transaction that turns the old items into the new ones, which is none if they are the
+    /// same.
+    pub fn new(name: &str, old: &[Item], new: &[Item]) -> Option<Self> {
+        let old_by_id: BTreeMap<PartId, &Item> =
+            old.iter().map(|item| (item.get_id(), item)).collect();
+        let new_by_id: BTreeMap<PartId, &Item> =
+            new.iter().map(|item| (item.get_id(), item)).collect();
+        let mut changes: Vec<Change> = Vec::new();
+        for old_item in old.iter() {
+            let new_item = new_by_id.get(&old_item.get_id()).copied();
+            if new_item != Some(old_item) {
+                changes.push(Change {
+                    id: old_item.get_id(),
+                    before: Some(old_item.clone()),
+                    after: new_item.cloned(),
+                });
+            }
+        }
+        for new_item in new.iter() {
+            if !old_by_id.contains_key(&new_item.get_id()) {
+                changes.push(Change {
+                    id: new_item.get_id(),
+                    before: None,
+                    after: Some(new_item.clone()),
+                });
+            }
+        }
+        (!changes.is_empty()).then(|| Self {
+            name: name.to_string(),
+            time: adjustment::now(),
+            user: None,
+            changes,
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}, {}",
+            adjustment::fmt_time(self.time),
+            self.name,
+            tr_n!("{} part changed", "{} parts changed", self.changes.len())
+        )?;
+        if let Some(user) = &self.user {
+            write!(f, ", {}", user)?;
+        }
+        Ok(())
+    }
+}
+
+/// A transaction that has been started but not committed, holding the items as they were before
+/// they were first changed in it.
+#[derive(Debug, Default)]
+pub struct Pending {
+    name: String,
+    before: BTreeMap<PartId, Option<Item>>,
+    /// The number of events that were waiting to be published when the transaction started.
+    events: usize,
+}
+
+impl Pending {
+    pub fn new(name: &str, events: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            before: BTreeMap::new(),
+            events,
+        }
+    }
+
+    /// Keeps the item with the ID as it is now, unless it was changed in the transaction before.
+    pub fn remember(&mut self, id: PartId, item: Option<&Item>) {
+        self.before.entry(id).or_insert_with(|| item.cloned());
+    }
+
+    /// The items as they were before the transaction, by ID.
+    pub fn get_before(&self) -> &BTreeMap<PartId, Option<Item>> {
+        &self.before
+    }
+
+    pub fn get_events(&self) -> usize {
+        self.events
+    }
+
+    /// The transaction that was made, given the items as they are now.
+    pub fn finish(self, items: &[Item]) -> Option<Transaction> {
+        let old: Vec<Item> = self.before.values().flatten().cloned().collect();
+        let new: Vec<Item> = self
+            .before
+            .keys()
+            .filter_map(|id| items.iter().find(|item| item.get_id() == *id))
+            .cloned()
+            .collect();
+        Transaction::new(&self.name, &old, &new)
+    }
+}
+
+pub fn get_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("transactions")
+}
+
+/// Appends the transaction to the log, recorded as made by the user if there is one.
+pub fn append(path: &Path, transaction: &Transaction, user: Option<&str>) -> Result<()> {
+    let transaction = Transaction {
+        user: user.map(str::to_string),
+        ..transaction.clone()
+    };
+    let mut appended = "---\n".to_string();
+    appended.push_str(&serde_yaml::to_string(&transaction).map_err(term_lib::Error::from)?);
+    io::append_private(path, &appended)?;
+    Ok(())
+}
+
+/// Reads the log, oldest first, which is empty if there is none.
+pub fn read(path: &Path) -> Result<Vec<Transaction>> {
+    let contents = match io::read_private(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut transactions = Vec::new();
+    if contents.trim().is_empty() {
+        return Ok(transactions);
+    }
+    for document in serde_yaml::Deserializer::from_str(&contents) {
+        transactions.push(Transaction::deserialize(document).map_err(term_lib::Error::from)?);
+    }
+    Ok(transactions)
+}
+
+fn write(path: &Path, transactions: &[Transaction]) -> Result<()> {
+    let mut contents = String::new();
+    for transaction in transactions {
+        contents.push_str("---\n");
+        contents.push_str(&serde_yaml::to_string(transaction).map_err(term_lib::Error::from)?);
+    }
+    io::write_private(path, &contents)?;
+    Ok(())
+}
+
+/// Puts the items back as they were before the transaction. Nothing is changed if any of the
+/// parts were changed again since, as that would be lost.
+pub fn undo(items: &mut Vec<Item>, transaction: &Transaction) -> Result<()> {
+    for change in transaction.changes.iter() {
+        let current = items.iter().find(|item| item.get_id() == change.id);
+        if current != change.after.as_ref() {
+            return Err(Error::ChangedSinceTransaction {
+                name: transaction.name.clone(),
+                part_id: change.id,
+            });
+        }
+    }
+    for change in transaction.changes.iter() {
+        let i = items.iter().position(|item| item.get_id() == change.id);
+        match (i, &change.before) {
+            (Some(i), Some(before)) => items[i] = before.clone(),
+            (Some(i), None) => {
+                items.remove(i);
+            }
+            (None, Some(before)) => items.push(before.clone()),
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Records the changes going from the old to the new items of the database as a transaction, if
+/// there are any.
+pub fn record(
+    db_path: &Path,
+    name: &str,
+    user: Option<&str>,
+    old: &[Item],
+    new: &[Item],
+) -> Result<()> {
+    match Transaction::new(name, old, new) {
+        Some(transaction) => append(&get_path(db_path), &transaction, user),
+        None => Ok(()),
+    }
+}
+
+/// Removes the last transaction from the log once it has been undone.
+pub fn remove_last(db_path: &Path) -> Result<()> {
+    let path = get_path(db_path);
+    let mut transactions = read(&path)?;
+    transactions.pop();
+    write(&path, &transactions)
+}
+
+/// Describes the transactions, newest first.
+pub fn describe(transactions: &[Transaction]) -> String {
+    if transactions.is_empty() {
+        return tr!("No transactions have been recorded yet.").to_string();
+    }
+    let lines: Vec<String> = transactions
+        .iter()
+        .rev()
+        .map(Transaction::to_string)
+        .collect();
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+
+    #[test]
+    fn test_transaction_and_undo() {
+        let path = PathBuf::from("test_transaction.yml");
+        let mut brick = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        let plate = Item::new(PartId::new(3020).unwrap(), "Plate 2 x 4".to_string());
+        let old = vec![brick.clone(), plate.clone()];
+
+        brick.add_color_group(ColorGroup::Basic, "A1".to_string());
+        let tile = Item::new(PartId::new(3068).unwrap(), "Tile 2 x 2".to_string());
+        let mut items = vec![brick.clone(), plate.clone(), tile];
+        assert!(Transaction::new("Part-out", &old, &old).is_none());
+        let transaction = Transaction::new("Part-out", &old, &items).unwrap();
+        assert_eq!(transaction.changes.len(), 2);
+
+        let result = (|| -> Result<()> {
+            record(&path, "Part-out", Some("malte"), &old, &items)?;
+            let transactions = read(&get_path(&path))?;
+            assert_eq!(transactions[0].user.as_deref(), Some("malte"));
+
+            let mut changed = items.clone();
+            changed[0].set_name("Brick 2x4");
+            assert!(undo(&mut changed, &transactions[0]).is_err());
+
+            undo(&mut items, &transactions[0])?;
+            assert_eq!(items, old);
+            remove_last(&path)?;
+            assert!(read(&get_path(&path))?.is_empty());
+            Ok(())
+        })();
+        let _ = std::fs::remove_file(get_path(&path));
+        result.unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::locale;
+
+/// IDs with this bit set belong to groups rather than parts.
+const GROUP_BIT: u32 = 1 << 31;
+
+/// The ID of an item, which is either a part ID or a group ID.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[serde(transparent)]
+pub struct PartId(u32);
+
+impl PartId {
+    /// Returns None if the ID is too large, as those are reserved for groups.
+    pub fn new(id: u32) -> Option<Self> {
+        (id < GROUP_BIT).then_some(Self(id))
+    }
+
+    pub fn group(number: u32) -> Option<Self> {
+        (number < GROUP_BIT).then_some(Self(number | GROUP_BIT))
+    }
+
+    pub fn is_group(&self) -> bool {
+        self.0 & GROUP_BIT != 0
+    }
+
+    /// The number shown to the user, which for groups is the group number.
+    pub fn get_number(&self) -> u32 {
+        self.0 & !GROUP_BIT
+    }
+
+    /// The item number used by BrickLink, groups have none.
+    pub fn to_bricklink(self) -> Option<String> {
+        (!self.is_group()).then(|| self.0.to_string())
+    }
+}
+
+impl fmt::Display for PartId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_number())
+    }
+}
+
+/// The ID of a color on BrickLink, by which BrickStore and the store API know the color of a lot.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[serde(try_from = "u32", into = "u32")]
+pub struct ColorId(u16);
+
+impl ColorId {
+    /// Returns None if the ID is too large to be a color, as BrickLink numbers its colors from 0
+    /// and has a few hundred of them.
+    pub const fn new(id: u32) -> Option<Self> {
+        if id <= u16::MAX as u32 {
+            Some(Self(id as u16))
+        } else {
+            None
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        s.trim().parse().ok().and_then(Self::new)
+    }
+
+    pub fn get(&self) -> u32 {
+        self.0 as u32
+    }
+}
+
+impl TryFrom<u32> for ColorId {
+    type Error = String;
+
+    fn try_from(id: u32) -> Result<Self, Self::Error> {
+        Self::new(id).ok_or_else(|| format!("{} is not a BrickLink color ID", id))
+    }
+}
+
+impl From<ColorId> for u32 {
+    fn from(id: ColorId) -> Self {
+        id.get()
+    }
+}
+
+impl fmt::Display for ColorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A number of pieces.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(transparent)]
+pub struct Quantity(u32);
+
+impl Quantity {
+    pub fn new(quantity: u32) -> Self {
+        Self(quantity)
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        s.trim().parse().ok().map(Self)
+    }
+
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", locale::fmt_count(self.0 as usize))
+    }
+}
+
+/// The first and last years a part or set was produced in.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Years {
+    pub from: u16,
+    pub to: u16,
+}
+
+impl Years {
+    pub fn new(from: u16, to: u16) -> Self {
+        Self {
+            from: from.min(to),
+            to: from.max(to),
+        }
+    }
+
+    pub fn contains(&self, year: u16) -> bool {
+        self.from <= year && year <= self.to
+    }
+}
+
+impl fmt::Display for Years {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.from == self.to {
+            write!(f, "{}", self.from)
+        } else {
+            write!(f, "{}-{}", self.from, self.to)
+        }
+    }
+}
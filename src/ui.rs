@@ -0,0 +1,791 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::BufRead;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use term_lib::command::Command;
+use term_lib::{display, input, prompt, Error, Result};
+
+use crate::key::Key;
+use crate::locale::tr;
+use crate::platform;
+use crate::theme::{self, Theme};
+use crate::width;
+
+/// Where key presses and the answers to prompts come from. The program normally reads them from
+/// the terminal, but they can also be scripted, e.g. to drive the modes in tests.
+pub trait InputSource<W: std::io::Write> {
+    fn wait_for_cmdchar(&mut self) -> Result<char>;
+    /// Waits for a key press with its modifiers. Sources without modifiers give plain keys.
+    fn wait_for_key(&mut self) -> Result<Key> {
+        self.wait_for_cmdchar().map(Key::new)
+    }
+    /// Returns true if there is input waiting to be read, without blocking.
+    fn has_pending(&mut self) -> bool {
+        false
+    }
+    /// Waits up to the timeout for input, and returns false if there was none. Sources that can
+    /// not wait report input right away.
+    fn poll(&mut self, _timeout: Duration) -> bool {
+        true
+    }
+    fn input_string(&mut self, w: &mut W, msg: &str) -> Result<String>;
+    fn input_u32(&mut self, w: &mut W, msg: &str) -> Result<u32>;
+    fn edit_string(&mut self, w: &mut W, msg: &str, old: &str) -> Result<String>;
+    fn edit_u32(&mut self, w: &mut W, msg: &str, old: u32) -> Result<u32>;
+    fn confirmation(&mut self, w: &mut W, msg: &str) -> Result<bool>;
+    /// Asks for confirmation in the given way. Sources that can not show it ask as usual.
+    fn confirm(&mut self, w: &mut W, msg: &str, kind: Confirm) -> Result<bool> {
+        match kind {
+            Confirm::Default(_) | Confirm::Destructive => self.confirmation(w, msg),
+            Confirm::Typed(text) => {
+                let prompt = format!("{}\n\n{}", msg, tr!("Type \"{}\" to confirm:", text));
+                Ok(self.input_string(w, &prompt)?.trim() == text.trim())
+            }
+        }
+    }
+    fn fzf_search(&mut self, opts: &str) -> Result<String>;
+    /// Returns the index of the selected option.
+    fn select(&mut self, w: &mut W, header: Option<&str>, options: &[String]) -> Result<usize>;
+    /// Returns the index of the selected command, given as (char, info) pairs.
+    fn select_cmd(&mut self, w: &mut W, header: &str, cmds: &[(char, String)]) -> Result<usize>;
+    /// Returns the index of the selected row of the table, with what to do with it. Sources that
+    /// can not pin the header and footer select from the rows as usual, to open the row.
+    fn select_row(&mut self, w: &mut W, table: &Table) -> Result<(usize, RowAction)> {
+        let header = format!("{}\n\n{}", table.header, table.columns);
+        let i = self.select(w, Some(&header), table.rows)?;
+        Ok((i, RowAction::Open))
+    }
+}
+
+/// What to do with the row selected in a table.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RowAction {
+    Open,
+    /// Change the number of pieces of a lot of the row, without leaving the table.
+    EditQuantity,
+    /// Move a lot of the row to another location, without leaving the table.
+    EditLocation,
+}
+
+/// Rows to select from, shown with the header and the names of the columns pinned at the top and
+/// the footer at the bottom while the rows scroll.
+pub struct Table<'a> {
+    pub header: &'a str,
+    pub columns: &'a str,
+    pub rows: &'a [String],
+    /// Where each column starts in the rows, so that wide tables can be panned by column.
+    pub column_starts: &'a [usize],
+    pub footer: &'a str,
+    /// The row selected at first.
+    pub selected: usize,
+    /// Whether the rows can be edited in place, see `RowAction`.
+    pub editable: bool,
+}
+
+/// Returns how many columns are hidden to the left and to the right when the table is panned to
+/// start at the first column, for columns starting at the given positions in rows of the given
+/// width.
+fn get_hidden_columns(
+    starts: &[usize],
+    table_width: usize,
+    first: usize,
+    width: usize,
+) -> (usize, usize) {
+    let shift = starts.get(first).copied().unwrap_or(0);
+    let ends = starts
+        .iter()
+        .skip(1)
+        .map(|start| start - 2)
+        .chain([table_width]);
+    let right = ends.skip(first).filter(|end| end - shift > width).count();
+    (first, right)
+}
+
+/// Returns the index of the first row shown, moved as little as possible from the offset so that
+/// the selected row is among the rows that fit in the body.
+fn scroll(selected: usize, offset: usize, body: usize) -> usize {
+    if selected < offset {
+        selected
+    } else if selected >= offset + body {
+        selected + 1 - body
+    } else {
+        offset
+    }
+}
+
+/// How a confirmation is asked, from actions that are easily undone to destructive ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirm<'a> {
+    /// Pressing Enter gives the answer.
+    Default(bool),
+    /// Going ahead is shown in red, and pressing Enter cancels.
+    Destructive,
+    /// The text, e.g. the name of the item, has to be typed to go ahead.
+    Typed(&'a str),
+}
+
+pub fn select_from_list<W, T, I>(
+    input: &mut dyn InputSource<W>,
+    w: &mut W,
+    header: Option<&str>,
+    options: I,
+) -> Result<T>
+where
+    W: std::io::Write,
+    T: fmt::Display,
+    I: Iterator<Item = T>,
+{
+    let mut options: Vec<T> = options.collect();
+    let names: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+    let i = input.select(w, header, &names)?;
+    Ok(options.swap_remove(i))
+}
+
+pub fn select_cmd<W, C, I>(
+    input: &mut dyn InputSource<W>,
+    w: &mut W,
+    header: &str,
+    cmds: I,
+) -> Result<C>
+where
+    W: std::io::Write,
+    C: Command,
+    I: Iterator<Item = C>,
+{
+    let mut cmds: Vec<C> = cmds.collect();
+    let pairs: Vec<(char, String)> = cmds
+        .iter()
+        .map(|c| (c.get_char(), c.get_info().to_string()))
+        .collect();
+    let i = input.select_cmd(w, header, &pairs)?;
+    Ok(cmds.swap_remove(i))
+}
+
+/// An answer given to a prompt, as recorded in macros.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum Answer {
+    Key(char),
+    /// A key pressed with Ctrl or Alt held down.
+    ModifiedKey(Key),
+    Text(String),
+    Number(u32),
+    Confirm(bool),
+    Select(usize),
+    /// A row selected in a table with what to do with it.
+    Row(usize, RowAction),
+}
+
+/// Wraps the input source to record the answers given to it, and to replay recorded answers before
+/// reading any more input.
+pub struct Macros<W: std::io::Write> {
+    inner: Box<dyn InputSource<W>>,
+    recording: Option<Vec<Answer>>,
+    queue: VecDeque<Answer>,
+}
+
+impl<W: std::io::Write> Macros<W> {
+    pub fn new(inner: Box<dyn InputSource<W>>) -> Self {
+        Self {
+            inner,
+            recording: None,
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// Stops recording, leaving out the key press that stopped it.
+    pub fn stop_recording(&mut self) -> Vec<Answer> {
+        let mut answers = self.recording.take().unwrap_or_default();
+        answers.pop();
+        answers
+    }
+
+    pub fn play(&mut self, answers: &[Answer], times: u32) {
+        for _ in 0..times {
+            self.queue.extend(answers.iter().cloned());
+        }
+    }
+
+    /// Returns the next answer, recorded or read with the given function.
+    fn next<T>(
+        &mut self,
+        read: impl FnOnce(&mut dyn InputSource<W>) -> Result<T>,
+        from: fn(Answer) -> Option<T>,
+        to: fn(&T) -> Answer,
+    ) -> Result<T> {
+        if let Some(answer) = self.queue.pop_front() {
+            // A macro that no longer fits what is asked is stopped.
+            return from(answer).ok_or_else(|| {
+                self.queue.clear();
+                Error::Escape
+            });
+        }
+        let res = read(self.inner.as_mut())?;
+        if let Some(recording) = &mut self.recording {
+            recording.push(to(&res));
+        }
+        Ok(res)
+    }
+}
+
+impl<W: std::io::Write> InputSource<W> for Macros<W> {
+    fn wait_for_cmdchar(&mut self) -> Result<char> {
+        self.next(
+            |i| i.wait_for_cmdchar(),
+            |a| match a {
+                Answer::Key(c) => Some(c),
+                _ => None,
+            },
+            |c| Answer::Key(*c),
+        )
+    }
+
+    fn wait_for_key(&mut self) -> Result<Key> {
+        self.next(
+            |i| i.wait_for_key(),
+            |a| match a {
+                Answer::Key(c) => Some(Key::new(c)),
+                Answer::ModifiedKey(key) => Some(key),
+                _ => None,
+            },
+            |key| match key.get_plain_char() {
+                Some(c) => Answer::Key(c),
+                None => Answer::ModifiedKey(*key),
+            },
+        )
+    }
+
+    fn has_pending(&mut self) -> bool {
+        !self.queue.is_empty() || self.inner.has_pending()
+    }
+
+    fn poll(&mut self, timeout: Duration) -> bool {
+        !self.queue.is_empty() || self.inner.poll(timeout)
+    }
+
+    fn input_string(&mut self, w: &mut W, msg: &str) -> Result<String> {
+        self.next(
+            |i| i.input_string(w, msg),
+            text,
+            |s| Answer::Text(s.clone()),
+        )
+    }
+
+    fn input_u32(&mut self, w: &mut W, msg: &str) -> Result<u32> {
+        self.next(|i| i.input_u32(w, msg), number, |n| Answer::Number(*n))
+    }
+
+    fn edit_string(&mut self, w: &mut W, msg: &str, old: &str) -> Result<String> {
+        self.next(
+            |i| i.edit_string(w, msg, old),
+            text,
+            |s| Answer::Text(s.clone()),
+        )
+    }
+
+    fn edit_u32(&mut self, w: &mut W, msg: &str, old: u32) -> Result<u32> {
+        self.next(|i| i.edit_u32(w, msg, old), number, |n| Answer::Number(*n))
+    }
+
+    fn confirmation(&mut self, w: &mut W, msg: &str) -> Result<bool> {
+        self.next(
+            |i| i.confirmation(w, msg),
+            |a| match a {
+                Answer::Confirm(b) => Some(b),
+                _ => None,
+            },
+            |b| Answer::Confirm(*b),
+        )
+    }
+
+    fn confirm(&mut self, w: &mut W, msg: &str, kind: Confirm) -> Result<bool> {
+        self.next(
+            |i| i.confirm(w, msg, kind),
+            |a| match a {
+                Answer::Confirm(b) => Some(b),
+                _ => None,
+            },
+            |b| Answer::Confirm(*b),
+        )
+    }
+
+    fn fzf_search(&mut self, opts: &str) -> Result<String> {
+        self.next(|i| i.fzf_search(opts), text, |s| Answer::Text(s.clone()))
+    }
+
+    fn select(&mut self, w: &mut W, header: Option<&str>, options: &[String]) -> Result<usize> {
+        let i = self.next(
+            |i| i.select(w, header, options),
+            select,
+            |i| Answer::Select(*i),
+        )?;
+        check_index(&mut self.queue, i, options.len())
+    }
+
+    fn select_cmd(&mut self, w: &mut W, header: &str, cmds: &[(char, String)]) -> Result<usize> {
+        let i = self.next(
+            |i| i.select_cmd(w, header, cmds),
+            select,
+            |i| Answer::Select(*i),
+        )?;
+        check_index(&mut self.queue, i, cmds.len())
+    }
+
+    fn select_row(&mut self, w: &mut W, table: &Table) -> Result<(usize, RowAction)> {
+        let (i, action) = self.next(
+            |i| i.select_row(w, table),
+            |a| match a {
+                Answer::Row(i, action) => Some((i, action)),
+                Answer::Select(i) => Some((i, RowAction::Open)),
+                _ => None,
+            },
+            |(i, action)| Answer::Row(*i, *action),
+        )?;
+        Ok((check_index(&mut self.queue, i, table.rows.len())?, action))
+    }
+}
+
+fn text(answer: Answer) -> Option<String> {
+    match answer {
+        Answer::Text(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn number(answer: Answer) -> Option<u32> {
+    match answer {
+        Answer::Number(n) => Some(n),
+        _ => None,
+    }
+}
+
+fn select(answer: Answer) -> Option<usize> {
+    match answer {
+        Answer::Select(i) => Some(i),
+        _ => None,
+    }
+}
+
+/// Stops the macro if it selects an option that is no longer there.
+fn check_index(queue: &mut VecDeque<Answer>, i: usize, len: usize) -> Result<usize> {
+    if i < len {
+        Ok(i)
+    } else {
+        queue.clear();
+        Err(Error::Escape)
+    }
+}
+
+/// An option in a list, remembering its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Choice<'a> {
+    index: usize,
+    c: char,
+    info: &'a str,
+}
+
+impl fmt::Display for Choice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.info)
+    }
+}
+
+impl Command for Choice<'_> {
+    fn get_char(&self) -> char {
+        self.c
+    }
+
+    fn get_info(&self) -> &str {
+        self.info
+    }
+}
+
+/// Reads input from the terminal.
+pub struct Terminal;
+
+impl<W: std::io::Write> InputSource<W> for Terminal {
+    fn wait_for_cmdchar(&mut self) -> Result<char> {
+        input::wait_for_cmdchar()
+    }
+
+    fn wait_for_key(&mut self) -> Result<Key> {
+        use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+        loop {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            let alt = key.modifiers.contains(KeyModifiers::ALT);
+            match key.code {
+                KeyCode::Esc => return Err(Error::Escape),
+                KeyCode::Char('c') if ctrl && !alt => return Err(Error::Quit),
+                KeyCode::Char(c) => {
+                    let (ctrl, alt) = platform::get_modifiers(c, ctrl, alt);
+                    return Ok(Key::with_modifiers(c, ctrl, alt));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn has_pending(&mut self) -> bool {
+        crossterm::event::poll(Duration::ZERO).unwrap_or(false)
+    }
+
+    fn poll(&mut self, timeout: Duration) -> bool {
+        crossterm::event::poll(timeout).unwrap_or(true)
+    }
+
+    fn input_string(&mut self, w: &mut W, msg: &str) -> Result<String> {
+        prompt::input_string(w, msg)
+    }
+
+    fn input_u32(&mut self, w: &mut W, msg: &str) -> Result<u32> {
+        prompt::input_u32(w, msg)
+    }
+
+    fn edit_string(&mut self, w: &mut W, msg: &str, old: &str) -> Result<String> {
+        prompt::edit_string(w, msg, old)
+    }
+
+    fn edit_u32(&mut self, w: &mut W, msg: &str, old: u32) -> Result<u32> {
+        prompt::edit_u32(w, msg, old)
+    }
+
+    fn confirmation(&mut self, w: &mut W, msg: &str) -> Result<bool> {
+        prompt::confirmation(w, msg)
+    }
+
+    fn confirm(&mut self, w: &mut W, msg: &str, kind: Confirm) -> Result<bool> {
+        use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+
+        let theme = theme::get();
+        let default = match kind {
+            Confirm::Default(default) => default,
+            Confirm::Destructive => false,
+            Confirm::Typed(text) => {
+                display::iter(w, msg.lines())?;
+                display::newline(w, 1)?;
+                let prompt = tr!("Type \"{}\" to confirm:", text);
+                return Ok(prompt::input_string(w, &theme.warning(&prompt))?.trim() == text.trim());
+            }
+        };
+        display::iter(w, msg.lines())?;
+        display::newline(w, 1)?;
+        let (yes, no) = (tr!("y: yes"), tr!("n: no"));
+        let options = match kind {
+            Confirm::Destructive => format!("{}  {}", theme.warning(yes), no),
+            _ if default => format!("{}  {}", theme.emphasis(yes), no),
+            _ => format!("{}  {}", yes, theme.emphasis(no)),
+        };
+        let hint = match (theme, kind) {
+            (Theme::Monochrome, Confirm::Destructive) => tr!("(Enter: the answer without !)"),
+            (Theme::Monochrome, _) => tr!("(Enter: the answer in brackets)"),
+            _ => tr!("(Enter: the bold answer)"),
+        };
+        display::line(w, format!("{}  {}", options, hint))?;
+        w.flush()?;
+
+        loop {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('y') => return Ok(true),
+                KeyCode::Char('n') => return Ok(false),
+                KeyCode::Enter => return Ok(default),
+                KeyCode::Esc => return Err(Error::Escape),
+                _ => continue,
+            }
+        }
+    }
+
+    fn fzf_search(&mut self, opts: &str) -> Result<String> {
+        prompt::fzf_search(opts)
+    }
+
+    fn select(&mut self, w: &mut W, header: Option<&str>, options: &[String]) -> Result<usize> {
+        let choices = options.iter().enumerate().map(|(index, info)| Choice {
+            index,
+            c: ' ',
+            info,
+        });
+        Ok(prompt::select_from_list(w, header, choices)?.index)
+    }
+
+    fn select_cmd(&mut self, w: &mut W, header: &str, cmds: &[(char, String)]) -> Result<usize> {
+        let choices =
+            cmds.iter()
+                .enumerate()
+                .map(|(index, (c, info))| Choice { index, c: *c, info });
+        Ok(prompt::select_cmd(w, header, choices)?.index)
+    }
+
+    fn select_row(&mut self, w: &mut W, table: &Table) -> Result<(usize, RowAction)> {
+        use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+        use crossterm::{cursor, queue, terminal};
+
+        let theme = theme::get();
+
+        if table.rows.is_empty() {
+            return Err(Error::Escape);
+        }
+        let header: Vec<&str> = table.header.lines().collect();
+        let keys = if table.editable {
+            tr!("Up/Down, PgUp/PgDn: scroll  Enter: select  c: count  m: move  Esc: cancel")
+        } else {
+            tr!("Up/Down, PgUp/PgDn: scroll  Enter: select  Esc: cancel")
+        };
+        let last = table.rows.len() - 1;
+        let table_width = table
+            .rows
+            .iter()
+            .chain([&table.columns.to_string()])
+            .map(|row| width::get_width(row))
+            .max()
+            .unwrap_or(0);
+        let mut selected = table.selected.min(last);
+        let (mut offset, mut first_column) = (0, 0);
+        loop {
+            let (width, height) = platform::get_size();
+            let width = width.saturating_sub(theme.get_row_marker_width());
+            let (hidden_left, hidden_right) =
+                get_hidden_columns(table.column_starts, table_width, first_column, width);
+            let shift = table.column_starts.get(first_column).copied().unwrap_or(0);
+            let pan = |line: &str| width::slice(line, shift, width);
+            // The header and column names take the top lines and the footer and keys the bottom
+            // two, and the rows scroll in between.
+            let body = height.saturating_sub(header.len() + 3).max(1);
+            offset = scroll(selected, offset, body);
+
+            queue!(
+                w,
+                terminal::Clear(terminal::ClearType::All),
+                cursor::MoveTo(0, 0)
+            )?;
+            for line in header.iter() {
+                write!(w, "{}\r\n", line)?;
+            }
+            let columns = theme.row(&pan(table.columns), false);
+            write!(w, "{}\r\n", theme.heading(&columns))?;
+            for (i, row) in table.rows.iter().enumerate().skip(offset).take(body) {
+                write!(w, "{}\r\n", theme.row(&pan(row), i == selected))?;
+            }
+            queue!(w, cursor::MoveTo(0, height.saturating_sub(2) as u16))?;
+            let mut footer = table.footer.to_string();
+            if hidden_left + hidden_right != 0 {
+                footer.push_str("  ");
+                footer.push_str(&tr!(
+                    "({} columns hidden to the left, {} to the right, h/l: pan)",
+                    hidden_left,
+                    hidden_right
+                ));
+            }
+            let footer = theme.row(&pan(&footer), false);
+            write!(w, "{}\r\n{}", theme.heading(&footer), keys)?;
+            w.flush()?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1).min(last),
+                KeyCode::PageUp => selected = selected.saturating_sub(body),
+                KeyCode::PageDown => selected = (selected + body).min(last),
+                KeyCode::Left | KeyCode::Char('h') => first_column = first_column.saturating_sub(1),
+                KeyCode::Right | KeyCode::Char('l') if hidden_right != 0 => first_column += 1,
+                KeyCode::Home => selected = 0,
+                KeyCode::End => selected = last,
+                KeyCode::Enter => return Ok((selected, RowAction::Open)),
+                KeyCode::Esc => return Err(Error::Escape),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Err(Error::Quit)
+                }
+                KeyCode::Char(c @ ('c' | 'm')) if table.editable => {
+                    // The prompt for the new value takes the place of the keys at the bottom, so
+                    // that the table stays in view while it is answered.
+                    queue!(
+                        w,
+                        cursor::MoveTo(0, height.saturating_sub(1) as u16),
+                        terminal::Clear(terminal::ClearType::CurrentLine)
+                    )?;
+                    w.flush()?;
+                    let action = match c {
+                        'c' => RowAction::EditQuantity,
+                        _ => RowAction::EditLocation,
+                    };
+                    return Ok((selected, action));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Reads one answer per line, e.g. from a pipe. Commands are given by the first character of a
+/// line, confirmations by "y" or "yes", and options by their text or their position starting at 1.
+/// An empty line cancels a command, and the program quits at the end of the input.
+pub struct Lines<R: BufRead> {
+    reader: R,
+}
+
+impl<R: BufRead> Lines<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    fn next_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(Error::Quit);
+        }
+        Ok(line.trim_end_matches(['\n', '\r']).to_string())
+    }
+
+    fn next_u32(&mut self) -> Result<u32> {
+        let line = self.next_line()?;
+        line.trim().parse().map_err(|_| Error::ParsingError(line))
+    }
+}
+
+impl<W: std::io::Write, R: BufRead> InputSource<W> for Lines<R> {
+    fn wait_for_cmdchar(&mut self) -> Result<char> {
+        self.next_line()?.chars().next().ok_or(Error::Escape)
+    }
+
+    fn input_string(&mut self, _: &mut W, _: &str) -> Result<String> {
+        self.next_line()
+    }
+
+    fn input_u32(&mut self, _: &mut W, _: &str) -> Result<u32> {
+        self.next_u32()
+    }
+
+    fn edit_string(&mut self, _: &mut W, _: &str, _: &str) -> Result<String> {
+        self.next_line()
+    }
+
+    fn edit_u32(&mut self, _: &mut W, _: &str, _: u32) -> Result<u32> {
+        self.next_u32()
+    }
+
+    fn confirmation(&mut self, _: &mut W, _: &str) -> Result<bool> {
+        let line = self.next_line()?.to_lowercase();
+        Ok(line == "y" || line == "yes")
+    }
+
+    /// An empty line gives the default answer, and text to confirm is given as the line.
+    fn confirm(&mut self, _: &mut W, _: &str, kind: Confirm) -> Result<bool> {
+        let line = self.next_line()?;
+        match kind {
+            Confirm::Typed(text) => Ok(line.trim() == text.trim()),
+            Confirm::Default(default) if line.trim().is_empty() => Ok(default),
+            _ => {
+                let line = line.trim().to_lowercase();
+                Ok(line == "y" || line == "yes")
+            }
+        }
+    }
+
+    fn fzf_search(&mut self, _: &str) -> Result<String> {
+        self.next_line()
+    }
+
+    fn select(&mut self, _: &mut W, _: Option<&str>, options: &[String]) -> Result<usize> {
+        let line = self.next_line()?;
+        if let Some(i) = options.iter().position(|o| *o == line) {
+            return Ok(i);
+        }
+        match line.trim().parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => Ok(n - 1),
+            _ => Err(Error::ParsingError(line)),
+        }
+    }
+
+    fn select_cmd(&mut self, _: &mut W, _: &str, cmds: &[(char, String)]) -> Result<usize> {
+        let c = self.next_line()?.chars().next().ok_or(Error::Escape)?;
+        cmds.iter()
+            .position(|(o, _)| *o == c)
+            .ok_or(Error::ParsingError(c.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let mut w = Vec::new();
+        let mut input = Lines::new("a\n3001\nBrick 2 x 4\n2\n\n".as_bytes());
+        let options = ["first".to_string(), "second".to_string()];
+
+        assert_eq!(
+            InputSource::<Vec<u8>>::wait_for_cmdchar(&mut input).unwrap(),
+            'a'
+        );
+        assert_eq!(input.input_u32(&mut w, "").unwrap(), 3001);
+        assert_eq!(input.input_string(&mut w, "").unwrap(), "Brick 2 x 4");
+        assert_eq!(input.select(&mut w, None, &options).unwrap(), 1);
+        assert!(matches!(
+            InputSource::<Vec<u8>>::wait_for_cmdchar(&mut input),
+            Err(Error::Escape)
+        ));
+        assert!(matches!(
+            InputSource::<Vec<u8>>::wait_for_cmdchar(&mut input),
+            Err(Error::Quit)
+        ));
+    }
+
+    #[test]
+    fn test_scroll() {
+        assert_eq!(scroll(0, 0, 10), 0);
+        assert_eq!(scroll(9, 0, 10), 0);
+        assert_eq!(scroll(10, 0, 10), 1);
+        assert_eq!(scroll(25, 3, 10), 16);
+        assert_eq!(scroll(4, 16, 10), 4);
+    }
+
+    #[test]
+    fn test_hidden_columns() {
+        // Columns of widths 4, 11, 6 and 7.
+        let starts = [0, 6, 19, 27];
+        assert_eq!(get_hidden_columns(&starts, 34, 0, 80), (0, 0));
+        assert_eq!(get_hidden_columns(&starts, 34, 0, 20), (0, 2));
+        assert_eq!(get_hidden_columns(&starts, 34, 2, 20), (2, 0));
+        assert_eq!(get_hidden_columns(&[], 0, 0, 20), (0, 0));
+    }
+
+    #[test]
+    fn test_lines_confirm() {
+        let mut w = Vec::new();
+        let mut input = Lines::new("\n\nbrick 2 x 4\nBrick 2 x 4\n".as_bytes());
+        assert!(input.confirm(&mut w, "", Confirm::Default(true)).unwrap());
+        assert!(!input.confirm(&mut w, "", Confirm::Destructive).unwrap());
+        let name = Confirm::Typed("Brick 2 x 4");
+        assert!(!input.confirm(&mut w, "", name).unwrap());
+        assert!(input.confirm(&mut w, "", name).unwrap());
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use log::{Log, Metadata, Record};
+
+use crate::error::Result;
+use crate::locale::{self, tr};
+use crate::timestamp::Timestamp;
+
+/// The log target of usage records, which go to the usage log instead of the other log output.
+pub const TARGET: &str = "usage";
+
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Passes log records on to `env_logger`, except for usage records, which are appended to the
+/// usage log if it is enabled. The log never leaves the machine.
+struct Logger {
+    inner: env_logger::Logger,
+    usage: Option<Mutex<fs::File>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.target() == TARGET {
+            self.usage.is_some()
+        } else {
+            self.inner.enabled(metadata)
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if record.target() != TARGET {
+            return self.inner.log(record);
+        }
+        if let Some(file) = &self.usage {
+            let line = format!("{}\t{}\n", Timestamp::now().get_secs(), record.args());
+            if let Ok(mut file) = file.lock() {
+                let _ = file.write_all(line.as_bytes());
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Sets up logging as `env_logger` does, where usage is also logged to the file if one is given.
+/// Can only be done once.
+pub fn init(path: Option<PathBuf>) -> Result<()> {
+    let inner = env_logger::Builder::from_default_env().build();
+    let mut max_level = inner.filter();
+    let usage = match path {
+        Some(path) => {
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?;
+            let _ = PATH.set(path);
+            max_level = max_level.max(log::LevelFilter::Info);
+            Some(Mutex::new(file))
+        }
+        None => None,
+    };
+    if log::set_boxed_logger(Box::new(Logger { inner, usage })).is_ok() {
+        log::set_max_level(max_level);
+    }
+    Ok(())
+}
+
+/// Where usage is logged in this session, if it is.
+pub fn get_path() -> Option<&'static Path> {
+    PATH.get().map(PathBuf::as_path)
+}
+
+/// Records that the command with the name was given.
+pub fn record_cmd(name: &str) {
+    log::info!(target: TARGET, "cmd\t{}", name);
+}
+
+/// Records the time spent in the mode with the name, from entering it until leaving it.
+pub fn record_mode(name: &str, time: Duration) {
+    log::info!(target: TARGET, "mode\t{}\t{}", name, time.as_millis());
+}
+
+/// How often each command was used and how long was spent in each mode.
+#[derive(Debug, Default, PartialEq)]
+pub struct Report {
+    pub cmds: BTreeMap<String, usize>,
+    pub modes: BTreeMap<String, Duration>,
+}
+
+/// Adds up the records of the usage log, skipping lines it cannot read.
+pub fn parse(contents: &str) -> Report {
+    let mut report = Report::default();
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields.as_slice() {
+            [_, "cmd", name] => *report.cmds.entry(name.to_string()).or_default() += 1,
+            [_, "mode", name, millis] => {
+                if let Ok(millis) = millis.parse() {
+                    *report.modes.entry(name.to_string()).or_default() +=
+                        Duration::from_millis(millis);
+                }
+            }
+            _ => {}
+        }
+    }
+    report
+}
+
+/// Reads the usage log of the session, which is empty if nothing was logged yet.
+pub fn read(path: &Path) -> Result<Report> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(parse(&contents)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Report::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl Report {
+    /// The commands by how often they were used, most used first, and the time in each mode.
+    /// Commands are given by the names used for binding them to keys.
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![tr!("Commands used:").to_string()];
+        let mut cmds: Vec<(&String, &usize)> = self.cmds.iter().collect();
+        cmds.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        lines.extend(
+            cmds.into_iter()
+                .map(|(name, count)| format!("  {:>6}  {}", count, name)),
+        );
+        lines.push(tr!("Time in modes:").to_string());
+        let mut modes: Vec<(&String, &Duration)> = self.modes.iter().collect();
+        modes.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        lines.extend(
+            modes
+                .into_iter()
+                .map(|(name, time)| format!("  {:>6}  {}", locale::fmt_duration(*time), name)),
+        );
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let contents = "1700000000\tcmd\tSaveEdit\n\
+            1700000001\tcmd\tEdit\n\
+            1700000002\tcmd\tSaveEdit\n\
+            1700000003\tmode\tEditItem\t90000\n\
+            1700000004\tmode\tDefault\t3660000\n\
+            1700000005\tmode\tEditItem\t30000\n\
+            not a record\n";
+        let report = parse(contents);
+        assert_eq!(report.cmds["SaveEdit"], 2);
+        assert_eq!(report.modes["EditItem"], Duration::from_secs(120));
+        assert_eq!(
+            report.describe(),
+            vec![
+                "Commands used:",
+                "       2  SaveEdit",
+                "       1  Edit",
+                "Time in modes:",
+                "  1h 01m  Default",
+                "  0h 02m  EditItem",
+            ]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use serde_json::{json, Value};
+
+use crate::data::Item;
+use crate::site::Site;
+
+/// Where BrickLink keeps a picture of each part, in its most common color.
+const IMAGE_URL: &str = "https://img.bricklink.com/ItemImage/PL";
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 1em; }
+input { font-size: 14pt; width: 100%; max-width: 30em; margin-bottom: 1em; }
+table { border-collapse: collapse; }
+th, td { text-align: left; padding: 2px 8px; border-bottom: 1px solid #ddd; vertical-align: middle; }
+img { width: 48px; height: 48px; object-fit: contain; }
+";
+
+/// Shows the parts whose ID, name, category or locations contain all of the words searched for.
+const SCRIPT: &str = "const rows = document.getElementById('rows');
+const count = document.getElementById('count');
+function cell(row, text) { const td = row.insertCell(); td.textContent = text; return td; }
+function show() {
+  const words = document.getElementById('search').value.toLowerCase().split(/\\s+/).filter(w => w);
+  rows.replaceChildren();
+  let shown = 0;
+  for (const part of PARTS) {
+    const text = [part.id, part.name, part.category, ...part.lots.map(l => l.location)]
+      .join(' ').toLowerCase();
+    if (!words.every(w => text.includes(w)) || shown >= 500) continue;
+    shown++;
+    const row = rows.insertRow();
+    const image = row.insertCell();
+    if (part.image) {
+      const img = document.createElement('img');
+      img.src = part.image; img.loading = 'lazy'; img.onerror = () => img.remove();
+      image.appendChild(img);
+    }
+    const id = row.insertCell();
+    if (part.url) {
+      const a = document.createElement('a'); a.href = part.url; a.textContent = part.id;
+      id.appendChild(a);
+    } else { id.textContent = part.id; }
+    cell(row, part.name);
+    cell(row, part.lots.map(l => l.color_group + ': ' + l.location +
+      (l.quantity === null ? '' : ' (' + l.quantity + ')')).join(', '));
+  }
+  count.textContent = shown + ' / ' + PARTS.length;
+}
+document.getElementById('search').addEventListener('input', show);
+show();
+";
+
+fn to_json(item: &Item, images: bool, site: Site) -> Value {
+    let lots: Vec<Value> = item
+        .get_locations()
+        .iter()
+        .map(|lot| {
+            json!({
+                "color_group": lot.get_color_group().to_string(),
+                "location": lot.get_location(),
+                "quantity": lot.get_quantity().map(|q| q.get()),
+            })
+        })
+        .collect();
+    let image = item
+        .get_id()
+        .to_bricklink()
+        .filter(|_| images)
+        .map(|number| format!("{}/{}.png", IMAGE_URL, number));
+    json!({
+        "id": item.get_id().to_string(),
+        "name": item.get_name(),
+        "category": item.get_category().unwrap_or_default(),
+        "lots": lots,
+        "url": site.get_part_url(item.get_id()),
+        "image": image,
+    })
+}
+
+/// Renders the inventory as a single HTML page with the parts embedded, which can be searched in
+/// a browser without a server. Pictures are loaded from BrickLink if `images`, and each part
+/// links to its page on the site.
+pub fn to_html(items: &[Item], images: bool, site: Site) -> String {
+    let parts: Vec<Value> = items
+        .iter()
+        .map(|item| to_json(item, images, site))
+        .collect();
+    // Names containing `</script>` must not end the script early.
+    let parts = Value::Array(parts).to_string().replace("</", "<\\/");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>Parts</title>\n<style>\n{}</style>\n</head>\n<body>\n\
+         <input id=\"search\" type=\"search\" placeholder=\"Search\" autofocus>\n\
+         <div id=\"count\"></div>\n<table>\n<tbody id=\"rows\"></tbody>\n</table>\n\
+         <script>\nconst PARTS = {};\n{}</script>\n</body>\n</html>\n",
+        STYLE, parts, SCRIPT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::PartId;
+
+    #[test]
+    fn test_to_html() {
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick </script>".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        let group = Item::new(PartId::group(1).unwrap(), "Technic".to_string());
+
+        let html = to_html(&[item, group], true, Site::BrickLink);
+        assert!(html.contains("Brick <\\/script>") && !html.contains("Brick </script>"));
+        assert!(html.contains("\"location\":\"A1\""));
+        assert_eq!(html.matches("ItemImage/PL/3001.png").count(), 1);
+        assert!(!to_html(&[], false, Site::BrickLink).contains("ItemImage/PL/"));
+    }
+}
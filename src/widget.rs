@@ -0,0 +1,186 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::budget;
+use crate::config::Config;
+use crate::data::{Database, Item};
+use crate::date::Date;
+use crate::filter::Filter;
+use crate::locale::{self, tr, tr_n};
+use crate::theme;
+use crate::types::PartId;
+
+/// The number of entries shown by widgets that list items.
+const MAX_ENTRIES: usize = 5;
+
+/// A section of the default screen. Which widgets are shown, and in which order, is set by the
+/// widgets list in the config.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Widget {
+    /// The items viewed most recently in this session.
+    RecentItems,
+    /// Lots with fewer pieces than the low stock threshold.
+    LowStock,
+    /// A summary of the database statistics.
+    Stats,
+    /// The number of items matching each saved filter.
+    SavedFilters,
+    /// The progress of each collection goal.
+    Goals,
+    /// How much of the monthly budget is left this month, as a warning once it is spent.
+    Budget,
+}
+
+pub fn default_widgets() -> Vec<Widget> {
+    vec![
+        Widget::RecentItems,
+        Widget::LowStock,
+        Widget::Goals,
+        Widget::Stats,
+    ]
+}
+
+impl fmt::Display for Widget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Widget::*;
+        let title = match self {
+            RecentItems => tr!("Recently viewed"),
+            LowStock => tr!("Low stock"),
+            Stats => tr!("Statistics"),
+            SavedFilters => tr!("Saved filters"),
+            Goals => tr!("Goals"),
+            Budget => tr!("Budget"),
+        };
+        write!(f, "{}", title)
+    }
+}
+
+impl Widget {
+    /// Returns the lines shown below the title of the widget.
+    pub fn get_lines(self, db: &Database, config: &Config, recent: &[PartId]) -> Vec<String> {
+        use Widget::*;
+        match self {
+            RecentItems => recent_items(db, recent),
+            LowStock => low_stock(db.get_items(), config.get_low_stock_threshold()),
+            Stats => vec![db.get_stats(true, None).summary()],
+            SavedFilters => saved_filters(db.get_items(), config),
+            Goals => goals(db, config),
+            Budget => budget(config),
+        }
+    }
+}
+
+fn recent_items(db: &Database, recent: &[PartId]) -> Vec<String> {
+    let lines: Vec<String> = recent
+        .iter()
+        .filter_map(|id| db.get_item_by_id(*id).ok())
+        .take(MAX_ENTRIES)
+        .map(|item| format!("{} {}", item.get_id(), item.get_name()))
+        .collect();
+    if lines.is_empty() {
+        return vec![tr!("No items have been viewed yet").to_string()];
+    }
+    lines
+}
+
+fn low_stock(items: &[Item], threshold: u32) -> Vec<String> {
+    let mut lots: Vec<_> = items
+        .iter()
+        .flat_map(|item| item.get_locations().iter().map(move |lot| (item, lot)))
+        .filter_map(|(item, lot)| Some((item, lot, lot.get_quantity()?.get())))
+        .filter(|(_, _, quantity)| *quantity < threshold)
+        .collect();
+    if lots.is_empty() {
+        return vec![tr!("No lots have fewer than {} pieces", threshold)];
+    }
+
+    lots.sort_by_key(|(_, _, quantity)| *quantity);
+    let mut lines: Vec<String> = lots
+        .iter()
+        .take(MAX_ENTRIES)
+        .map(|(item, lot, quantity)| {
+            tr!(
+                "{} {}, {} at {}: {}",
+                item.get_id(),
+                item.get_name(),
+                lot.get_color_group(),
+                lot.get_location(),
+                locale::fmt_count(*quantity as usize),
+            )
+        })
+        .collect();
+    if lots.len() > MAX_ENTRIES {
+        lines.push(tr_n!(
+            "and {} more lot",
+            "and {} more lots",
+            lots.len() - MAX_ENTRIES
+        ));
+    }
+    lines
+}
+
+fn saved_filters(items: &[Item], config: &Config) -> Vec<String> {
+    let filters = config.get_saved_filters();
+    if filters.is_empty() {
+        return vec![tr!("No filters have been saved in the config").to_string()];
+    }
+
+    filters
+        .iter()
+        .map(|(name, filter)| match Filter::parse(filter) {
+            Ok(filter) => {
+                let matches = items.iter().filter(|item| filter.matches(item)).count();
+                tr_n!("{} part matches {}", "{} parts match {}", matches, name)
+            }
+            Err(_) => tr!("The saved filter {} is invalid", name),
+        })
+        .collect()
+}
+
+fn goals(db: &Database, config: &Config) -> Vec<String> {
+    let goals = config.get_goals();
+    if goals.is_empty() {
+        return vec![tr!("No goals have been defined in the config").to_string()];
+    }
+    goals.iter().map(|goal| goal.fmt_progress(db)).collect()
+}
+
+fn budget(config: &Config) -> Vec<String> {
+    let Some(monthly_budget) = config.get_monthly_budget() else {
+        return vec![tr!("No monthly budget has been set in the config").to_string()];
+    };
+    match budget::read(&budget::get_path(&config.get_db_path())) {
+        Ok(purchases) => {
+            let (line, exceeded) = budget::describe(monthly_budget, &purchases, Date::today());
+            if exceeded {
+                vec![theme::get().warning(&line)]
+            } else {
+                vec![line]
+            }
+        }
+        Err(e) => vec![tr!("The purchases could not be read: {}", e)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ColorGroup;
+    use crate::types::Quantity;
+
+    #[test]
+    fn test_low_stock() {
+        let mut item = Item::new(PartId::new(3001).unwrap(), "Brick 2 x 4".to_string());
+        item.add_color_group(ColorGroup::Basic, "A1".to_string());
+        item.add_color_group(ColorGroup::Earth, "A2".to_string());
+        item.add_color_group(ColorGroup::Grey, "A3".to_string());
+        item.set_quantity(&ColorGroup::Basic, Some(Quantity::new(2)));
+        item.set_quantity(&ColorGroup::Earth, Some(Quantity::new(20)));
+
+        let lines = low_stock(&[item], 5);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("A1"));
+    }
+}
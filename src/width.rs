@@ -0,0 +1,60 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// The number of terminal columns the text takes, where wide characters like CJK and most emoji
+/// take two and combining marks none.
+pub fn get_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Pads the text with spaces at the end to take the width, if it is narrower.
+pub fn pad(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(get_width(text));
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// Returns the part of the text shown in the columns from `start` on, at most `width` columns
+/// wide. Characters are never split from their combining marks, and a wide character cut in half
+/// at either end is shown as a space.
+pub fn slice(text: &str, start: usize, width: usize) -> String {
+    let mut res = String::new();
+    let (mut column, mut taken) = (0, 0);
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        let end = column + grapheme_width;
+        if end > start {
+            let shown = end - column.max(start);
+            if taken + shown > width {
+                res.push_str(&" ".repeat(width - taken));
+                break;
+            }
+            if shown < grapheme_width {
+                res.push_str(&" ".repeat(shown));
+            } else {
+                res.push_str(grapheme);
+            }
+            taken += shown;
+        }
+        column = end;
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_width() {
+        assert_eq!(get_width("Brick 2 x 4"), 11);
+        assert_eq!(get_width("積み木"), 6);
+        assert_eq!(get_width("Cafe\u{301}"), 4);
+        assert_eq!(pad("積み", 6), "積み  ");
+        assert_eq!(pad("too wide", 3), "too wide");
+
+        assert_eq!(slice("Cafe\u{301} 積み木", 2, 5), "fe\u{301} 積");
+        assert_eq!(slice("積み木", 1, 3), " み");
+        assert_eq!(slice("積み木", 0, 3), "積 ");
+        assert_eq!(slice("abc", 5, 3), "");
+    }
+}